@@ -2,6 +2,8 @@
 //!
 //! [np]: https://docs.solanalabs.com/runtime/programs#ed25519-program
 
+#[cfg(feature = "verify")]
+use solana_precompile_error::PrecompileError;
 use {
     bytemuck::bytes_of,
     bytemuck_derive::{Pod, Zeroable},
@@ -106,3 +108,95 @@ pub fn new_ed25519_instruction_with_signature(
         data: instruction_data,
     }
 }
+
+/// Verify the signatures of an ed25519 program instruction with data `data`,
+/// as the runtime does before executing a transaction.
+///
+/// `instruction_datas` is the data of every instruction of the transaction,
+/// which the signature offsets index into. An instruction index of
+/// `u16::MAX` refers to the ed25519 instruction itself. With `verify_strict`,
+/// as when `ed25519_precompile_verify_strict` is active, small order public
+/// keys and malleable signatures are also rejected.
+#[cfg(feature = "verify")]
+pub fn verify(
+    data: &[u8],
+    instruction_datas: &[&[u8]],
+    verify_strict: bool,
+) -> Result<(), PrecompileError> {
+    if data.len() < SIGNATURE_OFFSETS_START {
+        return Err(PrecompileError::InvalidInstructionDataSize);
+    }
+    let num_signatures = usize::from(data[0]);
+    if num_signatures == 0 && data.len() > SIGNATURE_OFFSETS_START {
+        return Err(PrecompileError::InvalidInstructionDataSize);
+    }
+    let expected_data_size = num_signatures
+        .saturating_mul(SIGNATURE_OFFSETS_SERIALIZED_SIZE)
+        .saturating_add(SIGNATURE_OFFSETS_START);
+    // the padding byte at data[1] is not checked
+    if data.len() < expected_data_size {
+        return Err(PrecompileError::InvalidInstructionDataSize);
+    }
+    for i in 0..num_signatures {
+        let start = i
+            .saturating_mul(SIGNATURE_OFFSETS_SERIALIZED_SIZE)
+            .saturating_add(SIGNATURE_OFFSETS_START);
+        let end = start.saturating_add(SIGNATURE_OFFSETS_SERIALIZED_SIZE);
+        let offsets: Ed25519SignatureOffsets = bytemuck::pod_read_unaligned(&data[start..end]);
+
+        let signature = get_data_slice(
+            data,
+            instruction_datas,
+            offsets.signature_instruction_index,
+            offsets.signature_offset,
+            SIGNATURE_SERIALIZED_SIZE,
+        )?;
+        let signature = ed25519_dalek::Signature::from_slice(signature)
+            .map_err(|_| PrecompileError::InvalidSignature)?;
+        let pubkey = get_data_slice(
+            data,
+            instruction_datas,
+            offsets.public_key_instruction_index,
+            offsets.public_key_offset,
+            PUBKEY_SERIALIZED_SIZE,
+        )?;
+        let pubkey = ed25519_dalek::VerifyingKey::try_from(pubkey)
+            .map_err(|_| PrecompileError::InvalidPublicKey)?;
+        let message = get_data_slice(
+            data,
+            instruction_datas,
+            offsets.message_instruction_index,
+            offsets.message_data_offset,
+            usize::from(offsets.message_data_size),
+        )?;
+
+        if verify_strict {
+            pubkey.verify_strict(message, &signature)
+        } else {
+            ed25519_dalek::Verifier::verify(&pubkey, message, &signature)
+        }
+        .map_err(|_| PrecompileError::InvalidSignature)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "verify")]
+fn get_data_slice<'a>(
+    data: &'a [u8],
+    instruction_datas: &'a [&[u8]],
+    instruction_index: u16,
+    offset: u16,
+    size: usize,
+) -> Result<&'a [u8], PrecompileError> {
+    let instruction = if instruction_index == u16::MAX {
+        data
+    } else {
+        instruction_datas
+            .get(usize::from(instruction_index))
+            .ok_or(PrecompileError::InvalidDataOffsets)?
+    };
+    let start = usize::from(offset);
+    instruction
+        .get(start..start.saturating_add(size))
+        .ok_or(PrecompileError::InvalidDataOffsets)
+}
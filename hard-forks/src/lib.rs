@@ -59,6 +59,28 @@ impl HardForks {
     }
 }
 
+/// Generates registered hard forks, which are sorted by slot with unique
+/// slots and non-zero counts.
+#[cfg(feature = "proptest")]
+impl proptest::arbitrary::Arbitrary for HardForks {
+    type Parameters = ();
+    type Strategy = proptest::strategy::BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        use proptest::{arbitrary::any, collection, strategy::Strategy};
+
+        collection::vec(any::<u64>(), 0..32)
+            .prop_map(|slots| {
+                let mut hard_forks = HardForks::default();
+                for slot in slots {
+                    hard_forks.register(slot);
+                }
+                hard_forks
+            })
+            .boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -89,4 +111,13 @@ mod tests {
         assert_eq!(hf.get_hash_data(21, 11), Some([1, 0, 0, 0, 0, 0, 0, 0,]));
         assert_eq!(hf.get_hash_data(21, 20), None);
     }
+
+    #[cfg(feature = "proptest")]
+    proptest::proptest! {
+        #[test]
+        fn arbitrary_is_registered(hf in proptest::arbitrary::any::<HardForks>()) {
+            proptest::prop_assert!(hf.hard_forks.windows(2).all(|w| w[0].0 < w[1].0));
+            proptest::prop_assert!(hf.iter().all(|(_, count)| *count > 0));
+        }
+    }
 }
@@ -58,12 +58,23 @@ impl AsRef<[u8]> for Hash {
     }
 }
 
+fn encode_base58<'a>(h: &Hash, out: &'a mut [u8; MAX_BASE58_LEN]) -> &'a str {
+    let len = five8::encode_32(&h.0, out) as usize;
+    // any sequence of base58 chars is valid utf8
+    unsafe { from_utf8_unchecked(&out[..len]) }
+}
+
 fn write_as_base58(f: &mut fmt::Formatter, h: &Hash) -> fmt::Result {
     let mut out = [0u8; MAX_BASE58_LEN];
-    let len = five8::encode_32(&h.0, &mut out) as usize;
-    // any sequence of base58 chars is valid utf8
-    let as_str = unsafe { from_utf8_unchecked(&out[..len]) };
-    f.write_str(as_str)
+    f.write_str(encode_base58(h, &mut out))
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Hash {
+    fn format(&self, f: defmt::Formatter) {
+        let mut out = [0u8; MAX_BASE58_LEN];
+        defmt::write!(f, "{=str}", encode_base58(self, &mut out))
+    }
 }
 
 impl fmt::Debug for Hash {
@@ -115,6 +126,23 @@ impl FromStr for Hash {
     }
 }
 
+/// Describes the JSON produced by `Hash`'s serde implementation, an array of
+/// 32 bytes.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Hash {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Hash".into()
+    }
+
+    fn schema_id() -> std::borrow::Cow<'static, str> {
+        "solana_hash::Hash".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        <[u8; HASH_BYTES]>::json_schema(generator)
+    }
+}
+
 impl Hash {
     pub const fn new_from_array(hash_array: [u8; HASH_BYTES]) -> Self {
         Self(hash_array)
@@ -149,6 +177,16 @@ mod tests {
         assert!(Hash::new_unique() != Hash::new_unique());
     }
 
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_json_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(Hash)).unwrap();
+        assert_eq!(schema["title"], "Hash");
+        assert_eq!(schema["type"], "array");
+        assert_eq!(schema["minItems"], HASH_BYTES);
+        assert_eq!(schema["maxItems"], HASH_BYTES);
+    }
+
     #[test]
     fn test_hash_fromstr() {
         let hash = Hash::new_from_array([1; 32]);
@@ -0,0 +1,111 @@
+use {
+    criterion::{criterion_group, criterion_main, Criterion},
+    solana_hash::Hash,
+    solana_instruction::{AccountMeta, Instruction},
+    solana_message::{Message, MessageCompiler},
+    solana_pubkey::Pubkey,
+    std::hint::black_box,
+};
+
+fn make_instructions() -> Vec<Instruction> {
+    (0..4)
+        .map(|_| {
+            let accounts = (0..6)
+                .map(|i| AccountMeta::new(Pubkey::new_unique(), i == 0))
+                .collect();
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[0; 64], accounts)
+        })
+        .collect()
+}
+
+fn bench_compile(c: &mut Criterion) {
+    let instructions = make_instructions();
+    let payer = Pubkey::new_unique();
+    let blockhash = Hash::new_unique();
+
+    c.bench_function("try_new_with_blockhash", |b| {
+        b.iter(|| {
+            Message::try_new_with_blockhash(black_box(&instructions), Some(&payer), &blockhash)
+        })
+    });
+
+    let mut compiler = MessageCompiler::default();
+    let mut message = Message::default();
+    c.bench_function("compile_into", |b| {
+        b.iter(|| {
+            compiler.compile_into(
+                black_box(&instructions),
+                Some(&payer),
+                &blockhash,
+                &mut message,
+            )
+        })
+    });
+
+    // the messages of the benches above are dropped or overwritten, and these
+    // are freed by resetting the arena
+    #[cfg(feature = "bumpalo")]
+    {
+        let mut bump = bumpalo::Bump::new();
+        c.bench_function("compile_in", |b| {
+            b.iter(|| {
+                black_box(
+                    compiler
+                        .compile_in(&bump, black_box(&instructions), Some(&payer), &blockhash)
+                        .unwrap(),
+                );
+                bump.reset();
+            })
+        });
+    }
+}
+
+// the number of messages a batch processor keeps at once
+const BATCH_SIZE: usize = 1000;
+
+fn bench_compile_batch(c: &mut Criterion) {
+    let instructions = make_instructions();
+    let payer = Pubkey::new_unique();
+    let blockhashes: Vec<_> = (0..BATCH_SIZE).map(|_| Hash::new_unique()).collect();
+
+    c.bench_function("batch_try_new_with_blockhash", |b| {
+        b.iter(|| {
+            let batch: Vec<_> = blockhashes
+                .iter()
+                .map(|blockhash| {
+                    let message = Message::try_new_with_blockhash(
+                        black_box(&instructions),
+                        Some(&payer),
+                        blockhash,
+                    )
+                    .unwrap();
+                    let bytes = message.serialize();
+                    (message, bytes)
+                })
+                .collect();
+            black_box(batch);
+        })
+    });
+
+    #[cfg(feature = "bumpalo")]
+    {
+        let mut compiler = MessageCompiler::default();
+        let mut bump = bumpalo::Bump::new();
+        c.bench_function("batch_compile_in", |b| {
+            b.iter(|| {
+                let mut batch = bumpalo::collections::Vec::with_capacity_in(BATCH_SIZE, &bump);
+                batch.extend(blockhashes.iter().map(|blockhash| {
+                    let message = compiler
+                        .compile_in(&bump, black_box(&instructions), Some(&payer), blockhash)
+                        .unwrap();
+                    (message, message.serialize_in(&bump))
+                }));
+                black_box(batch);
+                bump.reset();
+            })
+        });
+    }
+}
+
+criterion_group!(benches, bench_compile, bench_compile_batch);
+criterion_main!(benches);
@@ -0,0 +1,40 @@
+use {
+    criterion::{criterion_group, criterion_main, BenchmarkId, Criterion},
+    solana_message::Message,
+    solana_pubkey::Pubkey,
+    std::hint::black_box,
+};
+
+/// Compare every pair of keys, as `has_duplicates` does for short key lists.
+fn has_duplicates_pairwise(keys: &[Pubkey]) -> bool {
+    keys.iter()
+        .enumerate()
+        .skip(1)
+        .any(|(i, key)| keys[..i].contains(key))
+}
+
+fn bench_has_duplicates(c: &mut Criterion) {
+    let mut group = c.benchmark_group("has_duplicates");
+    for num_keys in [8, 12, 16, 32, 64, 128] {
+        // without duplicates, every key is checked. Random keys, unlike
+        // `Pubkey::new_unique`, differ from their first bytes like real ones
+        let message = Message {
+            account_keys: (0..num_keys).map(|_| solana_pubkey::new_rand()).collect(),
+            ..Message::default()
+        };
+        group.bench_with_input(
+            BenchmarkId::new("pairwise", num_keys),
+            &message,
+            |b, message| b.iter(|| has_duplicates_pairwise(black_box(&message.account_keys))),
+        );
+        group.bench_with_input(
+            BenchmarkId::new("adaptive", num_keys),
+            &message,
+            |b, message| b.iter(|| black_box(message).has_duplicates()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_has_duplicates);
+criterion_main!(benches);
@@ -1,8 +1,15 @@
 use {
-    crate::{compiled_instruction::CompiledInstruction, v0::LoadedAddresses, CompileError},
+    crate::{
+        compiled_instruction::CompiledInstruction, legacy, v0, v0::LoadedAddresses, CompileError,
+        MessageHeader, VersionedMessage,
+    },
     solana_instruction::Instruction,
     solana_pubkey::Pubkey,
-    std::{collections::BTreeMap, iter::zip, ops::Index},
+    std::{
+        collections::{BTreeMap, HashSet},
+        iter::zip,
+        ops::Index,
+    },
 };
 
 /// Collection of static and dynamically loaded keys used to load accounts
@@ -148,6 +155,126 @@ impl PartialEq for AccountKeys<'_> {
     }
 }
 
+/// Account key queries shared by legacy and v0 messages, so that code that
+/// only looks up keys need not match on the message version.
+pub trait MessageAccounts {
+    fn header(&self) -> &MessageHeader;
+
+    /// The account keys stored in the message, without the addresses it loads
+    /// from address lookup tables.
+    fn static_account_keys(&self) -> &[Pubkey];
+
+    /// Returns true if the account at `index` is writable by the
+    /// instructions of the message, as computed by the `is_maybe_writable`
+    /// method of the message's version.
+    fn is_maybe_writable(
+        &self,
+        index: usize,
+        reserved_account_keys: Option<&HashSet<Pubkey>>,
+    ) -> bool;
+
+    /// The static account keys followed by `loaded_addresses`, the addresses
+    /// loaded by the message's address table lookups, in the order that
+    /// compiled instructions index them. Legacy messages load no addresses.
+    fn account_keys<'a>(
+        &'a self,
+        loaded_addresses: Option<&'a LoadedAddresses>,
+    ) -> AccountKeys<'a> {
+        AccountKeys::new(self.static_account_keys(), loaded_addresses)
+    }
+
+    /// The key of the account that pays the fee, if the message has any keys.
+    fn fee_payer(&self) -> Option<&Pubkey> {
+        self.static_account_keys().first()
+    }
+
+    /// Returns true if the account at `index` must sign the message.
+    fn is_signer(&self, index: usize) -> bool {
+        index < usize::from(self.header().num_required_signatures)
+    }
+
+    /// The keys of the accounts that must sign the message, in signature
+    /// order.
+    fn signer_account_keys(&self) -> &[Pubkey] {
+        let keys = self.static_account_keys();
+        let num_signers = usize::from(self.header().num_required_signatures);
+        &keys[..num_signers.min(keys.len())]
+    }
+}
+
+impl MessageAccounts for legacy::Message {
+    fn header(&self) -> &MessageHeader {
+        &self.header
+    }
+
+    fn static_account_keys(&self) -> &[Pubkey] {
+        &self.account_keys
+    }
+
+    fn is_maybe_writable(
+        &self,
+        index: usize,
+        reserved_account_keys: Option<&HashSet<Pubkey>>,
+    ) -> bool {
+        legacy::Message::is_maybe_writable(self, index, reserved_account_keys)
+    }
+}
+
+impl MessageAccounts for v0::Message {
+    fn header(&self) -> &MessageHeader {
+        &self.header
+    }
+
+    fn static_account_keys(&self) -> &[Pubkey] {
+        &self.account_keys
+    }
+
+    fn is_maybe_writable(
+        &self,
+        index: usize,
+        reserved_account_keys: Option<&HashSet<Pubkey>>,
+    ) -> bool {
+        v0::Message::is_maybe_writable(self, index, reserved_account_keys)
+    }
+}
+
+#[cfg(feature = "experimental-v1")]
+impl MessageAccounts for crate::v1::Message {
+    fn header(&self) -> &MessageHeader {
+        &self.header
+    }
+
+    fn static_account_keys(&self) -> &[Pubkey] {
+        &self.account_keys
+    }
+
+    fn is_maybe_writable(
+        &self,
+        index: usize,
+        reserved_account_keys: Option<&HashSet<Pubkey>>,
+    ) -> bool {
+        crate::v1::Message::is_maybe_writable(self, index, reserved_account_keys)
+    }
+}
+
+impl MessageAccounts for VersionedMessage {
+    fn header(&self) -> &MessageHeader {
+        VersionedMessage::header(self)
+    }
+
+    fn static_account_keys(&self) -> &[Pubkey] {
+        VersionedMessage::static_account_keys(self)
+    }
+
+    fn is_maybe_writable(
+        &self,
+        index: usize,
+        reserved_account_keys: Option<&HashSet<Pubkey>>,
+    ) -> bool {
+        VersionedMessage::is_maybe_writable(self, index, reserved_account_keys)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {super::*, solana_instruction::AccountMeta};
@@ -339,4 +466,72 @@ mod tests {
             Err(CompileError::AccountIndexOverflow)
         );
     }
+
+    #[test]
+    fn test_message_accounts() {
+        fn check<M: MessageAccounts>(
+            message: &M,
+            loaded_addresses: Option<&LoadedAddresses>,
+            payer: &Pubkey,
+            loaded_key: &Pubkey,
+            program_id: &Pubkey,
+        ) {
+            assert_eq!(message.fee_payer(), Some(payer));
+            assert_eq!(message.signer_account_keys(), [*payer]);
+            assert!(message.is_signer(0));
+            assert!(!message.is_signer(1));
+            let account_keys = message.account_keys(loaded_addresses);
+            assert_eq!(account_keys.len(), 3);
+            let position = |key| account_keys.iter().position(|k| k == key).unwrap();
+            assert!(message.is_maybe_writable(position(loaded_key), None));
+            assert!(!message.is_maybe_writable(position(program_id), None));
+        }
+
+        let payer = Pubkey::new_unique();
+        let loaded_key = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let instruction =
+            Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(loaded_key, false)]);
+        let legacy_message = legacy::Message::new(&[instruction.clone()], Some(&payer));
+        check(&legacy_message, None, &payer, &loaded_key, &program_id);
+        check(
+            &VersionedMessage::Legacy(legacy_message),
+            None,
+            &payer,
+            &loaded_key,
+            &program_id,
+        );
+
+        let lookup_table = crate::AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![loaded_key],
+        };
+        let v0_message = v0::Message::try_compile(
+            &payer,
+            &[instruction],
+            &[lookup_table],
+            solana_hash::Hash::default(),
+        )
+        .unwrap();
+        let loaded_addresses = LoadedAddresses {
+            writable: vec![loaded_key],
+            readonly: vec![],
+        };
+        assert_eq!(v0_message.account_keys.len(), 2);
+        check(
+            &v0_message,
+            Some(&loaded_addresses),
+            &payer,
+            &loaded_key,
+            &program_id,
+        );
+        check(
+            &VersionedMessage::V0(v0_message),
+            Some(&loaded_addresses),
+            &payer,
+            &loaded_key,
+            &program_id,
+        );
+        assert_eq!(legacy::Message::default().fee_payer(), None);
+    }
 }
@@ -0,0 +1,207 @@
+//! The account locks the runtime takes to process a message.
+use {crate::legacy::Message, solana_pubkey::Pubkey, std::collections::HashSet};
+
+/// The accounts a message locks for writing and for reading, returned by
+/// [`Message::account_locks`].
+///
+/// Both lists are in the order of the message's account keys. Two messages
+/// whose locks conflict cannot be processed in parallel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountLocks<'a> {
+    pub writable: Vec<&'a Pubkey>,
+    pub readonly: Vec<&'a Pubkey>,
+}
+
+impl AccountLocks<'_> {
+    /// Return true if either set of locks writes an account that the other
+    /// reads or writes.
+    pub fn conflicts_with(&self, other: &AccountLocks) -> bool {
+        let writes_locked = |writable: &[&Pubkey], locks: &AccountLocks| {
+            writable
+                .iter()
+                .any(|key| locks.writable.contains(key) || locks.readonly.contains(key))
+        };
+        writes_locked(&self.writable, other) || writes_locked(&other.writable, self)
+    }
+}
+
+pub(crate) fn account_locks<'a>(
+    message: &'a Message,
+    reserved_account_keys: &HashSet<Pubkey>,
+) -> AccountLocks<'a> {
+    let mut locks = AccountLocks {
+        writable: vec![],
+        readonly: vec![],
+    };
+    for (key, is_writable) in message
+        .account_keys
+        .iter()
+        .zip(message.writable_bitset(Some(reserved_account_keys)))
+    {
+        if is_writable {
+            locks.writable.push(key);
+        } else {
+            locks.readonly.push(key);
+        }
+    }
+    locks
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_instruction::{AccountMeta, Instruction},
+        solana_sdk_ids::{bpf_loader_upgradeable, sysvar},
+    };
+
+    #[test]
+    fn test_account_locks() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[],
+            vec![
+                AccountMeta::new(destination, false),
+                AccountMeta::new_readonly(source, false),
+                // reserved keys are locked for reading even if marked writable
+                AccountMeta::new(sysvar::clock::id(), false),
+            ],
+        );
+        let message = Message::new(&[instruction], Some(&payer));
+        let reserved_account_keys = HashSet::from([sysvar::clock::id()]);
+        let locks = message.account_locks(&reserved_account_keys);
+        assert_eq!(locks.writable, [&payer, &destination]);
+        assert_eq!(locks.readonly, [&sysvar::clock::id(), &program_id, &source]);
+        assert_eq!(
+            locks.writable.len() + locks.readonly.len(),
+            message.account_keys.len()
+        );
+
+        // a program invoked as writable is demoted, like the runtime does
+        let mut invoked = message.clone();
+        invoked.header.num_readonly_unsigned_accounts = 0;
+        assert!(invoked.is_writable_index(3));
+        let invoked_locks = invoked.account_locks(&reserved_account_keys);
+        assert_eq!(invoked_locks.writable, [&payer, &destination, &source]);
+        assert_eq!(invoked_locks.readonly, [&sysvar::clock::id(), &program_id]);
+
+        let reader = |key| {
+            let instruction = Instruction::new_with_bytes(
+                program_id,
+                &[],
+                vec![AccountMeta::new_readonly(key, false)],
+            );
+            Message::new(&[instruction], Some(&Pubkey::new_unique()))
+        };
+        let reads_source = reader(source);
+        let reads_destination = reader(destination);
+        let reserved_account_keys = HashSet::new();
+        // reads of the same account do not conflict, but a read and a write do
+        assert!(!locks.conflicts_with(&reads_source.account_locks(&reserved_account_keys)));
+        assert!(locks.conflicts_with(&reads_destination.account_locks(&reserved_account_keys)));
+        assert!(reads_destination
+            .account_locks(&reserved_account_keys)
+            .conflicts_with(&locks));
+    }
+
+    #[test]
+    fn test_account_locks_reserved_signers() {
+        let payer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let instruction =
+            Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(authority, true)]);
+        let message = Message::new(&[instruction], Some(&payer));
+
+        let locks = message.account_locks(&HashSet::new());
+        assert_eq!(locks.writable, [&payer, &authority]);
+        assert_eq!(locks.readonly, [&program_id]);
+
+        // even the fee payer and writable signers are demoted when reserved
+        let reserved_account_keys = HashSet::from([payer, authority]);
+        let locks = message.account_locks(&reserved_account_keys);
+        assert!(locks.writable.is_empty());
+        assert_eq!(locks.readonly, [&payer, &authority, &program_id]);
+        for (index, key) in message.account_keys.iter().enumerate() {
+            assert_eq!(
+                locks.writable.contains(&key),
+                message.is_maybe_writable(index, Some(&reserved_account_keys))
+            );
+        }
+
+        let empty = Message::default();
+        let empty = empty.account_locks(&reserved_account_keys);
+        assert!(empty.writable.is_empty() && empty.readonly.is_empty());
+        assert!(!empty.conflicts_with(&locks));
+    }
+
+    #[test]
+    fn test_account_locks_upgradeable_loader() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        // a program that is invoked and also passed as a writable account
+        let invoke_writable = |accounts: &[AccountMeta]| {
+            let mut accounts = accounts.to_vec();
+            accounts.push(AccountMeta::new(program_id, false));
+            Message::new(
+                &[Instruction::new_with_bytes(program_id, &[], accounts)],
+                Some(&payer),
+            )
+        };
+        let reserved_account_keys = HashSet::new();
+
+        let message = invoke_writable(&[]);
+        let locks = message.account_locks(&reserved_account_keys);
+        assert_eq!(locks.writable, [&payer]);
+        assert_eq!(locks.readonly, [&program_id]);
+
+        // programs stay writable while the upgradeable loader is present, so
+        // that they can be upgraded
+        let loader = bpf_loader_upgradeable::id();
+        let message = invoke_writable(&[AccountMeta::new_readonly(loader, false)]);
+        let locks = message.account_locks(&reserved_account_keys);
+        assert_eq!(locks.writable, [&payer, &program_id]);
+        assert_eq!(locks.readonly, [&loader]);
+        // unless the program is reserved
+        let locks = message.account_locks(&HashSet::from([program_id]));
+        assert_eq!(locks.writable, [&payer]);
+    }
+
+    #[test]
+    fn test_account_locks_duplicate_keys() {
+        let payer = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new(account, false)],
+        );
+        let mut message = Message::new(&[instruction], Some(&payer));
+        message.account_keys.push(account);
+        assert!(message.has_duplicates());
+
+        // each of the message's keys is listed, duplicates included
+        let locks = message.account_locks(&HashSet::new());
+        assert_eq!(
+            locks
+                .writable
+                .iter()
+                .filter(|key| ***key == account)
+                .count(),
+            1
+        );
+        assert_eq!(
+            locks
+                .readonly
+                .iter()
+                .filter(|key| ***key == account)
+                .count(),
+            1
+        );
+        assert!(locks.conflicts_with(&locks));
+    }
+}
@@ -1,6 +1,11 @@
 use {
-    crate::v0::{LoadedAddresses, MessageAddressTableLookup},
+    crate::{
+        v0::{LoadedAddresses, MessageAddressTableLookup},
+        AddressLookupTableAccount,
+    },
+    solana_pubkey::Pubkey,
     solana_transaction_error::AddressLoaderError,
+    std::collections::HashMap,
 };
 
 pub trait AddressLoader: Clone {
@@ -27,3 +32,33 @@ impl AddressLoader for SimpleAddressLoader {
         }
     }
 }
+
+/// Supplies the addresses stored in address lookup tables, for example from
+/// RPC, a cache or a snapshot, for [`v0::Message::resolve`].
+///
+/// [`v0::Message::resolve`]: crate::v0::Message::resolve
+pub trait AddressLookupTableProvider {
+    /// Return the addresses stored in the table at `table_key`, or `None` if
+    /// there is no such table.
+    fn lookup_table_addresses(&self, table_key: &Pubkey) -> Option<&[Pubkey]>;
+}
+
+impl AddressLookupTableProvider for [AddressLookupTableAccount] {
+    fn lookup_table_addresses(&self, table_key: &Pubkey) -> Option<&[Pubkey]> {
+        self.iter()
+            .find(|table| table.key == *table_key)
+            .map(|table| table.addresses.as_slice())
+    }
+}
+
+impl AddressLookupTableProvider for Vec<AddressLookupTableAccount> {
+    fn lookup_table_addresses(&self, table_key: &Pubkey) -> Option<&[Pubkey]> {
+        self.as_slice().lookup_table_addresses(table_key)
+    }
+}
+
+impl<S: std::hash::BuildHasher> AddressLookupTableProvider for HashMap<Pubkey, Vec<Pubkey>, S> {
+    fn lookup_table_addresses(&self, table_key: &Pubkey) -> Option<&[Pubkey]> {
+        self.get(table_key).map(Vec::as_slice)
+    }
+}
@@ -0,0 +1,273 @@
+//! A builder that adds the compute budget and durable nonce instructions of a
+//! message in the order the runtime requires.
+#[cfg(not(target_os = "solana"))]
+use crate::{v0, AddressLookupTableAccount};
+use {
+    crate::{
+        inline_compute_budget::{
+            compute_unit_limit_data, compute_unit_price_data, is_compute_budget_program,
+            parse_compute_unit_limit, parse_compute_unit_price,
+        },
+        inline_nonce::advance_nonce_account_instruction,
+        CompileError, Message, SizeBreakdown,
+    },
+    core::fmt,
+    solana_hash::Hash,
+    solana_instruction::Instruction,
+    solana_pubkey::Pubkey,
+    solana_sdk_ids::compute_budget,
+};
+
+/// The error returned when building a message with [`MessageBuilder`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageBuildError {
+    /// The instructions could not be compiled into a message
+    Compile(CompileError),
+    /// A transaction with the message and its signatures would not fit in a
+    /// packet, broken down by section
+    TooLarge(SizeBreakdown),
+}
+
+impl core::error::Error for MessageBuildError {}
+
+impl fmt::Display for MessageBuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MessageBuildError::Compile(err) => err.fmt(f),
+            MessageBuildError::TooLarge(breakdown) => f.write_fmt(format_args!(
+                "transaction of {} bytes does not fit in a packet",
+                breakdown.total()
+            )),
+        }
+    }
+}
+
+impl From<CompileError> for MessageBuildError {
+    fn from(err: CompileError) -> Self {
+        Self::Compile(err)
+    }
+}
+
+/// Builds a message from instructions, adding its `SetComputeUnitLimit`,
+/// `SetComputeUnitPrice` and `AdvanceNonceAccount` instructions.
+///
+/// The nonce is advanced by the first instruction, as the runtime requires of
+/// durable nonce transactions, and the compute budget instructions follow it.
+/// Compute budget instructions among the added instructions that set the same
+/// value are dropped, since the runtime rejects transactions that set a value
+/// twice. Building fails instead of returning a message that does not fit in
+/// a packet.
+///
+/// ```
+/// # use solana_hash::Hash;
+/// # use solana_instruction::Instruction;
+/// # use solana_message::MessageBuilder;
+/// # use solana_pubkey::Pubkey;
+/// let payer = Pubkey::new_unique();
+/// let nonce = Pubkey::new_unique();
+/// let message = MessageBuilder::new(payer)
+///     .instruction(Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]))
+///     .compute_budget(300_000, 1_000)
+///     .durable_nonce(nonce, payer, Hash::new_unique())
+///     .build()?;
+///
+/// assert_eq!(message.get_durable_nonce(), Some(&nonce));
+/// assert_eq!(message.find_compute_unit_limit(), Some((1, 300_000)));
+/// assert_eq!(message.find_compute_unit_price(), Some((2, 1_000)));
+/// # Ok::<(), solana_message::MessageBuildError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageBuilder {
+    payer: Pubkey,
+    instructions: Vec<Instruction>,
+    recent_blockhash: Hash,
+    nonce: Option<(Pubkey, Pubkey)>,
+    compute_unit_limit: Option<u32>,
+    compute_unit_price: Option<u64>,
+}
+
+impl MessageBuilder {
+    /// A builder for a message with `payer` as the fee payer and no
+    /// instructions.
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            instructions: Vec::new(),
+            recent_blockhash: Hash::default(),
+            nonce: None,
+            compute_unit_limit: None,
+            compute_unit_price: None,
+        }
+    }
+
+    /// Add an instruction after the instructions added before it.
+    pub fn instruction(mut self, instruction: Instruction) -> Self {
+        self.instructions.push(instruction);
+        self
+    }
+
+    /// Add instructions after the instructions added before them.
+    pub fn instructions(mut self, instructions: impl IntoIterator<Item = Instruction>) -> Self {
+        self.instructions.extend(instructions);
+        self
+    }
+
+    /// Set the recent blockhash of the message.
+    pub fn recent_blockhash(mut self, recent_blockhash: Hash) -> Self {
+        self.recent_blockhash = recent_blockhash;
+        self
+    }
+
+    /// Advance the durable nonce of `nonce_account` in the first instruction,
+    /// signed by `nonce_authority`, and use `durable_nonce`, the nonce
+    /// account's current nonce, as the recent blockhash.
+    pub fn durable_nonce(
+        mut self,
+        nonce_account: Pubkey,
+        nonce_authority: Pubkey,
+        durable_nonce: Hash,
+    ) -> Self {
+        self.nonce = Some((nonce_account, nonce_authority));
+        self.recent_blockhash = durable_nonce;
+        self
+    }
+
+    /// Set the compute unit limit of the message.
+    pub fn compute_unit_limit(mut self, units: u32) -> Self {
+        self.compute_unit_limit = Some(units);
+        self
+    }
+
+    /// Set the compute unit price of the message, in micro-lamports.
+    pub fn compute_unit_price(mut self, micro_lamports: u64) -> Self {
+        self.compute_unit_price = Some(micro_lamports);
+        self
+    }
+
+    /// Set both the compute unit limit and price of the message.
+    pub fn compute_budget(self, units: u32, micro_lamports: u64) -> Self {
+        self.compute_unit_limit(units)
+            .compute_unit_price(micro_lamports)
+    }
+
+    fn all_instructions(&self) -> Vec<Instruction> {
+        let compute_budget_instruction =
+            |data: Vec<u8>| Instruction::new_with_bytes(compute_budget::id(), &data, vec![]);
+        let is_replaced = |instruction: &Instruction| {
+            is_compute_budget_program(&instruction.program_id)
+                && ((self.compute_unit_limit.is_some()
+                    && parse_compute_unit_limit(&instruction.data).is_some())
+                    || (self.compute_unit_price.is_some()
+                        && parse_compute_unit_price(&instruction.data).is_some()))
+        };
+        self.nonce
+            .map(|(nonce_account, nonce_authority)| {
+                advance_nonce_account_instruction(&nonce_account, &nonce_authority)
+            })
+            .into_iter()
+            .chain(
+                self.compute_unit_limit
+                    .map(|units| compute_budget_instruction(compute_unit_limit_data(units))),
+            )
+            .chain(self.compute_unit_price.map(|micro_lamports| {
+                compute_budget_instruction(compute_unit_price_data(micro_lamports))
+            }))
+            .chain(
+                self.instructions
+                    .iter()
+                    .filter(|instruction| !is_replaced(instruction))
+                    .cloned(),
+            )
+            .collect()
+    }
+
+    /// Build a legacy message.
+    pub fn build(&self) -> Result<Message, MessageBuildError> {
+        let message = Message::try_new_with_blockhash(
+            &self.all_instructions(),
+            Some(&self.payer),
+            &self.recent_blockhash,
+        )?;
+        if !message.fits_in_packet() {
+            return Err(MessageBuildError::TooLarge(message.size_breakdown()));
+        }
+        Ok(message)
+    }
+
+    /// Build a v0 message that loads the keys it can from
+    /// `address_lookup_table_accounts`, like [`v0::Message::try_compile`].
+    #[cfg(not(target_os = "solana"))]
+    pub fn build_v0(
+        &self,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<v0::Message, MessageBuildError> {
+        let message = v0::Message::try_compile(
+            &self.payer,
+            &self.all_instructions(),
+            address_lookup_table_accounts,
+            self.recent_blockhash,
+        )?;
+        if !message.fits_in_packet() {
+            return Err(MessageBuildError::TooLarge(message.size_breakdown()));
+        }
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*, solana_compute_budget_interface::ComputeBudgetInstruction,
+        solana_instruction::AccountMeta,
+    };
+
+    #[test]
+    fn test_message_builder() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(program_id, &[1], vec![]);
+        let blockhash = Hash::new_unique();
+
+        let message = MessageBuilder::new(payer)
+            .instruction(instruction.clone())
+            .recent_blockhash(blockhash)
+            .build()
+            .unwrap();
+        assert_eq!(
+            message,
+            Message::new_with_blockhash(&[instruction.clone()], Some(&payer), &blockhash)
+        );
+
+        // the compute unit limit of the instructions is replaced, and the
+        // price is kept since the builder does not set one
+        let builder = MessageBuilder::new(payer)
+            .instructions([
+                ComputeBudgetInstruction::set_compute_unit_limit(1),
+                instruction.clone(),
+                ComputeBudgetInstruction::set_compute_unit_price(5),
+            ])
+            .compute_unit_limit(200_000);
+        let message = builder.build().unwrap();
+        assert_eq!(message.instructions.len(), 3);
+        assert_eq!(message.find_compute_unit_limit(), Some((0, 200_000)));
+        assert_eq!(message.find_compute_unit_price(), Some((2, 5)));
+        let message = builder.compute_unit_price(7).build_v0(&[]).unwrap();
+        assert_eq!(message.instructions.len(), 3);
+        assert_eq!(
+            message.instructions[1].data,
+            ComputeBudgetInstruction::set_compute_unit_price(7).data
+        );
+
+        let accounts = (0..40)
+            .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+            .collect();
+        let result = MessageBuilder::new(payer)
+            .instruction(Instruction::new_with_bytes(program_id, &[], accounts))
+            .compute_budget(1, 1)
+            .build();
+        let Err(MessageBuildError::TooLarge(breakdown)) = result else {
+            panic!("expected an oversized message, got {result:?}");
+        };
+        assert!(breakdown.total() > solana_packet::PACKET_DATA_SIZE);
+    }
+}
@@ -0,0 +1,114 @@
+//! Legacy messages allocated in a [`Bump`] arena, for batch processors that
+//! build and discard many messages.
+//!
+//! A [`Message`] here has the same contents as a [`crate::Message`], but
+//! borrows its account keys and instructions from the arena instead of
+//! owning vectors. Building one with [`MessageCompiler::compile_in`] and
+//! serializing it with [`Message::serialize_in`] never touches the global
+//! allocator once the compiler has grown, and resetting the arena frees every
+//! message at once.
+//!
+//! [`MessageCompiler::compile_in`]: crate::MessageCompiler::compile_in
+use {
+    crate::{short_vec_len, MessageHeader, MESSAGE_HEADER_LENGTH},
+    bumpalo::{collections::Vec as BumpVec, Bump},
+    solana_hash::{Hash, HASH_BYTES},
+    solana_pubkey::{Pubkey, PUBKEY_BYTES},
+    solana_short_vec::encode_shortu16_len,
+};
+
+/// A [`crate::compiled_instruction::CompiledInstruction`] allocated in an
+/// arena.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CompiledInstruction<'bump> {
+    pub program_id_index: u8,
+    pub accounts: &'bump [u8],
+    pub data: &'bump [u8],
+}
+
+/// A legacy [`crate::Message`] allocated in an arena, returned by
+/// [`MessageCompiler::compile_in`].
+///
+/// [`MessageCompiler::compile_in`]: crate::MessageCompiler::compile_in
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Message<'bump> {
+    pub header: MessageHeader,
+    pub account_keys: &'bump [Pubkey],
+    pub recent_blockhash: Hash,
+    pub instructions: &'bump [CompiledInstruction<'bump>],
+}
+
+impl<'bump> Message<'bump> {
+    /// Return the length of [`Message::serialize_in`]'s output.
+    pub fn serialized_size(&self) -> usize {
+        self.instructions.iter().fold(
+            MESSAGE_HEADER_LENGTH
+                .saturating_add(short_vec_len(self.account_keys.len()))
+                .saturating_add(self.account_keys.len().saturating_mul(PUBKEY_BYTES))
+                .saturating_add(HASH_BYTES)
+                .saturating_add(short_vec_len(self.instructions.len())),
+            |size, ix| {
+                size.saturating_add(1)
+                    .saturating_add(short_vec_len(ix.accounts.len()))
+                    .saturating_add(ix.accounts.len())
+                    .saturating_add(short_vec_len(ix.data.len()))
+                    .saturating_add(ix.data.len())
+            },
+        )
+    }
+
+    /// Serialize this message into `bump`, in the same wire format as
+    /// `crate::Message::serialize`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message has more account keys or instructions, or an
+    /// instruction has more accounts or data bytes, than a `u16` can count,
+    /// like `crate::Message::serialize`.
+    pub fn serialize_in<'a>(&self, bump: &'a Bump) -> &'a [u8] {
+        let mut bytes = BumpVec::with_capacity_in(self.serialized_size(), bump);
+        bytes.extend_from_slice(&[
+            self.header.num_required_signatures,
+            self.header.num_readonly_signed_accounts,
+            self.header.num_readonly_unsigned_accounts,
+        ]);
+        write_len(&mut bytes, self.account_keys.len());
+        for key in self.account_keys {
+            bytes.extend_from_slice(key.as_ref());
+        }
+        bytes.extend_from_slice(self.recent_blockhash.as_ref());
+        write_len(&mut bytes, self.instructions.len());
+        for ix in self.instructions {
+            bytes.push(ix.program_id_index);
+            write_len(&mut bytes, ix.accounts.len());
+            bytes.extend_from_slice(ix.accounts);
+            write_len(&mut bytes, ix.data.len());
+            bytes.extend_from_slice(ix.data);
+        }
+        bytes.into_bump_slice()
+    }
+
+    /// Copy this message out of the arena.
+    pub fn to_message(&self) -> crate::Message {
+        crate::Message {
+            header: self.header,
+            account_keys: self.account_keys.to_vec(),
+            recent_blockhash: self.recent_blockhash,
+            instructions: self
+                .instructions
+                .iter()
+                .map(|ix| crate::compiled_instruction::CompiledInstruction {
+                    program_id_index: ix.program_id_index,
+                    accounts: ix.accounts.to_vec(),
+                    data: ix.data.to_vec(),
+                })
+                .collect(),
+        }
+    }
+}
+
+fn write_len(bytes: &mut BumpVec<u8>, len: usize) {
+    let len = u16::try_from(len).expect("length larger than u16");
+    let (encoded, encoded_len) = encode_shortu16_len(len);
+    bytes.extend_from_slice(&encoded[..encoded_len]);
+}
@@ -1,8 +1,10 @@
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
 #[cfg(feature = "serde")]
 use serde_derive::{Deserialize, Serialize};
 #[cfg(feature = "frozen-abi")]
 use solana_frozen_abi_macro::AbiExample;
-use {solana_pubkey::Pubkey, solana_sanitize::Sanitize};
+use {crate::short_vec_len, solana_pubkey::Pubkey, solana_sanitize::Sanitize};
 
 /// A compact encoding of an instruction.
 ///
@@ -10,8 +12,17 @@ use {solana_pubkey::Pubkey, solana_sanitize::Sanitize};
 /// which is the core of a Solana transaction. It is created during the
 /// construction of `Message`. Most users will not interact with it directly.
 ///
+/// Like `Message`, its [`borsh`] serialization with the `borsh` feature is
+/// not the wire format.
+///
 /// [`Message`]: crate::Message
+/// [`borsh`]: https://docs.rs/borsh/latest/borsh/
 #[cfg_attr(feature = "frozen-abi", derive(AbiExample))]
+#[cfg_attr(
+    feature = "borsh",
+    derive(BorshSerialize, BorshDeserialize),
+    borsh(crate = "borsh")
+)]
 #[cfg_attr(
     feature = "serde",
     derive(Deserialize, Serialize),
@@ -53,4 +64,13 @@ impl CompiledInstruction {
     pub fn program_id<'a>(&self, program_ids: &'a [Pubkey]) -> &'a Pubkey {
         &program_ids[self.program_id_index as usize]
     }
+
+    /// The number of bytes this instruction takes up in a serialized message.
+    pub(crate) fn serialized_size(&self) -> usize {
+        1usize
+            .saturating_add(short_vec_len(self.accounts.len()))
+            .saturating_add(self.accounts.len())
+            .saturating_add(short_vec_len(self.data.len()))
+            .saturating_add(self.data.len())
+    }
 }
@@ -1,32 +1,144 @@
-#[cfg(not(target_os = "solana"))]
-use crate::{
-    v0::{LoadedAddresses, MessageAddressTableLookup},
-    AddressLookupTableAccount,
-};
 use {
-    crate::{inline_nonce::is_advance_nonce_instruction_data, MessageHeader},
+    crate::{inline_nonce::is_advance_nonce_instruction_data, AccountRole, MessageHeader},
     core::fmt,
-    solana_instruction::Instruction,
+    solana_instruction::{AccountMeta, Instruction},
     solana_pubkey::Pubkey,
+    solana_sanitize::SanitizeError,
     solana_sdk_ids::system_program,
     std::collections::BTreeMap,
 };
+#[cfg(not(target_os = "solana"))]
+use {
+    crate::{
+        short_vec_len,
+        v0::{LoadedAddresses, MessageAddressTableLookup},
+        AddressLookupTableAccount,
+    },
+    solana_pubkey::PUBKEY_BYTES,
+};
 
-/// A helper struct to collect pubkeys compiled for a set of instructions
+/// The account keys referenced by a set of instructions, with the role each
+/// key needs in a message.
+///
+/// This is the first step of compiling instructions into a message, and can be
+/// used to control how account indexes are assigned. Keys are always
+/// deduplicated, with each key given the most privileged role it is
+/// referenced with, since the runtime rejects messages that list a key twice.
+/// Within the message's account keys, the payer comes first and keys are
+/// grouped into writable signers, readonly signers, writable non-signers and
+/// readonly non-signers, as the message header requires. The order of keys
+/// within each group, and of keys loaded from lookup tables, is set by
+/// [`KeyOrdering`].
+///
+/// ```
+/// # use solana_hash::Hash;
+/// # use solana_instruction::{AccountMeta, Instruction};
+/// # use solana_message::{CompiledKeys, KeyOrdering, Message};
+/// # use solana_pubkey::Pubkey;
+/// let payer = Pubkey::new_unique();
+/// let (first, second) = (Pubkey::new_from_array([2; 32]), Pubkey::new_from_array([1; 32]));
+/// let instruction = Instruction::new_with_bytes(
+///     Pubkey::new_unique(),
+///     &[],
+///     vec![AccountMeta::new(first, false), AccountMeta::new(second, false)],
+/// );
+///
+/// let instructions = [instruction];
+/// let compiled_keys =
+///     CompiledKeys::compile_with_ordering(&instructions, Some(payer), KeyOrdering::FirstUse);
+/// let message =
+///     Message::try_new_with_compiled_keys(compiled_keys, &instructions, &Hash::default())?;
+/// assert_eq!(message.account_keys[1..3], [first, second]);
+/// # Ok::<(), solana_message::CompileError>(())
+/// ```
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
-pub(crate) struct CompiledKeys {
+pub struct CompiledKeys {
     payer: Option<Pubkey>,
     key_meta_map: BTreeMap<Pubkey, CompiledKeyMeta>,
 }
 
+/// The order of account keys within each group of a compiled message's keys,
+/// see [`CompiledKeys`].
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOrdering {
+    /// Sorted by key, as [`Message::new`] and [`v0::Message::try_compile`] do
+    ///
+    /// [`Message::new`]: crate::Message::new
+    /// [`v0::Message::try_compile`]: crate::v0::Message::try_compile
+    #[default]
+    Sorted,
+    /// In the order keys are first referenced: each instruction's program id
+    /// and then its accounts, in instruction order
+    FirstUse,
+}
+
 #[cfg_attr(target_os = "solana", allow(dead_code))]
 #[derive(PartialEq, Debug, Eq, Clone)]
 pub enum CompileError {
     AccountIndexOverflow,
     AddressTableLookupIndexOverflow,
     UnknownInstructionKey(Pubkey),
+    /// The message being changed is not sanitized, so its header does not
+    /// describe its account keys
+    InvalidMessage(SanitizeError),
 }
 
+/// A [`CompileError`] with hints for fixing a v0 message that references too
+/// many account keys.
+///
+/// Returned by [`v0::Message::try_compile_detailed`], and convertible into
+/// the corresponding [`CompileError`].
+///
+/// [`v0::Message::try_compile_detailed`]: crate::v0::Message::try_compile_detailed
+#[cfg_attr(target_os = "solana", allow(dead_code))]
+#[derive(PartialEq, Debug, Eq, Clone)]
+pub enum DetailedCompileError {
+    AccountIndexOverflow,
+    AddressTableLookupIndexOverflow,
+    UnknownInstructionKey(Pubkey),
+    /// The message being changed is not sanitized, so its header does not
+    /// describe its account keys
+    InvalidMessage(SanitizeError),
+    /// The message would index more account keys than fit in a `u8`
+    TooManyAccountKeys(AccountKeysOverflow),
+}
+
+/// The account keys of a v0 message that could not be compiled because its
+/// instructions reference more than 256 keys, returned in
+/// [`DetailedCompileError::TooManyAccountKeys`].
+///
+/// Keys loaded from address lookup tables are indexed like static keys, so
+/// they count towards the limit too. Loading the lookup eligible keys from
+/// tables makes the message smaller, but only removing
+/// [`AccountKeysOverflow::num_excess_keys`] keys from the instructions lets
+/// it compile.
+#[derive(PartialEq, Debug, Eq, Clone)]
+pub struct AccountKeysOverflow {
+    /// The number of keys stored in the message
+    pub num_static_keys: usize,
+    /// The number of keys loaded from lookup tables
+    pub num_loaded_keys: usize,
+    /// The static keys that lookup tables could load instead: those that are
+    /// not signers, program ids or the nonce account of a durable nonce
+    /// transaction
+    pub lookup_eligible_keys: Vec<Pubkey>,
+}
+
+impl AccountKeysOverflow {
+    /// The number of keys the message references, static and loaded.
+    pub fn num_keys(&self) -> usize {
+        self.num_static_keys.saturating_add(self.num_loaded_keys)
+    }
+
+    /// The number of keys that must be removed for the message to compile.
+    pub fn num_excess_keys(&self) -> usize {
+        self.num_keys().saturating_sub(MAX_ACCOUNT_KEYS)
+    }
+}
+
+// the number of account indexes that fit in a u8
+const MAX_ACCOUNT_KEYS: usize = 256;
+
 impl core::error::Error for CompileError {}
 
 impl fmt::Display for CompileError {
@@ -41,6 +153,63 @@ impl fmt::Display for CompileError {
             CompileError::UnknownInstructionKey(key) => f.write_fmt(format_args!(
                 "encountered unknown account key `{key}` during instruction compilation",
             )),
+            CompileError::InvalidMessage(err) => {
+                f.write_fmt(format_args!("message is not sanitized: {err}"))
+            }
+        }
+    }
+}
+
+impl From<CompileError> for DetailedCompileError {
+    fn from(error: CompileError) -> Self {
+        match error {
+            CompileError::AccountIndexOverflow => Self::AccountIndexOverflow,
+            CompileError::AddressTableLookupIndexOverflow => Self::AddressTableLookupIndexOverflow,
+            CompileError::UnknownInstructionKey(key) => Self::UnknownInstructionKey(key),
+            CompileError::InvalidMessage(err) => Self::InvalidMessage(err),
+        }
+    }
+}
+
+impl From<DetailedCompileError> for CompileError {
+    fn from(error: DetailedCompileError) -> Self {
+        match error {
+            DetailedCompileError::AccountIndexOverflow
+            | DetailedCompileError::TooManyAccountKeys(_) => Self::AccountIndexOverflow,
+            DetailedCompileError::AddressTableLookupIndexOverflow => {
+                Self::AddressTableLookupIndexOverflow
+            }
+            DetailedCompileError::UnknownInstructionKey(key) => Self::UnknownInstructionKey(key),
+            DetailedCompileError::InvalidMessage(err) => Self::InvalidMessage(err),
+        }
+    }
+}
+
+impl core::error::Error for DetailedCompileError {}
+
+impl fmt::Display for DetailedCompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DetailedCompileError::TooManyAccountKeys(overflow) => f.write_fmt(format_args!(
+                "{} account keys exceed the {MAX_ACCOUNT_KEYS} a message can index, {} must be \
+                 removed; {} static keys could instead be loaded from lookup tables to shrink \
+                 the message",
+                overflow.num_keys(),
+                overflow.num_excess_keys(),
+                overflow.lookup_eligible_keys.len(),
+            )),
+            DetailedCompileError::AccountIndexOverflow => {
+                fmt::Display::fmt(&CompileError::AccountIndexOverflow, f)
+            }
+            DetailedCompileError::AddressTableLookupIndexOverflow => {
+                fmt::Display::fmt(&CompileError::AddressTableLookupIndexOverflow, f)
+            }
+            DetailedCompileError::UnknownInstructionKey(key) => {
+                fmt::Display::fmt(&CompileError::UnknownInstructionKey(*key), f)
+            }
+            DetailedCompileError::InvalidMessage(err) => {
+                fmt::Display::fmt(&CompileError::InvalidMessage(err.clone()), f)
+            }
         }
     }
 }
@@ -51,28 +220,64 @@ struct CompiledKeyMeta {
     is_writable: bool,
     is_invoked: bool,
     is_nonce: bool,
+    /// The position of the key's first use with [`KeyOrdering::FirstUse`],
+    /// and 0 otherwise
+    first_use: usize,
+}
+
+impl CompiledKeyMeta {
+    /// Whether the key can be loaded from an address lookup table rather
+    /// than stored in the message.
+    fn is_lookup_eligible(&self) -> bool {
+        !self.is_signer && !self.is_invoked && !self.is_nonce
+    }
 }
 
 impl CompiledKeys {
     /// Compiles the pubkeys referenced by a list of instructions and organizes by
     /// signer/non-signer and writable/readonly.
-    pub(crate) fn compile(instructions: &[Instruction], payer: Option<Pubkey>) -> Self {
+    pub fn compile(instructions: &[Instruction], payer: Option<Pubkey>) -> Self {
+        Self::compile_with_ordering(instructions, payer, KeyOrdering::Sorted)
+    }
+
+    /// Compiles the pubkeys referenced by a list of instructions like
+    /// [`CompiledKeys::compile`], ordering them with `ordering`.
+    pub fn compile_with_ordering(
+        instructions: &[Instruction],
+        payer: Option<Pubkey>,
+        ordering: KeyOrdering,
+    ) -> Self {
+        fn key_meta(
+            key_meta_map: &mut BTreeMap<Pubkey, CompiledKeyMeta>,
+            key: Pubkey,
+            ordering: KeyOrdering,
+        ) -> &mut CompiledKeyMeta {
+            let first_use = match ordering {
+                KeyOrdering::Sorted => 0,
+                KeyOrdering::FirstUse => key_meta_map.len(),
+            };
+            key_meta_map.entry(key).or_insert_with(|| CompiledKeyMeta {
+                first_use,
+                ..CompiledKeyMeta::default()
+            })
+        }
+
         let mut key_meta_map = BTreeMap::<Pubkey, CompiledKeyMeta>::new();
         for ix in instructions {
-            let meta = key_meta_map.entry(ix.program_id).or_default();
+            let meta = key_meta(&mut key_meta_map, ix.program_id, ordering);
             meta.is_invoked = true;
             for account_meta in &ix.accounts {
-                let meta = key_meta_map.entry(account_meta.pubkey).or_default();
+                let meta = key_meta(&mut key_meta_map, account_meta.pubkey, ordering);
                 meta.is_signer |= account_meta.is_signer;
                 meta.is_writable |= account_meta.is_writable;
             }
         }
         if let Some(nonce_pubkey) = get_nonce_pubkey(instructions) {
-            let meta = key_meta_map.entry(*nonce_pubkey).or_default();
+            let meta = key_meta(&mut key_meta_map, *nonce_pubkey, ordering);
             meta.is_nonce = true;
         }
         if let Some(payer) = &payer {
-            let meta = key_meta_map.entry(*payer).or_default();
+            let meta = key_meta(&mut key_meta_map, *payer, ordering);
             meta.is_signer = true;
             meta.is_writable = true;
         }
@@ -82,75 +287,76 @@ impl CompiledKeys {
         }
     }
 
-    pub(crate) fn try_into_message_components(
-        self,
-    ) -> Result<(MessageHeader, Vec<Pubkey>), CompileError> {
-        let try_into_u8 = |num: usize| -> Result<u8, CompileError> {
-            u8::try_from(num).map_err(|_| CompileError::AccountIndexOverflow)
-        };
-
-        let Self {
-            payer,
-            mut key_meta_map,
-        } = self;
+    /// The keys in the order set by the [`KeyOrdering`] they were compiled
+    /// with.
+    fn ordered_keys(&self) -> Vec<(&Pubkey, &CompiledKeyMeta)> {
+        let mut keys: Vec<_> = self.key_meta_map.iter().collect();
+        // stable, so keys with the same `first_use` stay sorted by key
+        keys.sort_by_key(|(_, meta)| meta.first_use);
+        keys
+    }
 
+    /// Build the header and static account keys of a message from these
+    /// keys.
+    ///
+    /// Fails with [`CompileError::AccountIndexOverflow`] if a section of the
+    /// header would not fit in a `u8`.
+    pub fn try_into_message_components(
+        mut self,
+    ) -> Result<(MessageHeader, Vec<Pubkey>), CompileError> {
+        let payer = self.payer;
         if let Some(payer) = &payer {
-            key_meta_map.remove_entry(payer);
+            self.key_meta_map.remove_entry(payer);
         }
-
-        let writable_signer_keys: Vec<Pubkey> = payer
-            .into_iter()
-            .chain(
-                key_meta_map
-                    .iter()
-                    .filter_map(|(key, meta)| (meta.is_signer && meta.is_writable).then_some(*key)),
-            )
-            .collect();
-        let readonly_signer_keys: Vec<Pubkey> = key_meta_map
-            .iter()
-            .filter_map(|(key, meta)| (meta.is_signer && !meta.is_writable).then_some(*key))
-            .collect();
-        let writable_non_signer_keys: Vec<Pubkey> = key_meta_map
-            .iter()
-            .filter_map(|(key, meta)| (!meta.is_signer && meta.is_writable).then_some(*key))
-            .collect();
-        let readonly_non_signer_keys: Vec<Pubkey> = key_meta_map
-            .iter()
-            .filter_map(|(key, meta)| (!meta.is_signer && !meta.is_writable).then_some(*key))
-            .collect();
-
-        let signers_len = writable_signer_keys
-            .len()
-            .saturating_add(readonly_signer_keys.len());
-
-        let header = MessageHeader {
-            num_required_signatures: try_into_u8(signers_len)?,
-            num_readonly_signed_accounts: try_into_u8(readonly_signer_keys.len())?,
-            num_readonly_unsigned_accounts: try_into_u8(readonly_non_signer_keys.len())?,
+        let ordered_keys = self.ordered_keys();
+
+        // one pass per section of the header into a single allocation,
+        // instead of a vector per section
+        let num_payers = usize::from(payer.is_some());
+        let mut static_account_keys =
+            Vec::with_capacity(ordered_keys.len().saturating_add(num_payers));
+        static_account_keys.extend(payer);
+        let mut extend_section = |is_signer: bool, is_writable: bool| {
+            let len = static_account_keys.len();
+            static_account_keys.extend(ordered_keys.iter().filter_map(|(key, meta)| {
+                (meta.is_signer == is_signer && meta.is_writable == is_writable).then_some(**key)
+            }));
+            static_account_keys.len().saturating_sub(len)
         };
+        let num_writable_signers = extend_section(true, true).saturating_add(num_payers);
+        let num_readonly_signers = extend_section(true, false);
+        extend_section(false, true);
+        let num_readonly_non_signers = extend_section(false, false);
 
-        let static_account_keys = std::iter::empty()
-            .chain(writable_signer_keys)
-            .chain(readonly_signer_keys)
-            .chain(writable_non_signer_keys)
-            .chain(readonly_non_signer_keys)
-            .collect();
+        let header = MessageHeader::try_from_counts(
+            num_writable_signers.saturating_add(num_readonly_signers),
+            num_readonly_signers,
+            num_readonly_non_signers,
+        )?;
 
         Ok((header, static_account_keys))
     }
 
+    /// Move the keys that can be loaded from `lookup_table_account` out of
+    /// these keys, returning the lookup that loads them, or `None` if the
+    /// table has none of them.
+    ///
+    /// Signers, program ids and the nonce account of a durable nonce
+    /// transaction cannot be loaded from lookup tables. Fails with
+    /// [`CompileError::AddressTableLookupIndexOverflow`] if a key's index in
+    /// the table does not fit in a `u8`.
     #[cfg(not(target_os = "solana"))]
-    pub(crate) fn try_extract_table_lookup(
+    pub fn try_extract_table_lookup(
         &mut self,
         lookup_table_account: &AddressLookupTableAccount,
     ) -> Result<Option<(MessageAddressTableLookup, LoadedAddresses)>, CompileError> {
         let (writable_indexes, drained_writable_keys) = self
             .try_drain_keys_found_in_lookup_table(&lookup_table_account.addresses, |meta| {
-                !meta.is_signer && !meta.is_invoked && !meta.is_nonce && meta.is_writable
+                meta.is_lookup_eligible() && meta.is_writable
             })?;
         let (readonly_indexes, drained_readonly_keys) = self
             .try_drain_keys_found_in_lookup_table(&lookup_table_account.addresses, |meta| {
-                !meta.is_signer && !meta.is_invoked && !meta.is_nonce && !meta.is_writable
+                meta.is_lookup_eligible() && !meta.is_writable
             })?;
 
         // Don't extract lookup if no keys were found
@@ -171,6 +377,32 @@ impl CompiledKeys {
         )))
     }
 
+    /// Fail with [`DetailedCompileError::TooManyAccountKeys`] if these keys
+    /// and `num_loaded_keys` keys loaded from lookup tables cannot all be
+    /// indexed by a `u8`.
+    #[cfg(not(target_os = "solana"))]
+    pub(crate) fn check_num_keys(
+        &self,
+        num_loaded_keys: usize,
+    ) -> Result<(), DetailedCompileError> {
+        let num_static_keys = self.key_meta_map.len();
+        if num_static_keys.saturating_add(num_loaded_keys) <= MAX_ACCOUNT_KEYS {
+            return Ok(());
+        }
+        let lookup_eligible_keys = self
+            .ordered_keys()
+            .into_iter()
+            .filter_map(|(key, meta)| meta.is_lookup_eligible().then_some(*key))
+            .collect();
+        Err(DetailedCompileError::TooManyAccountKeys(
+            AccountKeysOverflow {
+                num_static_keys,
+                num_loaded_keys,
+                lookup_eligible_keys,
+            },
+        ))
+    }
+
     #[cfg(not(target_os = "solana"))]
     fn try_drain_keys_found_in_lookup_table(
         &mut self,
@@ -181,8 +413,8 @@ impl CompiledKeys {
         let mut drained_keys = Vec::new();
 
         for search_key in self
-            .key_meta_map
-            .iter()
+            .ordered_keys()
+            .into_iter()
             .filter_map(|(key, meta)| key_meta_filter(meta).then_some(key))
         {
             for (key_index, key) in lookup_table_addresses.iter().enumerate() {
@@ -203,6 +435,192 @@ impl CompiledKeys {
 
         Ok((lookup_table_indexes, drained_keys))
     }
+
+    /// Choose the lookup tables that minimize the serialized size of the
+    /// compiled message, returning their indexes in ascending order.
+    ///
+    /// Every subset is tried if there are at most
+    /// `MAX_EXHAUSTIVE_LOOKUP_TABLES` tables, otherwise tables are added
+    /// greedily while they shrink the message.
+    #[cfg(not(target_os = "solana"))]
+    pub(crate) fn select_lookup_tables(
+        &self,
+        lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Vec<usize> {
+        // each key that could be loaded, with whether it is writable and the
+        // tables it could be loaded from
+        let loadable_keys: Vec<(bool, Vec<usize>)> = self
+            .key_meta_map
+            .iter()
+            .filter(|(_, meta)| !meta.is_signer && !meta.is_invoked && !meta.is_nonce)
+            .map(|(key, meta)| {
+                let tables = lookup_table_accounts
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, table)| {
+                        table
+                            .addresses
+                            .iter()
+                            .position(|address| address == key)
+                            .is_some_and(|index| u8::try_from(index).is_ok())
+                    })
+                    .map(|(table_index, _)| table_index)
+                    .collect();
+                (meta.is_writable, tables)
+            })
+            .collect();
+
+        let size =
+            |selected: &[bool]| lookup_size(&loadable_keys, selected, lookup_table_accounts.len());
+        let num_tables = lookup_table_accounts.len();
+        let mut selected = vec![false; num_tables];
+        if num_tables <= MAX_EXHAUSTIVE_LOOKUP_TABLES {
+            let mut best = (size(&selected), 0);
+            for mask in 1..1u32.wrapping_shl(num_tables as u32) {
+                let candidate: Vec<bool> = (0..num_tables)
+                    .map(|i| mask.wrapping_shr(i as u32) & 1 != 0)
+                    .collect();
+                // prefer fewer tables on ties
+                let score = (size(&candidate), mask.count_ones());
+                if score < best {
+                    best = score;
+                    selected = candidate;
+                }
+            }
+        } else {
+            let mut best_size = size(&selected);
+            loop {
+                let mut best_addition = None;
+                for i in 0..num_tables {
+                    if selected[i] {
+                        continue;
+                    }
+                    selected[i] = true;
+                    let size = size(&selected);
+                    selected[i] = false;
+                    if !matches!(best_addition, Some((best, _)) if best <= size) {
+                        best_addition = Some((size, i));
+                    }
+                }
+                match best_addition {
+                    Some((size, i)) if size < best_size => {
+                        best_size = size;
+                        selected[i] = true;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        (0..num_tables).filter(|i| selected[*i]).collect()
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+const MAX_EXHAUSTIVE_LOOKUP_TABLES: usize = 12;
+
+/// The serialized size of the loadable keys if the `selected` tables are used,
+/// counting keys left static and the address table lookups. Like
+/// [`CompiledKeys::try_extract_table_lookup`], each key is loaded from the first
+/// selected table that contains it.
+#[cfg(not(target_os = "solana"))]
+fn lookup_size(
+    loadable_keys: &[(bool, Vec<usize>)],
+    selected: &[bool],
+    num_tables: usize,
+) -> usize {
+    let mut static_size = 0usize;
+    // writable and readonly counts per table
+    let mut counts = vec![(0usize, 0usize); num_tables];
+    for (is_writable, tables) in loadable_keys {
+        match tables.iter().find(|table_index| selected[**table_index]) {
+            Some(&table_index) if *is_writable => {
+                counts[table_index].0 = counts[table_index].0.saturating_add(1)
+            }
+            Some(&table_index) => counts[table_index].1 = counts[table_index].1.saturating_add(1),
+            None => static_size = static_size.saturating_add(PUBKEY_BYTES),
+        }
+    }
+    let used_tables = counts
+        .iter()
+        .filter(|(writable, readonly)| *writable > 0 || *readonly > 0);
+    used_tables
+        .clone()
+        .map(|(writable, readonly)| {
+            PUBKEY_BYTES
+                .saturating_add(short_vec_len(*writable))
+                .saturating_add(*writable)
+                .saturating_add(short_vec_len(*readonly))
+                .saturating_add(*readonly)
+        })
+        .fold(
+            static_size.saturating_add(short_vec_len(used_tables.count())),
+            usize::saturating_add,
+        )
+}
+
+impl MessageHeader {
+    /// Build the header of a message whose accounts are `account_metas`.
+    ///
+    /// Metas of the same key are merged like [`CompiledKeys`] merges them, so
+    /// a key is a signer if any of its metas is a signer, and writable if any
+    /// is writable. The fee payer must be included as a writable signer, and
+    /// program ids as read-only non-signers, to count them.
+    ///
+    /// Fails with [`CompileError::AccountIndexOverflow`] if a section of the
+    /// header would not fit in a `u8`.
+    ///
+    /// ```
+    /// # use solana_instruction::AccountMeta;
+    /// # use solana_message::MessageHeader;
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let account = Pubkey::new_unique();
+    /// let header = MessageHeader::try_from_account_metas(&[
+    ///     AccountMeta::new(payer, true),
+    ///     AccountMeta::new_readonly(account, false),
+    ///     AccountMeta::new_readonly(account, true),
+    /// ])?;
+    /// assert_eq!(
+    ///     header,
+    ///     MessageHeader {
+    ///         num_required_signatures: 2,
+    ///         num_readonly_signed_accounts: 1,
+    ///         num_readonly_unsigned_accounts: 0,
+    ///     }
+    /// );
+    /// # Ok::<(), solana_message::CompileError>(())
+    /// ```
+    pub fn try_from_account_metas(account_metas: &[AccountMeta]) -> Result<Self, CompileError> {
+        let mut roles = BTreeMap::<&Pubkey, AccountRole>::new();
+        for meta in account_metas {
+            let role = roles.entry(&meta.pubkey).or_default();
+            role.is_signer |= meta.is_signer;
+            role.is_writable |= meta.is_writable;
+        }
+        let num_signers = roles.values().filter(|role| role.is_signer).count();
+        let num_readonly = |is_signer: bool| {
+            roles
+                .values()
+                .filter(|role| role.is_signer == is_signer && !role.is_writable)
+                .count()
+        };
+        Self::try_from_counts(num_signers, num_readonly(true), num_readonly(false))
+    }
+
+    pub(crate) fn try_from_counts(
+        num_required_signatures: usize,
+        num_readonly_signed_accounts: usize,
+        num_readonly_unsigned_accounts: usize,
+    ) -> Result<Self, CompileError> {
+        let try_into_u8 = |num: usize| -> Result<u8, CompileError> {
+            u8::try_from(num).map_err(|_| CompileError::AccountIndexOverflow)
+        };
+        Ok(Self {
+            num_required_signatures: try_into_u8(num_required_signatures)?,
+            num_readonly_signed_accounts: try_into_u8(num_readonly_signed_accounts)?,
+            num_readonly_unsigned_accounts: try_into_u8(num_readonly_unsigned_accounts)?,
+        })
+    }
 }
 
 // inlined to avoid solana_nonce dep
@@ -224,7 +642,10 @@ fn get_nonce_pubkey(instructions: &[Instruction]) -> Option<&Pubkey> {
 #[cfg(test)]
 mod tests {
     use {
-        super::*, bitflags::bitflags, solana_instruction::AccountMeta,
+        super::*,
+        crate::{v0, Message},
+        bitflags::bitflags,
+        solana_hash::Hash,
         solana_sdk_ids::sysvar::recent_blockhashes,
         solana_system_interface::instruction::advance_nonce_account,
     };
@@ -251,6 +672,7 @@ mod tests {
                 is_writable: flags.contains(KeyFlags::WRITABLE),
                 is_invoked: flags.contains(KeyFlags::INVOKED),
                 is_nonce: flags.contains(KeyFlags::NONCE),
+                first_use: 0,
             }
         }
     }
@@ -510,6 +932,54 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_header_try_from_account_metas() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let instructions = [
+            Instruction::new_with_bytes(
+                program_id,
+                &[],
+                vec![
+                    AccountMeta::new_readonly(keys[0], true),
+                    AccountMeta::new_readonly(keys[1], false),
+                    AccountMeta::new(keys[0], false),
+                ],
+            ),
+            Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(payer, false)]),
+        ];
+        let account_metas: Vec<_> = std::iter::once(AccountMeta::new(payer, true))
+            .chain(instructions.iter().flat_map(|ix| {
+                std::iter::once(AccountMeta::new_readonly(ix.program_id, false))
+                    .chain(ix.accounts.iter().cloned())
+            }))
+            .collect();
+        let (header, _) = CompiledKeys::compile(&instructions, Some(payer))
+            .try_into_message_components()
+            .unwrap();
+        assert_eq!(
+            MessageHeader::try_from_account_metas(&account_metas),
+            Ok(header)
+        );
+        assert_eq!(
+            header,
+            MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 2,
+            }
+        );
+
+        let too_many_signers: Vec<_> = (0..256)
+            .map(|_| AccountMeta::new(Pubkey::new_unique(), true))
+            .collect();
+        assert_eq!(
+            MessageHeader::try_from_account_metas(&too_many_signers),
+            Err(CompileError::AccountIndexOverflow)
+        );
+    }
+
     #[test]
     fn test_try_extract_table_lookup() {
         let keys = vec![
@@ -724,4 +1194,160 @@ mod tests {
             Some(CompileError::AddressTableLookupIndexOverflow)
         );
     }
+
+    #[test]
+    fn test_compile_with_ordering() {
+        let payer = Pubkey::new_unique();
+        let keys = [5, 4, 3, 2, 1].map(|byte| Pubkey::new_from_array([byte; 32]));
+        let program_id = Pubkey::new_from_array([9; 32]);
+        let table_keys = [
+            Pubkey::new_from_array([8; 32]),
+            Pubkey::new_from_array([7; 32]),
+        ];
+        let instructions = [
+            Instruction::new_with_bytes(
+                program_id,
+                &[],
+                vec![
+                    AccountMeta::new(keys[0], true),
+                    AccountMeta::new_readonly(keys[1], false),
+                    AccountMeta::new(keys[2], false),
+                    AccountMeta::new(table_keys[0], false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &[],
+                vec![
+                    AccountMeta::new(keys[3], false),
+                    AccountMeta::new(table_keys[1], false),
+                    // a repeated key keeps its first position and its most
+                    // privileged role
+                    AccountMeta::new(keys[1], false),
+                ],
+            ),
+        ];
+
+        let sorted = CompiledKeys::compile(&instructions, Some(payer));
+        assert_eq!(
+            sorted,
+            CompiledKeys::compile_with_ordering(&instructions, Some(payer), KeyOrdering::Sorted)
+        );
+        let (_, static_keys) = sorted.try_into_message_components().unwrap();
+        assert_eq!(
+            static_keys,
+            [
+                payer,
+                keys[0],
+                keys[3],
+                keys[2],
+                keys[1],
+                table_keys[1],
+                table_keys[0],
+                program_id
+            ]
+        );
+
+        let first_use =
+            CompiledKeys::compile_with_ordering(&instructions, Some(payer), KeyOrdering::FirstUse);
+        let (header, static_keys) = first_use.clone().try_into_message_components().unwrap();
+        assert_eq!(
+            static_keys,
+            [
+                payer,
+                keys[0],
+                keys[1],
+                keys[2],
+                table_keys[0],
+                keys[3],
+                table_keys[1],
+                program_id
+            ]
+        );
+        let message =
+            Message::try_new_with_compiled_keys(first_use.clone(), &instructions, &Hash::default())
+                .unwrap();
+        assert_eq!(message.header, header);
+        assert_eq!(message.account_keys, static_keys);
+        for (decompiled, instruction) in message.decompile_instructions().zip(&instructions) {
+            let decompiled = decompiled.unwrap();
+            assert_eq!(decompiled.program_id, instruction.program_id);
+            assert!(decompiled
+                .accounts
+                .iter()
+                .map(|meta| meta.pubkey)
+                .eq(instruction.accounts.iter().map(|meta| meta.pubkey)));
+        }
+
+        // loaded keys follow the ordering too
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: table_keys.to_vec(),
+        };
+        let (message, loaded_addresses) =
+            v0::Message::try_compile_with_keys(first_use, &instructions, &[table], Hash::default())
+                .unwrap();
+        assert_eq!(loaded_addresses.writable, table_keys);
+        assert_eq!(message.address_table_lookups[0].writable_indexes, [0, 1]);
+
+        assert_eq!(
+            Message::try_new_with_compiled_keys(
+                CompiledKeys::compile(&instructions[..1], Some(payer)),
+                &instructions,
+                &Hash::default(),
+            ),
+            Err(CompileError::UnknownInstructionKey(keys[3]))
+        );
+    }
+
+    #[test]
+    fn test_v0_compile_too_many_account_keys() {
+        let payer = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let accounts: Vec<Pubkey> = (0..256).map(|_| Pubkey::new_unique()).collect();
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[],
+            accounts
+                .iter()
+                .map(|key| AccountMeta::new_readonly(*key, false))
+                .chain([AccountMeta::new_readonly(signer, true)])
+                .collect(),
+        );
+        let table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: accounts[..200].to_vec(),
+        };
+
+        // the error is only detailed where asked for
+        assert_eq!(
+            v0::Message::try_compile(
+                &payer,
+                &[instruction.clone()],
+                &[table.clone()],
+                Hash::default()
+            ),
+            Err(CompileError::AccountIndexOverflow)
+        );
+        let err =
+            v0::Message::try_compile_detailed(&payer, &[instruction], &[table], Hash::default())
+                .unwrap_err();
+        let DetailedCompileError::TooManyAccountKeys(overflow) = &err else {
+            panic!("expected too many account keys, got {err:?}");
+        };
+        assert_eq!(overflow.num_static_keys, 59);
+        assert_eq!(overflow.num_loaded_keys, 200);
+        assert_eq!(overflow.num_keys(), 259);
+        assert_eq!(overflow.num_excess_keys(), 3);
+        // the payer, signer and program id must stay static
+        let mut eligible_keys = accounts[200..].to_vec();
+        eligible_keys.sort();
+        assert_eq!(overflow.lookup_eligible_keys, eligible_keys);
+        assert_eq!(
+            err.to_string(),
+            "259 account keys exceed the 256 a message can index, 3 must be removed; 56 static \
+             keys could instead be loaded from lookup tables to shrink the message"
+        );
+    }
 }
@@ -0,0 +1,390 @@
+//! Compilation of legacy messages into reused buffers, for callers that build
+//! many messages.
+#[cfg(feature = "bumpalo")]
+use {
+    crate::bump,
+    bumpalo::{collections::Vec as BumpVec, Bump},
+};
+use {
+    crate::{
+        compiled_instruction::CompiledInstruction, AccountRole, CompileError, Message,
+        MessageHeader,
+    },
+    solana_hash::Hash,
+    solana_instruction::Instruction,
+    solana_pubkey::Pubkey,
+};
+
+#[derive(Debug, Clone, Copy)]
+struct KeyEntry {
+    key: Pubkey,
+    role: AccountRole,
+    /// The key's index in the compiled message's account keys
+    index: usize,
+}
+
+/// Compiles instructions into legacy messages like
+/// [`Message::try_new_with_blockhash`], reusing buffers instead of allocating
+/// new ones for every message.
+///
+/// The compiler keeps the scratch space it deduplicates keys in, and
+/// [`MessageCompiler::compile_into`] overwrites a message in place, reusing
+/// the capacity of its account keys and of its instructions' account indexes
+/// and data. Once a compiler and message have held a message as large as the
+/// next one, compiling it does not allocate, where `try_new_with_blockhash`
+/// allocates a map node per key and new vectors for every instruction. In
+/// `benches/compile.rs`, this halves the time to compile a message of four
+/// instructions.
+///
+/// ```
+/// # use solana_hash::Hash;
+/// # use solana_instruction::{AccountMeta, Instruction};
+/// # use solana_message::{Message, MessageCompiler};
+/// # use solana_pubkey::Pubkey;
+/// let payer = Pubkey::new_unique();
+/// let mut compiler = MessageCompiler::default();
+/// let mut message = Message::default();
+/// for data in 0..3u8 {
+///     let instruction = Instruction::new_with_bytes(
+///         Pubkey::new_unique(),
+///         &[data],
+///         vec![AccountMeta::new(Pubkey::new_unique(), false)],
+///     );
+///     let blockhash = Hash::new_unique();
+///     compiler.compile_into(&[instruction.clone()], Some(&payer), &blockhash, &mut message)?;
+///     assert_eq!(
+///         message,
+///         Message::new_with_blockhash(&[instruction], Some(&payer), &blockhash)
+///     );
+/// }
+/// # Ok::<(), solana_message::CompileError>(())
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MessageCompiler {
+    keys: Vec<KeyEntry>,
+}
+
+impl MessageCompiler {
+    /// Compile `instructions` into `message`, replacing its contents, so that
+    /// it equals the message [`Message::try_new_with_blockhash`] returns.
+    ///
+    /// Fails like `try_new_with_blockhash`, in which case the contents of
+    /// `message` are unspecified.
+    pub fn compile_into(
+        &mut self,
+        instructions: &[Instruction],
+        payer: Option<&Pubkey>,
+        recent_blockhash: &Hash,
+        message: &mut Message,
+    ) -> Result<(), CompileError> {
+        self.collect_keys(instructions, payer);
+        message.header = self.header()?;
+        message.recent_blockhash = *recent_blockhash;
+
+        let account_keys = &mut message.account_keys;
+        account_keys.clear();
+        self.assign_indexes(payer, |key| account_keys.push(key))?;
+
+        message.instructions.truncate(instructions.len());
+        let num_reused = message.instructions.len();
+        for (i, ix) in instructions.iter().enumerate() {
+            let program_id_index = self.index_of(&ix.program_id)?;
+            if i >= num_reused {
+                message.instructions.push(CompiledInstruction {
+                    program_id_index,
+                    accounts: Vec::with_capacity(ix.accounts.len()),
+                    data: Vec::new(),
+                });
+            }
+            let compiled = &mut message.instructions[i];
+            compiled.program_id_index = program_id_index;
+            compiled.accounts.clear();
+            for account_meta in &ix.accounts {
+                compiled.accounts.push(self.index_of(&account_meta.pubkey)?);
+            }
+            compiled.data.clear();
+            compiled.data.extend_from_slice(&ix.data);
+        }
+        Ok(())
+    }
+
+    /// Compile `instructions` into a message allocated in `bump`, equal to
+    /// the message [`Message::try_new_with_blockhash`] returns. See
+    /// [`bump::Message`].
+    ///
+    /// Like [`MessageCompiler::compile_into`], this does not allocate from
+    /// the global allocator once the compiler has held as many keys. Unlike
+    /// it, the messages stay valid until the arena is reset, so a batch of
+    /// them can be kept at once. In `benches/compile.rs`, compiling a message
+    /// this way takes about a quarter less time than
+    /// [`Message::try_new_with_blockhash`], and compiling and serializing a
+    /// batch of 1000 messages kept at once takes about a fifth less time.
+    ///
+    /// ```
+    /// # use bumpalo::Bump;
+    /// # use solana_hash::Hash;
+    /// # use solana_instruction::Instruction;
+    /// # use solana_message::{Message, MessageCompiler};
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let instructions = [Instruction::new_with_bytes(Pubkey::new_unique(), &[1], vec![])];
+    /// let mut compiler = MessageCompiler::default();
+    /// let mut bump = Bump::new();
+    /// for _ in 0..3 {
+    ///     let blockhash = Hash::new_unique();
+    ///     let message = compiler.compile_in(&bump, &instructions, Some(&payer), &blockhash)?;
+    ///     assert_eq!(
+    ///         message.to_message(),
+    ///         Message::new_with_blockhash(&instructions, Some(&payer), &blockhash)
+    ///     );
+    ///     // frees the message
+    ///     bump.reset();
+    /// }
+    /// # Ok::<(), solana_message::CompileError>(())
+    /// ```
+    #[cfg(feature = "bumpalo")]
+    pub fn compile_in<'bump>(
+        &mut self,
+        bump: &'bump Bump,
+        instructions: &[Instruction],
+        payer: Option<&Pubkey>,
+        recent_blockhash: &Hash,
+    ) -> Result<bump::Message<'bump>, CompileError> {
+        self.collect_keys(instructions, payer);
+        let header = self.header()?;
+        let mut account_keys = BumpVec::with_capacity_in(self.keys.len(), bump);
+        self.assign_indexes(payer, |key| account_keys.push(key))?;
+
+        let mut compiled_instructions = BumpVec::with_capacity_in(instructions.len(), bump);
+        for ix in instructions {
+            let program_id_index = self.index_of(&ix.program_id)?;
+            let mut accounts = BumpVec::with_capacity_in(ix.accounts.len(), bump);
+            for account_meta in &ix.accounts {
+                accounts.push(self.index_of(&account_meta.pubkey)?);
+            }
+            compiled_instructions.push(bump::CompiledInstruction {
+                program_id_index,
+                accounts: accounts.into_bump_slice(),
+                data: bump.alloc_slice_copy(&ix.data),
+            });
+        }
+        Ok(bump::Message {
+            header,
+            account_keys: account_keys.into_bump_slice(),
+            recent_blockhash: *recent_blockhash,
+            instructions: compiled_instructions.into_bump_slice(),
+        })
+    }
+
+    /// Assign the collected keys their indexes in the compiled message,
+    /// passing each key to `push` in index order: the payer, then the keys of
+    /// each section of the header in key order.
+    fn assign_indexes(
+        &mut self,
+        payer: Option<&Pubkey>,
+        mut push: impl FnMut(Pubkey),
+    ) -> Result<(), CompileError> {
+        let mut next_index = 0usize;
+        let mut assign_index = |entry: &mut KeyEntry| {
+            entry.index = next_index;
+            next_index = next_index.saturating_add(1);
+            push(entry.key);
+        };
+        if let Some(payer) = payer {
+            let position = self.position(payer)?;
+            assign_index(&mut self.keys[position]);
+        }
+        for is_signer in [true, false] {
+            for is_writable in [true, false] {
+                let role = AccountRole {
+                    is_signer,
+                    is_writable,
+                };
+                self.keys
+                    .iter_mut()
+                    .filter(|entry| entry.role == role && Some(&entry.key) != payer)
+                    .for_each(&mut assign_index);
+            }
+        }
+        Ok(())
+    }
+
+    /// Collect the keys of `instructions` and `payer`, sorted and
+    /// deduplicated, each with the most privileged role it is used with.
+    fn collect_keys(&mut self, instructions: &[Instruction], payer: Option<&Pubkey>) {
+        let entry = |key: Pubkey, is_signer: bool, is_writable: bool| KeyEntry {
+            key,
+            role: AccountRole {
+                is_signer,
+                is_writable,
+            },
+            index: 0,
+        };
+        self.keys.clear();
+        for ix in instructions {
+            self.keys.push(entry(ix.program_id, false, false));
+            self.keys.extend(
+                ix.accounts
+                    .iter()
+                    .map(|meta| entry(meta.pubkey, meta.is_signer, meta.is_writable)),
+            );
+        }
+        self.keys
+            .extend(payer.map(|payer| entry(*payer, true, true)));
+        self.keys.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+        // `duplicate` follows `kept` and is removed
+        self.keys.dedup_by(|duplicate, kept| {
+            let is_duplicate = duplicate.key == kept.key;
+            if is_duplicate {
+                kept.role.is_signer |= duplicate.role.is_signer;
+                kept.role.is_writable |= duplicate.role.is_writable;
+            }
+            is_duplicate
+        });
+    }
+
+    fn header(&self) -> Result<MessageHeader, CompileError> {
+        let count =
+            |f: fn(&AccountRole) -> bool| self.keys.iter().filter(|entry| f(&entry.role)).count();
+        MessageHeader::try_from_counts(
+            count(|role| role.is_signer),
+            count(|role| role.is_signer && !role.is_writable),
+            count(|role| !role.is_signer && !role.is_writable),
+        )
+    }
+
+    fn position(&self, key: &Pubkey) -> Result<usize, CompileError> {
+        self.keys
+            .binary_search_by(|entry| entry.key.cmp(key))
+            .map_err(|_| CompileError::UnknownInstructionKey(*key))
+    }
+
+    fn index_of(&self, key: &Pubkey) -> Result<u8, CompileError> {
+        let index = self.keys[self.position(key)?].index;
+        u8::try_from(index).map_err(|_| CompileError::AccountIndexOverflow)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_instruction::AccountMeta};
+
+    #[test]
+    fn test_compile_into() {
+        let payer = Pubkey::new_unique();
+        let shared = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let mut compiler = MessageCompiler::default();
+        let mut message = Message::default();
+
+        // roles are merged across instructions, and the payer is promoted to
+        // a writable signer
+        let instructions = [
+            Instruction::new_with_bytes(
+                program_id,
+                &[1, 2, 3],
+                vec![
+                    AccountMeta::new_readonly(shared, false),
+                    AccountMeta::new_readonly(Pubkey::new_unique(), true),
+                    AccountMeta::new(Pubkey::new_unique(), true),
+                    AccountMeta::new_readonly(payer, false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[4],
+                vec![
+                    AccountMeta::new(shared, false),
+                    AccountMeta::new_readonly(program_id, false),
+                ],
+            ),
+        ];
+        compiler
+            .compile_into(&instructions, Some(&payer), &blockhash, &mut message)
+            .unwrap();
+        assert_eq!(
+            message,
+            Message::new_with_blockhash(&instructions, Some(&payer), &blockhash)
+        );
+
+        // fewer, smaller instructions reuse the message's buffers
+        let keys_capacity = message.account_keys.capacity();
+        compiler
+            .compile_into(&instructions[1..], None, &blockhash, &mut message)
+            .unwrap();
+        assert_eq!(
+            message,
+            Message::new_with_blockhash(&instructions[1..], None, &blockhash)
+        );
+        assert_eq!(message.account_keys.capacity(), keys_capacity);
+        compiler
+            .compile_into(&[], Some(&payer), &Hash::default(), &mut message)
+            .unwrap();
+        assert_eq!(message, Message::new(&[], Some(&payer)));
+
+        let too_many_accounts = (0..256)
+            .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+            .collect();
+        let instruction = Instruction::new_with_bytes(program_id, &[], too_many_accounts);
+        assert_eq!(
+            compiler.compile_into(
+                &[instruction.clone()],
+                Some(&payer),
+                &blockhash,
+                &mut message
+            ),
+            Message::try_new_with_blockhash(&[instruction], Some(&payer), &blockhash).map(|_| ())
+        );
+    }
+
+    #[cfg(feature = "bumpalo")]
+    #[test]
+    fn test_compile_in() {
+        let payer = Pubkey::new_unique();
+        let shared = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let instructions = [
+            Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[1, 2, 3],
+                vec![
+                    AccountMeta::new_readonly(shared, true),
+                    AccountMeta::new(Pubkey::new_unique(), false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[0; 200],
+                vec![AccountMeta::new(shared, false)],
+            ),
+        ];
+        let expected = Message::new_with_blockhash(&instructions, Some(&payer), &blockhash);
+        let mut compiler = MessageCompiler::default();
+        let bump = Bump::new();
+
+        let message = compiler
+            .compile_in(&bump, &instructions, Some(&payer), &blockhash)
+            .unwrap();
+        assert_eq!(message.to_message(), expected);
+        assert_eq!(message.serialized_size(), expected.serialized_size());
+        assert_eq!(message.serialize_in(&bump), expected.serialize());
+
+        let empty = compiler
+            .compile_in(&bump, &[], None, &Hash::default())
+            .unwrap();
+        assert_eq!(empty.to_message(), Message::default());
+        assert_eq!(empty.serialize_in(&bump), Message::default().serialize());
+
+        let too_many_accounts = (0..256)
+            .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+            .collect();
+        let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], too_many_accounts);
+        assert_eq!(
+            compiler
+                .compile_in(&bump, &[instruction.clone()], Some(&payer), &blockhash)
+                .map(|_| ()),
+            Message::try_new_with_blockhash(&[instruction], Some(&payer), &blockhash).map(|_| ())
+        );
+    }
+}
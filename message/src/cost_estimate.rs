@@ -0,0 +1,215 @@
+//! The cost, in compute units, that the validator cost model charges a
+//! transaction for its signatures, write locks and instruction data.
+use {
+    crate::{compiled_instruction::CompiledInstruction, MessageHeader},
+    solana_pubkey::Pubkey,
+    solana_sdk_ids::{ed25519_program, secp256k1_program, secp256r1_program},
+};
+
+// inlined to avoid solana_cost_model dep
+const COMPUTE_UNIT_TO_US_RATIO: u64 = 30;
+
+/// The per-item costs of the validator cost model, passed to `cost_estimate`
+/// on messages.
+///
+/// The default costs are those of the validator's `block_cost_limits`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostModelParams {
+    /// The cost of each transaction signature
+    pub signature_cost: u64,
+    /// The cost of each signature verified by the secp256k1 program
+    pub secp256k1_verify_cost: u64,
+    /// The cost of each signature verified by the ed25519 program
+    pub ed25519_verify_cost: u64,
+    /// The cost of each signature verified by the secp256r1 program
+    pub secp256r1_verify_cost: u64,
+    /// The cost of each write lock
+    pub write_lock_cost: u64,
+    /// The number of instruction data bytes that cost one compute unit
+    pub instruction_data_bytes_per_unit: u64,
+}
+
+impl Default for CostModelParams {
+    fn default() -> Self {
+        Self {
+            signature_cost: COMPUTE_UNIT_TO_US_RATIO * 24,
+            secp256k1_verify_cost: COMPUTE_UNIT_TO_US_RATIO * 223,
+            // the cost with `ed25519_precompile_verify_strict` active
+            ed25519_verify_cost: COMPUTE_UNIT_TO_US_RATIO * 80,
+            secp256r1_verify_cost: COMPUTE_UNIT_TO_US_RATIO * 160,
+            write_lock_cost: COMPUTE_UNIT_TO_US_RATIO * 10,
+            instruction_data_bytes_per_unit: 140 / COMPUTE_UNIT_TO_US_RATIO,
+        }
+    }
+}
+
+/// The cost of a transaction that the validator cost model computes from its
+/// message, returned by `cost_estimate` on messages.
+///
+/// Execution cost, which depends on the programs a transaction invokes and
+/// its compute unit limit, is not included.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CostEstimate {
+    /// The cost of the transaction's signatures and the signatures verified
+    /// by precompiles
+    pub signature_cost: u64,
+    /// The cost of the write locks the runtime takes for the transaction,
+    /// after reserved accounts and program ids are demoted
+    pub write_lock_cost: u64,
+    /// The cost of the transaction's instruction data
+    pub data_bytes_cost: u64,
+}
+
+impl CostEstimate {
+    pub(crate) fn new(
+        params: &CostModelParams,
+        header: &MessageHeader,
+        account_keys: &[Pubkey],
+        instructions: &[CompiledInstruction],
+        num_write_locks: u64,
+    ) -> Self {
+        let mut signature_cost =
+            u64::from(header.num_required_signatures).saturating_mul(params.signature_cost);
+        let mut data_bytes = 0u64;
+        for ix in instructions {
+            data_bytes = data_bytes.saturating_add(ix.data.len() as u64);
+            let Some(program_id) = account_keys.get(usize::from(ix.program_id_index)) else {
+                continue;
+            };
+            let verify_cost = if secp256k1_program::check_id(program_id) {
+                params.secp256k1_verify_cost
+            } else if ed25519_program::check_id(program_id) {
+                params.ed25519_verify_cost
+            } else if secp256r1_program::check_id(program_id) {
+                params.secp256r1_verify_cost
+            } else {
+                continue;
+            };
+            // the first data byte of a precompile instruction is its
+            // signature count
+            let num_signatures = u64::from(ix.data.first().copied().unwrap_or_default());
+            signature_cost =
+                signature_cost.saturating_add(num_signatures.saturating_mul(verify_cost));
+        }
+        Self {
+            signature_cost,
+            write_lock_cost: num_write_locks.saturating_mul(params.write_lock_cost),
+            data_bytes_cost: data_bytes
+                .checked_div(params.instruction_data_bytes_per_unit)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The total cost, in compute units.
+    pub fn total(&self) -> u64 {
+        self.signature_cost
+            .saturating_add(self.write_lock_cost)
+            .saturating_add(self.data_bytes_cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{v0, Message, VersionedMessage},
+        solana_hash::Hash,
+        solana_instruction::{AccountMeta, Instruction},
+        std::collections::HashSet,
+    };
+
+    #[test]
+    fn test_cost_estimate() {
+        let payer = Pubkey::new_unique();
+        let params = CostModelParams::default();
+        let no_reserved_keys = HashSet::new();
+        let signer = Pubkey::new_unique();
+        let transfer = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[0; 12],
+            vec![
+                AccountMeta::new(signer, true),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            ],
+        );
+        let message = Message::new(&[transfer.clone()], Some(&payer));
+        assert_eq!(message.num_write_locks(&no_reserved_keys), 2);
+        let cost = message.cost_estimate(&params, &no_reserved_keys);
+        assert_eq!(
+            cost,
+            CostEstimate {
+                signature_cost: 2 * 720,
+                write_lock_cost: 2 * 300,
+                data_bytes_cost: 3,
+            }
+        );
+        assert_eq!(cost.total(), 2_043);
+
+        // reserved accounts are demoted to readonly and not charged
+        let reserved_keys = HashSet::from([signer]);
+        assert_eq!(message.num_write_locks(&reserved_keys), 1);
+        assert_eq!(
+            message
+                .cost_estimate(&params, &reserved_keys)
+                .write_lock_cost,
+            300
+        );
+
+        // so are writable program ids
+        let program_id = Pubkey::new_unique();
+        let invoke_writable_program =
+            Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(program_id, false)]);
+        let message = Message::new(&[invoke_writable_program], Some(&payer));
+        assert_eq!(message.num_write_locks(&no_reserved_keys), 1);
+
+        // precompile signatures are charged their verification cost
+        let instructions = [
+            Instruction::new_with_bytes(secp256k1_program::id(), &[2, 0, 0], vec![]),
+            Instruction::new_with_bytes(ed25519_program::id(), &[1], vec![]),
+            transfer.clone(),
+        ];
+        let message = Message::new(&instructions, Some(&payer));
+        assert_eq!(
+            message
+                .cost_estimate(&params, &no_reserved_keys)
+                .signature_cost,
+            2 * 720 + 2 * 6_690 + 2_400
+        );
+
+        // v0 messages also count the writable keys they load
+        let loaded_key = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new(loaded_key, false)],
+        );
+        let message = VersionedMessage::V0(
+            v0::Message::try_compile(
+                &payer,
+                &[instruction],
+                &[crate::AddressLookupTableAccount {
+                    key: Pubkey::new_unique(),
+                    addresses: vec![loaded_key],
+                }],
+                Hash::default(),
+            )
+            .unwrap(),
+        );
+        assert_eq!(message.num_write_locks(&no_reserved_keys), 2);
+        assert_eq!(
+            message.cost_estimate(
+                &CostModelParams {
+                    write_lock_cost: 1,
+                    instruction_data_bytes_per_unit: 0,
+                    ..params
+                },
+                &no_reserved_keys
+            ),
+            CostEstimate {
+                signature_cost: 720,
+                write_lock_cost: 2,
+                data_bytes_cost: 0,
+            }
+        );
+    }
+}
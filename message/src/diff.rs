@@ -0,0 +1,251 @@
+//! Differences between two legacy messages.
+use {
+    crate::{legacy::Message, MessageHeader},
+    core::fmt,
+    solana_hash::Hash,
+    solana_instruction::Instruction,
+    solana_pubkey::Pubkey,
+};
+
+/// The differences between two messages, returned by [`Message::diff`].
+///
+/// The `Display` implementation lists one difference per line and is meant
+/// for test failures and logs.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MessageDiff {
+    /// The old and new header, if they differ
+    pub header: Option<(MessageHeader, MessageHeader)>,
+    /// The old and new recent blockhash, if they differ
+    pub recent_blockhash: Option<(Hash, Hash)>,
+    /// Keys only in the new message
+    pub added_keys: Vec<Pubkey>,
+    /// Keys only in the old message
+    pub removed_keys: Vec<Pubkey>,
+    /// Keys in both messages at different indexes, with their old and new
+    /// index
+    pub moved_keys: Vec<(Pubkey, usize, usize)>,
+    /// The instructions that differ, in order
+    pub instructions: Vec<InstructionDiff>,
+}
+
+/// An instruction that differs between two messages.
+///
+/// Instructions are compared after resolving their account indexes, so
+/// instructions whose accounts only moved to other indexes are equal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstructionDiff {
+    pub index: usize,
+    /// The instruction in the old message, or `None` if it has no
+    /// instruction at `index` or the instruction refers to a missing account
+    pub old: Option<Instruction>,
+    /// The instruction in the new message, like `old`
+    pub new: Option<Instruction>,
+}
+
+impl InstructionDiff {
+    /// Returns true if both messages have the instruction, with different
+    /// program ids.
+    pub fn program_id_changed(&self) -> bool {
+        matches!((&self.old, &self.new), (Some(old), Some(new)) if old.program_id != new.program_id)
+    }
+
+    /// Returns true if both messages have the instruction, with different
+    /// accounts or account roles.
+    pub fn accounts_changed(&self) -> bool {
+        matches!((&self.old, &self.new), (Some(old), Some(new)) if old.accounts != new.accounts)
+    }
+
+    /// Returns true if both messages have the instruction, with different
+    /// data.
+    pub fn data_changed(&self) -> bool {
+        matches!((&self.old, &self.new), (Some(old), Some(new)) if old.data != new.data)
+    }
+}
+
+impl MessageDiff {
+    pub(crate) fn new(old: &Message, new: &Message) -> Self {
+        let index_of = |message: &Message, key: &Pubkey| message.position_of(key);
+        let moved_keys = old
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter_map(|(old_index, key)| {
+                let new_index = index_of(new, key)?;
+                (new_index != old_index).then_some((*key, old_index, new_index))
+            })
+            .collect();
+        let num_instructions = old.instructions.len().max(new.instructions.len());
+        let instructions = (0..num_instructions)
+            .filter_map(|index| {
+                let old = old.decompile_instruction(index);
+                let new = new.decompile_instruction(index);
+                (old != new).then_some(InstructionDiff { index, old, new })
+            })
+            .collect();
+        Self {
+            header: (old.header != new.header).then_some((old.header, new.header)),
+            recent_blockhash: (old.recent_blockhash != new.recent_blockhash)
+                .then_some((old.recent_blockhash, new.recent_blockhash)),
+            added_keys: new
+                .account_keys
+                .iter()
+                .filter(|key| index_of(old, key).is_none())
+                .copied()
+                .collect(),
+            removed_keys: old
+                .account_keys
+                .iter()
+                .filter(|key| index_of(new, key).is_none())
+                .copied()
+                .collect(),
+            moved_keys,
+            instructions,
+        }
+    }
+
+    /// Returns true if the messages are equal.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for MessageDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_empty() {
+            return f.write_str("messages are equal");
+        }
+        let mut lines = Vec::new();
+        if let Some((old, new)) = &self.header {
+            lines.push(format!("header: {old:?} -> {new:?}"));
+        }
+        if let Some((old, new)) = &self.recent_blockhash {
+            lines.push(format!("recent blockhash: {old} -> {new}"));
+        }
+        lines.extend(self.added_keys.iter().map(|key| format!("added key {key}")));
+        lines.extend(
+            self.removed_keys
+                .iter()
+                .map(|key| format!("removed key {key}")),
+        );
+        lines.extend(
+            self.moved_keys
+                .iter()
+                .map(|(key, old, new)| format!("moved key {key} from index {old} to {new}")),
+        );
+        for diff in &self.instructions {
+            let index = diff.index;
+            match (&diff.old, &diff.new) {
+                (None, Some(_)) => lines.push(format!("instruction {index}: added")),
+                (Some(_), None) => lines.push(format!("instruction {index}: removed")),
+                (Some(old), Some(new)) => {
+                    if diff.program_id_changed() {
+                        lines.push(format!(
+                            "instruction {index}: program id {} -> {}",
+                            old.program_id, new.program_id
+                        ));
+                    }
+                    if diff.accounts_changed() {
+                        lines.push(format!("instruction {index}: accounts changed"));
+                    }
+                    if diff.data_changed() {
+                        lines.push(format!(
+                            "instruction {index}: data changed, {} -> {} bytes",
+                            old.data.len(),
+                            new.data.len()
+                        ));
+                    }
+                }
+                (None, None) => lines.push(format!("instruction {index}: changed")),
+            }
+        }
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_instruction::AccountMeta};
+
+    #[test]
+    fn test_diff() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let a = Pubkey::new_unique();
+        let b = Pubkey::new_unique();
+        let message = Message::new(
+            &[
+                Instruction::new_with_bytes(program_id, &[1], vec![AccountMeta::new(a, false)]),
+                Instruction::new_with_bytes(program_id, &[2], vec![]),
+            ],
+            Some(&payer),
+        );
+        let diff = message.diff(&message);
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "messages are equal");
+
+        // replacing `a` with a signer changes the header and moves keys, and
+        // only the instruction using it differs
+        let mut other = Message::new(
+            &[
+                Instruction::new_with_bytes(program_id, &[1], vec![AccountMeta::new(b, true)]),
+                Instruction::new_with_bytes(program_id, &[2], vec![]),
+                Instruction::new_with_bytes(program_id, &[], vec![]),
+            ],
+            Some(&payer),
+        );
+        other.recent_blockhash = Hash::new_unique();
+        let diff = message.diff(&other);
+        assert_eq!(
+            diff.header,
+            Some((
+                message.header,
+                MessageHeader {
+                    num_required_signatures: 2,
+                    num_readonly_signed_accounts: 0,
+                    num_readonly_unsigned_accounts: 1,
+                }
+            ))
+        );
+        assert_eq!(
+            diff.recent_blockhash,
+            Some((Hash::default(), other.recent_blockhash))
+        );
+        assert_eq!(diff.added_keys, [b]);
+        assert_eq!(diff.removed_keys, [a]);
+        assert!(diff.moved_keys.is_empty());
+        assert_eq!(diff.instructions.len(), 2);
+        assert_eq!(diff.instructions[0].index, 0);
+        assert!(diff.instructions[0].accounts_changed());
+        assert!(!diff.instructions[0].data_changed());
+        assert!(!diff.instructions[0].program_id_changed());
+        assert_eq!(diff.instructions[1].index, 2);
+        assert_eq!(diff.instructions[1].old, None);
+        assert!(!diff.instructions[1].accounts_changed());
+
+        let report = diff.to_string();
+        assert!(report.contains(&format!("\nadded key {b}\nremoved key {a}\n")));
+        assert!(report.ends_with("instruction 0: accounts changed\ninstruction 2: added"));
+
+        // moving keys does not change instructions that use them
+        let mut other = message.clone();
+        other.account_keys.swap(1, 2);
+        for ix in &mut other.instructions {
+            ix.program_id_index = 1;
+            ix.accounts.iter_mut().for_each(|index| *index = 2);
+        }
+        other.header.num_readonly_unsigned_accounts = 0;
+        let diff = message.diff(&other);
+        assert_eq!(diff.moved_keys, [(a, 1, 2), (program_id, 2, 1)]);
+        assert!(diff.instructions.is_empty());
+
+        // data changes and removed instructions
+        let mut other = message.clone();
+        other.instructions[0].data = vec![1, 2, 3];
+        other.instructions.pop();
+        let diff = message.diff(&other);
+        assert_eq!(
+            diff.to_string(),
+            "instruction 0: data changed, 1 -> 3 bytes\ninstruction 1: removed"
+        );
+    }
+}
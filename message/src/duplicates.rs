@@ -0,0 +1,73 @@
+//! Detection of duplicate account keys.
+
+/// Above this many keys, duplicates are found by sorting rather than by
+/// comparing every pair of keys. Sorting, allocation included, is faster from
+/// about a dozen keys on, see `benches/duplicates.rs`.
+const SORT_THRESHOLD: usize = 12;
+
+/// Returns `true` if `keys` contains any duplicates.
+pub(crate) fn has_duplicates<T: Ord>(keys: &[T]) -> bool {
+    if keys.len() <= SORT_THRESHOLD {
+        // Note: This is an O(n^2) algorithm, but requires no heap allocations, which makes it
+        // faster than sorting up to `SORT_THRESHOLD` keys.
+        return keys
+            .iter()
+            .enumerate()
+            .skip(1)
+            .any(|(i, key)| keys[..i].contains(key));
+    }
+    let mut sorted: Vec<&T> = keys.iter().collect();
+    sorted.sort_unstable();
+    sorted.windows(2).any(|pair| pair[0] == pair[1])
+}
+
+/// Return a pair of indexes for each key of `keys` that repeats an earlier
+/// key: the index of the first occurrence and the index of the repeat,
+/// ordered by the index of the repeat.
+pub(crate) fn find_duplicates<T: Ord>(keys: &[T]) -> Vec<(usize, usize)> {
+    if keys.len() <= SORT_THRESHOLD {
+        return keys
+            .iter()
+            .enumerate()
+            .filter_map(|(i, key)| {
+                let first = keys[..i].iter().position(|k| k == key)?;
+                Some((first, i))
+            })
+            .collect();
+    }
+    let mut sorted: Vec<(&T, usize)> = keys.iter().zip(0..).collect();
+    // each run of equal keys starts with its first occurrence
+    sorted.sort_unstable();
+    let mut duplicates: Vec<(usize, usize)> = sorted
+        .chunk_by(|a, b| a.0 == b.0)
+        .flat_map(|run| run[1..].iter().map(|(_, i)| (run[0].1, *i)))
+        .collect();
+    duplicates.sort_unstable_by_key(|(_, i)| *i);
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_pubkey::Pubkey};
+
+    #[test]
+    fn test_find_duplicates() {
+        for len in [0, 1, 2, SORT_THRESHOLD, SORT_THRESHOLD + 1, 300] {
+            let mut keys: Vec<Pubkey> = (0..len).map(|_| Pubkey::new_unique()).collect();
+            assert!(!has_duplicates(&keys));
+            assert!(find_duplicates(&keys).is_empty());
+            if len < 2 {
+                continue;
+            }
+
+            keys.push(keys[1]);
+            keys.push(keys[0]);
+            keys.push(keys[1]);
+            assert!(has_duplicates(&keys));
+            assert_eq!(
+                find_duplicates(&keys),
+                [(1, len), (0, len + 1), (1, len + 2)]
+            );
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Base64 and base58 encodings of serialized messages, as sent to and
+//! returned by RPC.
+use {
+    base64::{prelude::BASE64_STANDARD, Engine},
+    core::fmt,
+    serde::{de::DeserializeOwned, Serialize},
+};
+
+/// The error returned when decoding a message from base64 or base58.
+#[derive(PartialEq, Debug, Eq, Clone)]
+pub enum MessageDecodeError {
+    /// The input is not base64 in the standard alphabet, with padding
+    InvalidBase64,
+    /// The input is base64 in the URL-safe alphabet, which messages are not
+    /// encoded with
+    UrlSafeBase64,
+    /// The input is not base58
+    InvalidBase58,
+    /// The decoded bytes are not a serialized message
+    InvalidMessage,
+    /// The decoded bytes hold a message followed by this many more bytes
+    TrailingBytes(usize),
+}
+
+impl core::error::Error for MessageDecodeError {}
+
+impl fmt::Display for MessageDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MessageDecodeError::InvalidBase64 => f.write_str("invalid base64"),
+            MessageDecodeError::UrlSafeBase64 => {
+                f.write_str("expected the standard base64 alphabet, found the URL-safe alphabet")
+            }
+            MessageDecodeError::InvalidBase58 => f.write_str("invalid base58"),
+            MessageDecodeError::InvalidMessage => f.write_str("invalid serialized message"),
+            MessageDecodeError::TrailingBytes(len) => f.write_fmt(format_args!(
+                "found {len} trailing bytes after the serialized message"
+            )),
+        }
+    }
+}
+
+pub(crate) fn to_base64<T: Serialize>(message: &T) -> String {
+    BASE64_STANDARD.encode(bincode::serialize(message).unwrap())
+}
+
+pub(crate) fn from_base64<T: DeserializeOwned>(encoded: &str) -> Result<T, MessageDecodeError> {
+    let bytes = BASE64_STANDARD.decode(encoded).map_err(|_| {
+        if encoded.contains(['-', '_']) {
+            MessageDecodeError::UrlSafeBase64
+        } else {
+            MessageDecodeError::InvalidBase64
+        }
+    })?;
+    deserialize(&bytes)
+}
+
+pub(crate) fn to_base58<T: Serialize>(message: &T) -> String {
+    bs58::encode(bincode::serialize(message).unwrap()).into_string()
+}
+
+pub(crate) fn from_base58<T: DeserializeOwned>(encoded: &str) -> Result<T, MessageDecodeError> {
+    let bytes = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|_| MessageDecodeError::InvalidBase58)?;
+    deserialize(&bytes)
+}
+
+fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, MessageDecodeError> {
+    let mut remaining = bytes;
+    let message = bincode::deserialize_from(&mut remaining)
+        .map_err(|_| MessageDecodeError::InvalidMessage)?;
+    if remaining.is_empty() {
+        Ok(message)
+    } else {
+        Err(MessageDecodeError::TrailingBytes(remaining.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{v0, Message, VersionedMessage},
+        base64::prelude::BASE64_URL_SAFE,
+        solana_hash::Hash,
+        solana_instruction::{AccountMeta, Instruction},
+        solana_pubkey::Pubkey,
+    };
+
+    #[test]
+    fn test_base64_and_base58() {
+        let payer = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[0xfb; 64],
+            vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        );
+        let message =
+            Message::new_with_blockhash(&[instruction], Some(&payer), &Hash::new_unique());
+        let serialized = message.serialize();
+
+        let encoded = message.to_base64();
+        assert_eq!(encoded, BASE64_STANDARD.encode(&serialized));
+        assert_eq!(Message::from_base64(&encoded), Ok(message.clone()));
+        let encoded = message.to_base58();
+        assert_eq!(encoded, bs58::encode(&serialized).into_string());
+        assert_eq!(Message::from_base58(&encoded), Ok(message.clone()));
+
+        // the data bytes encode to `+` and `/` in the standard alphabet
+        let url_safe = BASE64_URL_SAFE.encode(&serialized);
+        assert!(url_safe.contains(['-', '_']));
+        assert_eq!(
+            Message::from_base64(&url_safe),
+            Err(MessageDecodeError::UrlSafeBase64)
+        );
+        assert_eq!(
+            Message::from_base64("not base64!"),
+            Err(MessageDecodeError::InvalidBase64)
+        );
+        assert_eq!(
+            Message::from_base58("0OIl"),
+            Err(MessageDecodeError::InvalidBase58)
+        );
+        assert_eq!(
+            Message::from_base64(&BASE64_STANDARD.encode(&serialized[..100])),
+            Err(MessageDecodeError::InvalidMessage)
+        );
+        let mut trailing = serialized;
+        trailing.extend([0; 3]);
+        assert_eq!(
+            Message::from_base58(&bs58::encode(&trailing).into_string()),
+            Err(MessageDecodeError::TrailingBytes(3))
+        );
+
+        let message = VersionedMessage::V0(
+            v0::Message::try_compile(&payer, &[], &[], Hash::new_unique()).unwrap(),
+        );
+        assert_eq!(
+            VersionedMessage::from_base64(&message.to_base64()),
+            Ok(message.clone())
+        );
+        assert_eq!(
+            VersionedMessage::from_base58(&message.to_base58()),
+            Ok(message)
+        );
+    }
+}
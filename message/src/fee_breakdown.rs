@@ -0,0 +1,184 @@
+//! The fees a transaction pays, read from its message.
+use {
+    crate::{
+        compiled_instruction::CompiledInstruction,
+        inline_compute_budget::{
+            is_compute_budget_program, parse_compute_unit_limit, parse_compute_unit_price,
+            prioritization_fee, DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT, MAX_COMPUTE_UNIT_LIMIT,
+        },
+        MessageHeader,
+    },
+    solana_pubkey::Pubkey,
+    solana_sdk_ids::{ed25519_program, secp256k1_program, secp256r1_program},
+};
+
+/// The fees of a transaction, returned by `fee_breakdown` on messages.
+///
+/// Compute budget settings are read from the first `SetComputeUnitLimit` and
+/// `SetComputeUnitPrice` instructions. The runtime rejects transactions that
+/// set either more than once, or that have malformed compute budget
+/// instructions, and this breakdown does not check for that.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    /// The number of signatures paid for: the transaction's signatures and
+    /// the signatures verified by precompiles.
+    pub num_signatures: u64,
+    /// The fee for the signatures, in lamports
+    pub signature_fee: u64,
+    /// The compute unit limit set by a `SetComputeUnitLimit` instruction
+    pub compute_unit_limit: Option<u32>,
+    /// The compute unit price, in micro-lamports, set by a
+    /// `SetComputeUnitPrice` instruction
+    pub compute_unit_price: Option<u64>,
+    /// The prioritization fee, in lamports
+    ///
+    /// If no compute unit limit is set, the runtime default of 200,000 units
+    /// per instruction is assumed. The runtime gives builtin program
+    /// instructions smaller defaults, so this is an upper bound in that case.
+    pub prioritization_fee: u64,
+}
+
+impl FeeBreakdown {
+    pub(crate) fn new(
+        lamports_per_signature: u64,
+        header: &MessageHeader,
+        account_keys: &[Pubkey],
+        instructions: &[CompiledInstruction],
+    ) -> Self {
+        let num_signatures = num_signatures(header, account_keys, instructions);
+        let mut compute_unit_limit = None;
+        let mut compute_unit_price = None;
+        let mut num_compute_budget_instructions = 0u32;
+        for ix in instructions {
+            if !account_keys
+                .get(usize::from(ix.program_id_index))
+                .is_some_and(is_compute_budget_program)
+            {
+                continue;
+            }
+            num_compute_budget_instructions = num_compute_budget_instructions.saturating_add(1);
+            compute_unit_limit = compute_unit_limit.or_else(|| parse_compute_unit_limit(&ix.data));
+            compute_unit_price = compute_unit_price.or_else(|| parse_compute_unit_price(&ix.data));
+        }
+
+        let effective_compute_unit_limit = compute_unit_limit
+            .unwrap_or_else(|| {
+                let num_instructions = u32::try_from(instructions.len()).unwrap_or(u32::MAX);
+                num_instructions
+                    .saturating_sub(num_compute_budget_instructions)
+                    .saturating_mul(DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT)
+            })
+            .min(MAX_COMPUTE_UNIT_LIMIT);
+        Self {
+            num_signatures,
+            signature_fee: num_signatures.saturating_mul(lamports_per_signature),
+            compute_unit_limit,
+            compute_unit_price,
+            prioritization_fee: prioritization_fee(
+                compute_unit_price.unwrap_or_default(),
+                effective_compute_unit_limit,
+            ),
+        }
+    }
+
+    /// The total fee, in lamports.
+    pub fn total_fee(&self) -> u64 {
+        self.signature_fee.saturating_add(self.prioritization_fee)
+    }
+}
+
+/// Count the transaction's signatures and the signatures verified by
+/// precompile instructions, whose first data byte is their signature count.
+fn num_signatures(
+    header: &MessageHeader,
+    account_keys: &[Pubkey],
+    instructions: &[CompiledInstruction],
+) -> u64 {
+    instructions
+        .iter()
+        .filter(|ix| {
+            account_keys
+                .get(usize::from(ix.program_id_index))
+                .is_some_and(|program_id| {
+                    secp256k1_program::check_id(program_id)
+                        || ed25519_program::check_id(program_id)
+                        || secp256r1_program::check_id(program_id)
+                })
+        })
+        .map(|ix| u64::from(ix.data.first().copied().unwrap_or_default()))
+        .fold(
+            u64::from(header.num_required_signatures),
+            u64::saturating_add,
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{v0, Message, VersionedMessage},
+        solana_compute_budget_interface::ComputeBudgetInstruction,
+        solana_hash::Hash,
+        solana_instruction::{AccountMeta, Instruction},
+    };
+
+    #[test]
+    fn test_fee_breakdown() {
+        let payer = Pubkey::new_unique();
+        let transfer = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![AccountMeta::new(Pubkey::new_unique(), true)],
+        );
+        let message = Message::new(&[transfer.clone()], Some(&payer));
+        assert_eq!(
+            message.fee_breakdown(5_000),
+            FeeBreakdown {
+                num_signatures: 2,
+                signature_fee: 10_000,
+                ..FeeBreakdown::default()
+            }
+        );
+        assert_eq!(message.signature_fee(5_000), 10_000);
+
+        // precompile signatures are paid for, and the default limit applies
+        // per instruction that is not a compute budget instruction
+        let instructions = [
+            ComputeBudgetInstruction::set_compute_unit_price(1_000_000),
+            Instruction::new_with_bytes(secp256k1_program::id(), &[3, 1, 2], vec![]),
+            Instruction::new_with_bytes(ed25519_program::id(), &[1], vec![]),
+            transfer.clone(),
+        ];
+        let message = Message::new(&instructions, Some(&payer));
+        let fees = message.fee_breakdown(5_000);
+        assert_eq!(
+            fees,
+            FeeBreakdown {
+                num_signatures: 6,
+                signature_fee: 30_000,
+                compute_unit_limit: None,
+                compute_unit_price: Some(1_000_000),
+                prioritization_fee: 600_000,
+            }
+        );
+        assert_eq!(fees.total_fee(), 630_000);
+
+        // the first instruction of each kind counts, and the limit is capped
+        let instructions = [
+            ComputeBudgetInstruction::set_compute_unit_limit(2_000_000),
+            ComputeBudgetInstruction::set_compute_unit_price(3),
+            ComputeBudgetInstruction::set_compute_unit_price(4),
+            ComputeBudgetInstruction::set_compute_unit_limit(1),
+            transfer,
+        ];
+        let message = VersionedMessage::V0(
+            v0::Message::try_compile(&payer, &instructions, &[], Hash::default()).unwrap(),
+        );
+        let fees = message.fee_breakdown(0);
+        assert_eq!(fees.compute_unit_limit, Some(2_000_000));
+        assert_eq!(fees.compute_unit_price, Some(3));
+        assert_eq!(fees.prioritization_fee, 5);
+        assert_eq!(fees.signature_fee, 0);
+        assert_eq!(message.signature_fee(1), 2);
+    }
+}
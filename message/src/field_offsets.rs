@@ -0,0 +1,163 @@
+//! Where each field of a message is in its serialized form, for signers that
+//! display and verify fields without a full deserializer.
+use {
+    crate::{
+        compiled_instruction::CompiledInstruction, legacy::Message, short_vec_len,
+        MESSAGE_HEADER_LENGTH,
+    },
+    core::ops::Range,
+    solana_hash::HASH_BYTES,
+    solana_pubkey::PUBKEY_BYTES,
+};
+
+/// The bytes of a field in a serialized message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSpan {
+    /// The offset of the first byte of the field
+    pub offset: usize,
+    /// The number of bytes in the field
+    pub len: usize,
+}
+
+impl FieldSpan {
+    /// The range of the field's bytes, for indexing the serialized message.
+    pub fn range(&self) -> Range<usize> {
+        self.offset..self.offset.saturating_add(self.len)
+    }
+}
+
+/// Where the fields of one instruction are in a serialized message.
+///
+/// The spans of `accounts` and `data` leave out their length prefixes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstructionOffsets {
+    /// The whole instruction, including the length prefixes
+    pub instruction: FieldSpan,
+    pub program_id_index: FieldSpan,
+    pub accounts: FieldSpan,
+    pub data: FieldSpan,
+}
+
+/// Where the fields of a message are in its serialized form, returned by
+/// [`Message::field_offsets`].
+///
+/// Offsets are relative to the start of the message, not of a transaction,
+/// which puts the signatures before it. The length prefixes of the account
+/// keys and the instructions are not part of any span.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldOffsets {
+    pub header: FieldSpan,
+    /// Each account key, in order
+    pub account_keys: Vec<FieldSpan>,
+    pub recent_blockhash: FieldSpan,
+    /// Each instruction, in order
+    pub instructions: Vec<InstructionOffsets>,
+}
+
+/// The offset of the next field to be laid out.
+struct Cursor(usize);
+
+impl Cursor {
+    fn take(&mut self, len: usize) -> FieldSpan {
+        let span = FieldSpan {
+            offset: self.0,
+            len,
+        };
+        self.skip(len);
+        span
+    }
+
+    fn skip(&mut self, len: usize) {
+        self.0 = self.0.saturating_add(len);
+    }
+}
+
+fn instruction_offsets(cursor: &mut Cursor, ix: &CompiledInstruction) -> InstructionOffsets {
+    let start = cursor.0;
+    let program_id_index = cursor.take(1);
+    cursor.skip(short_vec_len(ix.accounts.len()));
+    let accounts = cursor.take(ix.accounts.len());
+    cursor.skip(short_vec_len(ix.data.len()));
+    let data = cursor.take(ix.data.len());
+    InstructionOffsets {
+        instruction: FieldSpan {
+            offset: start,
+            len: cursor.0.saturating_sub(start),
+        },
+        program_id_index,
+        accounts,
+        data,
+    }
+}
+
+pub(crate) fn field_offsets(message: &Message) -> FieldOffsets {
+    let mut cursor = Cursor(0);
+    let header = cursor.take(MESSAGE_HEADER_LENGTH);
+    cursor.skip(short_vec_len(message.account_keys.len()));
+    let account_keys = message
+        .account_keys
+        .iter()
+        .map(|_| cursor.take(PUBKEY_BYTES))
+        .collect();
+    let recent_blockhash = cursor.take(HASH_BYTES);
+    cursor.skip(short_vec_len(message.instructions.len()));
+    let instructions = message
+        .instructions
+        .iter()
+        .map(|ix| instruction_offsets(&mut cursor, ix))
+        .collect();
+    FieldOffsets {
+        header,
+        account_keys,
+        recent_blockhash,
+        instructions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_hash::Hash,
+        solana_instruction::{AccountMeta, Instruction},
+        solana_pubkey::Pubkey,
+    };
+
+    #[test]
+    fn test_field_offsets() {
+        let payer = Pubkey::new_unique();
+        let accounts = (0..200)
+            .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+            .collect::<Vec<_>>();
+        let instructions = [
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[7; 300], accounts[..2].to_vec()),
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[], accounts),
+        ];
+        let message = Message::new_with_blockhash(&instructions, Some(&payer), &Hash::new_unique());
+        let serialized = message.serialize();
+        let offsets = message.field_offsets();
+
+        assert_eq!(offsets.header, FieldSpan { offset: 0, len: 3 });
+        // the 203 account keys have a two byte length prefix
+        assert_eq!(offsets.account_keys[0].offset, 5);
+        assert_eq!(offsets.account_keys.len(), message.account_keys.len());
+        for (span, key) in offsets.account_keys.iter().zip(&message.account_keys) {
+            assert_eq!(&serialized[span.range()], key.as_ref());
+        }
+        assert_eq!(
+            &serialized[offsets.recent_blockhash.range()],
+            message.recent_blockhash.as_ref()
+        );
+        for (offsets, ix) in offsets.instructions.iter().zip(&message.instructions) {
+            assert_eq!(
+                serialized[offsets.program_id_index.range()],
+                [ix.program_id_index]
+            );
+            assert_eq!(serialized[offsets.accounts.range()], ix.accounts);
+            assert_eq!(serialized[offsets.data.range()], ix.data);
+            assert_eq!(offsets.instruction.len, ix.serialized_size());
+        }
+        let last = offsets.instructions.last().unwrap();
+        assert_eq!(last.instruction.range().end, serialized.len());
+    }
+}
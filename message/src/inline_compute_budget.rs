@@ -0,0 +1,93 @@
+//! Inlined compute budget instruction information to avoid a dependency on
+//! solana-compute-budget-interface
+use {solana_pubkey::Pubkey, solana_sdk_ids::compute_budget};
+
+/// Inlined `ComputeBudgetInstruction::SetComputeUnitLimit` discriminant
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+/// Inlined `ComputeBudgetInstruction::SetComputeUnitPrice` discriminant
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+
+/// The compute unit limit of each instruction, if the transaction does not
+/// set one.
+pub(crate) const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u32 = 200_000;
+/// The largest compute unit limit a transaction can have.
+pub(crate) const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+pub(crate) fn is_compute_budget_program(program_id: &Pubkey) -> bool {
+    compute_budget::check_id(program_id)
+}
+
+/// Return the units of a `ComputeBudgetInstruction::SetComputeUnitLimit`
+/// instruction's data.
+pub(crate) fn parse_compute_unit_limit(data: &[u8]) -> Option<u32> {
+    match data {
+        [SET_COMPUTE_UNIT_LIMIT, units @ ..] => units.try_into().ok().map(u32::from_le_bytes),
+        _ => None,
+    }
+}
+
+/// Return the micro-lamports of a `ComputeBudgetInstruction::SetComputeUnitPrice`
+/// instruction's data.
+pub(crate) fn parse_compute_unit_price(data: &[u8]) -> Option<u64> {
+    match data {
+        [SET_COMPUTE_UNIT_PRICE, micro_lamports @ ..] => {
+            micro_lamports.try_into().ok().map(u64::from_le_bytes)
+        }
+        _ => None,
+    }
+}
+
+/// Inlined `ComputeBudgetInstruction::set_compute_unit_limit` instruction data
+pub(crate) fn compute_unit_limit_data(units: u32) -> Vec<u8> {
+    [&[SET_COMPUTE_UNIT_LIMIT][..], &units.to_le_bytes()].concat()
+}
+
+/// Inlined `ComputeBudgetInstruction::set_compute_unit_price` instruction data
+pub(crate) fn compute_unit_price_data(micro_lamports: u64) -> Vec<u8> {
+    [&[SET_COMPUTE_UNIT_PRICE][..], &micro_lamports.to_le_bytes()].concat()
+}
+
+/// The prioritization fee, in lamports, of `compute_unit_limit` units at
+/// `compute_unit_price` micro-lamports each, rounded up.
+pub(crate) fn prioritization_fee(compute_unit_price: u64, compute_unit_limit: u32) -> u64 {
+    let micro_lamports =
+        u128::from(compute_unit_price).saturating_mul(u128::from(compute_unit_limit));
+    u64::try_from(micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT)).unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, solana_compute_budget_interface::ComputeBudgetInstruction};
+
+    #[test]
+    fn inline_instruction_data_matches_program() {
+        let ix = ComputeBudgetInstruction::set_compute_unit_limit(300_000);
+        assert_eq!(compute_unit_limit_data(300_000), ix.data);
+        assert!(is_compute_budget_program(&ix.program_id));
+        assert_eq!(parse_compute_unit_limit(&ix.data), Some(300_000));
+        assert_eq!(parse_compute_unit_price(&ix.data), None);
+
+        let ix = ComputeBudgetInstruction::set_compute_unit_price(u64::MAX);
+        assert_eq!(compute_unit_price_data(u64::MAX), ix.data);
+        assert_eq!(parse_compute_unit_price(&ix.data), Some(u64::MAX));
+        assert_eq!(parse_compute_unit_limit(&ix.data), None);
+
+        let ix = ComputeBudgetInstruction::request_heap_frame(32 * 1024);
+        assert_eq!(parse_compute_unit_limit(&ix.data), None);
+        assert_eq!(parse_compute_unit_price(&ix.data), None);
+
+        // trailing bytes do not deserialize
+        assert_eq!(parse_compute_unit_limit(&[2, 0, 0, 0, 0, 0]), None);
+    }
+
+    #[test]
+    fn test_prioritization_fee() {
+        assert_eq!(prioritization_fee(0, MAX_COMPUTE_UNIT_LIMIT), 0);
+        assert_eq!(prioritization_fee(1, 1), 1);
+        assert_eq!(prioritization_fee(1_000, 200_000), 200);
+        assert_eq!(prioritization_fee(1_000, 200_001), 201);
+        assert_eq!(prioritization_fee(u64::MAX, u32::MAX), u64::MAX);
+    }
+}
@@ -19,21 +19,51 @@ pub fn is_advance_nonce_instruction_data(data: &[u8]) -> bool {
     data.get(0..4) == Some(&ADVANCE_NONCE_DATA)
 }
 
-/// Inlined `advance_nonce_account` instruction creator to avoid
-/// solana_system_interface and bincode deps
-pub(crate) fn advance_nonce_account_instruction(
+/// The accounts of a `SystemInstruction::AdvanceNonceAccount` instruction, in
+/// order: the writable nonce account, the recent blockhashes sysvar, and the
+/// nonce authority, which must sign.
+pub fn advance_nonce_account_metas(
+    nonce_pubkey: &Pubkey,
+    nonce_authority_pubkey: &Pubkey,
+) -> [AccountMeta; 3] {
+    [
+        AccountMeta::new(*nonce_pubkey, false),
+        #[allow(deprecated)]
+        AccountMeta::new_readonly(sysvar::recent_blockhashes::id(), false),
+        AccountMeta::new_readonly(*nonce_authority_pubkey, true),
+    ]
+}
+
+/// A `SystemInstruction::AdvanceNonceAccount` instruction, which advances the
+/// durable nonce stored in `nonce_pubkey`, equal to
+/// `solana_system_interface::instruction::advance_nonce_account` without the
+/// solana_system_interface and bincode deps.
+///
+/// A transaction that uses a durable nonce instead of a recent blockhash must
+/// start with this instruction, and must be signed by
+/// `nonce_authority_pubkey`. [`Message::new_with_nonce`] adds it for you.
+///
+/// [`Message::new_with_nonce`]: crate::Message::new_with_nonce
+///
+/// ```
+/// # use solana_message::inline_nonce::{
+/// #     advance_nonce_account_instruction, is_advance_nonce_instruction_data,
+/// # };
+/// # use solana_pubkey::Pubkey;
+/// let nonce = Pubkey::new_unique();
+/// let nonce_authority = Pubkey::new_unique();
+/// let instruction = advance_nonce_account_instruction(&nonce, &nonce_authority);
+/// assert!(is_advance_nonce_instruction_data(&instruction.data));
+/// assert_eq!(instruction.accounts[0].pubkey, nonce);
+/// ```
+pub fn advance_nonce_account_instruction(
     nonce_pubkey: &Pubkey,
     nonce_authority_pubkey: &Pubkey,
 ) -> Instruction {
     Instruction::new_with_bytes(
         system_program::id(),
         &ADVANCE_NONCE_DATA,
-        vec![
-            AccountMeta::new(*nonce_pubkey, false),
-            #[allow(deprecated)]
-            AccountMeta::new_readonly(sysvar::recent_blockhashes::id(), false),
-            AccountMeta::new_readonly(*nonce_authority_pubkey, true),
-        ],
+        advance_nonce_account_metas(nonce_pubkey, nonce_authority_pubkey).to_vec(),
     )
 }
 
@@ -48,9 +78,14 @@ mod test {
     fn inline_instruction_data_matches_program() {
         let nonce = Pubkey::new_unique();
         let nonce_authority = Pubkey::new_unique();
+        let instruction = advance_nonce_account(&nonce, &nonce_authority);
         assert_eq!(
             advance_nonce_account_instruction(&nonce, &nonce_authority),
-            advance_nonce_account(&nonce, &nonce_authority),
+            instruction,
+        );
+        assert_eq!(
+            advance_nonce_account_metas(&nonce, &nonce_authority),
+            instruction.accounts[..],
         );
     }
 
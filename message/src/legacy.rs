@@ -11,23 +11,69 @@
 
 #![allow(clippy::arithmetic_side_effects)]
 
+#[cfg(not(target_os = "solana"))]
+use crate::v0;
+#[cfg(feature = "parse")]
+use crate::ParsedInstructions;
+#[cfg(all(not(target_os = "solana"), feature = "blake3"))]
+use crate::{blake3_hash_with_domain, MESSAGE_HASH_DOMAIN};
+#[cfg(feature = "encoding")]
+use crate::{encoding, MessageDecodeError};
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
 #[cfg(feature = "serde")]
 use serde_derive::{Deserialize, Serialize};
 #[cfg(feature = "frozen-abi")]
 use solana_frozen_abi_macro::{frozen_abi, AbiExample};
+#[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+use {
+    crate::precompiles::{self, PrecompileFeatureSet},
+    solana_transaction_error::TransactionError,
+};
 use {
     crate::{
-        compiled_instruction::CompiledInstruction, compiled_keys::CompiledKeys,
-        inline_nonce::advance_nonce_account_instruction, MessageHeader,
+        account_locks::{self, AccountLocks},
+        compiled_instruction::CompiledInstruction,
+        compiled_keys::{CompileError, CompiledKeys},
+        cost_estimate::{CostEstimate, CostModelParams},
+        diff::MessageDiff,
+        duplicates,
+        fee_breakdown::FeeBreakdown,
+        field_offsets::{self, FieldOffsets},
+        fits_in_packet,
+        inline_compute_budget::{
+            compute_unit_limit_data, compute_unit_price_data, is_compute_budget_program,
+            parse_compute_unit_limit, parse_compute_unit_price,
+        },
+        inline_nonce::{advance_nonce_account_instruction, is_advance_nonce_instruction_data},
+        short_vec_len, signer_privileges, signing_display, static_checks, visit, wire, AccountRole,
+        MessageHeader, RecentBlockhash, RecentBlockhashError, RedactedMessage,
+        ResolvedAccountMetas, SignerPrivilegeError, SigningDisplay, SizeBreakdown,
+        StaticConstraints, StaticViolation, WireError, MESSAGE_HEADER_LENGTH,
     },
-    solana_hash::Hash,
-    solana_instruction::Instruction,
-    solana_pubkey::Pubkey,
+    solana_hash::{Hash, HASH_BYTES},
+    solana_instruction::{AccountMeta, Instruction},
+    solana_pubkey::{Pubkey, PUBKEY_BYTES},
     solana_sanitize::{Sanitize, SanitizeError},
-    solana_sdk_ids::bpf_loader_upgradeable,
+    solana_sdk_ids::{bpf_loader_upgradeable, compute_budget, system_program},
     std::{collections::HashSet, convert::TryFrom},
 };
 
+// inlined to avoid solana_nonce dep
+const NONCED_TX_MARKER_IX_INDEX: usize = 0;
+#[cfg(test)]
+static_assertions::const_assert_eq!(
+    NONCED_TX_MARKER_IX_INDEX,
+    solana_nonce::NONCED_TX_MARKER_IX_INDEX as usize
+);
+
+// inlined to avoid spl_memo dep
+const MEMO_PROGRAM_IDS: [Pubkey; 2] = [
+    Pubkey::from_str_const("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+    // version 1 of the memo program
+    Pubkey::from_str_const("Memo1UhkJRfHyvLMcVucJwxXeuD728EqVDDwQDxFMNo"),
+];
+
 fn position(keys: &[Pubkey], key: &Pubkey) -> u8 {
     keys.iter().position(|k| k == key).unwrap() as u8
 }
@@ -46,8 +92,31 @@ fn compile_instruction(ix: &Instruction, keys: &[Pubkey]) -> CompiledInstruction
     }
 }
 
-fn compile_instructions(ixs: &[Instruction], keys: &[Pubkey]) -> Vec<CompiledInstruction> {
-    ixs.iter().map(|ix| compile_instruction(ix, keys)).collect()
+fn try_position(keys: &[Pubkey], key: &Pubkey) -> Result<u8, CompileError> {
+    let index = keys
+        .iter()
+        .position(|k| k == key)
+        .ok_or(CompileError::UnknownInstructionKey(*key))?;
+    u8::try_from(index).map_err(|_| CompileError::AccountIndexOverflow)
+}
+
+fn try_compile_instructions(
+    ixs: &[Instruction],
+    keys: &[Pubkey],
+) -> Result<Vec<CompiledInstruction>, CompileError> {
+    ixs.iter()
+        .map(|ix| {
+            Ok(CompiledInstruction {
+                program_id_index: try_position(keys, &ix.program_id)?,
+                data: ix.data.clone(),
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|account_meta| try_position(keys, &account_meta.pubkey))
+                    .collect::<Result<_, _>>()?,
+            })
+        })
+        .collect()
 }
 
 /// A Solana transaction message (legacy).
@@ -62,6 +131,14 @@ fn compile_instructions(ixs: &[Instruction], keys: &[Pubkey]) -> Vec<CompiledIns
 /// the caller has knowledge that the first account of the constructed
 /// transaction's `Message` is both a signer and the expected fee-payer, then
 /// redundantly specifying the fee-payer is not strictly required.
+///
+/// With the `borsh` feature, `Message` also implements [`borsh`]
+/// serialization, for programs and off-chain stores that standardize on it.
+/// That encoding is not the wire format: it prefixes vectors with `u32`
+/// lengths instead of the compact-u16 lengths of the wire format, so it is
+/// neither signed nor accepted by the network.
+///
+/// [`borsh`]: https://docs.rs/borsh/latest/borsh/
 // NOTE: Serialization-related changes must be paired with the custom serialization
 // for versioned messages in the `RemainingLegacyMessage` struct.
 #[cfg_attr(
@@ -69,6 +146,11 @@ fn compile_instructions(ixs: &[Instruction], keys: &[Pubkey]) -> Vec<CompiledIns
     frozen_abi(digest = "2THeaWnXSGDTsiadKytJTcbjrk4KjfMww9arRLZcwGnw"),
     derive(AbiExample)
 )]
+#[cfg_attr(
+    feature = "borsh",
+    derive(BorshSerialize, BorshDeserialize),
+    borsh(crate = "borsh")
+)]
 #[cfg_attr(
     feature = "serde",
     derive(Deserialize, Serialize),
@@ -276,19 +358,74 @@ impl Message {
         payer: Option<&Pubkey>,
         blockhash: &Hash,
     ) -> Self {
-        let compiled_keys = CompiledKeys::compile(instructions, payer.cloned());
-        let (header, account_keys) = compiled_keys
-            .try_into_message_components()
-            .expect("overflow when compiling message keys");
-        let instructions = compile_instructions(instructions, &account_keys);
-        Self::new_with_compiled_instructions(
+        Self::try_new_with_blockhash(instructions, payer, blockhash)
+            .expect("overflow when compiling message keys")
+    }
+
+    /// Create a new `Message`, like [`Message::new`], but return an error
+    /// instead of panicking if the instructions reference more accounts than
+    /// a message can address.
+    pub fn try_new(
+        instructions: &[Instruction],
+        payer: Option<&Pubkey>,
+    ) -> Result<Self, CompileError> {
+        Self::try_new_with_blockhash(instructions, payer, &Hash::default())
+    }
+
+    /// Create a new message while setting the blockhash, like
+    /// [`Message::new_with_blockhash`], but return an error instead of
+    /// panicking if the instructions reference more accounts than a message
+    /// can address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_hash::Hash;
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::{CompileError, Message};
+    /// # use solana_pubkey::Pubkey;
+    /// let accounts = (0..300)
+    ///     .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+    ///     .collect();
+    /// let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], accounts);
+    /// assert_eq!(
+    ///     Message::try_new_with_blockhash(&[instruction], None, &Hash::new_unique()),
+    ///     Err(CompileError::AccountIndexOverflow)
+    /// );
+    /// ```
+    pub fn try_new_with_blockhash(
+        instructions: &[Instruction],
+        payer: Option<&Pubkey>,
+        blockhash: &Hash,
+    ) -> Result<Self, CompileError> {
+        Self::try_new_with_compiled_keys(
+            CompiledKeys::compile(instructions, payer.cloned()),
+            instructions,
+            blockhash,
+        )
+    }
+
+    /// Create a new message from keys compiled from `instructions`, so that
+    /// callers control how account indexes are assigned. See
+    /// [`CompiledKeys`].
+    ///
+    /// Fails with [`CompileError::UnknownInstructionKey`] if `instructions`
+    /// use a key that `compiled_keys` does not have.
+    pub fn try_new_with_compiled_keys(
+        compiled_keys: CompiledKeys,
+        instructions: &[Instruction],
+        blockhash: &Hash,
+    ) -> Result<Self, CompileError> {
+        let (header, account_keys) = compiled_keys.try_into_message_components()?;
+        let instructions = try_compile_instructions(instructions, &account_keys)?;
+        Ok(Self::new_with_compiled_instructions(
             header.num_required_signatures,
             header.num_readonly_signed_accounts,
             header.num_readonly_unsigned_accounts,
             account_keys,
             *blockhash,
             instructions,
-        )
+        ))
     }
 
     /// Create a new message for a [nonced transaction].
@@ -428,6 +565,25 @@ impl Message {
         }
     }
 
+    /// Convert a v0 message to a legacy message with the same keys and
+    /// instructions.
+    ///
+    /// Legacy messages cannot load accounts from address lookup tables, so
+    /// this only succeeds when `message` has no address table lookups, and
+    /// otherwise returns `message` unchanged.
+    #[cfg(not(target_os = "solana"))]
+    pub fn try_from_v0(message: v0::Message) -> Result<Self, v0::Message> {
+        if !message.address_table_lookups.is_empty() {
+            return Err(message);
+        }
+        Ok(Self {
+            header: message.header,
+            account_keys: message.account_keys,
+            recent_blockhash: message.recent_blockhash,
+            instructions: message.instructions,
+        })
+    }
+
     /// Compute the blake3 hash of this transaction's message.
     #[cfg(all(not(target_os = "solana"), feature = "bincode", feature = "blake3"))]
     pub fn hash(&self) -> Hash {
@@ -438,23 +594,811 @@ impl Message {
     /// Compute the blake3 hash of a raw transaction message.
     #[cfg(all(not(target_os = "solana"), feature = "blake3"))]
     pub fn hash_raw_message(message_bytes: &[u8]) -> Hash {
-        use {blake3::traits::digest::Digest, solana_hash::HASH_BYTES};
-        let mut hasher = blake3::Hasher::new();
-        hasher.update(b"solana-tx-message-v1");
-        hasher.update(message_bytes);
-        let hash_bytes: [u8; HASH_BYTES] = hasher.finalize().into();
-        hash_bytes.into()
+        blake3_hash_with_domain(MESSAGE_HASH_DOMAIN, message_bytes)
+    }
+
+    /// Compute the blake3 hash of this message like [`Message::hash`], with
+    /// `domain` in place of [`MESSAGE_HASH_DOMAIN`].
+    ///
+    /// For off-chain protocols that sign message-like payloads, so that their
+    /// hashes can never be mistaken for a transaction's.
+    #[cfg(all(not(target_os = "solana"), feature = "bincode", feature = "blake3"))]
+    pub fn hash_with_domain(&self, domain: &[u8]) -> Hash {
+        Self::hash_raw_message_with_domain(domain, &self.serialize())
+    }
+
+    /// Compute the blake3 hash of a raw message with `domain` in place of
+    /// [`MESSAGE_HASH_DOMAIN`].
+    #[cfg(all(not(target_os = "solana"), feature = "blake3"))]
+    pub fn hash_raw_message_with_domain(domain: &[u8], message_bytes: &[u8]) -> Hash {
+        blake3_hash_with_domain(domain, message_bytes)
+    }
+
+    /// Compute the SHA-256 hash of `domain` followed by this serialized
+    /// message, for protocols that cannot use blake3.
+    #[cfg(all(feature = "bincode", feature = "sha256"))]
+    pub fn sha256_hash_with_domain(&self, domain: &[u8]) -> Hash {
+        Self::sha256_hash_raw_message_with_domain(domain, &self.serialize())
+    }
+
+    /// Compute the SHA-256 hash of `domain` followed by a raw message.
+    #[cfg(feature = "sha256")]
+    pub fn sha256_hash_raw_message_with_domain(domain: &[u8], message_bytes: &[u8]) -> Hash {
+        solana_sha256_hasher::hashv(&[domain, message_bytes])
     }
 
     pub fn compile_instruction(&self, ix: &Instruction) -> CompiledInstruction {
         compile_instruction(ix, &self.account_keys)
     }
 
+    /// Reconstruct the instruction at `instruction_index`, the inverse of
+    /// [`Message::compile_instruction`].
+    ///
+    /// Account metas are marked signer and writable according to the message
+    /// header, like [`Message::is_signer`] and the header rules of
+    /// [`Message::is_maybe_writable`], without demoting program ids or
+    /// reserved accounts. Returns `None` if the instruction or any of its
+    /// account indexes is out of bounds, which can only happen for messages
+    /// that do not pass sanitization.
+    ///
+    /// ```
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(
+    ///     Pubkey::new_unique(),
+    ///     &[1, 2, 3],
+    ///     vec![
+    ///         AccountMeta::new(payer, true),
+    ///         AccountMeta::new_readonly(Pubkey::new_unique(), false),
+    ///     ],
+    /// );
+    /// let message = Message::new(&[instruction.clone()], Some(&payer));
+    /// assert_eq!(message.decompile_instruction(0), Some(instruction));
+    /// assert_eq!(message.decompile_instruction(1), None);
+    /// ```
+    pub fn decompile_instruction(&self, instruction_index: usize) -> Option<Instruction> {
+        let ix = self.instructions.get(instruction_index)?;
+        let program_id = *self.account_keys.get(usize::from(ix.program_id_index))?;
+        let accounts = ix
+            .accounts
+            .iter()
+            .map(|index| {
+                let index = usize::from(*index);
+                Some(AccountMeta {
+                    pubkey: *self.account_keys.get(index)?,
+                    is_signer: self.is_signer(index),
+                    is_writable: self.is_writable_index(index),
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(Instruction {
+            program_id,
+            accounts,
+            data: ix.data.clone(),
+        })
+    }
+
+    /// Reconstruct every instruction in this message, in order. See
+    /// [`Message::decompile_instruction`].
+    pub fn decompile_instructions(&self) -> impl Iterator<Item = Option<Instruction>> + '_ {
+        (0..self.instructions.len()).map(|index| self.decompile_instruction(index))
+    }
+
+    /// Call `visit` with the program id, data and resolved accounts of each
+    /// instruction in this message, in order, without decompiling them into
+    /// owned [`Instruction`]s. Accounts have the roles
+    /// [`Message::decompile_instruction`] gives them.
+    ///
+    /// Fails with [`SanitizeError::IndexOutOfBounds`] at the first
+    /// instruction that indexes a missing key, after visiting the
+    /// instructions before it.
+    ///
+    /// ```
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(
+    ///     Pubkey::new_unique(),
+    ///     &[1, 2, 3],
+    ///     vec![AccountMeta::new(payer, true)],
+    /// );
+    /// let message = Message::new(&[instruction], Some(&payer));
+    ///
+    /// let mut num_signer_accounts = 0;
+    /// message.visit_instructions(|_program_id, _data, accounts| {
+    ///     num_signer_accounts += accounts.filter(|meta| meta.is_signer).count();
+    /// })?;
+    /// assert_eq!(num_signer_accounts, 1);
+    /// # Ok::<(), solana_sanitize::SanitizeError>(())
+    /// ```
+    pub fn visit_instructions<'a>(
+        &'a self,
+        visit: impl FnMut(&'a Pubkey, &'a [u8], ResolvedAccountMetas<'a>),
+    ) -> Result<(), SanitizeError> {
+        visit::visit_instructions(self, visit)
+    }
+
+    /// Format this message for logs, showing its keys' roles and its
+    /// instructions' programs, accounts and data lengths, but not their data,
+    /// and truncating keys. See [`RedactedMessage`].
+    pub fn redacted(&self) -> RedactedMessage<'_> {
+        RedactedMessage(self)
+    }
+
+    /// Render this message as an ordered list of statements for users to
+    /// review before signing, like on the screen of a hardware wallet. See
+    /// [`SigningDisplay`].
+    ///
+    /// ```
+    /// # use solana_message::{Message, SigningStatement};
+    /// # use solana_pubkey::Pubkey;
+    /// # use solana_system_interface::instruction as system_instruction;
+    /// let payer = Pubkey::new_unique();
+    /// let to = Pubkey::new_unique();
+    /// let instruction = system_instruction::transfer(&payer, &to, 42);
+    /// let message = Message::new(&[instruction], Some(&payer));
+    /// let display = message.signing_display();
+    /// assert_eq!(display.statements[0], SigningStatement::FeePayer(&payer));
+    /// assert_eq!(
+    ///     display.statements[2],
+    ///     SigningStatement::Transfer { from: &payer, to: &to, lamports: 42 }
+    /// );
+    /// let lines = display.to_string();
+    /// let lines = lines.lines().collect::<Vec<_>>();
+    /// assert_eq!(lines[0], format!("1. fee payer {payer}"));
+    /// assert_eq!(lines[2], format!("3. transfer 42 lamports from {payer} to {to}"));
+    /// ```
+    pub fn signing_display(&self) -> SigningDisplay<'_> {
+        signing_display::signing_display(self)
+    }
+
+    /// The memos of this message: the data of its instructions to either
+    /// version of the memo program, in instruction order.
+    ///
+    /// The memo program fails on data that is not valid UTF-8, so such
+    /// instructions are skipped, as are instructions whose program id index
+    /// is invalid.
+    pub fn memos(&self) -> Vec<&str> {
+        self.instructions
+            .iter()
+            .filter(|ix| {
+                self.account_keys
+                    .get(usize::from(ix.program_id_index))
+                    .is_some_and(|program_id| MEMO_PROGRAM_IDS.contains(program_id))
+            })
+            .filter_map(|ix| core::str::from_utf8(&ix.data).ok())
+            .collect()
+    }
+
+    /// Decode the instructions of this message, in order, into the
+    /// instruction types of the system, stake, vote, compute budget and
+    /// address lookup table programs.
+    ///
+    /// Instructions of other programs are returned undecoded. Instructions of
+    /// those programs whose data the program would reject fail to decode.
+    ///
+    /// ```
+    /// # use solana_message::{Message, ParsedInstruction};
+    /// # use solana_pubkey::Pubkey;
+    /// # use solana_system_interface::instruction::{self as system_instruction, SystemInstruction};
+    /// let from = Pubkey::new_unique();
+    /// let instruction = system_instruction::transfer(&from, &Pubkey::new_unique(), 42);
+    /// let message = Message::new(&[instruction], Some(&from));
+    ///
+    /// for parsed in message.parse_instructions() {
+    ///     if let ParsedInstruction::System(SystemInstruction::Transfer { lamports }) = parsed? {
+    ///         assert_eq!(lamports, 42);
+    ///     }
+    /// }
+    /// # Ok::<(), solana_message::ParseInstructionError>(())
+    /// ```
+    #[cfg(feature = "parse")]
+    pub fn parse_instructions(&self) -> ParsedInstructions<'_> {
+        ParsedInstructions::new(&self.account_keys, &self.instructions)
+    }
+
     #[cfg(feature = "bincode")]
     pub fn serialize(&self) -> Vec<u8> {
         bincode::serialize(self).unwrap()
     }
 
+    /// Deserialize a message from its wire format, for input that cannot be
+    /// trusted.
+    ///
+    /// Unlike bincode deserialization, this rejects trailing bytes and
+    /// versioned messages, checks length prefixes against the input before
+    /// allocating, and reports where the input is invalid. The message is
+    /// not sanitized.
+    pub fn deserialize_strict(bytes: &[u8]) -> Result<Self, WireError> {
+        wire::deserialize_legacy(bytes)
+    }
+
+    /// Serialize this message and encode it as base64, in the standard
+    /// alphabet with padding, as RPC expects.
+    #[cfg(feature = "encoding")]
+    pub fn to_base64(&self) -> String {
+        encoding::to_base64(self)
+    }
+
+    /// Decode a message from base64 in the standard alphabet with padding,
+    /// as RPC returns it. The message is not sanitized.
+    #[cfg(feature = "encoding")]
+    pub fn from_base64(encoded: &str) -> Result<Self, MessageDecodeError> {
+        encoding::from_base64(encoded)
+    }
+
+    /// Serialize this message and encode it as base58.
+    #[cfg(feature = "encoding")]
+    pub fn to_base58(&self) -> String {
+        encoding::to_base58(self)
+    }
+
+    /// Decode a message from base58. The message is not sanitized.
+    #[cfg(feature = "encoding")]
+    pub fn from_base58(encoded: &str) -> Result<Self, MessageDecodeError> {
+        encoding::from_base58(encoded)
+    }
+
+    /// Return the length of [`Message::serialize`]'s output, computed from
+    /// the lengths of the message's fields without serializing it.
+    pub fn serialized_size(&self) -> usize {
+        MESSAGE_HEADER_LENGTH
+            + short_vec_len(self.account_keys.len())
+            + self.account_keys.len() * PUBKEY_BYTES
+            + HASH_BYTES
+            + short_vec_len(self.instructions.len())
+            + self
+                .instructions
+                .iter()
+                .map(CompiledInstruction::serialized_size)
+                .sum::<usize>()
+    }
+
+    /// Returns true if a transaction with this message and its required
+    /// signatures is no larger than [`PACKET_DATA_SIZE`].
+    ///
+    /// [`PACKET_DATA_SIZE`]: solana_packet::PACKET_DATA_SIZE
+    pub fn fits_in_packet(&self) -> bool {
+        fits_in_packet(self.header.num_required_signatures, self.serialized_size())
+    }
+
+    /// Break down the size of a transaction with this message and its
+    /// required signatures by section, with suggestions for shrinking it if
+    /// it does not fit in a packet.
+    ///
+    /// ```
+    /// # use solana_instruction::Instruction;
+    /// # use solana_message::{Message, SizeSuggestion};
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[0; 1500], vec![]);
+    /// let message = Message::new(&[instruction], Some(&payer));
+    /// let breakdown = message.size_breakdown();
+    /// assert!(!message.fits_in_packet());
+    /// assert_eq!(breakdown.excess(), 438);
+    /// assert_eq!(
+    ///     breakdown.suggestions,
+    ///     [SizeSuggestion::ShrinkInstructionData { instruction_index: 0, data_len: 1500 }]
+    /// );
+    /// let expected = [
+    ///     "transaction size: 1670 bytes, 438 over the 1232 byte limit",
+    ///     "  signatures: 65",
+    ///     "  header: 4",
+    ///     "  static keys: 65",
+    ///     "  recent blockhash: 32",
+    ///     "  instruction 0: 1504 (1500 data)",
+    ///     "  address table lookups: 0",
+    ///     "suggestion: shrink the 1500 bytes of data of instruction 0 or move it to another \
+    ///      transaction",
+    /// ];
+    /// assert_eq!(breakdown.to_string(), expected.join("\n"));
+    /// ```
+    pub fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new(
+            false,
+            &self.header,
+            &self.account_keys,
+            |i| self.is_writable_index(i),
+            &self.instructions,
+            0,
+        )
+    }
+
+    /// Locate the header, each account key, the recent blockhash and each
+    /// instruction in [`Message::serialize`]'s output, computed from the
+    /// lengths of the message's fields without serializing it.
+    ///
+    /// Hardware wallets and remote signers can use the offsets to show and
+    /// check individual fields of the bytes they are asked to sign.
+    ///
+    /// ```
+    /// # use solana_hash::Hash;
+    /// # use solana_instruction::Instruction;
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+    /// let blockhash = Hash::new_unique();
+    /// let message = Message::new_with_blockhash(&[instruction], Some(&payer), &blockhash);
+    /// let serialized = message.serialize();
+    /// let offsets = message.field_offsets();
+    /// assert_eq!(&serialized[offsets.account_keys[0].range()], payer.as_ref());
+    /// assert_eq!(serialized[offsets.instructions[0].data.range()], [1, 2, 3]);
+    /// ```
+    pub fn field_offsets(&self) -> FieldOffsets {
+        field_offsets::field_offsets(self)
+    }
+
+    /// Describe how `other` differs from this message: its header, recent
+    /// blockhash, account keys and instructions.
+    ///
+    /// Useful for finding why two messages built for the same transaction
+    /// serialize, and so sign, differently.
+    ///
+    /// ```
+    /// # use solana_hash::Hash;
+    /// # use solana_instruction::Instruction;
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let program_id = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(program_id, &[1], vec![]);
+    /// let message = Message::new(&[instruction], Some(&payer));
+    /// assert!(message.diff(&message).is_empty());
+    ///
+    /// let mut other = message.clone();
+    /// other.instructions[0].data = vec![2];
+    /// other.recent_blockhash = Hash::new_unique();
+    /// let diff = message.diff(&other);
+    /// assert!(diff.recent_blockhash.is_some());
+    /// assert!(diff.instructions[0].data_changed());
+    /// assert_eq!(
+    ///     diff.to_string(),
+    ///     format!(
+    ///         "recent blockhash: {} -> {}\ninstruction 0: data changed, 1 -> 1 bytes",
+    ///         message.recent_blockhash, other.recent_blockhash,
+    ///     )
+    /// );
+    /// ```
+    pub fn diff(&self, other: &Self) -> MessageDiff {
+        MessageDiff::new(self, other)
+    }
+
+    /// Return the fee, in lamports, for the signatures of a transaction with
+    /// this message, including the signatures verified by precompiles.
+    pub fn signature_fee(&self, lamports_per_signature: u64) -> u64 {
+        self.fee_breakdown(lamports_per_signature).signature_fee
+    }
+
+    /// Return the signature and prioritization fees of a transaction with
+    /// this message, along with the compute budget it requests.
+    ///
+    /// ```
+    /// # use solana_compute_budget_interface::ComputeBudgetInstruction;
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let message = Message::new(
+    ///     &[
+    ///         ComputeBudgetInstruction::set_compute_unit_limit(300_000),
+    ///         ComputeBudgetInstruction::set_compute_unit_price(10_000),
+    ///     ],
+    ///     Some(&payer),
+    /// );
+    /// let fees = message.fee_breakdown(5_000);
+    /// assert_eq!(fees.signature_fee, 5_000);
+    /// assert_eq!(fees.compute_unit_limit, Some(300_000));
+    /// assert_eq!(fees.prioritization_fee, 3_000);
+    /// assert_eq!(fees.total_fee(), 8_000);
+    /// ```
+    pub fn fee_breakdown(&self, lamports_per_signature: u64) -> FeeBreakdown {
+        FeeBreakdown::new(
+            lamports_per_signature,
+            &self.header,
+            &self.account_keys,
+            &self.instructions,
+        )
+    }
+
+    /// Return the number of write locks the runtime takes for a transaction
+    /// with this message, after demoting reserved accounts and program ids
+    /// like [`Message::writable_bitset`].
+    ///
+    /// Pass the latest set of reserved account keys, like
+    /// [`ReservedAccountKeys::keys`], to get the count the validator cost
+    /// model charges.
+    ///
+    /// [`ReservedAccountKeys::keys`]: crate::ReservedAccountKeys::keys
+    pub fn num_write_locks(&self, reserved_account_keys: &HashSet<Pubkey>) -> u64 {
+        self.writable_bitset(Some(reserved_account_keys))
+            .into_iter()
+            .filter(|&is_writable| is_writable)
+            .count() as u64
+    }
+
+    /// Return the signature, write lock and instruction data costs that the
+    /// validator cost model charges a transaction with this message.
+    ///
+    /// Write locks are counted like [`Message::num_write_locks`].
+    pub fn cost_estimate(
+        &self,
+        params: &CostModelParams,
+        reserved_account_keys: &HashSet<Pubkey>,
+    ) -> CostEstimate {
+        CostEstimate::new(
+            params,
+            &self.header,
+            &self.account_keys,
+            &self.instructions,
+            self.num_write_locks(reserved_account_keys),
+        )
+    }
+
+    /// Verify the offsets and signatures of every ed25519 and secp256k1
+    /// program instruction, as the runtime does before executing a
+    /// transaction with this message.
+    ///
+    /// This checks signatures against the data the instructions refer to
+    /// without running the transaction, so invalid precompile usage can be
+    /// rejected before it is forwarded to a leader. The error is the one the
+    /// runtime returns: the index of the first invalid instruction with its
+    /// [`PrecompileError`] as a custom instruction error.
+    ///
+    /// [`PrecompileError`]: https://docs.rs/solana-precompile-error/latest/solana_precompile_error/enum.PrecompileError.html
+    #[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+    pub fn verify_precompiles(
+        &self,
+        feature_set: &PrecompileFeatureSet,
+    ) -> Result<(), TransactionError> {
+        precompiles::verify_precompiles(&self.account_keys, &self.instructions, feature_set)
+    }
+
+    /// Run every check of this message that needs no account state, and
+    /// return all the ways it fails them rather than the first.
+    ///
+    /// The message is sanitized, and its signature count, its size with its
+    /// signatures, its account keys and, if `constraints` includes them, its
+    /// writable reserved keys and precompile instructions are checked. This
+    /// is meant for admission control, such as an RPC node deciding whether
+    /// to forward a transaction.
+    ///
+    /// ```
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::{Message, StaticConstraints, StaticViolation};
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(
+    ///     Pubkey::new_unique(),
+    ///     &[0; 2000],
+    ///     vec![AccountMeta::new_readonly(Pubkey::new_unique(), true)],
+    /// );
+    /// let message = Message::new(&[instruction], Some(&payer));
+    /// let violations = message
+    ///     .verify_static(&StaticConstraints::new(1))
+    ///     .unwrap_err();
+    /// assert!(matches!(
+    ///     violations[..],
+    ///     [StaticViolation::TooManySignatures { .. }, StaticViolation::TooLarge(_)]
+    /// ));
+    /// ```
+    pub fn verify_static(
+        &self,
+        constraints: &StaticConstraints,
+    ) -> Result<(), Vec<StaticViolation>> {
+        static_checks::verify_static(self, constraints)
+    }
+
+    /// Find the first `SetComputeUnitLimit` instruction, returning its index
+    /// and the compute unit limit it sets.
+    pub fn find_compute_unit_limit(&self) -> Option<(usize, u32)> {
+        self.find_compute_budget_instruction(parse_compute_unit_limit)
+    }
+
+    /// Find the first `SetComputeUnitPrice` instruction, returning its index
+    /// and the compute unit price it sets, in micro-lamports.
+    pub fn find_compute_unit_price(&self) -> Option<(usize, u64)> {
+        self.find_compute_budget_instruction(parse_compute_unit_price)
+    }
+
+    fn find_compute_budget_instruction<T>(
+        &self,
+        parse: impl Fn(&[u8]) -> Option<T>,
+    ) -> Option<(usize, T)> {
+        self.instructions
+            .iter()
+            .enumerate()
+            .find_map(|(index, ix)| {
+                self.account_keys
+                    .get(usize::from(ix.program_id_index))
+                    .is_some_and(is_compute_budget_program)
+                    .then(|| parse(&ix.data))
+                    .flatten()
+                    .map(|value| (index, value))
+            })
+    }
+
+    /// Set the compute unit limit of this message, replacing the data of its
+    /// `SetComputeUnitLimit` instruction or inserting one.
+    ///
+    /// Other instructions and their account indexes are left untouched. A new
+    /// instruction goes first, or right after the advance nonce instruction of
+    /// a nonced message, and the compute budget program is appended to the
+    /// read-only account keys if it is not already one of the keys. Fails if
+    /// there is no room for another account key.
+    ///
+    /// This changes the message, so it must be signed again.
+    ///
+    /// ```
+    /// # use solana_instruction::Instruction;
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+    /// let mut message = Message::new(&[instruction], Some(&payer));
+    /// message.set_compute_unit_price(5_000)?;
+    /// message.set_compute_unit_price(10_000)?;
+    /// assert_eq!(message.find_compute_unit_price(), Some((0, 10_000)));
+    /// assert_eq!(message.instructions.len(), 2);
+    /// # Ok::<(), solana_message::CompileError>(())
+    /// ```
+    pub fn set_compute_unit_limit(&mut self, units: u32) -> Result<(), CompileError> {
+        let data = compute_unit_limit_data(units);
+        match self.find_compute_unit_limit() {
+            Some((index, _)) => {
+                self.instructions[index].data = data;
+                Ok(())
+            }
+            None => self.insert_compute_budget_instruction(data),
+        }
+    }
+
+    /// Set the compute unit price of this message, in micro-lamports,
+    /// replacing the data of its `SetComputeUnitPrice` instruction or
+    /// inserting one. See [`Message::set_compute_unit_limit`].
+    pub fn set_compute_unit_price(&mut self, micro_lamports: u64) -> Result<(), CompileError> {
+        let data = compute_unit_price_data(micro_lamports);
+        match self.find_compute_unit_price() {
+            Some((index, _)) => {
+                self.instructions[index].data = data;
+                Ok(())
+            }
+            None => self.insert_compute_budget_instruction(data),
+        }
+    }
+
+    fn insert_compute_budget_instruction(&mut self, data: Vec<u8>) -> Result<(), CompileError> {
+        let program_id_index = match self.account_keys.iter().position(is_compute_budget_program) {
+            Some(index) => index,
+            None => {
+                if self.account_keys.len() > usize::from(u8::MAX)
+                    || self.header.num_readonly_unsigned_accounts == u8::MAX
+                {
+                    return Err(CompileError::AccountIndexOverflow);
+                }
+                self.account_keys.push(compute_budget::id());
+                self.header.num_readonly_unsigned_accounts += 1;
+                self.account_keys.len() - 1
+            }
+        };
+        let index = usize::from(self.uses_durable_nonce());
+        self.instructions.insert(
+            index,
+            CompiledInstruction {
+                program_id_index: program_id_index as u8,
+                accounts: vec![],
+                data,
+            },
+        );
+        Ok(())
+    }
+
+    /// Returns true if the message uses a durable nonce, i.e. its first
+    /// instruction advances a nonce account. See
+    /// [`Message::get_durable_nonce`].
+    pub fn uses_durable_nonce(&self) -> bool {
+        self.get_durable_nonce().is_some()
+    }
+
+    /// If the message uses a durable nonce, return the pubkey of the nonce
+    /// account.
+    ///
+    /// Like the runtime, this requires the first instruction to be a system
+    /// program `AdvanceNonceAccount` instruction whose first account, the
+    /// nonce account, is writable.
+    pub fn get_durable_nonce(&self) -> Option<&Pubkey> {
+        let ix = self.instructions.get(NONCED_TX_MARKER_IX_INDEX)?;
+        let program_id = self.account_keys.get(usize::from(ix.program_id_index))?;
+        if !system_program::check_id(program_id) || !is_advance_nonce_instruction_data(&ix.data) {
+            return None;
+        }
+        let nonce_index = usize::from(*ix.accounts.first()?);
+        if !self.is_maybe_writable(nonce_index, None) {
+            return None;
+        }
+        self.account_keys.get(nonce_index)
+    }
+
+    /// Set the recent blockhash of this message.
+    ///
+    /// This changes the message, so it must be signed again.
+    pub fn set_recent_blockhash(&mut self, recent_blockhash: Hash) {
+        self.recent_blockhash = recent_blockhash;
+    }
+
+    /// Set the recent blockhash of this message to `recent_blockhash.hash`,
+    /// unless it is expired at `block_height` or the message uses a durable
+    /// nonce, whose recent blockhash is the nonce.
+    ///
+    /// Callers keep `recent_blockhash` to check
+    /// [`RecentBlockhash::is_expired`] when retrying the transaction. Like
+    /// [`Message::set_recent_blockhash`], this changes the message, so it must
+    /// be signed again.
+    pub fn set_recent_blockhash_checked(
+        &mut self,
+        recent_blockhash: &RecentBlockhash,
+        block_height: u64,
+    ) -> Result<(), RecentBlockhashError> {
+        if recent_blockhash.is_expired(block_height) {
+            return Err(RecentBlockhashError::Expired {
+                last_valid_block_height: recent_blockhash.last_valid_block_height,
+                block_height,
+            });
+        }
+        if self.get_durable_nonce().is_some() {
+            return Err(RecentBlockhashError::DurableNonce);
+        }
+        self.recent_blockhash = recent_blockhash.hash;
+        Ok(())
+    }
+
+    /// Make `payer` the fee payer of this message, moving it to the front of
+    /// the account keys as a writable signer.
+    ///
+    /// The previous fee payer is removed unless an instruction uses it, in
+    /// which case it stays a writable signer. The order of the other account
+    /// keys is kept. Fails if the message is not sanitized or the keys no
+    /// longer fit in the header.
+    ///
+    /// This changes the message, so it must be signed again.
+    ///
+    /// ```
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let sponsor = Pubkey::new_unique();
+    /// let user = Pubkey::new_unique();
+    /// let accounts = vec![AccountMeta::new(user, true)];
+    /// let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], accounts);
+    /// let mut message = Message::new(&[instruction.clone()], Some(&user));
+    /// message.set_fee_payer(&sponsor)?;
+    /// assert_eq!(message.account_keys[..2], [sponsor, user]);
+    /// assert_eq!(message.header.num_required_signatures, 2);
+    /// assert_eq!(message.decompile_instruction(0), Some(instruction));
+    /// # Ok::<(), solana_message::CompileError>(())
+    /// ```
+    pub fn set_fee_payer(&mut self, payer: &Pubkey) -> Result<(), CompileError> {
+        let previous_payer = (self.header.num_required_signatures > 0)
+            .then(|| self.account_keys.first().copied())
+            .flatten();
+        let removed_payer =
+            previous_payer.filter(|key| key != payer && !self.is_instruction_key(key));
+        self.merge_account_keys(Some(payer), &[], removed_payer.as_ref())
+    }
+
+    /// Append `instruction` to this message, merging its accounts into the
+    /// account keys.
+    ///
+    /// New keys are added after the existing keys with the same signer and
+    /// writable roles, and existing keys are promoted to signer or writable if
+    /// `instruction` requires it. The order of the account keys is kept
+    /// otherwise, and the indexes of the other instructions are updated. Fails
+    /// if the message is not sanitized or the keys no longer fit in the
+    /// header.
+    ///
+    /// This changes the message, so it must be signed again.
+    pub fn push_instruction(&mut self, instruction: Instruction) -> Result<(), CompileError> {
+        let payer = (self.header.num_required_signatures > 0)
+            .then(|| self.account_keys.first().copied())
+            .flatten();
+        let metas: Vec<AccountMeta> = [AccountMeta::new_readonly(instruction.program_id, false)]
+            .into_iter()
+            .chain(instruction.accounts.iter().cloned())
+            .collect();
+        self.merge_account_keys(payer.as_ref(), &metas, None)?;
+        let compiled = compile_instruction(&instruction, &self.account_keys);
+        self.instructions.push(compiled);
+        Ok(())
+    }
+
+    /// Returns true if an instruction uses `key`, as its program or as one of
+    /// its accounts.
+    fn is_instruction_key(&self, key: &Pubkey) -> bool {
+        self.position_of(key)
+            .and_then(|index| u8::try_from(index).ok())
+            .is_some_and(|index| {
+                self.instructions
+                    .iter()
+                    .any(|ix| ix.program_id_index == index || ix.accounts.contains(&index))
+            })
+    }
+
+    /// Merge `metas` into the account keys, drop `removed`, and re-sort the
+    /// keys with `payer` first, updating the header and the instruction
+    /// account indexes to match.
+    fn merge_account_keys(
+        &mut self,
+        payer: Option<&Pubkey>,
+        metas: &[AccountMeta],
+        removed: Option<&Pubkey>,
+    ) -> Result<(), CompileError> {
+        self.sanitize().map_err(CompileError::InvalidMessage)?;
+        // key, is signer, is writable, and index in the current keys
+        let mut keys: Vec<(Pubkey, bool, bool, Option<usize>)> = self
+            .account_keys
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| Some(*key) != removed)
+            .map(|(index, key)| {
+                (
+                    *key,
+                    self.is_signer(index),
+                    self.is_writable_index(index),
+                    Some(index),
+                )
+            })
+            .collect();
+        for meta in payer
+            .map(|payer| AccountMeta::new(*payer, true))
+            .iter()
+            .chain(metas)
+        {
+            match keys.iter_mut().find(|(key, ..)| *key == meta.pubkey) {
+                Some((_, is_signer, is_writable, _)) => {
+                    *is_signer |= meta.is_signer;
+                    *is_writable |= meta.is_writable;
+                }
+                None => keys.push((meta.pubkey, meta.is_signer, meta.is_writable, None)),
+            }
+        }
+        // stable, so keys keep their order within each group
+        keys.sort_by_key(|(key, is_signer, is_writable, _)| {
+            (!is_signer, !is_writable, Some(key) != payer)
+        });
+
+        let try_into_u8 =
+            |num: usize| u8::try_from(num).map_err(|_| CompileError::AccountIndexOverflow);
+        let count = |is_signer: bool, is_writable: bool| {
+            keys.iter()
+                .filter(|(_, signer, writable, _)| *signer == is_signer && *writable == is_writable)
+                .count()
+        };
+        let header = MessageHeader {
+            num_required_signatures: try_into_u8(count(true, true) + count(true, false))?,
+            num_readonly_signed_accounts: try_into_u8(count(true, false))?,
+            num_readonly_unsigned_accounts: try_into_u8(count(false, false))?,
+        };
+        try_into_u8(keys.len().saturating_sub(1))?;
+
+        let mut new_indexes = vec![0u8; self.account_keys.len()];
+        for (new_index, (_, _, _, old_index)) in keys.iter().enumerate() {
+            if let Some(old_index) = old_index {
+                new_indexes[*old_index] = new_index as u8;
+            }
+        }
+        for ix in &mut self.instructions {
+            ix.program_id_index = new_indexes[usize::from(ix.program_id_index)];
+            for index in &mut ix.accounts {
+                *index = new_indexes[usize::from(*index)];
+            }
+        }
+        self.header = header;
+        self.account_keys = keys.into_iter().map(|(key, ..)| key).collect();
+        Ok(())
+    }
+
     pub fn program_id(&self, instruction_index: usize) -> Option<&Pubkey> {
         Some(
             &self.account_keys[self.instructions.get(instruction_index)?.program_id_index as usize],
@@ -466,10 +1410,33 @@ impl Message {
     }
 
     pub fn program_ids(&self) -> Vec<&Pubkey> {
+        self.program_ids_iter().collect()
+    }
+
+    /// Iterate over the program id of each instruction, like
+    /// [`Message::program_ids`] without allocating.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message is not sanitized and an instruction's program id
+    /// index is out of bounds.
+    pub fn program_ids_iter(&self) -> impl Iterator<Item = &Pubkey> + Clone {
+        self.instructions_with_program_id()
+            .map(|(program_id, _)| program_id)
+    }
+
+    /// Iterate over the instructions along with their program ids.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the message is not sanitized and an instruction's program id
+    /// index is out of bounds.
+    pub fn instructions_with_program_id(
+        &self,
+    ) -> impl Iterator<Item = (&Pubkey, &CompiledInstruction)> + Clone {
         self.instructions
             .iter()
-            .map(|ix| &self.account_keys[ix.program_id_index as usize])
-            .collect()
+            .map(|ix| (&self.account_keys[ix.program_id_index as usize], ix))
     }
 
     /// Returns true if the account at the specified index is an account input
@@ -495,10 +1462,8 @@ impl Message {
     }
 
     pub fn program_position(&self, index: usize) -> Option<usize> {
-        let program_ids = self.program_ids();
-        program_ids
-            .iter()
-            .position(|&&pubkey| pubkey == self.account_keys[index])
+        self.program_ids_iter()
+            .position(|pubkey| *pubkey == self.account_keys[index])
     }
 
     pub fn maybe_executable(&self, i: usize) -> bool {
@@ -526,7 +1491,9 @@ impl Message {
     /// optional to allow clients to approximate writability without requiring
     /// fetching the latest set of reserved account keys. If this method is
     /// called by the runtime, the latest set of reserved account keys must be
-    /// passed.
+    /// passed. [`ReservedAccountKeys::keys`] is the latest set.
+    ///
+    /// [`ReservedAccountKeys::keys`]: crate::ReservedAccountKeys::keys
     pub fn is_maybe_writable(
         &self,
         i: usize,
@@ -537,20 +1504,89 @@ impl Message {
             && !self.demote_program_id(i)
     }
 
-    /// Returns true if the account at the specified index is in the optional
-    /// reserved account keys set.
-    fn is_account_maybe_reserved(
-        &self,
-        key_index: usize,
-        reserved_account_keys: Option<&HashSet<Pubkey>>,
-    ) -> bool {
-        let mut is_maybe_reserved = false;
+    /// Return whether each account is writable, like calling
+    /// [`Message::is_maybe_writable`] for every index, in one pass over the
+    /// instructions rather than one per account.
+    ///
+    /// ```
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let program_id = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(
+    ///     program_id,
+    ///     &[],
+    ///     vec![AccountMeta::new(Pubkey::new_unique(), false)],
+    /// );
+    /// let message = Message::new(&[instruction], Some(&Pubkey::new_unique()));
+    /// assert_eq!(message.writable_bitset(None), [true, true, false]);
+    /// ```
+    pub fn writable_bitset(&self, reserved_account_keys: Option<&HashSet<Pubkey>>) -> Vec<bool> {
+        let mut is_writable: Vec<_> = (0..self.account_keys.len())
+            .map(|i| self.is_writable_index(i))
+            .collect();
         if let Some(reserved_account_keys) = reserved_account_keys {
-            if let Some(key) = self.account_keys.get(key_index) {
-                is_maybe_reserved = reserved_account_keys.contains(key);
+            for (is_writable, key) in is_writable.iter_mut().zip(&self.account_keys) {
+                *is_writable &= !reserved_account_keys.contains(key);
             }
         }
-        is_maybe_reserved
+        if !self.is_upgradeable_loader_present() {
+            for ix in &self.instructions {
+                if let Some(is_writable) = is_writable.get_mut(usize::from(ix.program_id_index)) {
+                    *is_writable = false;
+                }
+            }
+        }
+        is_writable
+    }
+
+    /// Return the accounts the runtime locks for writing and for reading to
+    /// process this message, so schedulers can tell which messages conflict
+    /// without reimplementing [`Message::is_maybe_writable`].
+    ///
+    /// Pass the latest set of reserved account keys, like
+    /// [`ReservedAccountKeys::keys`], to get the locks the runtime takes.
+    /// The runtime rejects messages with duplicate keys before locking any
+    /// account, see [`Message::has_duplicates`].
+    ///
+    /// ```
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// # use std::collections::HashSet;
+    /// let program_id = Pubkey::new_unique();
+    /// let account = Pubkey::new_unique();
+    /// let payer = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(
+    ///     program_id,
+    ///     &[],
+    ///     vec![AccountMeta::new(account, false)],
+    /// );
+    /// let message = Message::new(&[instruction], Some(&payer));
+    /// let locks = message.account_locks(&HashSet::new());
+    /// assert_eq!(locks.writable, [&payer, &account]);
+    /// assert_eq!(locks.readonly, [&program_id]);
+    /// ```
+    ///
+    /// [`ReservedAccountKeys::keys`]: crate::ReservedAccountKeys::keys
+    pub fn account_locks(&self, reserved_account_keys: &HashSet<Pubkey>) -> AccountLocks {
+        account_locks::account_locks(self, reserved_account_keys)
+    }
+
+    /// Returns true if the account at the specified index is in the optional
+    /// reserved account keys set.
+    fn is_account_maybe_reserved(
+        &self,
+        key_index: usize,
+        reserved_account_keys: Option<&HashSet<Pubkey>>,
+    ) -> bool {
+        let mut is_maybe_reserved = false;
+        if let Some(reserved_account_keys) = reserved_account_keys {
+            if let Some(key) = self.account_keys.get(key_index) {
+                is_maybe_reserved = reserved_account_keys.contains(key);
+            }
+        }
+        is_maybe_reserved
     }
 
     pub fn is_signer(&self, i: usize) -> bool {
@@ -558,26 +1594,193 @@ impl Message {
     }
 
     pub fn signer_keys(&self) -> Vec<&Pubkey> {
+        self.signer_keys_iter().collect()
+    }
+
+    /// Return the role of the account at `index` as described by the header,
+    /// or `None` if there is no such account.
+    ///
+    /// Unlike [`Message::is_maybe_writable`], accounts are not demoted to
+    /// read-only for being reserved or invoked as programs.
+    pub fn account_role(&self, index: usize) -> Option<AccountRole> {
+        (index < self.account_keys.len()).then(|| AccountRole {
+            is_signer: self.is_signer(index),
+            is_writable: self.is_writable_index(index),
+        })
+    }
+
+    /// Iterate over the account keys along with their roles, in order.
+    pub fn account_roles(&self) -> impl Iterator<Item = (&Pubkey, AccountRole)> {
+        self.account_keys.iter().enumerate().map(|(index, key)| {
+            (
+                key,
+                AccountRole {
+                    is_signer: self.is_signer(index),
+                    is_writable: self.is_writable_index(index),
+                },
+            )
+        })
+    }
+
+    /// Iterate over the keys the header marks as writable.
+    pub fn writable_keys(&self) -> impl Iterator<Item = &Pubkey> {
+        self.account_roles()
+            .filter_map(|(key, role)| role.is_writable.then_some(key))
+    }
+
+    /// Iterate over the keys the header marks as read-only.
+    pub fn readonly_keys(&self) -> impl Iterator<Item = &Pubkey> {
+        self.account_roles()
+            .filter_map(|(key, role)| (!role.is_writable).then_some(key))
+    }
+
+    /// Iterate over the keys that must sign, like [`Message::signer_keys`]
+    /// without allocating.
+    pub fn signer_keys_iter(&self) -> impl Iterator<Item = &Pubkey> {
         // Clamp in case we're working on un-`sanitize()`ed input
         let last_key = self
             .account_keys
             .len()
             .min(self.header.num_required_signatures as usize);
-        self.account_keys[..last_key].iter().collect()
+        self.account_keys[..last_key].iter()
+    }
+
+    /// Return the index of `key` in [`Message::account_keys`].
+    pub fn position_of(&self, key: &Pubkey) -> Option<usize> {
+        self.account_keys.iter().position(|k| k == key)
+    }
+
+    /// Return the index of `key` among the keys that must sign, which is also
+    /// the index of its signature in a transaction with this message, or
+    /// `None` if `key` does not have to sign.
+    ///
+    /// ```
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let signer = Pubkey::new_unique();
+    /// let other = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(
+    ///     Pubkey::new_unique(),
+    ///     &[],
+    ///     vec![AccountMeta::new(other, false), AccountMeta::new(signer, true)],
+    /// );
+    /// let message = Message::new(&[instruction], Some(&payer));
+    /// assert_eq!(message.signer_position(&signer), Some(1));
+    /// assert_eq!(message.position_of(&other), Some(2));
+    /// assert_eq!(message.signer_position(&other), None);
+    /// ```
+    pub fn signer_position(&self, key: &Pubkey) -> Option<usize> {
+        self.signer_keys_iter().position(|k| k == key)
+    }
+
+    /// Return the keys that must sign but are not among `provided`, in the
+    /// order of their signatures, so signing coordinators can tell whose
+    /// signatures are still needed.
+    ///
+    /// Keys in `provided` that do not have to sign are ignored.
+    ///
+    /// ```
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let signer = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(
+    ///     Pubkey::new_unique(),
+    ///     &[],
+    ///     vec![AccountMeta::new(signer, true)],
+    /// );
+    /// let message = Message::new(&[instruction], Some(&payer));
+    /// assert_eq!(message.missing_signers(&[payer]), [&signer]);
+    /// assert!(message.missing_signers(&[signer, payer]).is_empty());
+    /// ```
+    pub fn missing_signers(&self, provided: &[Pubkey]) -> Vec<&Pubkey> {
+        self.signer_keys_iter()
+            .filter(|key| !provided.contains(key))
+            .collect()
+    }
+
+    /// Iterate over the keys that do not sign.
+    pub fn non_signer_keys(&self) -> impl Iterator<Item = &Pubkey> {
+        self.account_keys
+            .iter()
+            .skip(self.header.num_required_signatures as usize)
+    }
+
+    /// Iterate over the keys that must sign and that the header marks as
+    /// writable, starting with the fee payer.
+    ///
+    /// Like [`Message::writable_keys`], keys are not demoted to read-only for
+    /// being reserved or invoked as programs.
+    pub fn writable_signers(&self) -> impl Iterator<Item = &Pubkey> {
+        self.account_roles()
+            .filter_map(|(key, role)| (role.is_signer && role.is_writable).then_some(key))
+    }
+
+    /// Iterate over the keys that must sign and that the header marks as
+    /// read-only.
+    pub fn readonly_signers(&self) -> impl Iterator<Item = &Pubkey> {
+        self.account_roles()
+            .filter_map(|(key, role)| (role.is_signer && !role.is_writable).then_some(key))
+    }
+
+    /// Check that the fee payer is a writable signer, and that none of
+    /// `readonly_signers` is writable, so that their signatures do not
+    /// authorize writes to their accounts.
+    ///
+    /// Writability is that of [`Message::is_maybe_writable`], so keys in
+    /// `reserved_account_keys` and keys invoked as programs are read-only. A
+    /// fee payer demoted this way cannot be charged, and the runtime rejects
+    /// the transaction.
+    ///
+    /// ```
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::{Message, SignerPrivilegeError};
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let authority = Pubkey::new_unique();
+    /// let instruction = Instruction::new_with_bytes(
+    ///     Pubkey::new_unique(),
+    ///     &[],
+    ///     vec![AccountMeta::new(authority, true)],
+    /// );
+    /// let message = Message::new(&[instruction], Some(&payer));
+    /// assert_eq!(
+    ///     message.check_signer_privileges(&[authority], None),
+    ///     Err(SignerPrivilegeError::UnexpectedWritableSigner(authority))
+    /// );
+    /// ```
+    pub fn check_signer_privileges(
+        &self,
+        readonly_signers: &[Pubkey],
+        reserved_account_keys: Option<&HashSet<Pubkey>>,
+    ) -> Result<(), SignerPrivilegeError> {
+        signer_privileges::check_signer_privileges(self, readonly_signers, reserved_account_keys)
     }
 
     /// Returns `true` if `account_keys` has any duplicate keys.
     pub fn has_duplicates(&self) -> bool {
-        // Note: This is an O(n^2) algorithm, but requires no heap allocations. The benchmark
-        // `bench_has_duplicates` in benches/message_processor.rs shows that this implementation is
-        // ~50 times faster than using HashSet for very short slices.
-        for i in 1..self.account_keys.len() {
-            #[allow(clippy::arithmetic_side_effects)]
-            if self.account_keys[i..].contains(&self.account_keys[i - 1]) {
-                return true;
-            }
-        }
-        false
+        duplicates::has_duplicates(&self.account_keys)
+    }
+
+    /// Return the indexes of the duplicate keys in `account_keys`, as pairs of
+    /// the index of a key's first occurrence and the index of a repeat, ordered
+    /// by the index of the repeat.
+    ///
+    /// ```
+    /// # use solana_message::Message;
+    /// # use solana_pubkey::Pubkey;
+    /// let key = Pubkey::new_unique();
+    /// let message = Message {
+    ///     account_keys: vec![key, Pubkey::new_unique(), key, key],
+    ///     ..Message::default()
+    /// };
+    /// assert_eq!(message.find_duplicates(), [(0, 2), (0, 3)]);
+    /// ```
+    pub fn find_duplicates(&self) -> Vec<(usize, usize)> {
+        duplicates::find_duplicates(&self.account_keys)
     }
 
     /// Returns `true` if any account is the BPF upgradeable loader.
@@ -592,7 +1795,7 @@ impl Message {
 mod tests {
     use {
         super::*,
-        crate::MESSAGE_HEADER_LENGTH,
+        crate::{VersionedMessage, MESSAGE_HEADER_LENGTH},
         solana_instruction::AccountMeta,
         std::{collections::HashSet, str::FromStr},
     };
@@ -662,6 +1865,353 @@ mod tests {
         assert_eq!(message.header.num_required_signatures, 2);
     }
 
+    #[test]
+    fn test_try_new() {
+        let program_id = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let ix = Instruction::new_with_bincode(
+            program_id,
+            &0,
+            vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        );
+        assert_eq!(
+            Message::try_new_with_blockhash(&[ix.clone()], Some(&payer), &blockhash),
+            Ok(Message::new_with_blockhash(
+                &[ix.clone()],
+                Some(&payer),
+                &blockhash
+            ))
+        );
+        assert_eq!(
+            Message::try_new(&[ix.clone()], Some(&payer)),
+            Ok(Message::new(&[ix], Some(&payer)))
+        );
+
+        // too many signers for the header
+        let accounts = (0..256)
+            .map(|_| AccountMeta::new(Pubkey::new_unique(), true))
+            .collect();
+        let ix = Instruction::new_with_bincode(program_id, &0, accounts);
+        assert_eq!(
+            Message::try_new(&[ix], Some(&payer)),
+            Err(CompileError::AccountIndexOverflow)
+        );
+
+        // too many accounts to index, though each header count fits
+        let accounts = (0..255)
+            .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+            .collect();
+        let ix = Instruction::new_with_bincode(program_id, &0, accounts);
+        assert_eq!(
+            Message::try_new(&[ix], Some(&payer)),
+            Err(CompileError::AccountIndexOverflow)
+        );
+    }
+
+    #[test]
+    fn test_decompile_instruction() {
+        let payer = Pubkey::new_unique();
+        let program_id0 = Pubkey::new_unique();
+        let program_id1 = Pubkey::new_unique();
+        let ixs = [
+            Instruction::new_with_bincode(
+                program_id0,
+                &7u8,
+                vec![
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new_readonly(Pubkey::new_unique(), true),
+                    AccountMeta::new(Pubkey::new_unique(), false),
+                    AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                ],
+            ),
+            Instruction::new_with_bincode(program_id1, &(), vec![]),
+        ];
+        let message = Message::new(&ixs, Some(&payer));
+        assert_eq!(message.decompile_instruction(0).as_ref(), Some(&ixs[0]));
+        assert_eq!(
+            message.decompile_instructions().collect::<Option<Vec<_>>>(),
+            Some(ixs.to_vec())
+        );
+        assert_eq!(message.decompile_instruction(2), None);
+
+        // metas are derived from the header, so keys repeated across
+        // instructions take their most permissive role
+        let key = Pubkey::new_unique();
+        let ixs = [
+            Instruction::new_with_bincode(program_id0, &(), vec![AccountMeta::new(key, false)]),
+            Instruction::new_with_bincode(
+                program_id1,
+                &(),
+                vec![AccountMeta::new_readonly(key, false)],
+            ),
+        ];
+        let message = Message::new(&ixs, Some(&payer));
+        assert_eq!(
+            message.decompile_instruction(1).unwrap().accounts,
+            vec![AccountMeta::new(key, false)]
+        );
+
+        // unsanitized messages
+        let mut message = Message::new(&ixs, Some(&payer));
+        message.instructions[0].accounts.push(u8::MAX);
+        assert_eq!(message.decompile_instruction(0), None);
+        message.instructions[1].program_id_index = u8::MAX;
+        assert_eq!(message.decompile_instruction(1), None);
+    }
+
+    #[test]
+    fn test_set_compute_budget() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let ix = Instruction::new_with_bytes(
+            program_id,
+            &[1],
+            vec![
+                AccountMeta::new(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            ],
+        );
+        let mut message = Message::new(&[ix.clone()], Some(&payer));
+        let original = message.clone();
+        assert_eq!(message.find_compute_unit_limit(), None);
+        assert_eq!(message.find_compute_unit_price(), None);
+
+        message.set_compute_unit_limit(300_000).unwrap();
+        assert_eq!(message.account_keys.len(), original.account_keys.len() + 1);
+        assert_eq!(
+            message.header.num_readonly_unsigned_accounts,
+            original.header.num_readonly_unsigned_accounts + 1
+        );
+        message.set_compute_unit_price(7).unwrap();
+        assert_eq!(message.account_keys.len(), original.account_keys.len() + 1);
+        assert_eq!(message.find_compute_unit_limit(), Some((1, 300_000)));
+        assert_eq!(message.find_compute_unit_price(), Some((0, 7)));
+        assert_eq!(message.decompile_instruction(2), Some(ix.clone()));
+        assert!(message.sanitize().is_ok());
+
+        message.set_compute_unit_limit(1).unwrap();
+        message.set_compute_unit_price(8).unwrap();
+        assert_eq!(message.instructions.len(), 3);
+        assert_eq!(message.find_compute_unit_limit(), Some((1, 1)));
+        assert_eq!(message.find_compute_unit_price(), Some((0, 8)));
+        assert_eq!(
+            message.decompile_instructions().collect::<Option<Vec<_>>>(),
+            Some(vec![
+                Instruction::new_with_bytes(
+                    compute_budget::id(),
+                    &compute_unit_price_data(8),
+                    vec![]
+                ),
+                Instruction::new_with_bytes(
+                    compute_budget::id(),
+                    &compute_unit_limit_data(1),
+                    vec![]
+                ),
+                ix.clone(),
+            ])
+        );
+
+        // the advance nonce instruction stays first
+        let nonce_account = Pubkey::new_unique();
+        let mut message =
+            Message::new_with_nonce(vec![ix.clone()], Some(&payer), &nonce_account, &payer);
+        message.set_compute_unit_price(1).unwrap();
+        assert_eq!(message.find_compute_unit_price(), Some((1, 1)));
+        assert!(message.uses_durable_nonce());
+        assert!(message.sanitize().is_ok());
+
+        // no room for the compute budget program
+        let accounts = (0..254)
+            .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+            .collect();
+        let ix = Instruction::new_with_bytes(program_id, &[], accounts);
+        let mut message = Message::new(&[ix], Some(&payer));
+        assert_eq!(message.account_keys.len(), 256);
+        assert_eq!(
+            message.set_compute_unit_limit(1),
+            Err(CompileError::AccountIndexOverflow)
+        );
+    }
+
+    #[test]
+    fn test_push_instruction() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let [a, b, c, d] = [(); 4].map(|_| Pubkey::new_unique());
+        // keys in an order that compiling would not produce
+        let mut message = Message::new_with_compiled_instructions(
+            1,
+            0,
+            2,
+            vec![payer, c, a, program_id, b],
+            Hash::default(),
+            vec![CompiledInstruction::new_from_raw_parts(
+                3,
+                vec![],
+                vec![0, 1, 2, 4],
+            )],
+        );
+        let original = message.decompile_instruction(0).unwrap();
+
+        let ix = Instruction::new_with_bytes(
+            program_id,
+            &[9],
+            vec![
+                AccountMeta::new(d, false),
+                AccountMeta::new(b, false),
+                AccountMeta::new(a, false),
+            ],
+        );
+        message.push_instruction(ix.clone()).unwrap();
+        assert_eq!(message.account_keys, vec![payer, c, a, b, d, program_id]);
+        assert_eq!(
+            message.header,
+            MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            }
+        );
+        assert!(message.sanitize().is_ok());
+        assert_eq!(message.decompile_instruction(1), Some(ix));
+        // b was promoted to writable
+        let mut expected = original;
+        expected.accounts[3].is_writable = true;
+        assert_eq!(message.decompile_instruction(0), Some(expected));
+
+        // a new signer goes after the existing ones
+        let ix = Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(c, true)]);
+        message.push_instruction(ix).unwrap();
+        assert_eq!(message.account_keys, vec![payer, c, a, b, d, program_id]);
+        assert_eq!(message.header.num_required_signatures, 2);
+
+        let mut message = Message::default();
+        message
+            .instructions
+            .push(CompiledInstruction::new_from_raw_parts(0, vec![], vec![]));
+        assert_eq!(
+            message.push_instruction(Instruction::new_with_bytes(program_id, &[], vec![])),
+            Err(CompileError::InvalidMessage(
+                SanitizeError::IndexOutOfBounds
+            ))
+        );
+
+        // a header that claims more keys than the message has
+        let mut message = Message::new(&[], Some(&payer));
+        message.header.num_readonly_unsigned_accounts = 1;
+        assert_eq!(
+            message.set_fee_payer(&Pubkey::new_unique()),
+            Err(CompileError::InvalidMessage(
+                SanitizeError::IndexOutOfBounds
+            ))
+        );
+    }
+
+    #[test]
+    fn test_set_recent_blockhash_checked() {
+        let payer = Pubkey::new_unique();
+        let mut message = Message::new(&[], Some(&payer));
+        let recent_blockhash = RecentBlockhash {
+            hash: Hash::new_unique(),
+            last_valid_block_height: 150,
+        };
+        assert_eq!(recent_blockhash.blocks_remaining(100), 50);
+        assert_eq!(recent_blockhash.blocks_remaining(200), 0);
+        assert_eq!(
+            message.set_recent_blockhash_checked(&recent_blockhash, 151),
+            Err(RecentBlockhashError::Expired {
+                last_valid_block_height: 150,
+                block_height: 151,
+            })
+        );
+        assert_eq!(message.recent_blockhash, Hash::default());
+        assert_eq!(
+            message.set_recent_blockhash_checked(&recent_blockhash, 150),
+            Ok(())
+        );
+        assert_eq!(message.recent_blockhash, recent_blockhash.hash);
+
+        // the nonce of a durable nonce message is not replaced
+        let nonce = Pubkey::new_unique();
+        let mut message = Message::new_with_nonce(vec![], Some(&payer), &nonce, &payer);
+        assert_eq!(
+            message.set_recent_blockhash_checked(&recent_blockhash, 100),
+            Err(RecentBlockhashError::DurableNonce)
+        );
+        assert_eq!(message.recent_blockhash, Hash::default());
+    }
+
+    #[test]
+    fn test_set_fee_payer() {
+        let payer = Pubkey::new_unique();
+        let sponsor = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let ix = Instruction::new_with_bytes(
+            program_id,
+            &[],
+            vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        );
+        let mut message = Message::new(&[ix.clone()], Some(&payer));
+        message.set_recent_blockhash(Hash::new_unique());
+        let mut expected = Message::new(&[ix.clone()], Some(&sponsor));
+        expected.recent_blockhash = message.recent_blockhash;
+        // the previous payer is no longer needed
+        message.set_fee_payer(&sponsor).unwrap();
+        assert_eq!(message, expected);
+        message.set_fee_payer(&sponsor).unwrap();
+        assert_eq!(message, expected);
+
+        // the previous payer stays a signer if an instruction uses it
+        let ix = Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(payer, true)]);
+        let mut message = Message::new(&[ix.clone()], Some(&payer));
+        message.set_fee_payer(&sponsor).unwrap();
+        assert_eq!(message.account_keys, vec![sponsor, payer, program_id]);
+        assert_eq!(message.header.num_required_signatures, 2);
+        assert_eq!(message.decompile_instruction(0), Some(ix.clone()));
+
+        // an existing read-only key is promoted
+        let readonly = Pubkey::new_unique();
+        let ix = Instruction::new_with_bytes(
+            program_id,
+            &[],
+            vec![AccountMeta::new_readonly(readonly, false)],
+        );
+        let mut message = Message::new(&[ix], Some(&payer));
+        message.set_fee_payer(&readonly).unwrap();
+        assert_eq!(message.account_keys, vec![readonly, program_id]);
+        assert_eq!(message.header.num_readonly_unsigned_accounts, 1);
+        assert!(message.sanitize().is_ok());
+    }
+
+    #[test]
+    fn test_get_durable_nonce() {
+        let payer = Pubkey::new_unique();
+        let nonce_account = Pubkey::new_unique();
+        let ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+        let message =
+            Message::new_with_nonce(vec![ix.clone()], Some(&payer), &nonce_account, &payer);
+        assert!(message.uses_durable_nonce());
+        assert_eq!(message.get_durable_nonce(), Some(&nonce_account));
+
+        // the advance nonce instruction must come first
+        let mut message = message;
+        message.instructions.rotate_left(1);
+        assert!(!message.uses_durable_nonce());
+
+        let message = Message::new(&[ix], Some(&payer));
+        assert_eq!(message.get_durable_nonce(), None);
+
+        // the nonce account must be writable
+        let ix = Instruction::new_with_bytes(
+            system_program::id(),
+            &[4, 0, 0, 0],
+            vec![AccountMeta::new_readonly(nonce_account, false)],
+        );
+        let message = Message::new(&[ix], Some(&payer));
+        assert_eq!(message.get_durable_nonce(), None);
+    }
+
     #[test]
     fn test_program_position() {
         let program_id0 = Pubkey::default();
@@ -711,6 +2261,75 @@ mod tests {
         assert!(!message.is_maybe_writable(6, Some(&reserved_account_keys)));
     }
 
+    #[test]
+    fn test_writable_bitset() {
+        let keys: Vec<_> = (0..7).map(|_| Pubkey::new_unique()).collect();
+        let mut message = Message {
+            header: MessageHeader {
+                num_required_signatures: 3,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: keys.clone(),
+            recent_blockhash: Hash::default(),
+            instructions: vec![
+                CompiledInstruction::new_from_raw_parts(4, vec![], vec![0, 3]),
+                CompiledInstruction::new_from_raw_parts(6, vec![], vec![]),
+                // out of range program ids are ignored
+                CompiledInstruction::new_from_raw_parts(7, vec![], vec![]),
+            ],
+        };
+        let reserved_account_keys = HashSet::from([keys[1]]);
+        let assert_matches_per_index = |message: &Message| {
+            for reserved_account_keys in [None, Some(&reserved_account_keys)] {
+                let expected: Vec<_> = (0..keys.len())
+                    .map(|i| message.is_maybe_writable(i, reserved_account_keys))
+                    .collect();
+                assert_eq!(message.writable_bitset(reserved_account_keys), expected);
+            }
+        };
+
+        assert_eq!(
+            message.writable_bitset(Some(&reserved_account_keys)),
+            [true, false, false, true, false, true, false]
+        );
+        assert_matches_per_index(&message);
+
+        // program ids are not demoted if the upgradeable loader is present
+        message.account_keys[5] = bpf_loader_upgradeable::id();
+        assert_eq!(
+            message.writable_bitset(None),
+            [true, true, false, true, true, true, false]
+        );
+        assert_matches_per_index(&message);
+    }
+
+    #[test]
+    fn test_signer_position() {
+        let key0 = Pubkey::new_unique();
+        let key1 = Pubkey::new_unique();
+        let mut message = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                ..MessageHeader::default()
+            },
+            account_keys: vec![key0, key1, key1],
+            ..Message::default()
+        };
+        assert_eq!(message.position_of(&key1), Some(1));
+        assert_eq!(message.signer_position(&key0), Some(0));
+        assert_eq!(message.signer_position(&key1), None);
+        assert_eq!(message.position_of(&Pubkey::new_unique()), None);
+
+        // more required signatures than keys
+        message.header.num_required_signatures = 5;
+        assert_eq!(message.signer_position(&key1), Some(1));
+        assert_eq!(
+            VersionedMessage::Legacy(message).signer_position(&key1),
+            Some(1)
+        );
+    }
+
     #[test]
     fn test_is_account_maybe_reserved() {
         let key0 = Pubkey::new_unique();
@@ -746,6 +2365,14 @@ mod tests {
             instructions,
         );
         assert_eq!(message.program_ids(), vec![&loader2]);
+        assert_eq!(
+            message.program_ids_iter().collect::<Vec<_>>(),
+            message.program_ids()
+        );
+        assert_eq!(
+            message.instructions_with_program_id().collect::<Vec<_>>(),
+            vec![(&loader2, &message.instructions[0])]
+        );
     }
 
     #[test]
@@ -768,6 +2395,36 @@ mod tests {
         assert!(!message.is_instruction_account(2));
     }
 
+    #[test]
+    fn test_serialized_size() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        // cross the one and two byte short-vec boundaries
+        for (num_accounts, data_len) in [(0, 0), (3, 127), (3, 128), (130, 1), (1, 0x4000)] {
+            let accounts = (0..num_accounts)
+                .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+                .collect();
+            let ix = Instruction::new_with_bytes(program_id, &vec![0; data_len], accounts);
+            let message = Message::new(&[ix.clone(), ix], Some(&payer));
+            assert_eq!(message.serialized_size(), message.serialize().len());
+        }
+        assert_eq!(
+            Message::default().serialized_size(),
+            Message::default().serialize().len()
+        );
+
+        // one signature, so the message may take up all but 65 bytes
+        let ix = Instruction::new_with_bytes(program_id, &[0; 1062], vec![]);
+        let message = Message::new(&[ix], Some(&payer));
+        assert_eq!(
+            message.serialize().len() + 65,
+            solana_packet::PACKET_DATA_SIZE
+        );
+        assert!(message.fits_in_packet());
+        let ix = Instruction::new_with_bytes(program_id, &[0; 1063], vec![]);
+        assert!(!Message::new(&[ix], Some(&payer)).fits_in_packet());
+    }
+
     #[test]
     fn test_message_header_len_constant() {
         assert_eq!(
@@ -808,6 +2465,131 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_message_hash_with_domain() {
+        let message = Message::default();
+        assert_eq!(
+            message.hash_with_domain(MESSAGE_HASH_DOMAIN),
+            message.hash()
+        );
+        assert_ne!(message.hash_with_domain(b"my-protocol-v1"), message.hash());
+        assert_eq!(
+            message.hash_with_domain(b"my-protocol-v1"),
+            Message::hash_raw_message_with_domain(b"my-protocol-v1", &message.serialize())
+        );
+        let versioned = VersionedMessage::Legacy(message);
+        assert_eq!(
+            versioned.hash_with_domain(MESSAGE_HASH_DOMAIN),
+            versioned.hash()
+        );
+    }
+
+    #[cfg(feature = "sha256")]
+    #[test]
+    fn test_message_sha256_hash_with_domain() {
+        let message = Message::default();
+        let expected = Hash::new_from_array([
+            0xe8, 0x8a, 0xe0, 0xdc, 0xcf, 0x87, 0xa1, 0x46, 0x15, 0x5d, 0x67, 0x52, 0x9b, 0x8e,
+            0xae, 0xc7, 0xd5, 0x66, 0x07, 0x32, 0x4d, 0xd2, 0xef, 0xf9, 0xe3, 0xfe, 0x22, 0xae,
+            0x0b, 0x15, 0x9c, 0xf0,
+        ]);
+        assert_eq!(message.sha256_hash_with_domain(b"my-protocol-v1"), expected);
+        assert_eq!(
+            VersionedMessage::Legacy(message).sha256_hash_with_domain(b"my-protocol-v1"),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_account_roles() {
+        let keys = [(); 5].map(|_| Pubkey::new_unique());
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 2,
+            },
+            account_keys: keys.to_vec(),
+            ..Message::default()
+        };
+        let role = |is_signer, is_writable| AccountRole {
+            is_signer,
+            is_writable,
+        };
+        assert_eq!(
+            message.account_roles().collect::<Vec<_>>(),
+            vec![
+                (&keys[0], role(true, true)),
+                (&keys[1], role(true, false)),
+                (&keys[2], role(false, true)),
+                (&keys[3], role(false, false)),
+                (&keys[4], role(false, false)),
+            ]
+        );
+        assert_eq!(message.account_role(1), Some(role(true, false)));
+        assert_eq!(message.account_role(5), None);
+        assert_eq!(
+            message.writable_keys().collect::<Vec<_>>(),
+            [&keys[0], &keys[2]]
+        );
+        assert_eq!(
+            message.readonly_keys().collect::<Vec<_>>(),
+            [&keys[1], &keys[3], &keys[4]]
+        );
+        assert_eq!(
+            message.signer_keys_iter().collect::<Vec<_>>(),
+            message.signer_keys()
+        );
+        assert_eq!(
+            message.non_signer_keys().collect::<Vec<_>>(),
+            [&keys[2], &keys[3], &keys[4]]
+        );
+
+        // more signatures than keys
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 3,
+                ..MessageHeader::default()
+            },
+            account_keys: keys[..2].to_vec(),
+            ..Message::default()
+        };
+        assert_eq!(message.signer_keys_iter().count(), 2);
+        assert_eq!(message.non_signer_keys().count(), 0);
+    }
+
+    #[test]
+    fn test_missing_signers() {
+        let keys: Vec<_> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 3,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 0,
+            },
+            account_keys: keys.clone(),
+            ..Message::default()
+        };
+        assert_eq!(message.missing_signers(&[]), [&keys[0], &keys[1], &keys[2]]);
+        // order and non-signers in `provided` do not matter
+        assert_eq!(
+            message.missing_signers(&[keys[3], keys[2], keys[0]]),
+            [&keys[1]]
+        );
+        assert!(message.missing_signers(&keys).is_empty());
+
+        // the missing signers of an unsanitized header are clamped
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 5,
+                ..MessageHeader::default()
+            },
+            account_keys: keys[..2].to_vec(),
+            ..Message::default()
+        };
+        assert_eq!(message.missing_signers(&keys[1..]), [&keys[0]]);
+    }
+
     #[test]
     fn test_is_writable_index_saturating_behavior() {
         // Directly matching issue #150 PoC 1:
@@ -884,4 +2666,47 @@ mod tests {
         assert!(!message5.is_writable_index(0));
         assert!(!message5.is_writable_index(1));
     }
+
+    #[test]
+    fn test_memos() {
+        let payer = Pubkey::new_unique();
+        let memo = |program_id: Pubkey, data: &[u8]| {
+            Instruction::new_with_bytes(
+                program_id,
+                data,
+                vec![AccountMeta::new_readonly(payer, true)],
+            )
+        };
+        let instructions = [
+            memo(MEMO_PROGRAM_IDS[0], b"first"),
+            memo(Pubkey::new_unique(), b"not a memo"),
+            memo(MEMO_PROGRAM_IDS[0], &[0xff, 0xfe]),
+            memo(MEMO_PROGRAM_IDS[1], "second \u{1f4dd}".as_bytes()),
+            memo(MEMO_PROGRAM_IDS[0], b""),
+        ];
+        let mut message = Message::new(&instructions, Some(&payer));
+        assert_eq!(message.memos(), ["first", "second \u{1f4dd}", ""]);
+
+        message.instructions[0].program_id_index = 99;
+        assert_eq!(message.memos(), ["second \u{1f4dd}", ""]);
+        assert!(Message::new(&[], Some(&payer)).memos().is_empty());
+    }
+
+    #[cfg(all(feature = "borsh", feature = "bincode"))]
+    #[test]
+    fn test_borsh_roundtrip() {
+        let payer = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new_readonly(Pubkey::new_unique(), true)],
+        );
+        let message =
+            Message::new_with_blockhash(&[instruction], Some(&payer), &Hash::new_unique());
+
+        let bytes = borsh::to_vec(&message).unwrap();
+        assert_eq!(borsh::from_slice::<Message>(&bytes).unwrap(), message);
+        // the four vectors have u32 lengths instead of one byte short-vec ones
+        assert_eq!(bytes.len(), message.serialize().len() + 4 * 3);
+    }
 }
@@ -39,11 +39,45 @@
 //! types continue to be exposed to Solana programs, for backwards compatibility
 //! reasons.
 
+mod account_locks;
+mod builder;
+#[cfg(feature = "bumpalo")]
+pub mod bump;
 pub mod compiled_instruction;
 mod compiled_keys;
+mod compiler;
+mod cost_estimate;
+mod diff;
+mod duplicates;
+#[cfg(feature = "encoding")]
+mod encoding;
+mod fee_breakdown;
+mod field_offsets;
+mod inline_compute_budget;
 pub mod inline_nonce;
 pub mod inner_instruction;
 pub mod legacy;
+mod packer;
+#[cfg(feature = "parse")]
+mod parse;
+#[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+mod precompiles;
+mod recent_blockhash;
+mod redacted;
+#[cfg(feature = "rpc-json")]
+mod rpc_json;
+mod signer_privileges;
+mod signing_display;
+mod size_breakdown;
+mod static_checks;
+#[cfg(feature = "templates")]
+pub mod templates;
+mod visit;
+mod wire;
+#[cfg(all(not(target_os = "solana"), feature = "wire-schema"))]
+mod wire_schema;
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
 #[cfg(feature = "serde")]
 use serde_derive::{Deserialize, Serialize};
 #[cfg(feature = "frozen-abi")]
@@ -54,19 +88,59 @@ use solana_frozen_abi_macro::AbiExample;
 mod non_bpf_modules {
     mod account_keys;
     mod address_loader;
+    mod reserved_account_keys;
     mod sanitized;
     mod versions;
 
-    pub use {account_keys::*, address_loader::*, sanitized::*, versions::*};
+    pub use {
+        account_keys::*, address_loader::*, reserved_account_keys::*, sanitized::*, versions::*,
+    };
 }
 
+#[cfg(feature = "encoding")]
+pub use encoding::MessageDecodeError;
 #[cfg(not(target_os = "solana"))]
 pub use non_bpf_modules::*;
-pub use {compiled_keys::CompileError, legacy::Message};
+#[cfg(feature = "parse")]
+pub use parse::{ParseInstructionError, ParsedInstruction, ParsedInstructions};
+#[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+pub use precompiles::PrecompileFeatureSet;
+#[cfg(feature = "rpc-json")]
+pub use rpc_json::RpcJson;
+#[cfg(all(not(target_os = "solana"), feature = "wire-schema"))]
+pub use wire_schema::{
+    WireMessageVersion, WireSchema, WireStruct, WireStructField, WireType, WIRE_SCHEMA_VERSION,
+};
+pub use {
+    account_locks::AccountLocks,
+    builder::{MessageBuildError, MessageBuilder},
+    compiled_keys::{
+        AccountKeysOverflow, CompileError, CompiledKeys, DetailedCompileError, KeyOrdering,
+    },
+    compiler::MessageCompiler,
+    cost_estimate::{CostEstimate, CostModelParams},
+    diff::{InstructionDiff, MessageDiff},
+    fee_breakdown::FeeBreakdown,
+    field_offsets::{FieldOffsets, FieldSpan, InstructionOffsets},
+    legacy::Message,
+    packer::{MessagePacker, PackError},
+    recent_blockhash::{RecentBlockhash, RecentBlockhashError},
+    redacted::RedactedMessage,
+    signer_privileges::SignerPrivilegeError,
+    signing_display::{SigningDisplay, SigningStatement},
+    size_breakdown::{InstructionSize, SizeBreakdown, SizeSuggestion},
+    static_checks::{StaticConstraints, StaticViolation},
+    visit::{ResolvedAccountMeta, ResolvedAccountMetas},
+    wire::{WireError, WireErrorKind, WireField},
+};
 
 /// The length of a message header in bytes.
 pub const MESSAGE_HEADER_LENGTH: usize = 3;
 
+/// The domain prefix hashed before a serialized message by `hash` on
+/// messages.
+pub const MESSAGE_HASH_DOMAIN: &[u8] = b"solana-tx-message-v1";
+
 /// Describes the organization of a `Message`'s account keys.
 ///
 /// Every [`Instruction`] specifies which accounts it may reference, or
@@ -100,7 +174,17 @@ pub const MESSAGE_HEADER_LENGTH: usize = 3;
 /// access the same read-write accounts are processed sequentially.
 ///
 /// [PoH]: https://docs.solanalabs.com/consensus/synchronization
+///
+/// Like `Message`, its [`borsh`] serialization with the `borsh` feature is
+/// not part of the wire format.
+///
+/// [`borsh`]: https://docs.rs/borsh/latest/borsh/
 #[cfg_attr(feature = "frozen-abi", derive(AbiExample))]
+#[cfg_attr(
+    feature = "borsh",
+    derive(BorshSerialize, BorshDeserialize),
+    borsh(crate = "borsh")
+)]
 #[cfg_attr(
     feature = "serde",
     derive(Deserialize, Serialize),
@@ -123,6 +207,14 @@ pub struct MessageHeader {
     pub num_readonly_unsigned_accounts: u8,
 }
 
+/// Whether an account of a message must sign the transaction and whether the
+/// transaction may write to it, as described by the [`MessageHeader`].
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct AccountRole {
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
 /// The definition of address lookup table accounts.
 ///
 /// As used by the `crate::v0` message format.
@@ -131,3 +223,37 @@ pub struct AddressLookupTableAccount {
     pub key: solana_pubkey::Pubkey,
     pub addresses: Vec<solana_pubkey::Pubkey>,
 }
+
+/// The number of bytes used to encode `len` as a `solana_short_vec` length
+/// prefix.
+pub(crate) const fn short_vec_len(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+/// The blake3 hash of `domain` followed by `message_bytes`.
+#[cfg(all(not(target_os = "solana"), feature = "blake3"))]
+pub(crate) fn blake3_hash_with_domain(domain: &[u8], message_bytes: &[u8]) -> solana_hash::Hash {
+    use blake3::traits::digest::Digest;
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(domain);
+    hasher.update(message_bytes);
+    let hash_bytes: [u8; solana_hash::HASH_BYTES] = hasher.finalize().into();
+    hash_bytes.into()
+}
+
+// inlined to avoid solana_signature dep
+pub(crate) const SIGNATURE_BYTES: usize = 64;
+
+/// Whether a transaction signed by `num_required_signatures` signers over a
+/// message of `message_size` bytes fits in a packet.
+pub(crate) fn fits_in_packet(num_required_signatures: u8, message_size: usize) -> bool {
+    let num_signatures = usize::from(num_required_signatures);
+    short_vec_len(num_signatures)
+        .saturating_add(num_signatures.saturating_mul(SIGNATURE_BYTES))
+        .saturating_add(message_size)
+        <= solana_packet::PACKET_DATA_SIZE
+}
@@ -0,0 +1,376 @@
+//! Packing of instructions into as few messages as fit them, for tools that
+//! send more instructions than fit in one transaction.
+#[cfg(not(target_os = "solana"))]
+use crate::{v0, AddressLookupTableAccount};
+use {
+    crate::{CompileError, Message, SizeBreakdown},
+    core::{fmt, slice},
+    solana_hash::Hash,
+    solana_instruction::Instruction,
+    solana_pubkey::Pubkey,
+};
+
+/// The number of accounts a transaction may lock by default, as returned by
+/// `Bank::get_transaction_account_lock_limit`.
+// inlined to avoid solana_transaction_context dep
+const MAX_TX_ACCOUNT_LOCKS: usize = 64;
+
+/// The error returned when packing instructions with [`MessagePacker`].
+///
+/// Each variant holds the index of the group of instructions that does not
+/// fit in a message on its own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PackError {
+    /// The group could not be compiled into a message
+    Compile { group: usize, err: CompileError },
+    /// A transaction with a message of the group and its signatures would not
+    /// fit in a packet, broken down by section
+    TooLarge {
+        group: usize,
+        breakdown: SizeBreakdown,
+    },
+    /// A message of the group would lock more accounts than the limit
+    TooManyAccountLocks {
+        group: usize,
+        num_accounts: usize,
+        max_account_locks: usize,
+    },
+}
+
+impl core::error::Error for PackError {}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Compile { group, err } => f.write_fmt(format_args!(
+                "instruction group {group} does not compile: {err}"
+            )),
+            Self::TooLarge { group, breakdown } => f.write_fmt(format_args!(
+                "instruction group {group} needs a transaction of {} bytes, which does not fit \
+                 in a packet",
+                breakdown.total()
+            )),
+            Self::TooManyAccountLocks {
+                group,
+                num_accounts,
+                max_account_locks,
+            } => f.write_fmt(format_args!(
+                "instruction group {group} locks {num_accounts} accounts, more than \
+                 {max_account_locks}"
+            )),
+        }
+    }
+}
+
+/// Packs instructions, in order, into as few messages as they fit in, which
+/// are legacy messages or, with [`MessagePacker::pack_groups_v0`], v0
+/// messages.
+///
+/// Instructions are added to a message until the next one would make a
+/// transaction with the message not fit in a packet, or make the message lock
+/// more accounts than the limit, which is 64 by default. Every message has the
+/// same fee payer and recent blockhash.
+///
+/// Instructions keep their order, within and across messages, so the
+/// messages can be sent one after another. Instructions that must execute
+/// atomically, like creating an account and then writing to it, can be
+/// packed as a group with [`MessagePacker::pack_groups`], which keeps each
+/// group in a single message. Since a message of fewer instructions always
+/// fits if a message of more does, filling each message before starting the
+/// next gives the fewest messages that keep the order.
+///
+/// ```
+/// # use solana_instruction::{AccountMeta, Instruction};
+/// # use solana_message::MessagePacker;
+/// # use solana_pubkey::Pubkey;
+/// let payer = Pubkey::new_unique();
+/// let program_id = Pubkey::new_unique();
+/// let instructions: Vec<_> = (0..100)
+///     .map(|_| {
+///         let recipient = AccountMeta::new(Pubkey::new_unique(), false);
+///         Instruction::new_with_bytes(program_id, &[0; 8], vec![recipient])
+///     })
+///     .collect();
+/// let messages = MessagePacker::new(payer).pack(&instructions)?;
+/// assert_eq!(messages.len(), 5);
+/// assert!(messages.iter().all(|message| message.fits_in_packet()));
+/// # Ok::<(), solana_message::PackError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessagePacker {
+    payer: Pubkey,
+    recent_blockhash: Hash,
+    max_account_locks: usize,
+}
+
+impl MessagePacker {
+    /// A packer of messages with `payer` as the fee payer.
+    pub fn new(payer: Pubkey) -> Self {
+        Self {
+            payer,
+            recent_blockhash: Hash::default(),
+            max_account_locks: MAX_TX_ACCOUNT_LOCKS,
+        }
+    }
+
+    /// Set the recent blockhash of the messages.
+    pub fn recent_blockhash(mut self, recent_blockhash: Hash) -> Self {
+        self.recent_blockhash = recent_blockhash;
+        self
+    }
+
+    /// Set the most accounts each message may lock, for clusters with a
+    /// different limit than the default of 64.
+    pub fn max_account_locks(mut self, max_account_locks: usize) -> Self {
+        self.max_account_locks = max_account_locks;
+        self
+    }
+
+    /// Pack `instructions` in order, where any instruction may start a new
+    /// message.
+    pub fn pack(&self, instructions: &[Instruction]) -> Result<Vec<Message>, PackError> {
+        self.pack_groups(instructions.iter().map(slice::from_ref))
+    }
+
+    /// Pack groups of instructions in order, keeping the instructions of each
+    /// group in the same message.
+    pub fn pack_groups<'a>(
+        &self,
+        groups: impl IntoIterator<Item = &'a [Instruction]>,
+    ) -> Result<Vec<Message>, PackError> {
+        self.pack_with(groups, |instructions| {
+            let message = Message::try_new_with_blockhash(
+                instructions,
+                Some(&self.payer),
+                &self.recent_blockhash,
+            )?;
+            Ok(Packed {
+                too_large: (!message.fits_in_packet()).then(|| message.size_breakdown()),
+                num_accounts: message.account_keys.len(),
+                message,
+            })
+        })
+    }
+
+    /// Pack groups of instructions in order into v0 messages that load the
+    /// keys they can from `address_lookup_table_accounts`, like
+    /// [`v0::Message::try_compile`], keeping the instructions of each group in
+    /// the same message.
+    ///
+    /// Keys loaded from lookup tables take up less of a packet, so v0
+    /// messages are more often limited by the account locks.
+    #[cfg(not(target_os = "solana"))]
+    pub fn pack_groups_v0<'a>(
+        &self,
+        groups: impl IntoIterator<Item = &'a [Instruction]>,
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+    ) -> Result<Vec<v0::Message>, PackError> {
+        self.pack_with(groups, |instructions| {
+            let message = v0::Message::try_compile(
+                &self.payer,
+                instructions,
+                address_lookup_table_accounts,
+                self.recent_blockhash,
+            )?;
+            let num_accounts = message
+                .address_table_lookups
+                .iter()
+                .map(|lookup| {
+                    lookup
+                        .writable_indexes
+                        .len()
+                        .saturating_add(lookup.readonly_indexes.len())
+                })
+                .fold(message.account_keys.len(), usize::saturating_add);
+            Ok(Packed {
+                too_large: (!message.fits_in_packet()).then(|| message.size_breakdown()),
+                num_accounts,
+                message,
+            })
+        })
+    }
+
+    fn pack_with<'a, M>(
+        &self,
+        groups: impl IntoIterator<Item = &'a [Instruction]>,
+        compile: impl Fn(&[Instruction]) -> Result<Packed<M>, CompileError>,
+    ) -> Result<Vec<M>, PackError> {
+        let try_message = |group: usize, instructions: &[Instruction]| {
+            let packed = compile(instructions).map_err(|err| PackError::Compile { group, err })?;
+            if let Some(breakdown) = packed.too_large {
+                return Err(PackError::TooLarge { group, breakdown });
+            }
+            if packed.num_accounts > self.max_account_locks {
+                return Err(PackError::TooManyAccountLocks {
+                    group,
+                    num_accounts: packed.num_accounts,
+                    max_account_locks: self.max_account_locks,
+                });
+            }
+            Ok(packed.message)
+        };
+        let mut messages = vec![];
+        let mut instructions: Vec<Instruction> = vec![];
+        let mut message = None;
+        for (index, group) in groups.into_iter().enumerate() {
+            let num_instructions = instructions.len();
+            instructions.extend_from_slice(group);
+            if let Ok(packed) = try_message(index, &instructions) {
+                message = Some(packed);
+                continue;
+            }
+            if let Some(message) = message.take() {
+                messages.push(message);
+                instructions.drain(..num_instructions);
+            }
+            message = Some(try_message(index, &instructions)?);
+        }
+        messages.extend(message);
+        Ok(messages)
+    }
+}
+
+/// A message compiled by [`MessagePacker`], with what its limits are checked
+/// against.
+struct Packed<M> {
+    message: M,
+    /// The size breakdown of the message, if it does not fit in a packet
+    too_large: Option<SizeBreakdown>,
+    /// The number of accounts the message locks
+    num_accounts: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_instruction::AccountMeta};
+
+    fn transfer(program_id: Pubkey, data_len: usize) -> Instruction {
+        Instruction::new_with_bytes(
+            program_id,
+            &vec![1; data_len],
+            vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        )
+    }
+
+    #[test]
+    fn test_pack() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+        let packer = MessagePacker::new(payer).recent_blockhash(blockhash);
+        assert_eq!(packer.pack(&[]), Ok(vec![]));
+
+        // packet size fills up first
+        let instructions: Vec<_> = (0..100).map(|_| transfer(program_id, 8)).collect();
+        let messages = packer.pack(&instructions).unwrap();
+        assert_eq!(
+            messages
+                .iter()
+                .map(|message| message.instructions.len())
+                .collect::<Vec<_>>(),
+            [24, 24, 24, 24, 4]
+        );
+        // the instructions keep their order
+        let keys = |instructions: &[Instruction]| {
+            instructions
+                .iter()
+                .map(|ix| ix.accounts[0].pubkey)
+                .collect::<Vec<_>>()
+        };
+        let packed_keys: Vec<_> = messages
+            .iter()
+            .flat_map(|message| {
+                message
+                    .instructions
+                    .iter()
+                    .map(|ix| message.account_keys[usize::from(ix.accounts[0])])
+            })
+            .collect();
+        assert_eq!(packed_keys, keys(&instructions));
+        for message in &messages {
+            assert_eq!(message.account_keys[0], payer);
+            assert_eq!(message.recent_blockhash, blockhash);
+            assert!(message.account_keys.len() <= MAX_TX_ACCOUNT_LOCKS);
+        }
+
+        // larger instructions fit fewer to a message
+        let instructions: Vec<_> = (0..10).map(|_| transfer(program_id, 300)).collect();
+        let messages = packer.pack(&instructions).unwrap();
+        assert_eq!(messages.len(), 4);
+        assert!(messages.iter().all(Message::fits_in_packet));
+
+        let messages = packer
+            .clone()
+            .max_account_locks(4)
+            .pack(&instructions[..4])
+            .unwrap();
+        assert_eq!(messages.len(), 2);
+    }
+
+    #[test]
+    fn test_pack_groups() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let packer = MessagePacker::new(payer);
+        let instructions: Vec<_> = (0..6).map(|_| transfer(program_id, 300)).collect();
+
+        // groups are not split, so a group that does not fit in the rest of a
+        // message starts a new one
+        let groups = [&instructions[..2], &instructions[2..4], &instructions[4..]];
+        let messages = packer.pack_groups(groups).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert!(messages
+            .iter()
+            .all(|message| message.instructions.len() == 2));
+        assert_eq!(packer.pack(&instructions).unwrap().len(), 2);
+
+        // a group that does not fit on its own fails
+        assert!(matches!(
+            packer.pack_groups([&instructions[..1], &instructions[1..]]),
+            Err(PackError::TooLarge { group: 1, .. })
+        ));
+        assert_eq!(
+            packer
+                .clone()
+                .max_account_locks(3)
+                .pack_groups([&instructions[..2]]),
+            Err(PackError::TooManyAccountLocks {
+                group: 0,
+                num_accounts: 4,
+                max_account_locks: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn test_pack_groups_v0() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let instructions: Vec<_> = (0..100).map(|_| transfer(program_id, 8)).collect();
+        let lookup_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: instructions
+                .iter()
+                .map(|ix| ix.accounts[0].pubkey)
+                .collect(),
+        };
+
+        // with the recipients loaded from a lookup table, the account locks
+        // fill up first
+        let messages = MessagePacker::new(payer)
+            .pack_groups_v0(instructions.iter().map(slice::from_ref), &[lookup_table])
+            .unwrap();
+        assert_eq!(
+            messages
+                .iter()
+                .map(|message| message.instructions.len())
+                .collect::<Vec<_>>(),
+            [62, 38]
+        );
+        assert!(messages.iter().all(v0::Message::fits_in_packet));
+        assert_eq!(
+            messages[0].address_table_lookups[0].writable_indexes.len(),
+            62
+        );
+    }
+}
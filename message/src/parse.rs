@@ -0,0 +1,253 @@
+//! Typed decoding of the instructions of well-known programs, like the
+//! `jsonParsed` encoding of RPC.
+use {
+    crate::compiled_instruction::CompiledInstruction,
+    bincode::Options,
+    borsh::BorshDeserialize,
+    serde::de::DeserializeOwned,
+    solana_address_lookup_table_interface::instruction::ProgramInstruction,
+    solana_compute_budget_interface::ComputeBudgetInstruction,
+    solana_packet::PACKET_DATA_SIZE,
+    solana_pubkey::Pubkey,
+    solana_sdk_ids::{address_lookup_table, compute_budget, stake, system_program, vote},
+    solana_stake_interface::instruction::StakeInstruction,
+    solana_system_interface::instruction::SystemInstruction,
+    solana_vote_interface::instruction::VoteInstruction,
+    std::{fmt, slice},
+};
+
+/// An instruction of a message, decoded into its program's instruction type
+/// if its program is one of the well-known native programs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParsedInstruction<'a> {
+    /// An instruction of the system program
+    System(SystemInstruction),
+    /// An instruction of the stake program
+    Stake(StakeInstruction),
+    /// An instruction of the vote program
+    Vote(VoteInstruction),
+    /// An instruction of the compute budget program
+    ComputeBudget(ComputeBudgetInstruction),
+    /// An instruction of the address lookup table program
+    AddressLookupTable(ProgramInstruction),
+    /// An instruction of any other program, which is not decoded
+    Unknown {
+        program_id: &'a Pubkey,
+        instruction: &'a CompiledInstruction,
+    },
+}
+
+/// Reasons an instruction could not be decoded by `parse_instructions` on
+/// messages.
+#[derive(PartialEq, Debug, Eq, Clone)]
+pub enum ParseInstructionError {
+    /// The instruction's program id index is not an index of the message's
+    /// account keys
+    InvalidProgramIdIndex(u8),
+    /// The instruction's data is not an instruction of its program, which is
+    /// one of the well-known programs
+    InvalidInstructionData(Pubkey),
+}
+
+impl core::error::Error for ParseInstructionError {}
+
+impl fmt::Display for ParseInstructionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidProgramIdIndex(index) => {
+                f.write_fmt(format_args!("invalid program id index {index}"))
+            }
+            Self::InvalidInstructionData(program_id) => f.write_fmt(format_args!(
+                "invalid instruction data for program {program_id}"
+            )),
+        }
+    }
+}
+
+/// An iterator over the instructions of a message, decoded, returned by
+/// `parse_instructions` on messages.
+#[derive(Debug, Clone)]
+pub struct ParsedInstructions<'a> {
+    account_keys: &'a [Pubkey],
+    instructions: slice::Iter<'a, CompiledInstruction>,
+}
+
+impl<'a> ParsedInstructions<'a> {
+    pub(crate) fn new(account_keys: &'a [Pubkey], instructions: &'a [CompiledInstruction]) -> Self {
+        Self {
+            account_keys,
+            instructions: instructions.iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for ParsedInstructions<'a> {
+    type Item = Result<ParsedInstruction<'a>, ParseInstructionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let instruction = self.instructions.next()?;
+        Some(parse_instruction(self.account_keys, instruction))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.instructions.size_hint()
+    }
+}
+
+impl ExactSizeIterator for ParsedInstructions<'_> {}
+
+fn parse_instruction<'a>(
+    account_keys: &'a [Pubkey],
+    instruction: &'a CompiledInstruction,
+) -> Result<ParsedInstruction<'a>, ParseInstructionError> {
+    let program_id = account_keys
+        .get(usize::from(instruction.program_id_index))
+        .ok_or(ParseInstructionError::InvalidProgramIdIndex(
+            instruction.program_id_index,
+        ))?;
+    let data = instruction.data.as_slice();
+    let parsed = if system_program::check_id(program_id) {
+        limited_deserialize(data).map(ParsedInstruction::System)
+    } else if stake::check_id(program_id) {
+        limited_deserialize(data).map(ParsedInstruction::Stake)
+    } else if vote::check_id(program_id) {
+        limited_deserialize(data).map(ParsedInstruction::Vote)
+    } else if compute_budget::check_id(program_id) {
+        // like the runtime, ignore trailing bytes
+        ComputeBudgetInstruction::deserialize(&mut &data[..])
+            .ok()
+            .map(ParsedInstruction::ComputeBudget)
+    } else if address_lookup_table::check_id(program_id) {
+        limited_deserialize(data).map(ParsedInstruction::AddressLookupTable)
+    } else {
+        return Ok(ParsedInstruction::Unknown {
+            program_id,
+            instruction,
+        });
+    };
+    parsed.ok_or(ParseInstructionError::InvalidInstructionData(*program_id))
+}
+
+// inlined to avoid solana_bincode dep
+/// Deserialize instruction data like the native programs do, with a limit of
+/// the size of a packet.
+fn limited_deserialize<T: DeserializeOwned>(data: &[u8]) -> Option<T> {
+    bincode::options()
+        .with_limit(PACKET_DATA_SIZE as u64)
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .deserialize(data)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{v0, Message, VersionedMessage},
+        solana_hash::Hash,
+        solana_instruction::{AccountMeta, Instruction},
+        solana_stake_interface::state::{Authorized, Lockup},
+        solana_vote_interface::state::VoteAuthorize,
+    };
+
+    #[test]
+    fn test_parse_instructions() {
+        let payer = Pubkey::new_unique();
+        let stake_account = Pubkey::new_unique();
+        let vote_account = Pubkey::new_unique();
+        let unknown = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(payer, true)],
+        );
+        let mut instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(10_000),
+            solana_system_interface::instruction::transfer(&payer, &stake_account, 42),
+            unknown.clone(),
+            solana_vote_interface::instruction::authorize(
+                &vote_account,
+                &payer,
+                &payer,
+                VoteAuthorize::Voter,
+            ),
+            solana_address_lookup_table_interface::instruction::close_lookup_table(
+                Pubkey::new_unique(),
+                payer,
+                payer,
+            ),
+        ];
+        let authorized = Authorized::auto(&payer);
+        instructions.extend(solana_stake_interface::instruction::create_account(
+            &payer,
+            &stake_account,
+            &authorized,
+            &Lockup::default(),
+            1,
+        ));
+        let message = Message::new(&instructions, Some(&payer));
+
+        let parsed: Vec<_> = message
+            .parse_instructions()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            parsed,
+            [
+                ParsedInstruction::ComputeBudget(ComputeBudgetInstruction::SetComputeUnitLimit(
+                    10_000
+                )),
+                ParsedInstruction::System(SystemInstruction::Transfer { lamports: 42 }),
+                ParsedInstruction::Unknown {
+                    program_id: &unknown.program_id,
+                    instruction: &message.instructions[2],
+                },
+                ParsedInstruction::Vote(VoteInstruction::Authorize(payer, VoteAuthorize::Voter)),
+                ParsedInstruction::AddressLookupTable(ProgramInstruction::CloseLookupTable),
+                ParsedInstruction::System(SystemInstruction::CreateAccount {
+                    lamports: 1,
+                    space: solana_stake_interface::state::StakeStateV2::size_of() as u64,
+                    owner: stake::id(),
+                }),
+                ParsedInstruction::Stake(StakeInstruction::Initialize(
+                    authorized,
+                    Lockup::default()
+                )),
+            ]
+        );
+
+        // versioned messages decode their instructions the same way
+        let versioned = VersionedMessage::V0(
+            v0::Message::try_compile(&payer, &instructions, &[], Hash::default()).unwrap(),
+        );
+        assert_eq!(versioned.parse_instructions().len(), instructions.len());
+        assert_eq!(
+            versioned
+                .parse_instructions()
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            parsed
+        );
+
+        // invalid instructions of well-known programs fail, without
+        // affecting the rest
+        let mut invalid = message.clone();
+        invalid.instructions[0].data = vec![9];
+        invalid.instructions[1].data.truncate(2);
+        invalid.instructions[2].program_id_index = 99;
+        let parsed: Vec<_> = invalid.parse_instructions().take(4).collect();
+        assert_eq!(
+            parsed[..3],
+            [
+                Err(ParseInstructionError::InvalidInstructionData(
+                    compute_budget::id()
+                )),
+                Err(ParseInstructionError::InvalidInstructionData(
+                    system_program::id()
+                )),
+                Err(ParseInstructionError::InvalidProgramIdIndex(99)),
+            ]
+        );
+        assert!(parsed[3].is_ok());
+    }
+}
@@ -0,0 +1,153 @@
+//! Static verification of the ed25519 and secp256k1 precompile instructions
+//! of a message, as the runtime verifies them before executing a transaction.
+use {
+    crate::compiled_instruction::CompiledInstruction,
+    solana_instruction_error::InstructionError,
+    solana_pubkey::Pubkey,
+    solana_sdk_ids::{ed25519_program, secp256k1_program},
+    solana_transaction_error::TransactionError,
+};
+
+/// The runtime features that change how precompile instructions are
+/// verified, passed to [`Message::verify_precompiles`].
+///
+/// [`Message::verify_precompiles`]: crate::Message::verify_precompiles
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PrecompileFeatureSet {
+    /// Whether `ed25519_precompile_verify_strict` is active, so ed25519
+    /// signatures are checked with `verify_strict`, which also rejects
+    /// small order public keys and malleable signatures.
+    pub ed25519_verify_strict: bool,
+}
+
+impl PrecompileFeatureSet {
+    /// All features active, as on a cluster that has activated them.
+    pub const fn all_enabled() -> Self {
+        Self {
+            ed25519_verify_strict: true,
+        }
+    }
+}
+
+/// Verify every ed25519 and secp256k1 program instruction, returning the
+/// error the runtime fails the transaction with for the first invalid one.
+pub(crate) fn verify_precompiles(
+    account_keys: &[Pubkey],
+    instructions: &[CompiledInstruction],
+    feature_set: &PrecompileFeatureSet,
+) -> Result<(), TransactionError> {
+    let instruction_datas: Vec<_> = instructions.iter().map(|ix| ix.data.as_slice()).collect();
+    for (index, ix) in instructions.iter().enumerate() {
+        let Some(program_id) = account_keys.get(usize::from(ix.program_id_index)) else {
+            continue;
+        };
+        let result = if ed25519_program::check_id(program_id) {
+            solana_ed25519_program::verify(
+                &ix.data,
+                &instruction_datas,
+                feature_set.ed25519_verify_strict,
+            )
+        } else if secp256k1_program::check_id(program_id) {
+            solana_secp256k1_program::verify(&ix.data, &instruction_datas)
+        } else {
+            continue;
+        };
+        result.map_err(|err| {
+            TransactionError::InstructionError(index as u8, InstructionError::Custom(err as u32))
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::Message,
+        ed25519_dalek::{Signer, SigningKey},
+        solana_instruction::Instruction,
+        solana_precompile_error::PrecompileError,
+    };
+
+    fn custom_error(index: u8, err: PrecompileError) -> TransactionError {
+        TransactionError::InstructionError(index, InstructionError::Custom(err as u32))
+    }
+
+    #[test]
+    fn test_verify_precompiles() {
+        let payer = Pubkey::new_unique();
+        let feature_set = PrecompileFeatureSet::all_enabled();
+
+        let secp_privkey = [7; 32];
+        let (signature, recovery_id) =
+            solana_secp256k1_program::sign_message(&secp_privkey, b"hello").unwrap();
+        let secp_pubkey = libsecp256k1::PublicKey::from_secret_key(
+            &libsecp256k1::SecretKey::parse(&secp_privkey).unwrap(),
+        );
+        let eth_address = solana_secp256k1_program::eth_address_from_pubkey(
+            &secp_pubkey.serialize()[1..].try_into().unwrap(),
+        );
+        // instruction indexes of the secp256k1 instruction refer to the
+        // first instruction
+        let secp_ix = solana_secp256k1_program::new_secp256k1_instruction_with_signature(
+            b"hello",
+            &signature,
+            recovery_id,
+            &eth_address,
+        );
+
+        let ed25519_key = SigningKey::from_bytes(&[9; 32]);
+        let ed25519_ix = solana_ed25519_program::new_ed25519_instruction_with_signature(
+            b"world",
+            &ed25519_key.sign(b"world").to_bytes(),
+            ed25519_key.verifying_key().as_bytes(),
+        );
+        let other_ix = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+
+        let message = Message::new(
+            &[secp_ix.clone(), ed25519_ix.clone(), other_ix.clone()],
+            Some(&payer),
+        );
+        assert_eq!(message.verify_precompiles(&feature_set), Ok(()));
+
+        // a tampered message fails the signature check of its instruction
+        let mut tampered = message.clone();
+        *tampered.instructions[1].data.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            tampered.verify_precompiles(&feature_set),
+            Err(custom_error(1, PrecompileError::InvalidSignature))
+        );
+        let mut tampered = message.clone();
+        *tampered.instructions[0].data.last_mut().unwrap() ^= 1;
+        assert_eq!(
+            tampered.verify_precompiles(&feature_set),
+            Err(custom_error(0, PrecompileError::InvalidSignature))
+        );
+
+        // moving the secp256k1 instruction breaks its offsets, which refer to
+        // instruction 0
+        let message = Message::new(&[other_ix, secp_ix], Some(&payer));
+        assert_eq!(
+            message.verify_precompiles(&feature_set),
+            Err(custom_error(1, PrecompileError::InvalidSignature))
+        );
+
+        // offsets past the end of the instruction data
+        let mut truncated = ed25519_ix.clone();
+        truncated.data.truncate(100);
+        let message = Message::new(&[truncated], Some(&payer));
+        assert_eq!(
+            message.verify_precompiles(&feature_set),
+            Err(custom_error(0, PrecompileError::InvalidDataOffsets))
+        );
+
+        // signature counts that the data is too short for
+        let mut empty = ed25519_ix;
+        empty.data = vec![0, 0, 1];
+        let message = Message::new(&[empty], Some(&payer));
+        assert_eq!(
+            message.verify_precompiles(&feature_set),
+            Err(custom_error(0, PrecompileError::InvalidInstructionDataSize))
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Recent blockhashes along with when they expire, for retrying transactions
+//! until their blockhash is too old.
+use {core::fmt, solana_hash::Hash};
+
+/// A recent blockhash and the last block height at which a transaction with
+/// it can be processed, as returned by the `getLatestBlockhash` RPC method.
+///
+/// ```
+/// # use solana_hash::Hash;
+/// # use solana_message::{Message, RecentBlockhash};
+/// # use solana_pubkey::Pubkey;
+/// let mut message = Message::new(&[], Some(&Pubkey::new_unique()));
+/// let recent_blockhash = RecentBlockhash {
+///     hash: Hash::new_unique(),
+///     last_valid_block_height: 150,
+/// };
+/// message.set_recent_blockhash_checked(&recent_blockhash, 100)?;
+/// assert_eq!(message.recent_blockhash, recent_blockhash.hash);
+///
+/// // a retry loop stops once the blockhash expires
+/// assert!(!recent_blockhash.is_expired(150));
+/// assert!(recent_blockhash.is_expired(151));
+/// # Ok::<(), solana_message::RecentBlockhashError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RecentBlockhash {
+    pub hash: Hash,
+    pub last_valid_block_height: u64,
+}
+
+impl RecentBlockhash {
+    /// Return true if a transaction with this blockhash can no longer be
+    /// processed at `block_height`.
+    pub fn is_expired(&self, block_height: u64) -> bool {
+        block_height > self.last_valid_block_height
+    }
+
+    /// The number of blocks after `block_height` in which a transaction with
+    /// this blockhash can still be processed, or zero if it has expired.
+    pub fn blocks_remaining(&self, block_height: u64) -> u64 {
+        self.last_valid_block_height.saturating_sub(block_height)
+    }
+}
+
+/// The reasons [`Message::set_recent_blockhash_checked`] refuses to set a
+/// blockhash.
+///
+/// [`Message::set_recent_blockhash_checked`]: crate::Message::set_recent_blockhash_checked
+#[derive(PartialEq, Debug, Eq, Clone)]
+pub enum RecentBlockhashError {
+    /// The blockhash is expired at the current block height
+    Expired {
+        last_valid_block_height: u64,
+        block_height: u64,
+    },
+    /// The message uses a durable nonce, which its recent blockhash holds
+    DurableNonce,
+}
+
+impl core::error::Error for RecentBlockhashError {}
+
+impl fmt::Display for RecentBlockhashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Expired {
+                last_valid_block_height,
+                block_height,
+            } => f.write_fmt(format_args!(
+                "blockhash expired after block height {last_valid_block_height}, before the \
+                 current block height {block_height}"
+            )),
+            Self::DurableNonce => f.write_str("message uses a durable nonce"),
+        }
+    }
+}
@@ -0,0 +1,243 @@
+//! Formatting of messages for logs, without instruction data or full keys.
+use {
+    crate::{compiled_instruction::CompiledInstruction, legacy::Message},
+    core::fmt,
+    solana_hash::Hash,
+    solana_pubkey::Pubkey,
+};
+
+/// The number of base58 characters kept at each end of a truncated key.
+const KEY_PREFIX_LEN: usize = 4;
+
+/// A message formatted for logs, returned by [`Message::redacted`].
+///
+/// Both `Display` and `Debug` show the message's structure: its keys with
+/// their roles, and the program, accounts and data length of each
+/// instruction. Instruction data is never shown, and keys and the recent
+/// blockhash are truncated to their first and last four base58 characters.
+#[derive(Clone, Copy)]
+pub struct RedactedMessage<'a>(pub(crate) &'a Message);
+
+struct Truncated(String);
+
+impl Truncated {
+    fn new(encoded: String) -> Self {
+        let len = encoded.len();
+        if len <= KEY_PREFIX_LEN.saturating_mul(2) {
+            return Self(encoded);
+        }
+        Self(format!(
+            "{}..{}",
+            &encoded[..KEY_PREFIX_LEN],
+            &encoded[len.saturating_sub(KEY_PREFIX_LEN)..]
+        ))
+    }
+
+    fn key(key: &Pubkey) -> Self {
+        Self::new(key.to_string())
+    }
+
+    fn hash(hash: &Hash) -> Self {
+        Self::new(hash.to_string())
+    }
+}
+
+impl fmt::Display for Truncated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Debug for Truncated {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+struct RedactedKey<'a> {
+    message: &'a Message,
+    index: usize,
+}
+
+impl fmt::Debug for RedactedKey<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Key")
+            .field(
+                "pubkey",
+                &Truncated::key(&self.message.account_keys[self.index]),
+            )
+            .field("is_signer", &self.message.is_signer(self.index))
+            .field("is_writable", &self.message.is_writable_index(self.index))
+            .finish()
+    }
+}
+
+struct RedactedInstruction<'a>(&'a CompiledInstruction);
+
+impl fmt::Debug for RedactedInstruction<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Instruction")
+            .field("program_id_index", &self.0.program_id_index)
+            .field("accounts", &self.0.accounts)
+            .field("data_len", &self.0.data.len())
+            .finish()
+    }
+}
+
+impl fmt::Debug for RedactedMessage<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = self.0;
+        let keys: Vec<_> = (0..message.account_keys.len())
+            .map(|index| RedactedKey { message, index })
+            .collect();
+        let instructions: Vec<_> = message
+            .instructions
+            .iter()
+            .map(RedactedInstruction)
+            .collect();
+        f.debug_struct("RedactedMessage")
+            .field("header", &message.header)
+            .field("account_keys", &keys)
+            .field(
+                "recent_blockhash",
+                &Truncated::hash(&message.recent_blockhash),
+            )
+            .field("instructions", &instructions)
+            .finish()
+    }
+}
+
+impl fmt::Display for RedactedMessage<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = self.0;
+        f.write_fmt(format_args!(
+            "message with {} keys and {} instructions, recent blockhash {}",
+            message.account_keys.len(),
+            message.instructions.len(),
+            Truncated::hash(&message.recent_blockhash),
+        ))?;
+        for (index, key) in message.account_keys.iter().enumerate() {
+            let role = match (message.is_signer(index), message.is_writable_index(index)) {
+                (true, true) => "writable signer",
+                (true, false) => "readonly signer",
+                (false, true) => "writable",
+                (false, false) => "readonly",
+            };
+            f.write_fmt(format_args!(
+                "\n  key {index}: {} {role}",
+                Truncated::key(key)
+            ))?;
+        }
+        for (index, ix) in message.instructions.iter().enumerate() {
+            let program_id = message
+                .account_keys
+                .get(usize::from(ix.program_id_index))
+                .map(Truncated::key);
+            f.write_fmt(format_args!(
+                "\n  instruction {index}: program {} ({}), accounts {:?}, {} data bytes",
+                ix.program_id_index,
+                program_id.as_ref().map_or("invalid", |key| key.0.as_str()),
+                ix.accounts,
+                ix.data.len(),
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_instruction::{AccountMeta, Instruction},
+    };
+
+    #[test]
+    fn test_redacted() {
+        let payer = Pubkey::new_from_array([1; 32]);
+        let program_id = Pubkey::new_from_array([2; 32]);
+        let account = Pubkey::new_from_array([3; 32]);
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            b"secret memo",
+            vec![AccountMeta::new_readonly(account, false)],
+        );
+        let message = Message::new_with_blockhash(
+            &[instruction],
+            Some(&payer),
+            &Hash::new_from_array([4; 32]),
+        );
+
+        let display = message.redacted().to_string();
+        assert_eq!(
+            display,
+            "message with 3 keys and 1 instructions, recent blockhash GgBa..zLHq\n  \
+             key 0: 4vJ9..kLKi writable signer\n  \
+             key 1: 8qbH..VfeR readonly\n  \
+             key 2: CktR..Ezy8 readonly\n  \
+             instruction 0: program 1 (8qbH..VfeR), accounts [2], 11 data bytes"
+        );
+        let debug = format!("{:?}", message.redacted());
+        assert!(debug.contains("data_len: 11"));
+        for output in [display, debug] {
+            assert!(!output.contains("secret"));
+            assert!(!output.contains(&payer.to_string()));
+        }
+    }
+
+    #[test]
+    fn test_redacted_hides_data_and_keys() {
+        let data = vec![200, 201, 202, 203];
+        let keys: Vec<_> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        let instructions = [
+            Instruction::new_with_bytes(keys[0], &data, vec![AccountMeta::new(keys[1], true)]),
+            Instruction::new_with_bytes(keys[0], &data, vec![AccountMeta::new(keys[2], false)]),
+        ];
+        let blockhash = Hash::new_unique();
+        let message = Message::new_with_blockhash(&instructions, None, &blockhash);
+
+        let display = message.redacted().to_string();
+        let debug = format!("{:?}", message.redacted());
+        let pretty_debug = format!("{:#?}", message.redacted());
+        assert!(debug.contains("data_len: 4"));
+        for output in [display, debug, pretty_debug] {
+            for byte in &data {
+                assert!(!output.contains(&byte.to_string()), "{output}");
+            }
+            assert!(!output.contains(&blockhash.to_string()));
+            for key in &keys {
+                assert!(!output.contains(&key.to_string()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_redacted_invalid_program_id() {
+        let mut message = Message::new(
+            &[Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[1],
+                vec![],
+            )],
+            Some(&Pubkey::new_unique()),
+        );
+        message.instructions[0].program_id_index = 7;
+        assert!(message
+            .redacted()
+            .to_string()
+            .ends_with("instruction 0: program 7 (invalid), accounts [], 1 data bytes"));
+        assert!(format!("{:?}", message.redacted()).contains("program_id_index: 7"));
+
+        assert_eq!(
+            Message::default().redacted().to_string(),
+            "message with 0 keys and 0 instructions, recent blockhash 1111..1111"
+        );
+    }
+
+    #[test]
+    fn test_truncated() {
+        assert_eq!(Truncated::new(String::new()).0, "");
+        assert_eq!(Truncated::new("12345678".to_string()).0, "12345678");
+        assert_eq!(Truncated::new("123456789".to_string()).0, "1234..6789");
+    }
+}
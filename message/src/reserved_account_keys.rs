@@ -0,0 +1,193 @@
+//! The account keys the runtime never write locks.
+use {
+    solana_pubkey::Pubkey,
+    solana_sdk_ids::{
+        address_lookup_table, bpf_loader, bpf_loader_deprecated, bpf_loader_upgradeable,
+        compute_budget, config, ed25519_program, feature, loader_v4, native_loader,
+        secp256k1_program, secp256r1_program, stake, system_program, sysvar, vote,
+        zk_elgamal_proof_program, zk_token_proof_program,
+    },
+    std::collections::HashSet,
+};
+
+/// A version of the set of reserved account keys.
+///
+/// Versions only add keys, as the runtime activates the features that reserve
+/// them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReservedAccountKeysVersion {
+    /// The builtin programs and sysvars reserved before
+    /// `add_new_reserved_account_keys`
+    V1,
+    /// `V1` and the keys reserved by `add_new_reserved_account_keys` and
+    /// `enable_secp256r1_precompile`
+    #[default]
+    V2,
+}
+
+impl ReservedAccountKeysVersion {
+    /// The most recent version.
+    pub const LATEST: Self = Self::V2;
+}
+
+static RESERVED_ACCOUNT_KEYS_V1: &[Pubkey] = &[
+    // builtin programs
+    bpf_loader::id(),
+    bpf_loader_deprecated::id(),
+    bpf_loader_upgradeable::id(),
+    config::id(),
+    feature::id(),
+    #[allow(deprecated)]
+    stake::config::id(),
+    stake::id(),
+    system_program::id(),
+    vote::id(),
+    // sysvars
+    sysvar::clock::id(),
+    sysvar::epoch_schedule::id(),
+    sysvar::fees::id(),
+    sysvar::instructions::id(),
+    sysvar::recent_blockhashes::id(),
+    sysvar::rent::id(),
+    sysvar::rewards::id(),
+    sysvar::slot_hashes::id(),
+    sysvar::slot_history::id(),
+    sysvar::stake_history::id(),
+    // other
+    native_loader::id(),
+];
+
+static RESERVED_ACCOUNT_KEYS_V2: &[Pubkey] = &[
+    // builtin programs
+    address_lookup_table::id(),
+    compute_budget::id(),
+    ed25519_program::id(),
+    loader_v4::id(),
+    secp256k1_program::id(),
+    secp256r1_program::id(),
+    zk_elgamal_proof_program::id(),
+    zk_token_proof_program::id(),
+    // sysvars
+    sysvar::epoch_rewards::id(),
+    sysvar::last_restart_slot::id(),
+    // other
+    sysvar::id(),
+];
+
+/// The set of account keys that transactions may not write lock, such as
+/// builtin programs and sysvars.
+///
+/// The runtime demotes write locks on these keys to read locks. Pass
+/// [`ReservedAccountKeys::keys`] to `is_maybe_writable` to get the
+/// writability the runtime computes, rather than passing `None`, which
+/// approximates it.
+///
+/// ```
+/// # use solana_instruction::{AccountMeta, Instruction};
+/// # use solana_message::{Message, ReservedAccountKeys};
+/// # use solana_pubkey::Pubkey;
+/// # use solana_sdk_ids::sysvar;
+/// let instruction = Instruction::new_with_bytes(
+///     Pubkey::new_unique(),
+///     &[],
+///     vec![AccountMeta::new(sysvar::clock::id(), false)],
+/// );
+/// let message = Message::new(&[instruction], Some(&Pubkey::new_unique()));
+///
+/// let reserved_account_keys = ReservedAccountKeys::default();
+/// assert!(message.is_maybe_writable(1, None));
+/// assert!(!message.is_maybe_writable(1, Some(reserved_account_keys.keys())));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReservedAccountKeys {
+    keys: HashSet<Pubkey>,
+}
+
+impl Default for ReservedAccountKeys {
+    /// The keys of the latest version.
+    fn default() -> Self {
+        Self::new(ReservedAccountKeysVersion::LATEST)
+    }
+}
+
+impl ReservedAccountKeys {
+    /// The keys reserved as of `version`.
+    pub fn new(version: ReservedAccountKeysVersion) -> Self {
+        let mut keys = HashSet::from_iter(RESERVED_ACCOUNT_KEYS_V1.iter().copied());
+        if version == ReservedAccountKeysVersion::V2 {
+            keys.extend(RESERVED_ACCOUNT_KEYS_V2);
+        }
+        Self { keys }
+    }
+
+    /// A set with no keys, which [`ReservedAccountKeys::insert`] and
+    /// `extend` can add to.
+    pub fn empty() -> Self {
+        Self {
+            keys: HashSet::new(),
+        }
+    }
+
+    /// Reserve `key`, returning false if it was already reserved.
+    pub fn insert(&mut self, key: Pubkey) -> bool {
+        self.keys.insert(key)
+    }
+
+    /// Returns true if `key` is reserved.
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        self.keys.contains(key)
+    }
+
+    /// The reserved keys, as taken by `is_maybe_writable` and
+    /// [`SanitizedMessage::try_new`].
+    ///
+    /// [`SanitizedMessage::try_new`]: crate::SanitizedMessage::try_new
+    pub fn keys(&self) -> &HashSet<Pubkey> {
+        &self.keys
+    }
+}
+
+impl Extend<Pubkey> for ReservedAccountKeys {
+    fn extend<T: IntoIterator<Item = Pubkey>>(&mut self, iter: T) {
+        self.keys.extend(iter)
+    }
+}
+
+impl From<ReservedAccountKeys> for HashSet<Pubkey> {
+    fn from(reserved_account_keys: ReservedAccountKeys) -> Self {
+        reserved_account_keys.keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reserved_account_keys() {
+        let v1 = ReservedAccountKeys::new(ReservedAccountKeysVersion::V1);
+        let v2 = ReservedAccountKeys::default();
+        assert_eq!(v1.keys().len(), RESERVED_ACCOUNT_KEYS_V1.len());
+        assert_eq!(
+            v2.keys().len(),
+            RESERVED_ACCOUNT_KEYS_V1.len() + RESERVED_ACCOUNT_KEYS_V2.len()
+        );
+        assert!(v1.keys().is_subset(v2.keys()));
+        assert!(v1.contains(&system_program::id()));
+        assert!(!v1.contains(&compute_budget::id()));
+        assert!(v2.contains(&compute_budget::id()));
+
+        let key = Pubkey::new_unique();
+        let mut extended = ReservedAccountKeys::empty();
+        assert!(!extended.contains(&system_program::id()));
+        assert!(extended.insert(key));
+        assert!(!extended.insert(key));
+        extended.extend(v1.keys().iter().copied());
+        assert!(extended.contains(&key));
+        assert!(extended.contains(&sysvar::rent::id()));
+        assert_eq!(
+            HashSet::from(extended).len(),
+            RESERVED_ACCOUNT_KEYS_V1.len() + 1
+        );
+    }
+}
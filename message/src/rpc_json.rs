@@ -0,0 +1,332 @@
+//! The JSON shape of messages in the RPC `json` transaction encoding.
+#[cfg(not(target_os = "solana"))]
+use crate::{v0, VersionedMessage};
+use {
+    crate::{compiled_instruction::CompiledInstruction, legacy::Message, MessageHeader},
+    core::{fmt::Display, str::FromStr},
+    serde::{
+        de::{self, Deserialize, Deserializer},
+        ser::{Serialize, Serializer},
+    },
+    serde_derive::{Deserialize, Serialize},
+    solana_hash::Hash,
+    solana_pubkey::Pubkey,
+};
+
+/// A message serialized in the shape the RPC `json` encoding uses, rather than
+/// the bincode-oriented shape of the message's own serde implementation.
+///
+/// Account keys and the recent blockhash are base58 strings, and instruction
+/// data is base58 encoded. v0 messages have an `addressTableLookups` field,
+/// which legacy messages leave out.
+///
+/// ```
+/// # use solana_instruction::Instruction;
+/// # use solana_message::{Message, RpcJson};
+/// # use solana_pubkey::Pubkey;
+/// let payer = Pubkey::new_unique();
+/// let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+/// let message = Message::new(&[instruction], Some(&payer));
+///
+/// let json = serde_json::to_value(RpcJson(&message)).unwrap();
+/// assert_eq!(json["accountKeys"][0], payer.to_string());
+/// assert_eq!(json["instructions"][0]["data"], "Ldp");
+///
+/// let RpcJson(decoded) = serde_json::from_value::<RpcJson<Message>>(json).unwrap();
+/// assert_eq!(decoded, message);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RpcJson<T>(pub T);
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UiMessage {
+    header: MessageHeader,
+    account_keys: Vec<String>,
+    recent_blockhash: String,
+    instructions: Vec<UiInstruction>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    address_table_lookups: Option<Vec<UiAddressTableLookup>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UiInstruction {
+    program_id_index: u8,
+    accounts: Vec<u8>,
+    data: String,
+    // always null in messages, only set for inner instructions
+    #[serde(default)]
+    stack_height: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UiAddressTableLookup {
+    account_key: String,
+    writable_indexes: Vec<u8>,
+    readonly_indexes: Vec<u8>,
+}
+
+impl UiMessage {
+    fn new(
+        header: MessageHeader,
+        account_keys: &[Pubkey],
+        recent_blockhash: &Hash,
+        instructions: &[CompiledInstruction],
+    ) -> Self {
+        Self {
+            header,
+            account_keys: account_keys.iter().map(Pubkey::to_string).collect(),
+            recent_blockhash: recent_blockhash.to_string(),
+            instructions: instructions
+                .iter()
+                .map(|ix| UiInstruction {
+                    program_id_index: ix.program_id_index,
+                    accounts: ix.accounts.clone(),
+                    data: bs58::encode(&ix.data).into_string(),
+                    stack_height: None,
+                })
+                .collect(),
+            address_table_lookups: None,
+        }
+    }
+
+    fn account_keys<E: de::Error>(&self) -> Result<Vec<Pubkey>, E> {
+        self.account_keys.iter().map(|key| parse(key)).collect()
+    }
+
+    fn instructions<E: de::Error>(&self) -> Result<Vec<CompiledInstruction>, E> {
+        self.instructions
+            .iter()
+            .map(|ix| {
+                Ok(CompiledInstruction {
+                    program_id_index: ix.program_id_index,
+                    accounts: ix.accounts.clone(),
+                    data: bs58::decode(&ix.data).into_vec().map_err(E::custom)?,
+                })
+            })
+            .collect()
+    }
+
+    fn into_legacy<E: de::Error>(self) -> Result<Message, E> {
+        Ok(Message {
+            header: self.header,
+            account_keys: self.account_keys()?,
+            recent_blockhash: parse(&self.recent_blockhash)?,
+            instructions: self.instructions()?,
+        })
+    }
+}
+
+fn parse<T, E>(s: &str) -> Result<T, E>
+where
+    T: FromStr,
+    T::Err: Display,
+    E: de::Error,
+{
+    s.parse().map_err(E::custom)
+}
+
+impl Serialize for RpcJson<&Message> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let message = self.0;
+        UiMessage::new(
+            message.header,
+            &message.account_keys,
+            &message.recent_blockhash,
+            &message.instructions,
+        )
+        .serialize(serializer)
+    }
+}
+
+impl Serialize for RpcJson<Message> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RpcJson(&self.0).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for RpcJson<Message> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let message = UiMessage::deserialize(deserializer)?;
+        if message.address_table_lookups.is_some() {
+            return Err(de::Error::custom(
+                "expected a legacy message, found address table lookups",
+            ));
+        }
+        message.into_legacy().map(Self)
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+impl Serialize for RpcJson<&VersionedMessage> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let message = match self.0 {
+            VersionedMessage::Legacy(message) => return RpcJson(message).serialize(serializer),
+            VersionedMessage::V0(message) => message,
+        };
+        let mut ui_message = UiMessage::new(
+            message.header,
+            &message.account_keys,
+            &message.recent_blockhash,
+            &message.instructions,
+        );
+        ui_message.address_table_lookups = Some(
+            message
+                .address_table_lookups
+                .iter()
+                .map(|lookup| UiAddressTableLookup {
+                    account_key: lookup.account_key.to_string(),
+                    writable_indexes: lookup.writable_indexes.clone(),
+                    readonly_indexes: lookup.readonly_indexes.clone(),
+                })
+                .collect(),
+        );
+        ui_message.serialize(serializer)
+    }
+}
+
+#[cfg(not(target_os = "solana"))]
+impl Serialize for RpcJson<VersionedMessage> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        RpcJson(&self.0).serialize(serializer)
+    }
+}
+
+/// Messages with an `addressTableLookups` field are v0 messages, and others
+/// are legacy messages.
+#[cfg(not(target_os = "solana"))]
+impl<'de> Deserialize<'de> for RpcJson<VersionedMessage> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let message = UiMessage::deserialize(deserializer)?;
+        let Some(lookups) = &message.address_table_lookups else {
+            return message
+                .into_legacy()
+                .map(|message| Self(VersionedMessage::Legacy(message)));
+        };
+        let address_table_lookups = lookups
+            .iter()
+            .map(|lookup| {
+                Ok(v0::MessageAddressTableLookup {
+                    account_key: parse(&lookup.account_key)?,
+                    writable_indexes: lookup.writable_indexes.clone(),
+                    readonly_indexes: lookup.readonly_indexes.clone(),
+                })
+            })
+            .collect::<Result<_, D::Error>>()?;
+        Ok(Self(VersionedMessage::V0(v0::Message {
+            header: message.header,
+            account_keys: message.account_keys()?,
+            recent_blockhash: parse(&message.recent_blockhash)?,
+            instructions: message.instructions()?,
+            address_table_lookups,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::AddressLookupTableAccount,
+        serde_json::json,
+        solana_instruction::{AccountMeta, Instruction},
+    };
+
+    #[test]
+    fn test_rpc_json_legacy() {
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![Pubkey::new_from_array([1; 32]), Pubkey::default()],
+            recent_blockhash: Hash::new_from_array([2; 32]),
+            instructions: vec![CompiledInstruction::new_from_raw_parts(
+                1,
+                vec![2, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0],
+                vec![0, 0],
+            )],
+        };
+        let expected = json!({
+            "header": {
+                "numRequiredSignatures": 1,
+                "numReadonlySignedAccounts": 0,
+                "numReadonlyUnsignedAccounts": 1,
+            },
+            "accountKeys": [
+                "4vJ9JU1bJJE96FWSJKvHsmmFADCg4gpZQff4P3bkLKi",
+                "11111111111111111111111111111111",
+            ],
+            "recentBlockhash": "8qbHbw2BbbTHBW1sbeqakYXVKRQM8Ne7pLK7m6CVfeR",
+            "instructions": [{
+                "programIdIndex": 1,
+                "accounts": [0, 0],
+                "data": "3Bxs412MvVNQj175",
+                "stackHeight": null,
+            }],
+        });
+        assert_eq!(serde_json::to_value(RpcJson(&message)).unwrap(), expected);
+        assert_eq!(
+            serde_json::from_value::<RpcJson<Message>>(expected.clone()).unwrap(),
+            RpcJson(message.clone())
+        );
+        assert_eq!(
+            serde_json::from_value::<RpcJson<VersionedMessage>>(expected).unwrap(),
+            RpcJson(VersionedMessage::Legacy(message))
+        );
+
+        let invalid_key = json!({
+            "header": {
+                "numRequiredSignatures": 0,
+                "numReadonlySignedAccounts": 0,
+                "numReadonlyUnsignedAccounts": 0,
+            },
+            "accountKeys": ["not base58"],
+            "recentBlockhash": Hash::default().to_string(),
+            "instructions": [],
+        });
+        assert!(serde_json::from_value::<RpcJson<Message>>(invalid_key).is_err());
+    }
+
+    #[test]
+    fn test_rpc_json_v0() {
+        let payer = Pubkey::new_unique();
+        let table_key = Pubkey::new_unique();
+        let loaded_key = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(loaded_key, false)],
+        );
+        let message = VersionedMessage::V0(
+            v0::Message::try_compile(
+                &payer,
+                &[instruction],
+                &[AddressLookupTableAccount {
+                    key: table_key,
+                    addresses: vec![loaded_key],
+                }],
+                Hash::new_unique(),
+            )
+            .unwrap(),
+        );
+        let json = serde_json::to_value(RpcJson(&message)).unwrap();
+        assert_eq!(
+            json["addressTableLookups"],
+            json!([{
+                "accountKey": table_key.to_string(),
+                "writableIndexes": [0],
+                "readonlyIndexes": [],
+            }])
+        );
+        assert_eq!(json["instructions"][0]["data"], "Ldp");
+        assert_eq!(
+            serde_json::from_value::<RpcJson<VersionedMessage>>(json.clone()).unwrap(),
+            RpcJson(message)
+        );
+        assert!(serde_json::from_value::<RpcJson<Message>>(json).is_err());
+    }
+}
@@ -33,16 +33,7 @@ pub struct LegacyMessage<'a> {
 
 impl LegacyMessage<'_> {
     pub fn new(message: legacy::Message, reserved_account_keys: &HashSet<Pubkey>) -> Self {
-        let is_writable_account_cache = message
-            .account_keys
-            .iter()
-            .enumerate()
-            .map(|(i, _key)| {
-                message.is_writable_index(i)
-                    && !reserved_account_keys.contains(&message.account_keys[i])
-                    && !message.demote_program_id(i)
-            })
-            .collect::<Vec<_>>();
+        let is_writable_account_cache = message.writable_bitset(Some(reserved_account_keys));
         Self {
             message: Cow::Owned(message),
             is_writable_account_cache,
@@ -53,6 +44,10 @@ impl LegacyMessage<'_> {
         self.message.has_duplicates()
     }
 
+    pub fn find_duplicates(&self) -> Vec<(usize, usize)> {
+        self.message.find_duplicates()
+    }
+
     pub fn is_key_called_as_program(&self, key_index: usize) -> bool {
         self.message.is_key_called_as_program(key_index)
     }
@@ -126,6 +121,15 @@ impl SanitizedMessage {
         }
     }
 
+    /// Return the indexes of duplicate account keys, as pairs of the index of
+    /// a key's first occurrence and the index of a repeat
+    pub fn find_duplicates(&self) -> Vec<(usize, usize)> {
+        match self {
+            SanitizedMessage::Legacy(message) => message.find_duplicates(),
+            SanitizedMessage::V0(message) => message.find_duplicates(),
+        }
+    }
+
     /// Message header which identifies the number of signer and writable or
     /// readonly accounts
     pub fn header(&self) -> &MessageHeader {
@@ -0,0 +1,115 @@
+//! Checks of what the signatures of a message authorize.
+use {crate::legacy::Message, core::fmt, solana_pubkey::Pubkey, std::collections::HashSet};
+
+/// The reasons a message fails [`Message::check_signer_privileges`].
+#[derive(PartialEq, Debug, Eq, Clone)]
+pub enum SignerPrivilegeError {
+    /// The message has no fee payer, or its fee payer is read-only, either in
+    /// the header or because it is reserved or invoked as a program
+    FeePayerNotWritable,
+    /// A signer that was expected to be read-only is writable
+    UnexpectedWritableSigner(Pubkey),
+}
+
+impl core::error::Error for SignerPrivilegeError {}
+
+impl fmt::Display for SignerPrivilegeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::FeePayerNotWritable => f.write_str("fee payer is not writable"),
+            Self::UnexpectedWritableSigner(key) => {
+                f.write_fmt(format_args!("signer {key} is unexpectedly writable"))
+            }
+        }
+    }
+}
+
+pub(crate) fn check_signer_privileges(
+    message: &Message,
+    readonly_signers: &[Pubkey],
+    reserved_account_keys: Option<&HashSet<Pubkey>>,
+) -> Result<(), SignerPrivilegeError> {
+    if !message.is_signer(0) || !message.is_maybe_writable(0, reserved_account_keys) {
+        return Err(SignerPrivilegeError::FeePayerNotWritable);
+    }
+    for (index, key) in message.signer_keys_iter().enumerate() {
+        if readonly_signers.contains(key) && message.is_maybe_writable(index, reserved_account_keys)
+        {
+            return Err(SignerPrivilegeError::UnexpectedWritableSigner(*key));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::MessageHeader,
+        solana_instruction::{AccountMeta, Instruction},
+    };
+
+    #[test]
+    fn test_check_signer_privileges() {
+        let payer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            program_id,
+            &[],
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new(owner, true),
+            ],
+        );
+        let message = Message::new(&[instruction], Some(&payer));
+        assert_eq!(
+            message.writable_signers().collect::<Vec<_>>(),
+            [&payer, &owner]
+        );
+        assert_eq!(message.readonly_signers().collect::<Vec<_>>(), [&authority]);
+
+        assert_eq!(message.check_signer_privileges(&[authority], None), Ok(()));
+        assert_eq!(
+            message.check_signer_privileges(&[authority, owner], None),
+            Err(SignerPrivilegeError::UnexpectedWritableSigner(owner))
+        );
+        // a reserved key is demoted to read-only
+        assert_eq!(
+            message.check_signer_privileges(&[owner], Some(&HashSet::from([owner]))),
+            Ok(())
+        );
+
+        // the fee payer is demoted when reserved or invoked as a program
+        assert_eq!(
+            message.check_signer_privileges(&[], Some(&HashSet::from([payer]))),
+            Err(SignerPrivilegeError::FeePayerNotWritable)
+        );
+        let invoked_payer = Message::new(
+            &[Instruction::new_with_bytes(payer, &[], vec![])],
+            Some(&payer),
+        );
+        assert_eq!(
+            invoked_payer.check_signer_privileges(&[], None),
+            Err(SignerPrivilegeError::FeePayerNotWritable)
+        );
+        let readonly_payer = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 0,
+            },
+            account_keys: vec![payer],
+            ..Message::default()
+        };
+        assert_eq!(
+            readonly_payer.check_signer_privileges(&[], None),
+            Err(SignerPrivilegeError::FeePayerNotWritable)
+        );
+        assert_eq!(
+            Message::default().check_signer_privileges(&[], None),
+            Err(SignerPrivilegeError::FeePayerNotWritable)
+        );
+    }
+}
@@ -0,0 +1,431 @@
+//! Rendering of messages as statements for users to review before signing.
+use {
+    crate::{
+        compiled_instruction::CompiledInstruction,
+        inline_compute_budget::{
+            is_compute_budget_program, parse_compute_unit_limit, parse_compute_unit_price,
+        },
+        inline_nonce::is_advance_nonce_instruction_data,
+        legacy::Message,
+        AccountRole,
+    },
+    core::fmt,
+    solana_hash::Hash,
+    solana_pubkey::Pubkey,
+    solana_sdk_ids::system_program,
+};
+
+/// Inlined `SystemInstruction::Transfer` discriminant to avoid
+/// solana_system_interface and bincode deps
+const SYSTEM_TRANSFER: [u8; 4] = [2, 0, 0, 0];
+
+/// One statement of a [`SigningDisplay`].
+///
+/// Keys are shown in full, so users can compare them against the ones they
+/// expect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SigningStatement<'a> {
+    /// The first signer, who pays the transaction fee
+    FeePayer(&'a Pubkey),
+    /// Another signer of the message
+    Signer { key: &'a Pubkey, is_writable: bool },
+    /// The recent blockhash, or the durable nonce
+    RecentBlockhash(&'a Hash),
+    /// A transfer of the system program
+    Transfer {
+        from: &'a Pubkey,
+        to: &'a Pubkey,
+        lamports: u64,
+    },
+    /// An advance of a durable nonce by the system program
+    AdvanceNonce {
+        nonce_account: &'a Pubkey,
+        authority: &'a Pubkey,
+    },
+    /// A compute unit limit set with the compute budget program
+    SetComputeUnitLimit(u32),
+    /// A compute unit price, in micro-lamports, set with the compute budget
+    /// program
+    SetComputeUnitPrice(u64),
+    /// An instruction that is not decoded
+    Invoke {
+        program_id: &'a Pubkey,
+        accounts: Vec<(&'a Pubkey, AccountRole)>,
+        data: &'a [u8],
+    },
+    /// The instruction at this index refers to accounts the message does not
+    /// have
+    InvalidInstruction(usize),
+}
+
+fn role_name(role: AccountRole) -> &'static str {
+    match (role.is_signer, role.is_writable) {
+        (true, true) => "writable signer",
+        (true, false) => "readonly signer",
+        (false, true) => "writable",
+        (false, false) => "readonly",
+    }
+}
+
+impl fmt::Display for SigningStatement<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::FeePayer(key) => f.write_fmt(format_args!("fee payer {key}")),
+            Self::Signer { key, is_writable } => {
+                let role = AccountRole {
+                    is_signer: true,
+                    is_writable: *is_writable,
+                };
+                f.write_fmt(format_args!("{} {key}", role_name(role)))
+            }
+            Self::RecentBlockhash(hash) => f.write_fmt(format_args!("recent blockhash {hash}")),
+            Self::Transfer { from, to, lamports } => f.write_fmt(format_args!(
+                "transfer {lamports} lamports from {from} to {to}"
+            )),
+            Self::AdvanceNonce {
+                nonce_account,
+                authority,
+            } => f.write_fmt(format_args!(
+                "advance nonce account {nonce_account} with authority {authority}"
+            )),
+            Self::SetComputeUnitLimit(units) => {
+                f.write_fmt(format_args!("set compute unit limit to {units}"))
+            }
+            Self::SetComputeUnitPrice(micro_lamports) => f.write_fmt(format_args!(
+                "set compute unit price to {micro_lamports} micro-lamports"
+            )),
+            Self::Invoke {
+                program_id,
+                accounts,
+                data,
+            } => {
+                f.write_fmt(format_args!("call program {program_id} with accounts ["))?;
+                for (index, (key, role)) in accounts.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    f.write_fmt(format_args!("{key} ({})", role_name(*role)))?;
+                }
+                f.write_fmt(format_args!("] and {} data bytes", data.len()))?;
+                if !data.is_empty() {
+                    f.write_str(" 0x")?;
+                    for byte in data.iter() {
+                        f.write_fmt(format_args!("{byte:02x}"))?;
+                    }
+                }
+                Ok(())
+            }
+            Self::InvalidInstruction(index) => {
+                f.write_fmt(format_args!("invalid instruction {index}"))
+            }
+        }
+    }
+}
+
+/// A message rendered as an ordered list of statements for users to review
+/// before signing, returned by [`Message::signing_display`].
+///
+/// The statements name the fee payer, the other signers and the recent
+/// blockhash, then describe each instruction in order. Transfers, durable
+/// nonce advances and compute budget instructions are decoded. Other
+/// instructions show their program, accounts and data. `Display` writes one
+/// numbered statement per line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SigningDisplay<'a> {
+    pub statements: Vec<SigningStatement<'a>>,
+}
+
+impl fmt::Display for SigningDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, statement) in self.statements.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            f.write_fmt(format_args!("{}. {statement}", index.saturating_add(1)))?;
+        }
+        Ok(())
+    }
+}
+
+fn instruction_statement<'a>(
+    message: &'a Message,
+    index: usize,
+    ix: &'a CompiledInstruction,
+) -> SigningStatement<'a> {
+    let account = |index: u8| {
+        let index = usize::from(index);
+        Some((
+            message.account_keys.get(index)?,
+            message.account_role(index)?,
+        ))
+    };
+    let (Some((program_id, _)), Some(accounts)) = (
+        account(ix.program_id_index),
+        ix.accounts
+            .iter()
+            .map(|index| account(*index))
+            .collect::<Option<Vec<_>>>(),
+    ) else {
+        return SigningStatement::InvalidInstruction(index);
+    };
+    let data = ix.data.as_slice();
+
+    if system_program::check_id(program_id) {
+        if let (Some(lamports), [(from, _), (to, _), ..]) = (parse_transfer(data), &accounts[..]) {
+            return SigningStatement::Transfer { from, to, lamports };
+        }
+        if let (true, [(nonce_account, _), _, (authority, _), ..]) =
+            (is_advance_nonce_instruction_data(data), &accounts[..])
+        {
+            return SigningStatement::AdvanceNonce {
+                nonce_account,
+                authority,
+            };
+        }
+    } else if is_compute_budget_program(program_id) {
+        if let Some(units) = parse_compute_unit_limit(data) {
+            return SigningStatement::SetComputeUnitLimit(units);
+        }
+        if let Some(micro_lamports) = parse_compute_unit_price(data) {
+            return SigningStatement::SetComputeUnitPrice(micro_lamports);
+        }
+    }
+    SigningStatement::Invoke {
+        program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Return the lamports of a `SystemInstruction::Transfer` instruction's data,
+/// ignoring trailing bytes like the system program does.
+fn parse_transfer(data: &[u8]) -> Option<u64> {
+    if data.get(..4)? != SYSTEM_TRANSFER {
+        return None;
+    }
+    data.get(4..12)?.try_into().ok().map(u64::from_le_bytes)
+}
+
+pub(crate) fn signing_display(message: &Message) -> SigningDisplay {
+    let mut statements = vec![];
+    for (index, key) in message.signer_keys_iter().enumerate() {
+        statements.push(if index == 0 {
+            SigningStatement::FeePayer(key)
+        } else {
+            SigningStatement::Signer {
+                key,
+                is_writable: message.is_writable_index(index),
+            }
+        });
+    }
+    statements.push(SigningStatement::RecentBlockhash(&message.recent_blockhash));
+    statements.extend(
+        message
+            .instructions
+            .iter()
+            .enumerate()
+            .map(|(index, ix)| instruction_statement(message, index, ix)),
+    );
+    SigningDisplay { statements }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::inline_compute_budget::{compute_unit_limit_data, compute_unit_price_data},
+        crate::inline_nonce::advance_nonce_account_instruction,
+        solana_instruction::{AccountMeta, Instruction},
+    };
+
+    #[test]
+    fn test_signing_display() {
+        let payer = Pubkey::new_from_array([1; 32]);
+        let recipient = Pubkey::new_from_array([2; 32]);
+        let nonce_account = Pubkey::new_from_array([3; 32]);
+        let program_id = Pubkey::new_from_array([4; 32]);
+        let owner = Pubkey::new_from_array([5; 32]);
+        let transfer_data = [&SYSTEM_TRANSFER[..], &42u64.to_le_bytes()].concat();
+        let instructions = [
+            advance_nonce_account_instruction(&nonce_account, &payer),
+            Instruction::new_with_bytes(
+                solana_sdk_ids::compute_budget::id(),
+                &compute_unit_limit_data(10_000),
+                vec![],
+            ),
+            Instruction::new_with_bytes(
+                solana_sdk_ids::compute_budget::id(),
+                &compute_unit_price_data(7),
+                vec![],
+            ),
+            Instruction::new_with_bytes(
+                system_program::id(),
+                &transfer_data,
+                vec![
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new(recipient, false),
+                ],
+            ),
+            Instruction::new_with_bytes(
+                program_id,
+                &[0xab, 0x01],
+                vec![
+                    AccountMeta::new_readonly(owner, true),
+                    AccountMeta::new(recipient, false),
+                ],
+            ),
+        ];
+        let message = Message::new_with_blockhash(
+            &instructions,
+            Some(&payer),
+            &Hash::new_from_array([6; 32]),
+        );
+
+        let display = message.signing_display();
+        assert_eq!(
+            display.statements,
+            [
+                SigningStatement::FeePayer(&payer),
+                SigningStatement::Signer {
+                    key: &owner,
+                    is_writable: false,
+                },
+                SigningStatement::RecentBlockhash(&message.recent_blockhash),
+                SigningStatement::AdvanceNonce {
+                    nonce_account: &nonce_account,
+                    authority: &payer,
+                },
+                SigningStatement::SetComputeUnitLimit(10_000),
+                SigningStatement::SetComputeUnitPrice(7),
+                SigningStatement::Transfer {
+                    from: &payer,
+                    to: &recipient,
+                    lamports: 42,
+                },
+                SigningStatement::Invoke {
+                    program_id: &program_id,
+                    accounts: vec![
+                        (
+                            &owner,
+                            AccountRole {
+                                is_signer: true,
+                                is_writable: false,
+                            }
+                        ),
+                        (
+                            &recipient,
+                            AccountRole {
+                                is_signer: false,
+                                is_writable: true,
+                            }
+                        ),
+                    ],
+                    data: &[0xab, 0x01],
+                },
+            ]
+        );
+        let lines: Vec<_> = display.to_string().lines().map(String::from).collect();
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[0], format!("1. fee payer {payer}"));
+        assert_eq!(lines[1], format!("2. readonly signer {owner}"));
+        assert_eq!(
+            lines[6],
+            format!("7. transfer 42 lamports from {payer} to {recipient}")
+        );
+        assert_eq!(
+            lines[7],
+            format!(
+                "8. call program {program_id} with accounts [{owner} (readonly signer), \
+                 {recipient} (writable)] and 2 data bytes 0xab01"
+            )
+        );
+
+        // instructions with invalid indexes are flagged, not skipped
+        let mut invalid = message.clone();
+        invalid.instructions[3].accounts[1] = 99;
+        assert_eq!(
+            invalid.signing_display().statements[6],
+            SigningStatement::InvalidInstruction(3)
+        );
+        // short transfer data is not decoded
+        invalid.instructions[3] = message.instructions[3].clone();
+        invalid.instructions[3].data.truncate(8);
+        assert!(matches!(
+            invalid.signing_display().statements[6],
+            SigningStatement::Invoke { .. }
+        ));
+    }
+
+    #[test]
+    fn test_signing_display_edge_cases() {
+        let message = Message::default();
+        assert_eq!(
+            message.signing_display().statements,
+            [SigningStatement::RecentBlockhash(&message.recent_blockhash)]
+        );
+        assert_eq!(
+            message.signing_display().to_string(),
+            format!("1. recent blockhash {}", Hash::default())
+        );
+
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let transfer_data = [&SYSTEM_TRANSFER[..], &42u64.to_le_bytes(), &[9]].concat();
+        let transfer_accounts = vec![
+            AccountMeta::new(payer, true),
+            AccountMeta::new(recipient, false),
+        ];
+        let nonce_instruction = advance_nonce_account_instruction(&recipient, &payer);
+        let instructions = [
+            // trailing bytes are ignored, like the system program does
+            Instruction::new_with_bytes(
+                system_program::id(),
+                &transfer_data,
+                transfer_accounts.clone(),
+            ),
+            // only the system program's transfers are decoded
+            Instruction::new_with_bytes(program_id, &transfer_data, transfer_accounts),
+            // too few accounts for a nonce advance
+            Instruction::new_with_bytes(
+                system_program::id(),
+                &nonce_instruction.data,
+                nonce_instruction.accounts[..2].to_vec(),
+            ),
+            Instruction::new_with_bytes(solana_sdk_ids::compute_budget::id(), &[9, 9], vec![]),
+            Instruction::new_with_bytes(program_id, &[], vec![]),
+        ];
+        let message = Message::new(&instructions, Some(&payer));
+        let statements = message.signing_display().statements;
+        assert_eq!(
+            statements[2],
+            SigningStatement::Transfer {
+                from: &payer,
+                to: &recipient,
+                lamports: 42,
+            }
+        );
+        for statement in &statements[3..] {
+            assert!(
+                matches!(statement, SigningStatement::Invoke { .. }),
+                "{statement:?}"
+            );
+        }
+        assert_eq!(
+            statements[6].to_string(),
+            format!("call program {program_id} with accounts [] and 0 data bytes")
+        );
+
+        // an out of range program id is flagged
+        let mut invalid = message.clone();
+        invalid.instructions[4].program_id_index = 99;
+        assert_eq!(
+            invalid.signing_display().statements[6],
+            SigningStatement::InvalidInstruction(4)
+        );
+        assert_eq!(
+            invalid.signing_display().statements[6].to_string(),
+            "invalid instruction 4"
+        );
+    }
+}
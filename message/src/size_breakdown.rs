@@ -0,0 +1,321 @@
+//! Reports on where the bytes of a transaction go, for messages that do not
+//! fit in a packet.
+use {
+    crate::{
+        compiled_instruction::CompiledInstruction, short_vec_len, MessageHeader,
+        MESSAGE_HEADER_LENGTH, SIGNATURE_BYTES,
+    },
+    core::fmt,
+    solana_hash::HASH_BYTES,
+    solana_packet::PACKET_DATA_SIZE,
+    solana_pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+/// The bytes taken up by one instruction of a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InstructionSize {
+    /// The length of the instruction data
+    pub data: usize,
+    /// The size of the whole compiled instruction, including its data
+    pub total: usize,
+}
+
+/// A way to make an oversized transaction fit in a packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SizeSuggestion {
+    /// Load `keys` from an address lookup table in a v0 message instead of
+    /// listing them in the message, saving `bytes_saved` bytes.
+    UseLookupTable {
+        keys: Vec<Pubkey>,
+        bytes_saved: usize,
+    },
+    /// The data of the instruction at `instruction_index` is at least as
+    /// large as the excess, so shrinking it or moving the instruction to
+    /// another transaction would make the transaction fit.
+    ShrinkInstructionData {
+        instruction_index: usize,
+        data_len: usize,
+    },
+}
+
+/// The serialized size of a transaction, broken down by section, returned by
+/// `size_breakdown` on messages.
+///
+/// Sizes count the signatures that the message requires, so they describe
+/// the whole transaction. Every section includes its length prefix. The
+/// `Display` implementation lists the sections and suggestions and is meant to
+/// be shown to users.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SizeBreakdown {
+    /// The required signatures
+    pub signatures: usize,
+    /// The version prefix, the message header and the instruction count
+    pub header: usize,
+    /// The account keys listed in the message
+    pub static_keys: usize,
+    /// The recent blockhash
+    pub recent_blockhash: usize,
+    /// Each instruction, in order
+    pub instructions: Vec<InstructionSize>,
+    /// The address table lookups, which legacy messages do not have
+    pub address_table_lookups: usize,
+    /// Ways to shrink the transaction, only given if it does not fit in a
+    /// packet
+    pub suggestions: Vec<SizeSuggestion>,
+}
+
+impl SizeBreakdown {
+    pub(crate) fn new(
+        is_versioned: bool,
+        header: &MessageHeader,
+        account_keys: &[Pubkey],
+        is_writable_index: impl Fn(usize) -> bool,
+        instructions: &[CompiledInstruction],
+        address_table_lookups: usize,
+    ) -> Self {
+        let num_signatures = usize::from(header.num_required_signatures);
+        let mut breakdown = Self {
+            signatures: short_vec_len(num_signatures)
+                .saturating_add(num_signatures.saturating_mul(SIGNATURE_BYTES)),
+            header: usize::from(is_versioned)
+                .saturating_add(MESSAGE_HEADER_LENGTH)
+                .saturating_add(short_vec_len(instructions.len())),
+            static_keys: short_vec_len(account_keys.len())
+                .saturating_add(account_keys.len().saturating_mul(PUBKEY_BYTES)),
+            recent_blockhash: HASH_BYTES,
+            instructions: instructions
+                .iter()
+                .map(|ix| InstructionSize {
+                    data: ix.data.len(),
+                    total: ix.serialized_size(),
+                })
+                .collect(),
+            address_table_lookups,
+            suggestions: Vec::new(),
+        };
+        let excess = breakdown.excess();
+        if excess == 0 {
+            return breakdown;
+        }
+
+        // signers and programs cannot be loaded from lookup tables
+        let loadable_keys: Vec<(Pubkey, bool)> = account_keys
+            .iter()
+            .enumerate()
+            .skip(num_signatures)
+            .filter(|(index, _)| {
+                !instructions
+                    .iter()
+                    .any(|ix| usize::from(ix.program_id_index) == *index)
+            })
+            .map(|(index, key)| (*key, is_writable_index(index)))
+            .collect();
+        let num_writable = loadable_keys
+            .iter()
+            .filter(|(_, is_writable)| *is_writable)
+            .count();
+        let num_readonly = loadable_keys.len().saturating_sub(num_writable);
+        let lookup_cost = PUBKEY_BYTES
+            .saturating_add(short_vec_len(num_writable))
+            .saturating_add(num_writable)
+            .saturating_add(short_vec_len(num_readonly))
+            .saturating_add(num_readonly)
+            // a legacy message needs a version prefix and a lookup count
+            .saturating_add(if is_versioned { 0 } else { 2 });
+        let bytes_saved = loadable_keys
+            .len()
+            .saturating_mul(PUBKEY_BYTES)
+            .saturating_sub(lookup_cost);
+        if bytes_saved > 0 {
+            breakdown.suggestions.push(SizeSuggestion::UseLookupTable {
+                keys: loadable_keys.into_iter().map(|(key, _)| key).collect(),
+                bytes_saved,
+            });
+        }
+
+        if let Some((instruction_index, size)) = breakdown
+            .instructions
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, size)| size.data)
+            .filter(|(_, size)| size.data >= excess)
+        {
+            breakdown
+                .suggestions
+                .push(SizeSuggestion::ShrinkInstructionData {
+                    instruction_index,
+                    data_len: size.data,
+                });
+        }
+        breakdown
+    }
+
+    /// The size of the whole transaction.
+    pub fn total(&self) -> usize {
+        self.instructions
+            .iter()
+            .map(|size| size.total)
+            .fold(self.signatures, usize::saturating_add)
+            .saturating_add(self.header)
+            .saturating_add(self.static_keys)
+            .saturating_add(self.recent_blockhash)
+            .saturating_add(self.address_table_lookups)
+    }
+
+    /// The number of bytes by which the transaction exceeds
+    /// [`PACKET_DATA_SIZE`], or 0 if it fits.
+    pub fn excess(&self) -> usize {
+        self.total().saturating_sub(PACKET_DATA_SIZE)
+    }
+}
+
+impl fmt::Display for SizeBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let total = self.total();
+        match self.excess() {
+            0 => writeln!(f, "transaction size: {total} of {PACKET_DATA_SIZE} bytes")?,
+            excess => writeln!(
+                f,
+                "transaction size: {total} bytes, {excess} over the {PACKET_DATA_SIZE} byte limit"
+            )?,
+        }
+        writeln!(f, "  signatures: {}", self.signatures)?;
+        writeln!(f, "  header: {}", self.header)?;
+        writeln!(f, "  static keys: {}", self.static_keys)?;
+        writeln!(f, "  recent blockhash: {}", self.recent_blockhash)?;
+        for (index, InstructionSize { data, total }) in self.instructions.iter().enumerate() {
+            writeln!(f, "  instruction {index}: {total} ({data} data)")?;
+        }
+        write!(f, "  address table lookups: {}", self.address_table_lookups)?;
+        for suggestion in &self.suggestions {
+            match suggestion {
+                SizeSuggestion::UseLookupTable { keys, bytes_saved } => write!(
+                    f,
+                    "\nsuggestion: load {} keys from an address lookup table to save {bytes_saved} \
+                     bytes",
+                    keys.len()
+                )?,
+                SizeSuggestion::ShrinkInstructionData {
+                    instruction_index,
+                    data_len,
+                } => write!(
+                    f,
+                    "\nsuggestion: shrink the {data_len} bytes of data of instruction \
+                     {instruction_index} or move it to another transaction"
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{v0, AddressLookupTableAccount, Message, VersionedMessage},
+        solana_hash::Hash,
+        solana_instruction::{AccountMeta, Instruction},
+    };
+
+    #[test]
+    fn test_size_breakdown() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let keys: Vec<Pubkey> = (0..20).map(|_| Pubkey::new_unique()).collect();
+        let instructions = [
+            Instruction::new_with_bytes(
+                program_id,
+                &[0; 10],
+                keys.iter()
+                    .enumerate()
+                    .map(|(i, key)| AccountMeta {
+                        pubkey: *key,
+                        is_signer: false,
+                        is_writable: i % 2 == 0,
+                    })
+                    .collect(),
+            ),
+            Instruction::new_with_bytes(program_id, &[0; 600], vec![]),
+        ];
+
+        let message = Message::new(&instructions, Some(&payer));
+        let breakdown = message.size_breakdown();
+        assert_eq!(breakdown.signatures, 65);
+        assert_eq!(breakdown.header, 4);
+        assert_eq!(breakdown.static_keys, 1 + 22 * 32);
+        assert_eq!(
+            breakdown.instructions,
+            [
+                InstructionSize {
+                    data: 10,
+                    total: 33
+                },
+                InstructionSize {
+                    data: 600,
+                    total: 604
+                },
+            ]
+        );
+        assert_eq!(breakdown.address_table_lookups, 0);
+        assert_eq!(breakdown.total(), 65 + message.serialize().len());
+        assert_eq!(breakdown.excess(), breakdown.total() - PACKET_DATA_SIZE);
+        let SizeSuggestion::UseLookupTable {
+            keys: loadable_keys,
+            bytes_saved,
+        } = &breakdown.suggestions[0]
+        else {
+            panic!("{:?}", breakdown.suggestions);
+        };
+        assert_eq!(loadable_keys.len(), keys.len());
+        assert!(loadable_keys.iter().all(|key| keys.contains(key)));
+        // the lookup table costs 32 bytes plus an index per key
+        assert_eq!(*bytes_saved, 20 * 32 - (32 + 1 + 10 + 1 + 10) - 2);
+        assert_eq!(
+            breakdown.suggestions[1],
+            SizeSuggestion::ShrinkInstructionData {
+                instruction_index: 1,
+                data_len: 600
+            }
+        );
+        let report = breakdown.to_string();
+        assert!(report.starts_with(&format!(
+            "transaction size: {} bytes, {} over the 1232 byte limit\n",
+            breakdown.total(),
+            breakdown.excess()
+        )));
+        assert!(report.contains("\n  instruction 1: 604 (600 data)\n"));
+        assert!(report.ends_with(
+            "\nsuggestion: shrink the 600 bytes of data of instruction 1 or move it to another \
+             transaction"
+        ));
+
+        // following the suggestion makes the transaction fit
+        let message = VersionedMessage::V0(
+            v0::Message::try_compile(
+                &payer,
+                &instructions,
+                &[AddressLookupTableAccount {
+                    key: Pubkey::new_unique(),
+                    addresses: keys,
+                }],
+                Hash::default(),
+            )
+            .unwrap(),
+        );
+        let v0_breakdown = message.size_breakdown();
+        assert_eq!(v0_breakdown.header, 5);
+        assert_eq!(v0_breakdown.address_table_lookups, 1 + 32 + 1 + 10 + 1 + 10);
+        assert_eq!(v0_breakdown.total(), 65 + message.serialize().len());
+        assert_eq!(
+            breakdown.total() - v0_breakdown.total(),
+            20 * 32 - (32 + 1 + 10 + 1 + 10) - 2
+        );
+        assert_eq!(v0_breakdown.excess(), 0);
+        assert!(v0_breakdown.suggestions.is_empty());
+        assert!(v0_breakdown.to_string().starts_with(&format!(
+            "transaction size: {} of 1232 bytes\n",
+            v0_breakdown.total()
+        )));
+    }
+}
@@ -0,0 +1,228 @@
+//! Checks of a message that need no account state, for rejecting
+//! transactions before they are forwarded or scheduled.
+#[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+use {crate::PrecompileFeatureSet, solana_transaction_error::TransactionError};
+use {
+    crate::{legacy::Message, SizeBreakdown, SIGNATURE_BYTES},
+    core::fmt,
+    solana_packet::PACKET_DATA_SIZE,
+    solana_pubkey::Pubkey,
+    solana_sanitize::{Sanitize, SanitizeError},
+    std::collections::HashSet,
+};
+
+/// The limits and runtime state a message is checked against by
+/// [`Message::verify_static`].
+///
+/// More checks may be added, so this can't be built with a struct
+/// expression. Start from [`StaticConstraints::new`] or the default and set
+/// the fields of the checks to run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct StaticConstraints<'a> {
+    /// The most signatures a message may require
+    pub max_signatures: usize,
+    /// The reserved account keys, which a message must not request to write,
+    /// or `None` to skip the check
+    pub reserved_account_keys: Option<&'a HashSet<Pubkey>>,
+    /// The features precompile instructions are verified with, or `None` to
+    /// skip verifying them
+    #[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+    pub precompile_feature_set: Option<PrecompileFeatureSet>,
+}
+
+impl StaticConstraints<'_> {
+    /// Limit the signatures a message may require to `max_signatures`,
+    /// without checking reserved keys or precompiles.
+    pub fn new(max_signatures: usize) -> Self {
+        Self {
+            max_signatures,
+            reserved_account_keys: None,
+            #[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+            precompile_feature_set: None,
+        }
+    }
+}
+
+impl Default for StaticConstraints<'_> {
+    /// The most signatures that fit in a packet, without checking reserved
+    /// keys or precompiles.
+    fn default() -> Self {
+        Self::new(
+            PACKET_DATA_SIZE
+                .saturating_sub(1)
+                .saturating_div(SIGNATURE_BYTES),
+        )
+    }
+}
+
+/// A way a message fails [`Message::verify_static`].
+#[derive(PartialEq, Debug, Eq, Clone)]
+#[non_exhaustive]
+pub enum StaticViolation {
+    /// The message is not sanitized
+    Sanitize(SanitizeError),
+    /// The message requires more signatures than the limit
+    TooManySignatures {
+        num_signatures: usize,
+        max_signatures: usize,
+    },
+    /// A transaction with the message and its signatures does not fit in a
+    /// packet, broken down by section
+    TooLarge(SizeBreakdown),
+    /// Account keys are repeated, as pairs of the index of a key's first
+    /// occurrence and the index of a repeat
+    DuplicateAccountKeys(Vec<(usize, usize)>),
+    /// The header marks a reserved account key as writable
+    WritableReservedKey(Pubkey),
+    /// A precompile instruction is invalid, with the error the runtime
+    /// returns for it
+    #[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+    Precompile(TransactionError),
+}
+
+impl core::error::Error for StaticViolation {}
+
+impl fmt::Display for StaticViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Sanitize(err) => f.write_fmt(format_args!("message is not sanitized: {err}")),
+            Self::TooManySignatures {
+                num_signatures,
+                max_signatures,
+            } => f.write_fmt(format_args!(
+                "message requires {num_signatures} signatures, more than {max_signatures}"
+            )),
+            Self::TooLarge(breakdown) => f.write_fmt(format_args!(
+                "transaction of {} bytes does not fit in a packet",
+                breakdown.total()
+            )),
+            Self::DuplicateAccountKeys(duplicates) => f.write_fmt(format_args!(
+                "message has {} duplicate account keys",
+                duplicates.len()
+            )),
+            Self::WritableReservedKey(key) => {
+                f.write_fmt(format_args!("reserved account key {key} is writable"))
+            }
+            #[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+            Self::Precompile(err) => f.write_fmt(format_args!("invalid precompile: {err}")),
+        }
+    }
+}
+
+pub(crate) fn verify_static(
+    message: &Message,
+    constraints: &StaticConstraints,
+) -> Result<(), Vec<StaticViolation>> {
+    let mut violations = vec![];
+    if let Err(err) = message.sanitize() {
+        violations.push(StaticViolation::Sanitize(err));
+    }
+    let num_signatures = usize::from(message.header.num_required_signatures);
+    if num_signatures > constraints.max_signatures {
+        violations.push(StaticViolation::TooManySignatures {
+            num_signatures,
+            max_signatures: constraints.max_signatures,
+        });
+    }
+    if !message.fits_in_packet() {
+        violations.push(StaticViolation::TooLarge(message.size_breakdown()));
+    }
+    let duplicates = message.find_duplicates();
+    if !duplicates.is_empty() {
+        violations.push(StaticViolation::DuplicateAccountKeys(duplicates));
+    }
+    if let Some(reserved_account_keys) = constraints.reserved_account_keys {
+        violations.extend(
+            message
+                .writable_keys()
+                .filter(|key| reserved_account_keys.contains(*key))
+                .map(|key| StaticViolation::WritableReservedKey(*key)),
+        );
+    }
+    #[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+    if let Some(feature_set) = &constraints.precompile_feature_set {
+        if let Err(err) = message.verify_precompiles(feature_set) {
+            violations.push(StaticViolation::Precompile(err));
+        }
+    }
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_instruction::{AccountMeta, Instruction},
+        solana_sdk_ids::sysvar,
+    };
+
+    #[test]
+    fn test_verify_static() {
+        let payer = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![
+                AccountMeta::new(sysvar::clock::id(), false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), true),
+            ],
+        );
+        let message = Message::new(&[instruction], Some(&payer));
+        let constraints = StaticConstraints::default();
+        assert_eq!(constraints.max_signatures, 19);
+        assert_eq!(message.verify_static(&constraints), Ok(()));
+
+        // every violation is reported at once
+        let reserved_account_keys = HashSet::from([sysvar::clock::id()]);
+        let mut invalid = message.clone();
+        invalid.account_keys.push(payer);
+        invalid.instructions[0].data = vec![0; 1200];
+        invalid.instructions[0].program_id_index = 9;
+        let mut strict = constraints;
+        strict.max_signatures = 1;
+        strict.reserved_account_keys = Some(&reserved_account_keys);
+        assert_eq!(
+            invalid.verify_static(&strict),
+            Err(vec![
+                StaticViolation::Sanitize(SanitizeError::IndexOutOfBounds),
+                StaticViolation::TooManySignatures {
+                    num_signatures: 2,
+                    max_signatures: 1,
+                },
+                StaticViolation::TooLarge(invalid.size_breakdown()),
+                StaticViolation::DuplicateAccountKeys(vec![(0, 4)]),
+                StaticViolation::WritableReservedKey(sysvar::clock::id()),
+            ])
+        );
+    }
+
+    #[cfg(all(not(target_os = "solana"), feature = "precompiles"))]
+    #[test]
+    fn test_verify_static_precompiles() {
+        use {
+            solana_instruction_error::InstructionError, solana_sdk_ids::ed25519_program,
+            solana_transaction_error::TransactionError,
+        };
+
+        let payer = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(ed25519_program::id(), &[1, 0], vec![]);
+        let message = Message::new(&[instruction], Some(&payer));
+        assert_eq!(message.verify_static(&StaticConstraints::default()), Ok(()));
+        assert!(matches!(
+            message
+                .verify_static(&StaticConstraints {
+                    precompile_feature_set: Some(PrecompileFeatureSet::all_enabled()),
+                    ..StaticConstraints::default()
+                })
+                .unwrap_err()[..],
+            [StaticViolation::Precompile(
+                TransactionError::InstructionError(0, InstructionError::Custom(_))
+            )]
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+//! Unsigned messages for common system program and address lookup table
+//! flows.
+//!
+//! Each function builds the instructions of a flow and compiles them into a
+//! legacy [`Message`] with `payer` as the fee payer. The messages still need
+//! to be signed by the signers they list.
+//!
+//! ```
+//! # use solana_hash::Hash;
+//! # use solana_message::templates;
+//! # use solana_pubkey::Pubkey;
+//! let from = Pubkey::new_unique();
+//! let to = Pubkey::new_unique();
+//! let message = templates::transfer(&from, &to, 1_000_000, &Hash::new_unique());
+//! assert_eq!(message.signer_keys(), [&from]);
+//! ```
+use {
+    crate::Message,
+    solana_address_lookup_table_interface::instruction as address_lookup_table,
+    solana_hash::Hash,
+    solana_pubkey::{Pubkey, PubkeyError},
+    solana_system_interface::instruction as system_instruction,
+};
+
+/// A message that transfers `lamports` from `from`, which also pays the fee,
+/// to `to`.
+pub fn transfer(from: &Pubkey, to: &Pubkey, lamports: u64, recent_blockhash: &Hash) -> Message {
+    let instruction = system_instruction::transfer(from, to, lamports);
+    Message::new_with_blockhash(&[instruction], Some(from), recent_blockhash)
+}
+
+/// A message that creates an account at the address derived from `base`,
+/// `seed` and `owner`, funded by `payer`, returned with that address.
+///
+/// Both `payer` and `base` must sign. Fails if the seed is too long or the
+/// derived address is invalid.
+pub fn create_account_with_seed(
+    payer: &Pubkey,
+    base: &Pubkey,
+    seed: &str,
+    lamports: u64,
+    space: u64,
+    owner: &Pubkey,
+    recent_blockhash: &Hash,
+) -> Result<(Message, Pubkey), PubkeyError> {
+    let address = Pubkey::create_with_seed(base, seed, owner)?;
+    let instruction = system_instruction::create_account_with_seed(
+        payer, &address, base, seed, lamports, space, owner,
+    );
+    let message = Message::new_with_blockhash(&[instruction], Some(payer), recent_blockhash);
+    Ok((message, address))
+}
+
+/// A message that creates and initializes the durable nonce account `nonce`
+/// with `authority` as its nonce authority, funded by `payer`.
+///
+/// Both `payer` and `nonce` must sign, and `lamports` must make the account
+/// rent exempt.
+pub fn create_nonce_account(
+    payer: &Pubkey,
+    nonce: &Pubkey,
+    authority: &Pubkey,
+    lamports: u64,
+    recent_blockhash: &Hash,
+) -> Message {
+    let instructions = system_instruction::create_nonce_account(payer, nonce, authority, lamports);
+    Message::new_with_blockhash(&instructions, Some(payer), recent_blockhash)
+}
+
+/// A message that only advances the durable nonce of `nonce`.
+///
+/// `nonce_blockhash` is the nonce's current durable nonce, which is the
+/// message's recent blockhash, as in [`Message::new_with_nonce`]. Both
+/// `payer` and `authority` must sign.
+pub fn advance_nonce_account(
+    payer: &Pubkey,
+    nonce: &Pubkey,
+    authority: &Pubkey,
+    nonce_blockhash: &Hash,
+) -> Message {
+    let instruction = system_instruction::advance_nonce_account(nonce, authority);
+    Message::new_with_blockhash(&[instruction], Some(payer), nonce_blockhash)
+}
+
+/// A message that withdraws `lamports` from the durable nonce account
+/// `nonce` to `to`.
+///
+/// Both `payer` and `authority` must sign.
+pub fn withdraw_nonce_account(
+    payer: &Pubkey,
+    nonce: &Pubkey,
+    authority: &Pubkey,
+    to: &Pubkey,
+    lamports: u64,
+    recent_blockhash: &Hash,
+) -> Message {
+    let instruction = system_instruction::withdraw_nonce_account(nonce, authority, to, lamports);
+    Message::new_with_blockhash(&[instruction], Some(payer), recent_blockhash)
+}
+
+/// A message that creates an address lookup table owned by `authority`,
+/// funded by `payer`, returned with the table's address.
+///
+/// The table address is derived from `authority` and `recent_slot`, which
+/// must be a recent slot. Only `payer` must sign.
+pub fn create_lookup_table(
+    payer: &Pubkey,
+    authority: &Pubkey,
+    recent_slot: u64,
+    recent_blockhash: &Hash,
+) -> (Message, Pubkey) {
+    let (instruction, table) =
+        address_lookup_table::create_lookup_table(*authority, *payer, recent_slot);
+    let message = Message::new_with_blockhash(&[instruction], Some(payer), recent_blockhash);
+    (message, table)
+}
+
+/// A message that appends `new_addresses` to the address lookup table
+/// `table`, with `payer` funding the extra rent.
+///
+/// Both `payer` and `authority` must sign.
+pub fn extend_lookup_table(
+    payer: &Pubkey,
+    table: &Pubkey,
+    authority: &Pubkey,
+    new_addresses: Vec<Pubkey>,
+    recent_blockhash: &Hash,
+) -> Message {
+    let instruction =
+        address_lookup_table::extend_lookup_table(*table, *authority, Some(*payer), new_addresses);
+    Message::new_with_blockhash(&[instruction], Some(payer), recent_blockhash)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, solana_sdk_ids::system_program};
+
+    #[test]
+    fn test_templates() {
+        let payer = Pubkey::new_unique();
+        let authority = Pubkey::new_unique();
+        let nonce = Pubkey::new_unique();
+        let to = Pubkey::new_unique();
+        let blockhash = Hash::new_unique();
+
+        let message = transfer(&payer, &to, 42, &blockhash);
+        assert_eq!(message.signer_keys(), [&payer]);
+        assert_eq!(message.recent_blockhash, blockhash);
+
+        let (message, address) =
+            create_account_with_seed(&payer, &authority, "seed", 1, 0, &to, &blockhash).unwrap();
+        assert_eq!(
+            address,
+            Pubkey::create_with_seed(&authority, "seed", &to).unwrap()
+        );
+        assert_eq!(message.signer_keys(), [&payer, &authority]);
+        assert!(message.position_of(&address).is_some());
+        assert_eq!(
+            create_account_with_seed(&payer, &authority, &"x".repeat(33), 1, 0, &to, &blockhash),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+
+        let message = create_nonce_account(&payer, &nonce, &authority, 1, &blockhash);
+        assert_eq!(message.signer_keys(), [&payer, &nonce]);
+        assert_eq!(message.instructions.len(), 2);
+
+        let message = advance_nonce_account(&payer, &nonce, &authority, &blockhash);
+        assert_eq!(message.signer_keys(), [&payer, &authority]);
+        assert_eq!(message.get_durable_nonce(), Some(&nonce));
+
+        let message = withdraw_nonce_account(&payer, &nonce, &authority, &to, 1, &blockhash);
+        assert_eq!(message.signer_keys(), [&payer, &authority]);
+        assert_eq!(message.program_ids(), [&system_program::id()]);
+
+        let (message, table) = create_lookup_table(&payer, &authority, 7, &blockhash);
+        assert_eq!(
+            table,
+            address_lookup_table::derive_lookup_table_address(&authority, 7).0
+        );
+        assert_eq!(message.signer_keys(), [&payer]);
+
+        let message = extend_lookup_table(&payer, &table, &authority, vec![to], &blockhash);
+        assert_eq!(message.signer_keys(), [&payer, &authority]);
+        assert!(message.is_maybe_writable(message.position_of(&table).unwrap(), None));
+    }
+}
@@ -1,9 +1,19 @@
+#[cfg(feature = "parse")]
+use crate::ParsedInstructions;
+#[cfg(feature = "encoding")]
+use crate::{encoding, MessageDecodeError};
 #[cfg(feature = "frozen-abi")]
 use solana_frozen_abi_macro::{frozen_abi, AbiEnumVisitor, AbiExample};
+#[cfg(feature = "precompiles")]
+use {
+    crate::precompiles::{self, PrecompileFeatureSet},
+    solana_transaction_error::TransactionError,
+};
 use {
     crate::{
         compiled_instruction::CompiledInstruction, legacy::Message as LegacyMessage,
-        v0::MessageAddressTableLookup, MessageHeader,
+        v0::MessageAddressTableLookup, wire, CostEstimate, CostModelParams, FeeBreakdown,
+        MessageHeader, SizeBreakdown, WireError,
     },
     solana_hash::Hash,
     solana_pubkey::Pubkey,
@@ -22,6 +32,8 @@ use {
 
 mod sanitized;
 pub mod v0;
+#[cfg(feature = "experimental-v1")]
+pub mod v1;
 
 pub use sanitized::*;
 
@@ -69,6 +81,23 @@ impl VersionedMessage {
         }
     }
 
+    /// Return the index of `key` in the static account keys. Keys loaded
+    /// from address lookup tables are not found.
+    pub fn position_of(&self, key: &Pubkey) -> Option<usize> {
+        self.static_account_keys().iter().position(|k| k == key)
+    }
+
+    /// Return the index of `key` among the keys that must sign, which is also
+    /// the index of its signature in a transaction with this message. See
+    /// [`LegacyMessage::signer_position`].
+    pub fn signer_position(&self, key: &Pubkey) -> Option<usize> {
+        let num_signers = usize::from(self.header().num_required_signatures);
+        self.static_account_keys()
+            .iter()
+            .take(num_signers)
+            .position(|k| k == key)
+    }
+
     pub fn address_table_lookups(&self) -> Option<&[MessageAddressTableLookup]> {
         match self {
             Self::Legacy(_) => None,
@@ -145,11 +174,136 @@ impl VersionedMessage {
         }
     }
 
+    /// Decode the instructions of this message, like
+    /// [`LegacyMessage::parse_instructions`].
+    #[cfg(feature = "parse")]
+    pub fn parse_instructions(&self) -> ParsedInstructions<'_> {
+        ParsedInstructions::new(self.static_account_keys(), self.instructions())
+    }
+
     #[cfg(feature = "bincode")]
     pub fn serialize(&self) -> Vec<u8> {
         bincode::serialize(self).unwrap()
     }
 
+    /// Deserialize a legacy or v0 message from its wire format, for input
+    /// that cannot be trusted. See [`Message::deserialize_strict`].
+    ///
+    /// [`Message::deserialize_strict`]: crate::Message::deserialize_strict
+    pub fn deserialize_strict(bytes: &[u8]) -> Result<Self, WireError> {
+        wire::deserialize_versioned(bytes)
+    }
+
+    /// Serialize this message and encode it as base64, in the standard
+    /// alphabet with padding, as RPC expects.
+    #[cfg(feature = "encoding")]
+    pub fn to_base64(&self) -> String {
+        encoding::to_base64(self)
+    }
+
+    /// Decode a message from base64 in the standard alphabet with padding,
+    /// as RPC returns it. The message is not sanitized.
+    #[cfg(feature = "encoding")]
+    pub fn from_base64(encoded: &str) -> Result<Self, MessageDecodeError> {
+        encoding::from_base64(encoded)
+    }
+
+    /// Serialize this message and encode it as base58.
+    #[cfg(feature = "encoding")]
+    pub fn to_base58(&self) -> String {
+        encoding::to_base58(self)
+    }
+
+    /// Decode a message from base58. The message is not sanitized.
+    #[cfg(feature = "encoding")]
+    pub fn from_base58(encoded: &str) -> Result<Self, MessageDecodeError> {
+        encoding::from_base58(encoded)
+    }
+
+    /// Return the length of [`VersionedMessage::serialize`]'s output without
+    /// serializing the message.
+    pub fn serialized_size(&self) -> usize {
+        match self {
+            Self::Legacy(message) => message.serialized_size(),
+            Self::V0(message) => message.serialized_size(),
+        }
+    }
+
+    /// Return the fee, in lamports, for the signatures of a transaction with
+    /// this message, including the signatures verified by precompiles.
+    pub fn signature_fee(&self, lamports_per_signature: u64) -> u64 {
+        match self {
+            Self::Legacy(message) => message.signature_fee(lamports_per_signature),
+            Self::V0(message) => message.signature_fee(lamports_per_signature),
+        }
+    }
+
+    /// Return the signature and prioritization fees of a transaction with
+    /// this message, along with the compute budget it requests.
+    pub fn fee_breakdown(&self, lamports_per_signature: u64) -> FeeBreakdown {
+        match self {
+            Self::Legacy(message) => message.fee_breakdown(lamports_per_signature),
+            Self::V0(message) => message.fee_breakdown(lamports_per_signature),
+        }
+    }
+
+    /// Return the number of write locks the runtime takes for a transaction
+    /// with this message, after demoting reserved accounts and program ids.
+    pub fn num_write_locks(&self, reserved_account_keys: &HashSet<Pubkey>) -> u64 {
+        match self {
+            Self::Legacy(message) => message.num_write_locks(reserved_account_keys),
+            Self::V0(message) => message.num_write_locks(reserved_account_keys),
+        }
+    }
+
+    /// Return the signature, write lock and instruction data costs that the
+    /// validator cost model charges a transaction with this message.
+    pub fn cost_estimate(
+        &self,
+        params: &CostModelParams,
+        reserved_account_keys: &HashSet<Pubkey>,
+    ) -> CostEstimate {
+        match self {
+            Self::Legacy(message) => message.cost_estimate(params, reserved_account_keys),
+            Self::V0(message) => message.cost_estimate(params, reserved_account_keys),
+        }
+    }
+
+    /// Break down the size of a transaction with this message and its
+    /// required signatures by section, with suggestions for shrinking it if
+    /// it does not fit in a packet.
+    pub fn size_breakdown(&self) -> SizeBreakdown {
+        match self {
+            Self::Legacy(message) => message.size_breakdown(),
+            Self::V0(message) => message.size_breakdown(),
+        }
+    }
+
+    /// Returns true if a transaction with this message and its required
+    /// signatures is no larger than [`PACKET_DATA_SIZE`].
+    ///
+    /// [`PACKET_DATA_SIZE`]: solana_packet::PACKET_DATA_SIZE
+    pub fn fits_in_packet(&self) -> bool {
+        match self {
+            Self::Legacy(message) => message.fits_in_packet(),
+            Self::V0(message) => message.fits_in_packet(),
+        }
+    }
+
+    /// Verify the ed25519 and secp256k1 program instructions of this
+    /// message. See [`LegacyMessage::verify_precompiles`].
+    #[cfg(feature = "precompiles")]
+    pub fn verify_precompiles(
+        &self,
+        feature_set: &PrecompileFeatureSet,
+    ) -> Result<(), TransactionError> {
+        precompiles::verify_precompiles(
+            self.static_account_keys(),
+            self.instructions(),
+            feature_set,
+        )
+    }
+
     #[cfg(all(feature = "bincode", feature = "blake3"))]
     /// Compute the blake3 hash of this transaction's message
     pub fn hash(&self) -> Hash {
@@ -160,12 +314,23 @@ impl VersionedMessage {
     #[cfg(feature = "blake3")]
     /// Compute the blake3 hash of a raw transaction message
     pub fn hash_raw_message(message_bytes: &[u8]) -> Hash {
-        use blake3::traits::digest::Digest;
-        let mut hasher = blake3::Hasher::new();
-        hasher.update(b"solana-tx-message-v1");
-        hasher.update(message_bytes);
-        let hash_bytes: [u8; solana_hash::HASH_BYTES] = hasher.finalize().into();
-        hash_bytes.into()
+        crate::blake3_hash_with_domain(crate::MESSAGE_HASH_DOMAIN, message_bytes)
+    }
+
+    /// Compute the blake3 hash of this message like
+    /// [`VersionedMessage::hash`], with `domain` in place of
+    /// [`MESSAGE_HASH_DOMAIN`](crate::MESSAGE_HASH_DOMAIN). See
+    /// [`LegacyMessage::hash_with_domain`].
+    #[cfg(all(feature = "bincode", feature = "blake3"))]
+    pub fn hash_with_domain(&self, domain: &[u8]) -> Hash {
+        crate::blake3_hash_with_domain(domain, &self.serialize())
+    }
+
+    /// Compute the SHA-256 hash of `domain` followed by this serialized
+    /// message.
+    #[cfg(all(feature = "bincode", feature = "sha256"))]
+    pub fn sha256_hash_with_domain(&self, domain: &[u8]) -> Hash {
+        LegacyMessage::sha256_hash_raw_message_with_domain(domain, &self.serialize())
     }
 }
 
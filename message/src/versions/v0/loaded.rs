@@ -1,7 +1,7 @@
 #[cfg(feature = "serde")]
 use serde_derive::{Deserialize, Serialize};
 use {
-    crate::{v0, AccountKeys},
+    crate::{duplicates, v0, AccountKeys},
     solana_pubkey::Pubkey,
     solana_sdk_ids::bpf_loader_upgradeable,
     std::{borrow::Cow, collections::HashSet},
@@ -84,7 +84,10 @@ impl<'a> LoadedMessage<'a> {
         loaded_message
     }
 
-    fn set_is_writable_account_cache(&mut self, reserved_account_keys: &HashSet<Pubkey>) {
+    pub(super) fn set_is_writable_account_cache(
+        &mut self,
+        reserved_account_keys: &HashSet<Pubkey>,
+    ) {
         let is_writable_account_cache = self
             .account_keys()
             .iter()
@@ -109,8 +112,17 @@ impl<'a> LoadedMessage<'a> {
 
     /// Returns true if any account keys are duplicates
     pub fn has_duplicates(&self) -> bool {
-        let mut uniq = HashSet::new();
-        self.account_keys().iter().any(|x| !uniq.insert(x))
+        let account_keys: Vec<&Pubkey> = self.account_keys().iter().collect();
+        duplicates::has_duplicates(&account_keys)
+    }
+
+    /// Return the indexes of the duplicate keys among the static and loaded
+    /// account keys. See [`legacy::Message::find_duplicates`].
+    ///
+    /// [`legacy::Message::find_duplicates`]: crate::legacy::Message::find_duplicates
+    pub fn find_duplicates(&self) -> Vec<(usize, usize)> {
+        let account_keys: Vec<&Pubkey> = self.account_keys().iter().collect();
+        duplicates::find_duplicates(&account_keys)
     }
 
     /// Returns true if the account at the specified index was requested to be
@@ -226,6 +238,7 @@ mod tests {
         let message = check_test_loaded_message().0;
 
         assert!(!message.has_duplicates());
+        assert!(message.find_duplicates().is_empty());
     }
 
     #[test]
@@ -255,6 +268,14 @@ mod tests {
         for keys in keys.into_iter().permutations(keys_len).unique() {
             let message = create_message_with_dupe_keys(keys);
             assert!(message.has_duplicates());
+            let duplicates = message.find_duplicates();
+            assert_eq!(duplicates.len(), 1);
+            let (first, repeat) = duplicates[0];
+            assert!(first < repeat);
+            assert_eq!(
+                message.account_keys().get(first),
+                message.account_keys().get(repeat)
+            );
         }
     }
 
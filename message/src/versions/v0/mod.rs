@@ -17,15 +17,23 @@ use solana_frozen_abi_macro::AbiExample;
 use {
     crate::{
         compiled_instruction::CompiledInstruction,
-        compiled_keys::{CompileError, CompiledKeys},
-        AccountKeys, AddressLookupTableAccount, MessageHeader,
+        compiled_keys::{CompileError, CompiledKeys, DetailedCompileError},
+        cost_estimate::{CostEstimate, CostModelParams},
+        fits_in_packet,
+        legacy::Message as LegacyMessage,
+        short_vec_len, AccountKeys, AddressLookupTableAccount, AddressLookupTableProvider,
+        FeeBreakdown, MessageHeader, SizeBreakdown, MESSAGE_HEADER_LENGTH,
     },
-    solana_hash::Hash,
+    solana_hash::{Hash, HASH_BYTES},
     solana_instruction::Instruction,
-    solana_pubkey::Pubkey,
+    solana_pubkey::{Pubkey, PUBKEY_BYTES},
     solana_sanitize::SanitizeError,
     solana_sdk_ids::bpf_loader_upgradeable,
-    std::collections::HashSet,
+    solana_transaction_error::AddressLoaderError,
+    std::{
+        borrow::Cow,
+        collections::{HashMap, HashSet},
+    },
 };
 
 mod loaded;
@@ -50,6 +58,47 @@ pub struct MessageAddressTableLookup {
     pub readonly_indexes: Vec<u8>,
 }
 
+impl MessageAddressTableLookup {
+    /// Merge lookups of the same table into one, in the order of each table's
+    /// first lookup, with sorted and deduplicated indexes.
+    ///
+    /// An index that a table's lookups load both as writable and as
+    /// read-only is only loaded as writable. Merging changes which positions
+    /// the loaded addresses have, so merge the lookups of a message with
+    /// [`Message::merge_address_table_lookups`], which updates its
+    /// instructions to match.
+    pub fn merge(lookups: &[Self]) -> Vec<Self> {
+        let mut merged: Vec<Self> = Vec::new();
+        for lookup in lookups {
+            match merged
+                .iter_mut()
+                .find(|merged| merged.account_key == lookup.account_key)
+            {
+                Some(merged) => {
+                    merged
+                        .writable_indexes
+                        .extend_from_slice(&lookup.writable_indexes);
+                    merged
+                        .readonly_indexes
+                        .extend_from_slice(&lookup.readonly_indexes);
+                }
+                None => merged.push(lookup.clone()),
+            }
+        }
+        for lookup in &mut merged {
+            lookup.writable_indexes.sort_unstable();
+            lookup.writable_indexes.dedup();
+            lookup.readonly_indexes.sort_unstable();
+            lookup.readonly_indexes.dedup();
+            let writable_indexes = &lookup.writable_indexes;
+            lookup
+                .readonly_indexes
+                .retain(|index| writable_indexes.binary_search(index).is_err());
+        }
+        merged
+    }
+}
+
 /// A Solana transaction message (v0).
 ///
 /// This message format supports succinct account loading with
@@ -269,8 +318,129 @@ impl Message {
         address_lookup_table_accounts: &[AddressLookupTableAccount],
         recent_blockhash: Hash,
     ) -> Result<Self, CompileError> {
-        let mut compiled_keys = CompiledKeys::compile(instructions, Some(*payer));
+        Self::try_compile_detailed(
+            payer,
+            instructions,
+            address_lookup_table_accounts,
+            recent_blockhash,
+        )
+        .map_err(CompileError::from)
+    }
+
+    /// Create a signable transaction message like [`Message::try_compile`],
+    /// but fail with a [`DetailedCompileError`], which reports how many keys
+    /// to remove and which keys could be loaded from lookup tables instead if
+    /// the instructions reference more keys than a message can index.
+    pub fn try_compile_detailed(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<Self, DetailedCompileError> {
+        Self::compile_with_keys(
+            CompiledKeys::compile(instructions, Some(*payer)),
+            instructions,
+            address_lookup_table_accounts,
+            recent_blockhash,
+        )
+        .map(|(message, _)| message)
+    }
 
+    /// Create a signable transaction message like [`Message::try_compile`],
+    /// but only use the subset of `candidate_tables` that minimizes the
+    /// serialized size of the message.
+    ///
+    /// Tables that would only load a few keys cost more than they save, and
+    /// tables that only contain keys already loaded from another table are
+    /// skipped entirely. Returns the compiled message along with the
+    /// addresses it loads from lookup tables, so callers can tell which keys
+    /// were loaded and which were left in the message's static
+    /// `account_keys`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_hash::Hash;
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::{v0, AddressLookupTableAccount};
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let accounts: Vec<Pubkey> = (0..8).map(|_| Pubkey::new_unique()).collect();
+    /// let instruction = Instruction::new_with_bytes(
+    ///     Pubkey::new_unique(),
+    ///     &[],
+    ///     accounts.iter().map(|key| AccountMeta::new(*key, false)).collect(),
+    /// );
+    /// let small_table = AddressLookupTableAccount {
+    ///     key: Pubkey::new_unique(),
+    ///     addresses: accounts[..1].to_vec(),
+    /// };
+    /// let large_table = AddressLookupTableAccount {
+    ///     key: Pubkey::new_unique(),
+    ///     addresses: accounts.clone(),
+    /// };
+    ///
+    /// let (message, loaded_addresses) = v0::Message::try_compile_with_tables(
+    ///     &payer,
+    ///     &[instruction],
+    ///     &[small_table, large_table.clone()],
+    ///     Hash::default(),
+    /// )?;
+    /// assert_eq!(message.address_table_lookups.len(), 1);
+    /// assert_eq!(message.address_table_lookups[0].account_key, large_table.key);
+    /// assert_eq!(loaded_addresses.writable.len(), accounts.len());
+    /// # Ok::<(), solana_message::CompileError>(())
+    /// ```
+    pub fn try_compile_with_tables(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        candidate_tables: &[AddressLookupTableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<(Self, LoadedAddresses), CompileError> {
+        let compiled_keys = CompiledKeys::compile(instructions, Some(*payer));
+        let selected_tables: Vec<AddressLookupTableAccount> = compiled_keys
+            .select_lookup_tables(candidate_tables)
+            .into_iter()
+            .map(|table_index| candidate_tables[table_index].clone())
+            .collect();
+        Self::try_compile_with_keys(
+            compiled_keys,
+            instructions,
+            &selected_tables,
+            recent_blockhash,
+        )
+    }
+
+    /// Create a signable transaction message from keys compiled from
+    /// `instructions`, so that callers control how account indexes are
+    /// assigned. See [`CompiledKeys`].
+    ///
+    /// Keys are loaded from `address_lookup_table_accounts` in order, like
+    /// [`Message::try_compile`]. Returns the compiled message along with the
+    /// addresses it loads from lookup tables. Fails with
+    /// [`CompileError::UnknownInstructionKey`] if `instructions` use a key
+    /// that `compiled_keys` does not have.
+    pub fn try_compile_with_keys(
+        compiled_keys: CompiledKeys,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<(Self, LoadedAddresses), CompileError> {
+        Self::compile_with_keys(
+            compiled_keys,
+            instructions,
+            address_lookup_table_accounts,
+            recent_blockhash,
+        )
+        .map_err(CompileError::from)
+    }
+
+    fn compile_with_keys(
+        mut compiled_keys: CompiledKeys,
+        instructions: &[Instruction],
+        address_lookup_table_accounts: &[AddressLookupTableAccount],
+        recent_blockhash: Hash,
+    ) -> Result<(Self, LoadedAddresses), DetailedCompileError> {
         let mut address_table_lookups = Vec::with_capacity(address_lookup_table_accounts.len());
         let mut loaded_addresses_list = Vec::with_capacity(address_lookup_table_accounts.len());
         for lookup_table_account in address_lookup_table_accounts {
@@ -282,18 +452,112 @@ impl Message {
             }
         }
 
+        let dynamic_keys: LoadedAddresses = loaded_addresses_list.into_iter().collect();
+        compiled_keys.check_num_keys(dynamic_keys.len())?;
         let (header, static_keys) = compiled_keys.try_into_message_components()?;
-        let dynamic_keys = loaded_addresses_list.into_iter().collect();
         let account_keys = AccountKeys::new(&static_keys, Some(&dynamic_keys));
         let instructions = account_keys.try_compile_instructions(instructions)?;
 
-        Ok(Self {
-            header,
-            account_keys: static_keys,
-            recent_blockhash,
-            instructions,
-            address_table_lookups,
-        })
+        Ok((
+            Self {
+                header,
+                account_keys: static_keys,
+                recent_blockhash,
+                instructions,
+                address_table_lookups,
+            },
+            dynamic_keys,
+        ))
+    }
+
+    /// Load the addresses that this message looks up in address lookup
+    /// tables, with the tables' contents supplied by `tables`.
+    ///
+    /// Fails with [`AddressLoaderError::LookupTableAccountNotFound`] if a
+    /// table is missing, and with [`AddressLoaderError::InvalidLookupIndex`]
+    /// if a lookup index is out of bounds. Whether tables are active is up to
+    /// the provider.
+    pub fn resolve_addresses(
+        &self,
+        tables: &(impl AddressLookupTableProvider + ?Sized),
+    ) -> Result<LoadedAddresses, AddressLoaderError> {
+        let mut loaded_addresses = LoadedAddresses::default();
+        for lookup in &self.address_table_lookups {
+            let addresses = tables
+                .lookup_table_addresses(&lookup.account_key)
+                .ok_or(AddressLoaderError::LookupTableAccountNotFound)?;
+            let load = |indexes: &[u8], loaded: &mut Vec<Pubkey>| {
+                for index in indexes {
+                    let address = addresses
+                        .get(usize::from(*index))
+                        .ok_or(AddressLoaderError::InvalidLookupIndex)?;
+                    loaded.push(*address);
+                }
+                Ok(())
+            };
+            load(&lookup.writable_indexes, &mut loaded_addresses.writable)?;
+            load(&lookup.readonly_indexes, &mut loaded_addresses.readonly)?;
+        }
+        Ok(loaded_addresses)
+    }
+
+    /// Resolve this message's address table lookups with `tables`, see
+    /// [`Message::resolve_addresses`], and combine the message with the loaded
+    /// addresses.
+    ///
+    /// No account keys are treated as reserved when working out which
+    /// accounts are writable. Use [`LoadedMessage::new`] with the loaded
+    /// addresses to pass the runtime's reserved account keys.
+    ///
+    /// ```
+    /// # use solana_hash::Hash;
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::{v0, AddressLookupTableAccount};
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// let account = Pubkey::new_unique();
+    /// let tables = vec![AddressLookupTableAccount {
+    ///     key: Pubkey::new_unique(),
+    ///     addresses: vec![account],
+    /// }];
+    /// let program_id = Pubkey::new_unique();
+    /// let instruction =
+    ///     Instruction::new_with_bytes(program_id, &[], vec![AccountMeta::new(account, false)]);
+    /// let message =
+    ///     v0::Message::try_compile(&payer, &[instruction], &tables, Hash::default()).unwrap();
+    ///
+    /// let loaded_message = message.resolve(&tables).unwrap();
+    /// assert_eq!(loaded_message.loaded_addresses.writable, [account]);
+    /// assert!(loaded_message.is_writable(2));
+    /// ```
+    pub fn resolve(
+        &self,
+        tables: &(impl AddressLookupTableProvider + ?Sized),
+    ) -> Result<LoadedMessage<'_>, AddressLoaderError> {
+        let mut loaded_message = LoadedMessage {
+            message: Cow::Borrowed(self),
+            loaded_addresses: Cow::Owned(self.resolve_addresses(tables)?),
+            is_writable_account_cache: Vec::new(),
+        };
+        loaded_message.set_is_writable_account_cache(&HashSet::new());
+        Ok(loaded_message)
+    }
+
+    /// Convert a legacy message to a v0 message with the same keys and
+    /// instructions and no address table lookups.
+    ///
+    /// Fails if the result is not a valid v0 message, for example because the
+    /// legacy message has more than 256 account keys.
+    pub fn try_from_legacy(message: LegacyMessage) -> Result<Self, SanitizeError> {
+        let message = Self {
+            header: message.header,
+            account_keys: message.account_keys,
+            recent_blockhash: message.recent_blockhash,
+            instructions: message.instructions,
+            address_table_lookups: Vec::new(),
+        };
+        message.sanitize()?;
+        Ok(message)
     }
 
     #[cfg(feature = "bincode")]
@@ -302,6 +566,118 @@ impl Message {
         bincode::serialize(&(crate::MESSAGE_VERSION_PREFIX, self)).unwrap()
     }
 
+    /// Return the length of [`Message::serialize`]'s output, including the
+    /// version prefix, computed from the lengths of the message's fields
+    /// without serializing it.
+    pub fn serialized_size(&self) -> usize {
+        let instructions_size = self
+            .instructions
+            .iter()
+            .map(CompiledInstruction::serialized_size)
+            .fold(0, usize::saturating_add);
+        // version prefix
+        1usize
+            .saturating_add(MESSAGE_HEADER_LENGTH)
+            .saturating_add(short_vec_len(self.account_keys.len()))
+            .saturating_add(self.account_keys.len().saturating_mul(PUBKEY_BYTES))
+            .saturating_add(HASH_BYTES)
+            .saturating_add(short_vec_len(self.instructions.len()))
+            .saturating_add(instructions_size)
+            .saturating_add(self.address_table_lookups_size())
+    }
+
+    fn address_table_lookups_size(&self) -> usize {
+        self.address_table_lookups
+            .iter()
+            .map(|lookup| {
+                PUBKEY_BYTES
+                    .saturating_add(short_vec_len(lookup.writable_indexes.len()))
+                    .saturating_add(lookup.writable_indexes.len())
+                    .saturating_add(short_vec_len(lookup.readonly_indexes.len()))
+                    .saturating_add(lookup.readonly_indexes.len())
+            })
+            .fold(
+                short_vec_len(self.address_table_lookups.len()),
+                usize::saturating_add,
+            )
+    }
+
+    /// Break down the size of a transaction with this message and its
+    /// required signatures by section, with suggestions for shrinking it if
+    /// it does not fit in a packet.
+    pub fn size_breakdown(&self) -> SizeBreakdown {
+        SizeBreakdown::new(
+            true,
+            &self.header,
+            &self.account_keys,
+            |i| self.is_writable_index(i),
+            &self.instructions,
+            self.address_table_lookups_size(),
+        )
+    }
+
+    /// Returns true if a transaction with this message and its required
+    /// signatures is no larger than [`PACKET_DATA_SIZE`].
+    ///
+    /// [`PACKET_DATA_SIZE`]: solana_packet::PACKET_DATA_SIZE
+    pub fn fits_in_packet(&self) -> bool {
+        fits_in_packet(self.header.num_required_signatures, self.serialized_size())
+    }
+
+    /// Return the fee, in lamports, for the signatures of a transaction with
+    /// this message, including the signatures verified by precompiles.
+    pub fn signature_fee(&self, lamports_per_signature: u64) -> u64 {
+        self.fee_breakdown(lamports_per_signature).signature_fee
+    }
+
+    /// Return the signature and prioritization fees of a transaction with
+    /// this message, along with the compute budget it requests.
+    pub fn fee_breakdown(&self, lamports_per_signature: u64) -> FeeBreakdown {
+        FeeBreakdown::new(
+            lamports_per_signature,
+            &self.header,
+            &self.account_keys,
+            &self.instructions,
+        )
+    }
+
+    /// Return the number of write locks the runtime takes for a transaction
+    /// with this message, after demoting reserved accounts and program ids
+    /// like [`Message::is_maybe_writable`], including the writable addresses
+    /// it loads from lookup tables.
+    ///
+    /// Loaded addresses are not known before they are loaded, so they are
+    /// never counted as reserved.
+    pub fn num_write_locks(&self, reserved_account_keys: &HashSet<Pubkey>) -> u64 {
+        let num_loaded_writable = self
+            .address_table_lookups
+            .iter()
+            .map(|lookup| lookup.writable_indexes.len())
+            .fold(0usize, usize::saturating_add);
+        let num_account_keys = self.account_keys.len().saturating_add(num_loaded_writable);
+        (0..num_account_keys)
+            .filter(|&key_index| self.is_maybe_writable(key_index, Some(reserved_account_keys)))
+            .count() as u64
+    }
+
+    /// Return the signature, write lock and instruction data costs that the
+    /// validator cost model charges a transaction with this message.
+    ///
+    /// Write locks are counted like [`Message::num_write_locks`].
+    pub fn cost_estimate(
+        &self,
+        params: &CostModelParams,
+        reserved_account_keys: &HashSet<Pubkey>,
+    ) -> CostEstimate {
+        CostEstimate::new(
+            params,
+            &self.header,
+            &self.account_keys,
+            &self.instructions,
+            self.num_write_locks(reserved_account_keys),
+        )
+    }
+
     /// Returns true if the account at the specified index is called as a program by an instruction
     pub fn is_key_called_as_program(&self, key_index: usize) -> bool {
         if let Ok(key_index) = u8::try_from(key_index) {
@@ -351,7 +727,10 @@ impl Message {
     /// writable. Before loading addresses, we can't demote write locks properly
     /// so this should not be used by the runtime. The `reserved_account_keys`
     /// param is optional to allow clients to approximate writability without
-    /// requiring fetching the latest set of reserved account keys.
+    /// requiring fetching the latest set of reserved account keys, which
+    /// [`ReservedAccountKeys::keys`] returns.
+    ///
+    /// [`ReservedAccountKeys::keys`]: crate::ReservedAccountKeys::keys
     pub fn is_maybe_writable(
         &self,
         key_index: usize,
@@ -366,6 +745,94 @@ impl Message {
             }
     }
 
+    /// Merge this message's lookups of the same table, sort their indexes and
+    /// remove duplicates, like [`MessageAddressTableLookup::merge`], and
+    /// update the account indexes of the instructions to the new positions of
+    /// the loaded addresses.
+    ///
+    /// Fails with [`SanitizeError::IndexOutOfBounds`], without changing the
+    /// message, if an instruction uses an index past the loaded addresses.
+    ///
+    /// ```
+    /// # use solana_message::{compiled_instruction::CompiledInstruction, v0};
+    /// # use solana_message::v0::MessageAddressTableLookup;
+    /// # use solana_pubkey::Pubkey;
+    /// let table = Pubkey::new_unique();
+    /// let lookup = |index| MessageAddressTableLookup {
+    ///     account_key: table,
+    ///     writable_indexes: vec![],
+    ///     readonly_indexes: vec![index],
+    /// };
+    /// let mut message = v0::Message {
+    ///     account_keys: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+    ///     // the same address is loaded twice, at indexes 2 and 4
+    ///     address_table_lookups: vec![lookup(7), lookup(3), lookup(7)],
+    ///     instructions: vec![CompiledInstruction::new_from_raw_parts(1, vec![], vec![2, 4])],
+    ///     ..v0::Message::default()
+    /// };
+    /// message.merge_address_table_lookups()?;
+    /// assert_eq!(message.address_table_lookups[0].readonly_indexes, [3, 7]);
+    /// assert_eq!(message.instructions[0].accounts, [3, 3]);
+    /// # Ok::<(), solana_sanitize::SanitizeError>(())
+    /// ```
+    pub fn merge_address_table_lookups(&mut self) -> Result<(), SanitizeError> {
+        // the table and index of each loaded address, in the order they are
+        // loaded: the writable addresses of every lookup, then the read-only
+        // ones
+        fn loaded_order(lookups: &[MessageAddressTableLookup]) -> Vec<(Pubkey, u8)> {
+            let loaded = |indexes: fn(&MessageAddressTableLookup) -> &[u8]| {
+                lookups.iter().flat_map(move |lookup| {
+                    indexes(lookup)
+                        .iter()
+                        .map(|index| (lookup.account_key, *index))
+                })
+            };
+            loaded(|lookup| &lookup.writable_indexes)
+                .chain(loaded(|lookup| &lookup.readonly_indexes))
+                .collect()
+        }
+
+        let merged = MessageAddressTableLookup::merge(&self.address_table_lookups);
+        let old_order = loaded_order(&self.address_table_lookups);
+        let new_positions: HashMap<(Pubkey, u8), usize> = loaded_order(&merged)
+            .into_iter()
+            .enumerate()
+            .map(|(position, address)| (address, position))
+            .collect();
+        let num_static_keys = self.account_keys.len();
+        let remap = |index: u8| -> Result<u8, SanitizeError> {
+            let Some(loaded_position) = usize::from(index).checked_sub(num_static_keys) else {
+                return Ok(index);
+            };
+            let address = old_order
+                .get(loaded_position)
+                .ok_or(SanitizeError::IndexOutOfBounds)?;
+            // merged lookups load every address the old ones did, in at
+            // most as many positions
+            let new_position = new_positions[address].saturating_add(num_static_keys);
+            u8::try_from(new_position).map_err(|_| SanitizeError::IndexOutOfBounds)
+        };
+
+        let instructions = self
+            .instructions
+            .iter()
+            .map(|ix| {
+                Ok(CompiledInstruction {
+                    program_id_index: remap(ix.program_id_index)?,
+                    accounts: ix
+                        .accounts
+                        .iter()
+                        .map(|index| remap(*index))
+                        .collect::<Result<_, _>>()?,
+                    data: ix.data.clone(),
+                })
+            })
+            .collect::<Result<_, SanitizeError>>()?;
+        self.instructions = instructions;
+        self.address_table_lookups = merged;
+        Ok(())
+    }
+
     /// Returns true if the account at the specified index is in the reserved
     /// account keys set. Before loading addresses, we can't detect reserved
     /// account keys properly so this shouldn't be used by the runtime.
@@ -386,7 +853,10 @@ impl Message {
 
 #[cfg(test)]
 mod tests {
-    use {super::*, crate::VersionedMessage, solana_instruction::AccountMeta};
+    use {
+        super::*, crate::VersionedMessage, solana_instruction::AccountMeta,
+        std::collections::HashMap,
+    };
 
     #[test]
     fn test_sanitize() {
@@ -654,6 +1124,57 @@ mod tests {
         assert_eq!(message.serialize(), versioned_msg.serialize());
     }
 
+    #[test]
+    fn test_serialized_size() {
+        let payer = Pubkey::new_unique();
+        let lookup_table_keys: Vec<Pubkey> = (0..200).map(|_| Pubkey::new_unique()).collect();
+        let instructions = [Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[0; 200],
+            lookup_table_keys
+                .iter()
+                .enumerate()
+                .map(|(i, key)| AccountMeta {
+                    pubkey: *key,
+                    is_signer: false,
+                    is_writable: i % 2 == 0,
+                })
+                .chain([AccountMeta::new(Pubkey::new_unique(), true)])
+                .collect(),
+        )];
+        let lookup_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: lookup_table_keys,
+        };
+        let message =
+            Message::try_compile(&payer, &instructions, &[lookup_table], Hash::new_unique())
+                .unwrap();
+        assert_eq!(message.address_table_lookups.len(), 1);
+        assert_eq!(message.serialized_size(), message.serialize().len());
+        assert!(message.fits_in_packet());
+        assert_eq!(
+            Message::default().serialized_size(),
+            Message::default().serialize().len()
+        );
+
+        let versioned_message = VersionedMessage::V0(message);
+        assert_eq!(
+            versioned_message.serialized_size(),
+            versioned_message.serialize().len()
+        );
+        assert!(versioned_message.fits_in_packet());
+
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                ..MessageHeader::default()
+            },
+            account_keys: (0..38).map(|_| Pubkey::new_unique()).collect(),
+            ..Message::default()
+        };
+        assert!(!message.fits_in_packet());
+    }
+
     #[test]
     fn test_try_compile() {
         let mut keys = vec![];
@@ -713,6 +1234,183 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_resolve() {
+        let payer = Pubkey::new_unique();
+        let keys = [(); 4].map(|_| Pubkey::new_unique());
+        let tables = vec![
+            AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![keys[0], keys[1]],
+            },
+            AddressLookupTableAccount {
+                key: Pubkey::new_unique(),
+                addresses: vec![keys[2], keys[3]],
+            },
+        ];
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[],
+            vec![
+                AccountMeta::new_readonly(keys[0], false),
+                AccountMeta::new(keys[3], false),
+                AccountMeta::new(keys[1], false),
+            ],
+        );
+        let (message, loaded_addresses) =
+            Message::try_compile_with_tables(&payer, &[instruction], &tables, Hash::default())
+                .unwrap();
+
+        assert_eq!(
+            message.resolve_addresses(&tables),
+            Ok(loaded_addresses.clone())
+        );
+        let map: HashMap<Pubkey, Vec<Pubkey>> = tables
+            .iter()
+            .map(|table| (table.key, table.addresses.clone()))
+            .collect();
+        let loaded_message = message.resolve(&map).unwrap();
+        assert_eq!(loaded_message.loaded_addresses.as_ref(), &loaded_addresses);
+        assert_eq!(
+            loaded_message,
+            LoadedMessage::new(message.clone(), loaded_addresses, &HashSet::new())
+        );
+
+        assert_eq!(
+            message.resolve(&Vec::new()),
+            Err(AddressLoaderError::LookupTableAccountNotFound)
+        );
+        let mut truncated = tables;
+        for table in &mut truncated {
+            table.addresses.truncate(1);
+        }
+        assert_eq!(
+            message.resolve_addresses(&truncated),
+            Err(AddressLoaderError::InvalidLookupIndex)
+        );
+    }
+
+    #[test]
+    fn test_try_from_legacy() {
+        let payer = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[1, 2, 3],
+            vec![AccountMeta::new(Pubkey::new_unique(), false)],
+        );
+        let legacy = LegacyMessage::new_with_blockhash(
+            &[instruction.clone()],
+            Some(&payer),
+            &Hash::new_unique(),
+        );
+        let message = Message::try_from_legacy(legacy.clone()).unwrap();
+        assert_eq!(
+            message,
+            Message::try_compile(&payer, &[instruction], &[], legacy.recent_blockhash).unwrap()
+        );
+        assert_eq!(LegacyMessage::try_from_v0(message), Ok(legacy.clone()));
+
+        let mut too_many_keys = legacy.clone();
+        too_many_keys
+            .account_keys
+            .extend((0..256).map(|_| Pubkey::new_unique()));
+        assert_eq!(
+            Message::try_from_legacy(too_many_keys),
+            Err(SanitizeError::IndexOutOfBounds)
+        );
+
+        let mut with_lookups = Message::try_from_legacy(legacy).unwrap();
+        with_lookups
+            .address_table_lookups
+            .push(MessageAddressTableLookup {
+                account_key: Pubkey::new_unique(),
+                writable_indexes: vec![0],
+                readonly_indexes: vec![],
+            });
+        assert_eq!(
+            LegacyMessage::try_from_v0(with_lookups.clone()),
+            Err(with_lookups)
+        );
+    }
+
+    #[test]
+    fn test_try_compile_with_tables() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let writable_keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let readonly_key = Pubkey::new_unique();
+        let instructions = vec![Instruction {
+            program_id,
+            accounts: writable_keys
+                .iter()
+                .map(|key| AccountMeta::new(*key, false))
+                .chain([AccountMeta::new_readonly(readonly_key, false)])
+                .collect(),
+            data: vec![],
+        }];
+        let subset_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: writable_keys[..2].to_vec(),
+        };
+        // loading a single key costs more than it saves
+        let single_key_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![readonly_key],
+        };
+        let superset_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: [writable_keys.clone(), vec![program_id]].concat(),
+        };
+        let candidate_tables = vec![subset_table, single_key_table, superset_table];
+
+        let recent_blockhash = Hash::new_unique();
+        let (message, loaded_addresses) = Message::try_compile_with_tables(
+            &payer,
+            &instructions,
+            &candidate_tables,
+            recent_blockhash,
+        )
+        .unwrap();
+        assert_eq!(
+            message,
+            Message::try_compile(
+                &payer,
+                &instructions,
+                &candidate_tables[2..],
+                recent_blockhash
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            message.address_table_lookups,
+            vec![MessageAddressTableLookup {
+                account_key: candidate_tables[2].key,
+                writable_indexes: vec![0, 1, 2, 3],
+                readonly_indexes: vec![],
+            }]
+        );
+        assert_eq!(message.account_keys.len(), 3);
+        assert!(message.account_keys.contains(&readonly_key));
+        assert_eq!(
+            loaded_addresses.writable.iter().collect::<HashSet<_>>(),
+            writable_keys.iter().collect::<HashSet<_>>()
+        );
+        assert!(loaded_addresses.readonly.is_empty());
+        assert!(
+            message.serialize().len()
+                < Message::try_compile(&payer, &instructions, &candidate_tables, recent_blockhash)
+                    .unwrap()
+                    .serialize()
+                    .len()
+        );
+
+        // no tables
+        let (message, loaded_addresses) =
+            Message::try_compile_with_tables(&payer, &instructions, &[], recent_blockhash).unwrap();
+        assert!(message.address_table_lookups.is_empty());
+        assert!(loaded_addresses.is_empty());
+    }
+
     #[test]
     fn test_is_maybe_writable() {
         let key0 = Pubkey::new_unique();
@@ -779,4 +1477,103 @@ mod tests {
         assert!(!message.is_account_maybe_reserved(3, None));
         assert!(!message.is_account_maybe_reserved(4, None));
     }
+
+    #[test]
+    fn test_merge_address_table_lookups() {
+        let table_a = Pubkey::new_unique();
+        let table_b = Pubkey::new_unique();
+        let lookup = |account_key, writable_indexes: &[u8], readonly_indexes: &[u8]| {
+            MessageAddressTableLookup {
+                account_key,
+                writable_indexes: writable_indexes.to_vec(),
+                readonly_indexes: readonly_indexes.to_vec(),
+            }
+        };
+        let lookups = vec![
+            lookup(table_a, &[5], &[2, 9]),
+            lookup(table_b, &[1], &[]),
+            lookup(table_a, &[0, 5], &[2, 0]),
+        ];
+        assert_eq!(
+            MessageAddressTableLookup::merge(&lookups),
+            [
+                lookup(table_a, &[0, 5], &[2, 9]),
+                lookup(table_b, &[1], &[])
+            ]
+        );
+
+        let program_id = Pubkey::new_unique();
+        let message = Message {
+            header: MessageHeader {
+                num_required_signatures: 1,
+                num_readonly_signed_accounts: 0,
+                num_readonly_unsigned_accounts: 1,
+            },
+            account_keys: vec![Pubkey::new_unique(), program_id],
+            // loaded: a5 b1 a0 a5 | a2 a9 a2 a0
+            address_table_lookups: lookups,
+            instructions: vec![
+                CompiledInstruction::new_from_raw_parts(1, vec![], vec![0, 2, 3, 4, 5]),
+                CompiledInstruction::new_from_raw_parts(1, vec![1], vec![6, 7, 8, 9]),
+            ],
+            ..Message::default()
+        };
+        let tables: HashMap<_, Vec<_>> = [table_a, table_b]
+            .into_iter()
+            .map(|key| (key, (0..10).map(|_| Pubkey::new_unique()).collect()))
+            .collect();
+        let resolve = |message: &Message| {
+            let mut writable = vec![];
+            let mut readonly = vec![];
+            for lookup in &message.address_table_lookups {
+                let addresses = &tables[&lookup.account_key];
+                writable.extend(
+                    lookup
+                        .writable_indexes
+                        .iter()
+                        .map(|i| addresses[*i as usize]),
+                );
+                readonly.extend(
+                    lookup
+                        .readonly_indexes
+                        .iter()
+                        .map(|i| addresses[*i as usize]),
+                );
+            }
+            let keys: Vec<_> = message
+                .account_keys
+                .iter()
+                .chain(&writable)
+                .chain(&readonly)
+                .copied()
+                .collect();
+            message
+                .instructions
+                .iter()
+                .map(|ix| {
+                    ix.accounts
+                        .iter()
+                        .map(|index| keys[*index as usize])
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut merged = message.clone();
+        merged.merge_address_table_lookups().unwrap();
+        assert_eq!(resolve(&merged), resolve(&message));
+        assert_eq!(merged.instructions[0].accounts, [0, 3, 4, 2, 3]);
+        assert_eq!(merged.instructions[1].accounts, [5, 6, 5, 2]);
+        assert!(merged.serialize().len() < message.serialize().len());
+        assert!(merged.sanitize().is_ok());
+
+        // out of range indexes fail without changing the message
+        let mut invalid = message.clone();
+        invalid.instructions[1].accounts.push(10);
+        assert_eq!(
+            invalid.merge_address_table_lookups(),
+            Err(SanitizeError::IndexOutOfBounds)
+        );
+        assert_eq!(invalid.address_table_lookups, message.address_table_lookups);
+    }
 }
@@ -0,0 +1,354 @@
+//! An experimental message format with `u16` account indexes.
+//!
+//! Instructions of [`v0`] and legacy messages index account keys with a
+//! `u8`, which limits a message to 256 keys. A v1 message indexes them with a
+//! `u16`, so protocol changes such as larger transactions can be prototyped
+//! against these types. Counts, including the number of instructions, are
+//! short-vec encoded `u16`s as in earlier versions.
+//!
+//! No cluster accepts v1 messages, and [`VersionedMessage`] does not include
+//! them. The format may change in any release, which is why it is only
+//! available with the `experimental-v1` feature.
+//!
+//! [`v0`]: crate::v0
+//! [`VersionedMessage`]: crate::VersionedMessage
+//!
+//! # Serialization
+//!
+//! A v1 message is serialized like a legacy message, after the version
+//! prefix `0x81`, except that each instruction's program id index and
+//! account indexes are little-endian `u16`s.
+use {
+    crate::{
+        compiled_keys::{CompileError, CompiledKeys},
+        short_vec_len, wire, MessageHeader, WireError, MESSAGE_HEADER_LENGTH,
+        MESSAGE_VERSION_PREFIX,
+    },
+    solana_hash::{Hash, HASH_BYTES},
+    solana_instruction::Instruction,
+    solana_pubkey::{Pubkey, PUBKEY_BYTES},
+    solana_sanitize::{Sanitize, SanitizeError},
+    solana_sdk_ids::bpf_loader_upgradeable,
+    std::collections::{HashMap, HashSet},
+};
+
+/// The version of this message format, serialized after
+/// [`MESSAGE_VERSION_PREFIX`].
+pub const MESSAGE_VERSION: u8 = 1;
+
+/// An instruction of a v1 message, like
+/// [`CompiledInstruction`](crate::compiled_instruction::CompiledInstruction)
+/// with `u16` indexes.
+#[derive(Default, Debug, PartialEq, Eq, Clone)]
+pub struct CompiledInstruction {
+    /// Index into the message's account keys of the program that executes
+    /// this instruction
+    pub program_id_index: u16,
+    /// Indexes into the message's account keys of the accounts to pass to
+    /// the program
+    pub accounts: Vec<u16>,
+    /// The program input data
+    pub data: Vec<u8>,
+}
+
+impl CompiledInstruction {
+    fn serialized_size(&self) -> usize {
+        2usize
+            .saturating_add(short_vec_len(self.accounts.len()))
+            .saturating_add(self.accounts.len().saturating_mul(2))
+            .saturating_add(short_vec_len(self.data.len()))
+            .saturating_add(self.data.len())
+    }
+}
+
+/// An experimental transaction message (v1) with `u16` account indexes.
+///
+/// See the [module documentation](self) for the format.
+#[derive(Default, Debug, PartialEq, Eq, Clone)]
+pub struct Message {
+    /// The message header, identifying signed and read-only `account_keys`.
+    pub header: MessageHeader,
+    /// All the account keys used by this transaction.
+    pub account_keys: Vec<Pubkey>,
+    /// The id of a recent ledger entry.
+    pub recent_blockhash: Hash,
+    /// Programs that will be executed in sequence and committed in one atomic
+    /// transaction if all succeed.
+    pub instructions: Vec<CompiledInstruction>,
+}
+
+impl Sanitize for Message {
+    fn sanitize(&self) -> Result<(), SanitizeError> {
+        let num_account_keys = self.account_keys.len();
+        // signing area and read-only non-signing area should not overlap
+        if usize::from(self.header.num_required_signatures)
+            .saturating_add(usize::from(self.header.num_readonly_unsigned_accounts))
+            > num_account_keys
+        {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+        // there should be at least 1 RW fee-payer account.
+        if self.header.num_readonly_signed_accounts >= self.header.num_required_signatures {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+        for ix in &self.instructions {
+            // a program cannot be the payer
+            if ix.program_id_index == 0 || usize::from(ix.program_id_index) >= num_account_keys {
+                return Err(SanitizeError::IndexOutOfBounds);
+            }
+            if ix
+                .accounts
+                .iter()
+                .any(|index| usize::from(*index) >= num_account_keys)
+            {
+                return Err(SanitizeError::IndexOutOfBounds);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Message {
+    /// Compile `instructions` into a message with `payer` as the fee payer,
+    /// ordering the account keys like [`legacy::Message::new`].
+    ///
+    /// Fails if the instructions use more keys than a `u16` can index, or
+    /// more than 255 keys in a section of the header.
+    ///
+    /// [`legacy::Message::new`]: crate::legacy::Message::new
+    ///
+    /// ```
+    /// # use solana_hash::Hash;
+    /// # use solana_instruction::{AccountMeta, Instruction};
+    /// # use solana_message::v1;
+    /// # use solana_pubkey::Pubkey;
+    /// let payer = Pubkey::new_unique();
+    /// // more writable accounts than a legacy or v0 message can index
+    /// let accounts = (0..300)
+    ///     .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+    ///     .collect();
+    /// let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], accounts);
+    /// let message = v1::Message::try_compile(&payer, &[instruction], Hash::default())?;
+    /// assert_eq!(message.account_keys.len(), 302);
+    /// assert_eq!(v1::Message::deserialize(&message.serialize()), Ok(message));
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn try_compile(
+        payer: &Pubkey,
+        instructions: &[Instruction],
+        recent_blockhash: Hash,
+    ) -> Result<Self, CompileError> {
+        let (header, account_keys) =
+            CompiledKeys::compile(instructions, Some(*payer)).try_into_message_components()?;
+        let indexes = account_keys
+            .iter()
+            .enumerate()
+            .map(|(index, key)| {
+                u16::try_from(index)
+                    .map(|index| (*key, index))
+                    .map_err(|_| CompileError::AccountIndexOverflow)
+            })
+            .collect::<Result<HashMap<_, _>, _>>()?;
+        let index_of = |key: &Pubkey| {
+            indexes
+                .get(key)
+                .copied()
+                .ok_or(CompileError::UnknownInstructionKey(*key))
+        };
+        let instructions = instructions
+            .iter()
+            .map(|ix| {
+                Ok(CompiledInstruction {
+                    program_id_index: index_of(&ix.program_id)?,
+                    accounts: ix
+                        .accounts
+                        .iter()
+                        .map(|meta| index_of(&meta.pubkey))
+                        .collect::<Result<_, _>>()?,
+                    data: ix.data.clone(),
+                })
+            })
+            .collect::<Result<_, CompileError>>()?;
+        Ok(Self {
+            header,
+            account_keys,
+            recent_blockhash,
+            instructions,
+        })
+    }
+
+    /// Serialize this message, with its version prefix.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.serialized_size());
+        bytes.push(MESSAGE_VERSION_PREFIX | MESSAGE_VERSION);
+        bytes.extend_from_slice(&[
+            self.header.num_required_signatures,
+            self.header.num_readonly_signed_accounts,
+            self.header.num_readonly_unsigned_accounts,
+        ]);
+        write_len(&mut bytes, self.account_keys.len());
+        for key in &self.account_keys {
+            bytes.extend_from_slice(key.as_ref());
+        }
+        bytes.extend_from_slice(self.recent_blockhash.as_ref());
+        write_len(&mut bytes, self.instructions.len());
+        for ix in &self.instructions {
+            bytes.extend_from_slice(&ix.program_id_index.to_le_bytes());
+            write_len(&mut bytes, ix.accounts.len());
+            for index in &ix.accounts {
+                bytes.extend_from_slice(&index.to_le_bytes());
+            }
+            write_len(&mut bytes, ix.data.len());
+            bytes.extend_from_slice(&ix.data);
+        }
+        bytes
+    }
+
+    /// Deserialize a message serialized by [`Message::serialize`], like the
+    /// `deserialize_strict` methods of the other versions.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, WireError> {
+        wire::deserialize_v1(bytes)
+    }
+
+    /// Return the length of [`Message::serialize`]'s output.
+    pub fn serialized_size(&self) -> usize {
+        1usize
+            .saturating_add(MESSAGE_HEADER_LENGTH)
+            .saturating_add(short_vec_len(self.account_keys.len()))
+            .saturating_add(self.account_keys.len().saturating_mul(PUBKEY_BYTES))
+            .saturating_add(HASH_BYTES)
+            .saturating_add(short_vec_len(self.instructions.len()))
+            .saturating_add(
+                self.instructions
+                    .iter()
+                    .map(CompiledInstruction::serialized_size)
+                    .fold(0, usize::saturating_add),
+            )
+    }
+
+    /// Returns true if the account at `index` was requested to be writable.
+    fn is_writable_index(&self, index: usize) -> bool {
+        let num_signed_accounts = usize::from(self.header.num_required_signatures);
+        if index < num_signed_accounts {
+            index
+                < num_signed_accounts
+                    .saturating_sub(usize::from(self.header.num_readonly_signed_accounts))
+        } else {
+            index
+                < self
+                    .account_keys
+                    .len()
+                    .saturating_sub(usize::from(self.header.num_readonly_unsigned_accounts))
+        }
+    }
+
+    /// Returns true if the account at `index` is writable by the instructions
+    /// in this message, like [`legacy::Message::is_maybe_writable`].
+    ///
+    /// [`legacy::Message::is_maybe_writable`]: crate::legacy::Message::is_maybe_writable
+    pub fn is_maybe_writable(
+        &self,
+        index: usize,
+        reserved_account_keys: Option<&HashSet<Pubkey>>,
+    ) -> bool {
+        let Some(key) = self.account_keys.get(index) else {
+            return false;
+        };
+        let is_reserved = reserved_account_keys.is_some_and(|reserved| reserved.contains(key));
+        // demote program ids
+        let is_demoted_program_id = self
+            .instructions
+            .iter()
+            .any(|ix| usize::from(ix.program_id_index) == index)
+            && !self.account_keys.contains(&bpf_loader_upgradeable::id());
+        self.is_writable_index(index) && !is_reserved && !is_demoted_program_id
+    }
+}
+
+fn write_len(bytes: &mut Vec<u8>, len: usize) {
+    let mut rem = len;
+    loop {
+        let byte = (rem & 0x7f) as u8;
+        rem >>= 7;
+        if rem == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{MessageAccounts, WireErrorKind, WireField},
+        solana_instruction::AccountMeta,
+    };
+
+    #[test]
+    fn test_v1_message() {
+        let payer = Pubkey::new_unique();
+        let program_id = Pubkey::new_unique();
+        let signer = Pubkey::new_unique();
+        let mut accounts: Vec<_> = (0..299)
+            .map(|_| AccountMeta::new(Pubkey::new_unique(), false))
+            .collect();
+        accounts.push(AccountMeta::new_readonly(signer, true));
+        let instruction = Instruction::new_with_bytes(program_id, &[1, 2, 3], accounts.clone());
+        let message = Message::try_compile(&payer, &[instruction], Hash::new_unique()).unwrap();
+        assert_eq!(
+            message.header,
+            MessageHeader {
+                num_required_signatures: 2,
+                num_readonly_signed_accounts: 1,
+                num_readonly_unsigned_accounts: 1,
+            }
+        );
+        assert_eq!(message.sanitize(), Ok(()));
+        let ix = &message.instructions[0];
+        assert_eq!(ix.program_id_index, 301);
+        assert_eq!(
+            message.account_keys[usize::from(ix.accounts[298])],
+            accounts[298].pubkey
+        );
+        assert_eq!(ix.accounts[299], 1);
+
+        // the roles of the shared accounts trait
+        assert_eq!(message.fee_payer(), Some(&payer));
+        assert_eq!(message.signer_account_keys(), [payer, signer]);
+        assert!(MessageAccounts::is_maybe_writable(&message, 300, None));
+        assert!(!MessageAccounts::is_maybe_writable(&message, 1, None));
+        assert!(!MessageAccounts::is_maybe_writable(&message, 301, None));
+        assert!(!MessageAccounts::is_maybe_writable(
+            &message,
+            0,
+            Some(&HashSet::from([payer]))
+        ));
+
+        let bytes = message.serialize();
+        assert_eq!(bytes.len(), message.serialized_size());
+        assert_eq!(bytes[0], 0x81);
+        assert_eq!(Message::deserialize(&bytes), Ok(message.clone()));
+        for len in 0..bytes.len() {
+            assert!(Message::deserialize(&bytes[..len]).is_err());
+        }
+        let mut v0_bytes = bytes.clone();
+        v0_bytes[0] = MESSAGE_VERSION_PREFIX;
+        assert_eq!(
+            Message::deserialize(&v0_bytes),
+            Err(WireError {
+                offset: 0,
+                field: WireField::VersionPrefix,
+                kind: WireErrorKind::UnsupportedVersion(0),
+            })
+        );
+
+        let mut invalid = message;
+        invalid.instructions[0].accounts.push(302);
+        assert_eq!(invalid.sanitize(), Err(SanitizeError::IndexOutOfBounds));
+        invalid.instructions[0].accounts.pop();
+        invalid.instructions[0].program_id_index = 0;
+        assert_eq!(invalid.sanitize(), Err(SanitizeError::IndexOutOfBounds));
+    }
+}
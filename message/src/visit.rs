@@ -0,0 +1,131 @@
+//! Borrowed views of the instructions of a message, with their accounts
+//! resolved to keys and roles.
+use {
+    crate::{compiled_instruction::CompiledInstruction, legacy::Message},
+    solana_pubkey::Pubkey,
+    solana_sanitize::SanitizeError,
+    std::slice,
+};
+
+/// An account of an instruction, resolved against the account keys of a
+/// message, like the `AccountMeta` [`Message::decompile_instruction`] returns
+/// but borrowing its key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedAccountMeta<'a> {
+    pub pubkey: &'a Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// An iterator over the accounts of an instruction, passed to the visitor of
+/// [`Message::visit_instructions`].
+#[derive(Debug, Clone)]
+pub struct ResolvedAccountMetas<'a> {
+    message: &'a Message,
+    indexes: slice::Iter<'a, u8>,
+}
+
+impl<'a> Iterator for ResolvedAccountMetas<'a> {
+    type Item = ResolvedAccountMeta<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = usize::from(*self.indexes.next()?);
+        Some(ResolvedAccountMeta {
+            // checked by `visit_instructions`
+            pubkey: &self.message.account_keys[index],
+            is_signer: self.message.is_signer(index),
+            is_writable: self.message.is_writable_index(index),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indexes.size_hint()
+    }
+}
+
+impl ExactSizeIterator for ResolvedAccountMetas<'_> {}
+
+pub(crate) fn visit_instructions<'a>(
+    message: &'a Message,
+    mut visit: impl FnMut(&'a Pubkey, &'a [u8], ResolvedAccountMetas<'a>),
+) -> Result<(), SanitizeError> {
+    let num_keys = message.account_keys.len();
+    for CompiledInstruction {
+        program_id_index,
+        accounts,
+        data,
+    } in &message.instructions
+    {
+        let program_id = message
+            .account_keys
+            .get(usize::from(*program_id_index))
+            .ok_or(SanitizeError::IndexOutOfBounds)?;
+        if accounts.iter().any(|index| usize::from(*index) >= num_keys) {
+            return Err(SanitizeError::IndexOutOfBounds);
+        }
+        visit(
+            program_id,
+            data,
+            ResolvedAccountMetas {
+                message,
+                indexes: accounts.iter(),
+            },
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        solana_instruction::{AccountMeta, Instruction},
+    };
+
+    #[test]
+    fn test_visit_instructions() {
+        let payer = Pubkey::new_unique();
+        let instructions = [
+            Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[1, 2],
+                vec![
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new_readonly(Pubkey::new_unique(), true),
+                    AccountMeta::new(Pubkey::new_unique(), false),
+                ],
+            ),
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]),
+        ];
+        let message = Message::new(&instructions, Some(&payer));
+
+        let mut visited = vec![];
+        message
+            .visit_instructions(|program_id, data, accounts| {
+                assert_eq!(accounts.len(), accounts.clone().count());
+                visited.push(Instruction {
+                    program_id: *program_id,
+                    accounts: accounts
+                        .map(|meta| AccountMeta {
+                            pubkey: *meta.pubkey,
+                            is_signer: meta.is_signer,
+                            is_writable: meta.is_writable,
+                        })
+                        .collect(),
+                    data: data.to_vec(),
+                });
+            })
+            .unwrap();
+        assert_eq!(visited, instructions);
+
+        // instructions before an invalid one are visited
+        let mut invalid = message.clone();
+        invalid.instructions[1].accounts.push(9);
+        let mut num_visited = 0;
+        assert_eq!(
+            invalid.visit_instructions(|_, _, _| num_visited += 1),
+            Err(SanitizeError::IndexOutOfBounds)
+        );
+        assert_eq!(num_visited, 1);
+    }
+}
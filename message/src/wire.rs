@@ -0,0 +1,484 @@
+//! A strict parser for the wire format of messages, for input that cannot be
+//! trusted.
+//!
+//! Unlike bincode deserialization, parsing rejects trailing bytes, checks
+//! every length prefix against the rest of the input before allocating, and
+//! reports the offset and field of the first error.
+#[cfg(all(not(target_os = "solana"), feature = "experimental-v1"))]
+use crate::v1;
+#[cfg(not(target_os = "solana"))]
+use crate::{
+    v0::{self, MessageAddressTableLookup},
+    VersionedMessage, MESSAGE_VERSION_PREFIX,
+};
+use {
+    crate::{compiled_instruction::CompiledInstruction, legacy::Message, MessageHeader},
+    core::fmt,
+    solana_hash::{Hash, HASH_BYTES},
+    solana_pubkey::{Pubkey, PUBKEY_BYTES},
+};
+
+/// The part of a serialized message that a [`WireError`] is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireField {
+    /// The version prefix of a versioned message
+    VersionPrefix,
+    Header,
+    AccountKeys,
+    RecentBlockhash,
+    Instructions,
+    ProgramIdIndex {
+        instruction: usize,
+    },
+    InstructionAccounts {
+        instruction: usize,
+    },
+    InstructionData {
+        instruction: usize,
+    },
+    AddressTableLookups,
+    LookupTableKey {
+        lookup: usize,
+    },
+    WritableIndexes {
+        lookup: usize,
+    },
+    ReadonlyIndexes {
+        lookup: usize,
+    },
+    /// After the last field of the message, where trailing bytes are
+    End,
+}
+
+/// What is wrong with the field of a [`WireError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WireErrorKind {
+    /// The input ends in the field
+    UnexpectedEnd,
+    /// The length prefix is not a canonical short-vec encoding of a `u16`
+    InvalidLength,
+    /// The length prefix declares this many elements, more than the rest of
+    /// the input can hold
+    LengthExceedsInput(usize),
+    /// The message is versioned, with this version, which is not supported
+    UnsupportedVersion(u8),
+    /// A legacy message was expected, but the first byte has the version
+    /// prefix bit set
+    VersionedMessage,
+    /// The input has this many bytes after the message
+    TrailingBytes(usize),
+}
+
+/// The error returned by the `deserialize_strict` methods of messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WireError {
+    /// The offset in the input of the start of `field`, or of the trailing
+    /// bytes
+    pub offset: usize,
+    pub field: WireField,
+    pub kind: WireErrorKind,
+}
+
+impl core::error::Error for WireError {}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let offset = self.offset;
+        let field = self.field;
+        match self.kind {
+            WireErrorKind::UnexpectedEnd => {
+                f.write_fmt(format_args!("input ends in {field:?} at offset {offset}"))
+            }
+            WireErrorKind::InvalidLength => f.write_fmt(format_args!(
+                "invalid length prefix of {field:?} at offset {offset}"
+            )),
+            WireErrorKind::LengthExceedsInput(len) => f.write_fmt(format_args!(
+                "length {len} of {field:?} at offset {offset} exceeds the input"
+            )),
+            WireErrorKind::UnsupportedVersion(version) => {
+                f.write_fmt(format_args!("unsupported message version {version}"))
+            }
+            WireErrorKind::VersionedMessage => {
+                f.write_str("expected a legacy message, found a versioned message")
+            }
+            WireErrorKind::TrailingBytes(len) => f.write_fmt(format_args!(
+                "found {len} trailing bytes at offset {offset}"
+            )),
+        }
+    }
+}
+
+struct Reader<'a> {
+    input: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error(&self, offset: usize, field: WireField, kind: WireErrorKind) -> WireError {
+        WireError {
+            offset,
+            field,
+            kind,
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.input.len().saturating_sub(self.offset)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.offset).copied()
+    }
+
+    fn read_bytes(&mut self, len: usize, field: WireField) -> Result<&'a [u8], WireError> {
+        let end = self.offset.saturating_add(len);
+        let bytes = self
+            .input
+            .get(self.offset..end)
+            .ok_or_else(|| self.error(self.offset, field, WireErrorKind::UnexpectedEnd))?;
+        self.offset = end;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self, field: WireField) -> Result<u8, WireError> {
+        self.read_bytes(1, field).map(|bytes| bytes[0])
+    }
+
+    #[cfg(all(not(target_os = "solana"), feature = "experimental-v1"))]
+    fn read_u16(&mut self, field: WireField) -> Result<u16, WireError> {
+        self.read_bytes(2, field)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    /// Read a short-vec length prefix of elements of at least
+    /// `min_element_size` bytes, failing if the rest of the input cannot
+    /// hold that many.
+    fn read_len(&mut self, min_element_size: usize, field: WireField) -> Result<usize, WireError> {
+        let start = self.offset;
+        let mut len = 0usize;
+        for shift in [0, 7, 14] {
+            let byte = self.read_u8(field)?;
+            len |= usize::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                // a final zero byte after the first is an alias of a shorter
+                // encoding
+                if (byte == 0 && shift > 0) || len > usize::from(u16::MAX) {
+                    return Err(self.error(start, field, WireErrorKind::InvalidLength));
+                }
+                if len.saturating_mul(min_element_size) > self.remaining() {
+                    return Err(self.error(start, field, WireErrorKind::LengthExceedsInput(len)));
+                }
+                return Ok(len);
+            }
+        }
+        Err(self.error(start, field, WireErrorKind::InvalidLength))
+    }
+
+    fn read_vec(&mut self, field: WireField) -> Result<Vec<u8>, WireError> {
+        let len = self.read_len(1, field)?;
+        self.read_bytes(len, field).map(<[u8]>::to_vec)
+    }
+
+    fn read_pubkey(&mut self, field: WireField) -> Result<Pubkey, WireError> {
+        let bytes = self.read_bytes(PUBKEY_BYTES, field)?;
+        Ok(Pubkey::new_from_array(bytes.try_into().unwrap()))
+    }
+
+    fn read_header(&mut self) -> Result<MessageHeader, WireError> {
+        let bytes = self.read_bytes(3, WireField::Header)?;
+        Ok(MessageHeader {
+            num_required_signatures: bytes[0],
+            num_readonly_signed_accounts: bytes[1],
+            num_readonly_unsigned_accounts: bytes[2],
+        })
+    }
+
+    fn read_account_keys(&mut self) -> Result<Vec<Pubkey>, WireError> {
+        let len = self.read_len(PUBKEY_BYTES, WireField::AccountKeys)?;
+        (0..len)
+            .map(|_| self.read_pubkey(WireField::AccountKeys))
+            .collect()
+    }
+
+    fn read_recent_blockhash(&mut self) -> Result<Hash, WireError> {
+        let bytes = self.read_bytes(HASH_BYTES, WireField::RecentBlockhash)?;
+        Ok(Hash::new_from_array(bytes.try_into().unwrap()))
+    }
+
+    fn read_instructions(&mut self) -> Result<Vec<CompiledInstruction>, WireError> {
+        // a program id index and two empty length prefixes
+        let len = self.read_len(3, WireField::Instructions)?;
+        (0..len)
+            .map(|instruction| {
+                Ok(CompiledInstruction {
+                    program_id_index: self.read_u8(WireField::ProgramIdIndex { instruction })?,
+                    accounts: self.read_vec(WireField::InstructionAccounts { instruction })?,
+                    data: self.read_vec(WireField::InstructionData { instruction })?,
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(all(not(target_os = "solana"), feature = "experimental-v1"))]
+    fn read_v1_instructions(&mut self) -> Result<Vec<v1::CompiledInstruction>, WireError> {
+        // a program id index and two empty length prefixes
+        let len = self.read_len(4, WireField::Instructions)?;
+        (0..len)
+            .map(|instruction| {
+                let program_id_index = self.read_u16(WireField::ProgramIdIndex { instruction })?;
+                let field = WireField::InstructionAccounts { instruction };
+                let num_accounts = self.read_len(2, field)?;
+                Ok(v1::CompiledInstruction {
+                    program_id_index,
+                    accounts: (0..num_accounts)
+                        .map(|_| self.read_u16(field))
+                        .collect::<Result<_, _>>()?,
+                    data: self.read_vec(WireField::InstructionData { instruction })?,
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(not(target_os = "solana"))]
+    fn read_address_table_lookups(&mut self) -> Result<Vec<MessageAddressTableLookup>, WireError> {
+        // a key and two empty length prefixes
+        let len = self.read_len(
+            PUBKEY_BYTES.saturating_add(2),
+            WireField::AddressTableLookups,
+        )?;
+        (0..len)
+            .map(|lookup| {
+                Ok(MessageAddressTableLookup {
+                    account_key: self.read_pubkey(WireField::LookupTableKey { lookup })?,
+                    writable_indexes: self.read_vec(WireField::WritableIndexes { lookup })?,
+                    readonly_indexes: self.read_vec(WireField::ReadonlyIndexes { lookup })?,
+                })
+            })
+            .collect()
+    }
+
+    fn read_legacy_message(&mut self) -> Result<Message, WireError> {
+        Ok(Message {
+            header: self.read_header()?,
+            account_keys: self.read_account_keys()?,
+            recent_blockhash: self.read_recent_blockhash()?,
+            instructions: self.read_instructions()?,
+        })
+    }
+
+    fn finish<T>(self, message: T) -> Result<T, WireError> {
+        match self.remaining() {
+            0 => Ok(message),
+            len => Err(self.error(
+                self.offset,
+                WireField::End,
+                WireErrorKind::TrailingBytes(len),
+            )),
+        }
+    }
+}
+
+pub(crate) fn deserialize_legacy(input: &[u8]) -> Result<Message, WireError> {
+    let mut reader = Reader { input, offset: 0 };
+    if reader.peek().is_some_and(|byte| byte & 0x80 != 0) {
+        return Err(reader.error(0, WireField::Header, WireErrorKind::VersionedMessage));
+    }
+    let message = reader.read_legacy_message()?;
+    reader.finish(message)
+}
+
+#[cfg(not(target_os = "solana"))]
+pub(crate) fn deserialize_versioned(input: &[u8]) -> Result<VersionedMessage, WireError> {
+    let mut reader = Reader { input, offset: 0 };
+    let prefix = reader.peek().unwrap_or_default();
+    if prefix & MESSAGE_VERSION_PREFIX == 0 {
+        return deserialize_legacy(input).map(VersionedMessage::Legacy);
+    }
+    reader.read_u8(WireField::VersionPrefix)?;
+    let version = prefix & !MESSAGE_VERSION_PREFIX;
+    if version != 0 {
+        return Err(reader.error(
+            0,
+            WireField::VersionPrefix,
+            WireErrorKind::UnsupportedVersion(version),
+        ));
+    }
+    let message = v0::Message {
+        header: reader.read_header()?,
+        account_keys: reader.read_account_keys()?,
+        recent_blockhash: reader.read_recent_blockhash()?,
+        instructions: reader.read_instructions()?,
+        address_table_lookups: reader.read_address_table_lookups()?,
+    };
+    reader.finish(VersionedMessage::V0(message))
+}
+
+#[cfg(all(not(target_os = "solana"), feature = "experimental-v1"))]
+pub(crate) fn deserialize_v1(input: &[u8]) -> Result<v1::Message, WireError> {
+    let mut reader = Reader { input, offset: 0 };
+    let prefix = reader.read_u8(WireField::VersionPrefix)?;
+    if prefix != MESSAGE_VERSION_PREFIX | v1::MESSAGE_VERSION {
+        return Err(reader.error(
+            0,
+            WireField::VersionPrefix,
+            WireErrorKind::UnsupportedVersion(prefix & !MESSAGE_VERSION_PREFIX),
+        ));
+    }
+    let message = v1::Message {
+        header: reader.read_header()?,
+        account_keys: reader.read_account_keys()?,
+        recent_blockhash: reader.read_recent_blockhash()?,
+        instructions: reader.read_v1_instructions()?,
+    };
+    reader.finish(message)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::AddressLookupTableAccount,
+        solana_instruction::{AccountMeta, Instruction},
+    };
+
+    fn error(offset: usize, field: WireField, kind: WireErrorKind) -> WireError {
+        WireError {
+            offset,
+            field,
+            kind,
+        }
+    }
+
+    #[test]
+    fn test_deserialize_strict() {
+        let payer = Pubkey::new_unique();
+        let loaded_key = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(
+            Pubkey::new_unique(),
+            &[7; 200],
+            vec![AccountMeta::new(loaded_key, false)],
+        );
+        let message =
+            Message::new_with_blockhash(&[instruction.clone()], Some(&payer), &Hash::new_unique());
+        let bytes = message.serialize();
+        assert_eq!(Message::deserialize_strict(&bytes), Ok(message.clone()));
+        let versioned = VersionedMessage::Legacy(message);
+        assert_eq!(
+            VersionedMessage::deserialize_strict(&bytes),
+            Ok(versioned.clone())
+        );
+
+        let v0_message = VersionedMessage::V0(
+            v0::Message::try_compile(
+                &payer,
+                &[instruction],
+                &[AddressLookupTableAccount {
+                    key: Pubkey::new_unique(),
+                    addresses: vec![loaded_key],
+                }],
+                Hash::new_unique(),
+            )
+            .unwrap(),
+        );
+        let v0_bytes = v0_message.serialize();
+        assert_eq!(
+            VersionedMessage::deserialize_strict(&v0_bytes),
+            Ok(v0_message)
+        );
+        assert_eq!(
+            Message::deserialize_strict(&v0_bytes),
+            Err(error(0, WireField::Header, WireErrorKind::VersionedMessage))
+        );
+
+        // every truncation fails, where bincode would also fail
+        for len in 0..bytes.len() {
+            assert!(Message::deserialize_strict(&bytes[..len]).is_err());
+        }
+        for len in 0..v0_bytes.len() {
+            assert!(VersionedMessage::deserialize_strict(&v0_bytes[..len]).is_err());
+        }
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert_eq!(
+            Message::deserialize_strict(&trailing),
+            Err(error(
+                bytes.len(),
+                WireField::End,
+                WireErrorKind::TrailingBytes(1)
+            ))
+        );
+        let mut v0_trailing = v0_bytes.clone();
+        v0_trailing.extend([0, 0]);
+        assert_eq!(
+            VersionedMessage::deserialize_strict(&v0_trailing),
+            Err(error(
+                v0_bytes.len(),
+                WireField::End,
+                WireErrorKind::TrailingBytes(2)
+            ))
+        );
+
+        // header, then a key count of 3 with only 2 keys
+        let mut short = bytes[..3 + 1 + 2 * PUBKEY_BYTES].to_vec();
+        short[3] = 3;
+        assert_eq!(
+            Message::deserialize_strict(&short),
+            Err(error(
+                3,
+                WireField::AccountKeys,
+                WireErrorKind::LengthExceedsInput(3)
+            ))
+        );
+
+        // a huge length is rejected before allocating
+        let huge = [1, 0, 0, 0xff, 0xff, 0x03];
+        assert_eq!(
+            Message::deserialize_strict(&huge),
+            Err(error(
+                3,
+                WireField::AccountKeys,
+                WireErrorKind::LengthExceedsInput(0xffff)
+            ))
+        );
+        // lengths that overflow u16 or have a redundant final byte
+        for invalid in [[1, 0, 0, 0xff, 0xff, 0x04], [1, 0, 0, 0x81, 0x80, 0x00]] {
+            assert_eq!(
+                Message::deserialize_strict(&invalid),
+                Err(error(
+                    3,
+                    WireField::AccountKeys,
+                    WireErrorKind::InvalidLength
+                ))
+            );
+        }
+
+        // the instruction data length of the only instruction is too long
+        let data_len_offset = bytes.len() - 200 - 2;
+        let mut invalid_data = bytes.clone();
+        invalid_data[data_len_offset] = 0xc9;
+        assert_eq!(
+            Message::deserialize_strict(&invalid_data),
+            Err(error(
+                data_len_offset,
+                WireField::InstructionData { instruction: 0 },
+                WireErrorKind::LengthExceedsInput(201)
+            ))
+        );
+
+        let mut v1_bytes = v0_bytes.clone();
+        v1_bytes[0] = MESSAGE_VERSION_PREFIX | 1;
+        assert_eq!(
+            VersionedMessage::deserialize_strict(&v1_bytes),
+            Err(error(
+                0,
+                WireField::VersionPrefix,
+                WireErrorKind::UnsupportedVersion(1)
+            ))
+        );
+        assert_eq!(
+            VersionedMessage::deserialize_strict(&[]),
+            Err(error(0, WireField::Header, WireErrorKind::UnexpectedEnd))
+        );
+    }
+}
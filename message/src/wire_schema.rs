@@ -0,0 +1,318 @@
+//! A language-neutral description of the wire layout of messages, for
+//! generating encoders and decoders in other languages.
+use {
+    crate::MESSAGE_VERSION_PREFIX, serde_derive::Serialize, solana_hash::HASH_BYTES,
+    solana_pubkey::PUBKEY_BYTES,
+};
+
+/// The version of the layout of [`WireSchema`] itself, increased whenever its
+/// shape changes.
+pub const WIRE_SCHEMA_VERSION: u32 = 1;
+
+/// How a value is laid out on the wire.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum WireType {
+    /// A single byte
+    U8,
+    /// A fixed number of bytes, like a public key or a hash
+    Bytes { len: usize },
+    /// A compact-u16 length prefix followed by that many elements
+    ShortVec { element: Box<WireType> },
+    /// The fields of the named [`WireStruct`], in order
+    Struct { name: &'static str },
+}
+
+/// A field of a [`WireStruct`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WireStructField {
+    /// The name of the field, as in the serde representation of the Rust type
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub ty: WireType,
+}
+
+/// A type laid out as its fields one after another, without padding.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WireStruct {
+    pub name: &'static str,
+    pub fields: Vec<WireStructField>,
+}
+
+/// A version of the message format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WireMessageVersion {
+    /// The name of the version, `legacy` or `v0`
+    pub version: &'static str,
+    /// The byte before the message that identifies the version, which legacy
+    /// messages do not have
+    pub prefix: Option<u8>,
+    /// The name of the [`WireStruct`] that follows the prefix
+    pub root: &'static str,
+}
+
+/// The wire layout of legacy and v0 messages, built from the same types and
+/// constants as the serialization of this crate.
+///
+/// Serializing it, for example as JSON with `serde_json`, gives a schema
+/// that clients in other languages can generate code from. Structs list
+/// their fields in wire order, and a version's message is its prefix, if
+/// any, followed by its root struct. `shortVec` lengths are compact-u16:
+/// seven bits per byte, least significant first, with the high bit set on
+/// every byte but the last, in at most three bytes.
+///
+/// ```
+/// # use solana_message::WireSchema;
+/// let schema = serde_json::to_value(WireSchema::generate()).unwrap();
+/// assert_eq!(schema["messages"][1]["version"], "v0");
+/// assert_eq!(schema["messages"][1]["prefix"], 128);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WireSchema {
+    pub schema_version: u32,
+    pub structs: Vec<WireStruct>,
+    pub messages: Vec<WireMessageVersion>,
+}
+
+impl WireSchema {
+    /// Describe the wire layout of the message versions of this crate.
+    pub fn generate() -> Self {
+        let field = |name, ty| WireStructField { name, ty };
+        let short_vec = |element| WireType::ShortVec {
+            element: Box::new(element),
+        };
+        let structure = |name| WireType::Struct { name };
+        let pubkey = || WireType::Bytes { len: PUBKEY_BYTES };
+
+        let header = WireStruct {
+            name: "MessageHeader",
+            fields: vec![
+                field("numRequiredSignatures", WireType::U8),
+                field("numReadonlySignedAccounts", WireType::U8),
+                field("numReadonlyUnsignedAccounts", WireType::U8),
+            ],
+        };
+        let message_fields = || {
+            vec![
+                field("header", structure("MessageHeader")),
+                field("accountKeys", short_vec(pubkey())),
+                field("recentBlockhash", WireType::Bytes { len: HASH_BYTES }),
+                field("instructions", short_vec(structure("CompiledInstruction"))),
+            ]
+        };
+        let mut v0_fields = message_fields();
+        v0_fields.push(field(
+            "addressTableLookups",
+            short_vec(structure("MessageAddressTableLookup")),
+        ));
+
+        Self {
+            schema_version: WIRE_SCHEMA_VERSION,
+            structs: vec![
+                header,
+                WireStruct {
+                    name: "CompiledInstruction",
+                    fields: vec![
+                        field("programIdIndex", WireType::U8),
+                        field("accounts", short_vec(WireType::U8)),
+                        field("data", short_vec(WireType::U8)),
+                    ],
+                },
+                WireStruct {
+                    name: "MessageAddressTableLookup",
+                    fields: vec![
+                        field("accountKey", pubkey()),
+                        field("writableIndexes", short_vec(WireType::U8)),
+                        field("readonlyIndexes", short_vec(WireType::U8)),
+                    ],
+                },
+                WireStruct {
+                    name: "LegacyMessage",
+                    fields: message_fields(),
+                },
+                WireStruct {
+                    name: "V0Message",
+                    fields: v0_fields,
+                },
+            ],
+            messages: vec![
+                WireMessageVersion {
+                    version: "legacy",
+                    prefix: None,
+                    root: "LegacyMessage",
+                },
+                WireMessageVersion {
+                    version: "v0",
+                    prefix: Some(MESSAGE_VERSION_PREFIX),
+                    root: "V0Message",
+                },
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{v0, AddressLookupTableAccount, Message, VersionedMessage, MESSAGE_HEADER_LENGTH},
+        serde_json::Value,
+        solana_hash::Hash,
+        solana_instruction::{AccountMeta, Instruction},
+        solana_pubkey::Pubkey,
+        solana_short_vec::encode_shortu16_len,
+        std::collections::HashSet,
+    };
+
+    /// Encode `value`, the serde representation of a message or one of its
+    /// parts, as `ty` is laid out by `schema`, like a generated encoder would.
+    fn encode(schema: &WireSchema, ty: &WireType, value: &Value, bytes: &mut Vec<u8>) {
+        match ty {
+            WireType::U8 => bytes.push(u8::try_from(value.as_u64().unwrap()).unwrap()),
+            WireType::Bytes { len } => {
+                let array = value.as_array().unwrap();
+                assert_eq!(array.len(), *len);
+                for byte in array {
+                    encode(schema, &WireType::U8, byte, bytes);
+                }
+            }
+            WireType::ShortVec { element } => {
+                // serde represents the length prefix as the first element
+                let (serde_len, items) = value.as_array().unwrap().split_first().unwrap();
+                let (len, len_size) = encode_shortu16_len(u16::try_from(items.len()).unwrap());
+                let len = &len[..len_size];
+                assert_eq!(serde_json::to_value(len).unwrap(), *serde_len);
+                bytes.extend_from_slice(len);
+                for item in items {
+                    encode(schema, element, item, bytes);
+                }
+            }
+            WireType::Struct { name } => {
+                let fields = &schema
+                    .structs
+                    .iter()
+                    .find(|structure| structure.name == *name)
+                    .unwrap()
+                    .fields;
+                let object = value.as_object().unwrap();
+                assert_eq!(object.len(), fields.len());
+                for field in fields {
+                    encode(schema, &field.ty, &object[field.name], bytes);
+                }
+            }
+        }
+    }
+
+    fn encode_message(schema: &WireSchema, version: &str, value: &Value) -> Vec<u8> {
+        let version = schema
+            .messages
+            .iter()
+            .find(|message| message.version == version)
+            .unwrap();
+        let mut bytes = version.prefix.into_iter().collect();
+        encode(
+            schema,
+            &WireType::Struct { name: version.root },
+            value,
+            &mut bytes,
+        );
+        bytes
+    }
+
+    #[test]
+    fn test_wire_schema() {
+        let schema = WireSchema::generate();
+        let payer = Pubkey::new_unique();
+        let loaded = Pubkey::new_unique();
+        let accounts = (0..130)
+            .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+            .chain([AccountMeta::new(loaded, false)])
+            .collect();
+        let instructions = [
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[7; 200], accounts),
+            Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]),
+        ];
+        let blockhash = Hash::new_unique();
+
+        let legacy = Message::new_with_blockhash(&instructions, Some(&payer), &blockhash);
+        let value = serde_json::to_value(&legacy).unwrap();
+        assert_eq!(
+            encode_message(&schema, "legacy", &value),
+            legacy.serialize()
+        );
+
+        let lookup_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![Pubkey::new_unique(), loaded],
+        };
+        let v0 =
+            v0::Message::try_compile(&payer, &instructions, &[lookup_table], blockhash).unwrap();
+        assert_eq!(v0.address_table_lookups[0].writable_indexes, [1]);
+        let value = serde_json::to_value(&v0).unwrap();
+        assert_eq!(encode_message(&schema, "v0", &value), v0.serialize());
+
+        assert_eq!(schema.structs[0].fields.len(), MESSAGE_HEADER_LENGTH);
+        let json = serde_json::to_value(&schema).unwrap();
+        assert_eq!(json["schemaVersion"], WIRE_SCHEMA_VERSION);
+        assert_eq!(
+            json["structs"][3]["fields"][1],
+            serde_json::json!({
+                "name": "accountKeys",
+                "type": {"kind": "shortVec", "element": {"kind": "bytes", "len": 32}},
+            })
+        );
+        assert_eq!(json["messages"][0]["prefix"], Value::Null);
+    }
+
+    #[test]
+    fn test_wire_schema_edge_cases() {
+        let schema = WireSchema::generate();
+
+        // empty messages and vectors
+        let legacy = Message::default();
+        let value = serde_json::to_value(&legacy).unwrap();
+        assert_eq!(
+            encode_message(&schema, "legacy", &value),
+            legacy.serialize()
+        );
+        let v0 = v0::Message::default();
+        let value = serde_json::to_value(&v0).unwrap();
+        assert_eq!(encode_message(&schema, "v0", &value), v0.serialize());
+
+        // lengths of 0x4000 and more take three bytes
+        let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[7; 0x4000], vec![]);
+        let legacy = Message::new(&[instruction], Some(&Pubkey::new_unique()));
+        let value = serde_json::to_value(&legacy).unwrap();
+        let bytes = encode_message(&schema, "legacy", &value);
+        assert_eq!(bytes, legacy.serialize());
+        let data_len_offset = bytes.len() - 0x4000 - 3;
+        assert_eq!(bytes[data_len_offset..][..3], [0x80, 0x80, 0x01]);
+
+        // the prefix is what tells a v0 message from a legacy one
+        let v0_version = &schema.messages[1];
+        assert_eq!(v0_version.prefix, Some(MESSAGE_VERSION_PREFIX));
+        assert_eq!(
+            VersionedMessage::V0(v0).serialize()[0],
+            v0_version.prefix.unwrap()
+        );
+
+        // every struct is named once, and every named struct is described
+        let names: HashSet<_> = schema.structs.iter().map(|s| s.name).collect();
+        assert_eq!(names.len(), schema.structs.len());
+        let mut referenced: Vec<_> = schema.messages.iter().map(|m| m.root).collect();
+        for field in schema.structs.iter().flat_map(|s| &s.fields) {
+            let mut ty = &field.ty;
+            while let WireType::ShortVec { element } = ty {
+                ty = element;
+            }
+            if let WireType::Struct { name } = ty {
+                referenced.push(name);
+            }
+        }
+        assert!(referenced.iter().all(|name| names.contains(name)));
+    }
+}
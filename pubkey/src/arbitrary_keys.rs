@@ -0,0 +1,133 @@
+//! [`Arbitrary`] generators for specific classes of [`Pubkey`].
+//!
+//! A plain `Pubkey` generated by `Arbitrary` lands on the ed25519 curve about
+//! half of the time. Fuzzers that exercise program logic often need to target
+//! one class deliberately, e.g. signer-like keys or program derived addresses.
+use {
+    crate::{bytes_are_curve_point, Pubkey, MAX_SEEDS, MAX_SEED_LEN},
+    arbitrary::{Arbitrary, Result, Unstructured},
+    curve25519_dalek::{edwards::EdwardsPoint, scalar::Scalar},
+    std::vec::Vec,
+};
+
+/// A `Pubkey` that is a valid ed25519 point, like a wallet address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OnCurvePubkey(pub Pubkey);
+
+impl OnCurvePubkey {
+    /// Derive the public key for the given secret scalar bytes.
+    pub fn from_scalar_bytes(bytes: [u8; 32]) -> Self {
+        let point = EdwardsPoint::mul_base(&Scalar::from_bytes_mod_order(bytes));
+        Self(Pubkey::from(point.compress().to_bytes()))
+    }
+}
+
+impl<'a> Arbitrary<'a> for OnCurvePubkey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from_scalar_bytes(u.arbitrary()?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <[u8; 32]>::size_hint(depth)
+    }
+}
+
+/// A `Pubkey` that is not a valid ed25519 point, and thus has no
+/// corresponding secret key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OffCurvePubkey(pub Pubkey);
+
+impl OffCurvePubkey {
+    /// Rehash the given bytes until they no longer lie on the curve.
+    pub fn from_bytes(mut bytes: [u8; 32]) -> Self {
+        while bytes_are_curve_point(bytes) {
+            bytes = solana_sha256_hasher::hash(&bytes).to_bytes();
+        }
+        Self(Pubkey::from(bytes))
+    }
+}
+
+impl<'a> Arbitrary<'a> for OffCurvePubkey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self::from_bytes(u.arbitrary()?))
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        <[u8; 32]>::size_hint(depth)
+    }
+}
+
+/// A valid program derived address along with the inputs that derive it.
+///
+/// `seeds` respects [`MAX_SEEDS`] (leaving room for the bump seed) and
+/// [`MAX_SEED_LEN`], and `bump` is the canonical bump seed returned by
+/// [`Pubkey::find_program_address`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramDerivedAddress {
+    pub address: Pubkey,
+    pub program_id: Pubkey,
+    pub seeds: Vec<Vec<u8>>,
+    pub bump: u8,
+}
+
+impl ProgramDerivedAddress {
+    /// Return the seeds, including the bump seed, as passed to
+    /// [`Pubkey::create_program_address`].
+    pub fn signer_seeds(&self) -> Vec<&[u8]> {
+        self.seeds
+            .iter()
+            .map(Vec::as_slice)
+            .chain(core::iter::once(core::slice::from_ref(&self.bump)))
+            .collect()
+    }
+}
+
+impl<'a> Arbitrary<'a> for ProgramDerivedAddress {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let program_id = Pubkey::arbitrary(u)?;
+        let num_seeds = u.int_in_range(0..=MAX_SEEDS - 1)?;
+        let seeds = (0..num_seeds)
+            .map(|_| {
+                let len = u.int_in_range(0..=MAX_SEED_LEN)?;
+                u.bytes(len).map(<[u8]>::to_vec)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let seed_slices = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+        let (address, bump) = Pubkey::try_find_program_address(&seed_slices, &program_id)
+            .ok_or(arbitrary::Error::IncorrectFormat)?;
+        Ok(Self {
+            address,
+            program_id,
+            seeds,
+            bump,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, rand::RngCore};
+
+    #[test]
+    fn test_arbitrary_key_classes() {
+        for _ in 0..100 {
+            let mut bytes = [0u8; 1024];
+            rand::thread_rng().fill_bytes(&mut bytes);
+            let mut u = Unstructured::new(&bytes);
+
+            let OnCurvePubkey(on_curve) = u.arbitrary().unwrap();
+            assert!(on_curve.is_on_curve());
+
+            let OffCurvePubkey(off_curve) = u.arbitrary().unwrap();
+            assert!(!off_curve.is_on_curve());
+
+            let pda = ProgramDerivedAddress::arbitrary(&mut u).unwrap();
+            assert!(pda.seeds.len() < MAX_SEEDS);
+            assert!(!pda.address.is_on_curve());
+            assert_eq!(
+                Pubkey::create_program_address(&pda.signer_seeds(), &pda.program_id),
+                Ok(pda.address)
+            );
+        }
+    }
+}
@@ -0,0 +1,206 @@
+//! Base58 encoding and decoding of pubkeys.
+//!
+//! `five8` is used by default, and the portable `bs58` implementation with
+//! the `bs58-default` feature. With the `bs58` feature, either one, or both
+//! side by side to check that they agree, can be picked for a single
+//! conversion with [`Pubkey::from_str_with`] and [`Pubkey::display_with`].
+//!
+//! [`Pubkey::from_str_with`]: crate::Pubkey::from_str_with
+//! [`Pubkey::display_with`]: crate::Pubkey::display_with
+use crate::{MAX_BASE58_LEN, PUBKEY_BYTES};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Base58Error {
+    InvalidChar,
+    TooShort,
+    TooLong,
+}
+
+/// The implementation used to encode and decode base58 pubkeys.
+#[cfg(feature = "bs58")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base58Backend {
+    /// `five8`, which uses SIMD instructions where available.
+    Five8,
+    /// `bs58`, which is slower but portable.
+    Bs58,
+    /// Both, panicking if their results differ. Meant for tests and fuzzing.
+    Differential,
+}
+
+fn five8_decode_32(s: &str, out: &mut [u8; PUBKEY_BYTES]) -> Result<(), Base58Error> {
+    five8::decode_32(s, out).map_err(|e| match e {
+        five8::DecodeError::InvalidChar(_) => Base58Error::InvalidChar,
+        five8::DecodeError::TooShort => Base58Error::TooShort,
+        five8::DecodeError::TooLong
+        | five8::DecodeError::LargestTermTooHigh
+        | five8::DecodeError::OutputTooLong => Base58Error::TooLong,
+    })
+}
+
+#[cfg(feature = "bs58")]
+fn bs58_decode_32(s: &str, out: &mut [u8; PUBKEY_BYTES]) -> Result<(), Base58Error> {
+    // like five8, reject invalid characters before looking at the length
+    if !s.chars().all(crate::is_base58_char) {
+        return Err(Base58Error::InvalidChar);
+    }
+    match bs58::decode(s).onto(&mut out[..]) {
+        Ok(PUBKEY_BYTES) => Ok(()),
+        Ok(_) => Err(Base58Error::TooShort),
+        Err(bs58::decode::Error::BufferTooSmall) => Err(Base58Error::TooLong),
+        Err(_) => Err(Base58Error::InvalidChar),
+    }
+}
+
+fn five8_encode_32(bytes: &[u8; PUBKEY_BYTES], out: &mut [u8; MAX_BASE58_LEN]) -> usize {
+    five8::encode_32(bytes, out) as usize
+}
+
+#[cfg(feature = "bs58")]
+fn bs58_encode_32(bytes: &[u8; PUBKEY_BYTES], out: &mut [u8; MAX_BASE58_LEN]) -> usize {
+    // 32 bytes always fit in `MAX_BASE58_LEN` characters
+    bs58::encode(bytes).onto(&mut out[..]).unwrap()
+}
+
+/// Decode `s` into `out`, which is unspecified if an error is returned.
+pub(crate) fn decode_32(s: &str, out: &mut [u8; PUBKEY_BYTES]) -> Result<(), Base58Error> {
+    #[cfg(feature = "bs58-default")]
+    {
+        bs58_decode_32(s, out)
+    }
+    #[cfg(not(feature = "bs58-default"))]
+    {
+        five8_decode_32(s, out)
+    }
+}
+
+/// Encode `bytes` into `out`, returning the length of the encoding.
+pub(crate) fn encode_32(bytes: &[u8; PUBKEY_BYTES], out: &mut [u8; MAX_BASE58_LEN]) -> usize {
+    #[cfg(feature = "bs58-default")]
+    {
+        bs58_encode_32(bytes, out)
+    }
+    #[cfg(not(feature = "bs58-default"))]
+    {
+        five8_encode_32(bytes, out)
+    }
+}
+
+/// Same as [`decode_32`], with the given `backend`.
+#[cfg(feature = "bs58")]
+pub(crate) fn decode_32_with(
+    backend: Base58Backend,
+    s: &str,
+    out: &mut [u8; PUBKEY_BYTES],
+) -> Result<(), Base58Error> {
+    match backend {
+        Base58Backend::Five8 => five8_decode_32(s, out),
+        Base58Backend::Bs58 => bs58_decode_32(s, out),
+        Base58Backend::Differential => {
+            let mut expected = [0; PUBKEY_BYTES];
+            let expected = bs58_decode_32(s, &mut expected).map(|()| expected);
+            let result = five8_decode_32(s, out).map(|()| *out);
+            assert_eq!(result, expected, "base58 backends disagree on {s:?}");
+            result.map(|_| ())
+        }
+    }
+}
+
+/// Same as [`encode_32`], with the given `backend`.
+#[cfg(feature = "bs58")]
+pub(crate) fn encode_32_with(
+    backend: Base58Backend,
+    bytes: &[u8; PUBKEY_BYTES],
+    out: &mut [u8; MAX_BASE58_LEN],
+) -> usize {
+    match backend {
+        Base58Backend::Five8 => five8_encode_32(bytes, out),
+        Base58Backend::Bs58 => bs58_encode_32(bytes, out),
+        Base58Backend::Differential => {
+            let mut expected = [0; MAX_BASE58_LEN];
+            let expected_len = bs58_encode_32(bytes, &mut expected);
+            let len = five8_encode_32(bytes, out);
+            assert_eq!(
+                out[..len],
+                expected[..expected_len],
+                "base58 backends disagree on {bytes:?}"
+            );
+            len
+        }
+    }
+}
+
+#[cfg(all(test, feature = "bs58"))]
+mod tests {
+    use {
+        super::*,
+        std::{string::String, vec::Vec},
+    };
+
+    fn inputs() -> Vec<String> {
+        let mut inputs = (0..1_000)
+            .map(|_| bs58::encode(rand::random::<[u8; 32]>()).into_string())
+            .collect::<Vec<_>>();
+        inputs.extend(
+            [
+                "",
+                "1",
+                "11111111111111111111111111111111",
+                "111111111111111111111111111111111",
+                "JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFG",
+                "JEKNVnkbo3jma5nREBBJCDoXFVeKkD56V3xKrvRmWxFH",
+                "zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz",
+                "zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz0",
+                "0",
+                "I11111111111111111111111111111111",
+                "☉111111111111111111111111111111111",
+            ]
+            .map(String::from),
+        );
+        inputs
+    }
+
+    #[test]
+    fn test_backends_agree() {
+        for s in inputs() {
+            let mut five8_out = [0; PUBKEY_BYTES];
+            let mut bs58_out = [0; PUBKEY_BYTES];
+            let five8_result = five8_decode_32(&s, &mut five8_out).map(|()| five8_out);
+            let bs58_result = bs58_decode_32(&s, &mut bs58_out).map(|()| bs58_out);
+            assert_eq!(five8_result, bs58_result, "{s:?}");
+
+            if let Ok(bytes) = five8_result {
+                let mut five8_out = [0; MAX_BASE58_LEN];
+                let mut bs58_out = [0; MAX_BASE58_LEN];
+                let five8_len = five8_encode_32(&bytes, &mut five8_out);
+                let bs58_len = bs58_encode_32(&bytes, &mut bs58_out);
+                assert_eq!(five8_out[..five8_len], bs58_out[..bs58_len]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_backend() {
+        use {crate::Pubkey, core::str::FromStr, std::string::ToString};
+
+        let key = Pubkey::new_unique();
+        let s = key.to_string();
+        for backend in [
+            Base58Backend::Five8,
+            Base58Backend::Bs58,
+            Base58Backend::Differential,
+        ] {
+            assert_eq!(key.display_with(backend).to_string(), s);
+            assert_eq!(
+                std::format!("{:>48}", key.display_with(backend)),
+                std::format!("{key:>48}")
+            );
+            assert_eq!(Pubkey::from_str_with(backend, &s), Ok(key));
+            assert_eq!(Pubkey::from_str_with(backend, "0"), Pubkey::from_str("0"));
+            assert_eq!(
+                Pubkey::from_str_with(backend, &"1".repeat(MAX_BASE58_LEN + 1)),
+                Pubkey::from_str(&"1".repeat(MAX_BASE58_LEN + 1))
+            );
+        }
+    }
+}
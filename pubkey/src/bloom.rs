@@ -0,0 +1,272 @@
+//! A bloom filter specialized for pubkeys.
+#[cfg(feature = "serde")]
+use serde_derive::{Deserialize, Serialize};
+use {
+    crate::{Pubkey, PUBKEY_BYTES},
+    core::{fmt, mem},
+    std::{vec, vec::Vec},
+};
+
+const BITS_PER_WORD: u64 = u64::BITS as u64;
+const MAX_NUM_HASHES: u32 = 32;
+/// 512 MiB of bits, enough for over 400 million keys at a 1% false positive
+/// rate
+const MAX_NUM_BITS: u64 = 1 << 32;
+
+/// A bloom filter over [`Pubkey`]s.
+///
+/// Like [`PubkeyHasher`], this relies on pubkeys being uniformly distributed
+/// and uses subslices of the pubkey itself as hash values, so inserts and
+/// lookups do not hash at all. It should not be used when adversarially
+/// chosen keys might be used to inflate the false positive rate.
+///
+/// [`PubkeyHasher`]: crate::PubkeyHasher
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "RawPubkeyBloom"))]
+#[derive(Clone, PartialEq, Eq)]
+pub struct PubkeyBloom {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+/// Error returned when bloom filter parameters are invalid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PubkeyBloomError {
+    /// The number of bits is zero, larger than supported or does not match
+    /// the bit storage
+    InvalidNumBits,
+    /// The number of hashes is zero or larger than supported
+    InvalidNumHashes,
+    /// The false positive rate is not in `(0, 1)`
+    InvalidFalsePositiveRate,
+}
+
+impl core::error::Error for PubkeyBloomError {}
+
+impl fmt::Display for PubkeyBloomError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PubkeyBloomError::InvalidNumBits => f.write_str("Invalid number of bloom filter bits"),
+            PubkeyBloomError::InvalidNumHashes => {
+                f.write_str("Invalid number of bloom filter hashes")
+            }
+            PubkeyBloomError::InvalidFalsePositiveRate => {
+                f.write_str("Invalid bloom filter false positive rate")
+            }
+        }
+    }
+}
+
+impl PubkeyBloom {
+    /// Create a filter sized to hold `num_items` keys with the given false
+    /// positive rate.
+    ///
+    /// Returns [`PubkeyBloomError::InvalidFalsePositiveRate`] if the rate is
+    /// not in `(0, 1)`, and [`PubkeyBloomError::InvalidNumBits`] if the
+    /// filter would need more bits than supported.
+    pub fn new(num_items: usize, false_positive_rate: f64) -> Result<Self, PubkeyBloomError> {
+        // also rejects NaN
+        if !(false_positive_rate > 0.0 && false_positive_rate < 1.0) {
+            return Err(PubkeyBloomError::InvalidFalsePositiveRate);
+        }
+        let num_items = num_items.max(1) as f64;
+        let ln2 = core::f64::consts::LN_2;
+        let num_bits = (-num_items * false_positive_rate.ln() / (ln2 * ln2)).ceil();
+        let num_hashes = (num_bits / num_items * ln2).round();
+        // float to int casts saturate, so an oversized filter is rejected by
+        // `with_params`
+        Self::with_params(
+            (num_bits as u64).max(1),
+            (num_hashes as u32).clamp(1, MAX_NUM_HASHES),
+        )
+    }
+
+    /// Create a filter with an explicit number of bits and hash functions.
+    ///
+    /// Returns [`PubkeyBloomError::InvalidNumBits`] if `num_bits` is zero or
+    /// above 2^32, and [`PubkeyBloomError::InvalidNumHashes`] if
+    /// `num_hashes` is zero or above 32.
+    pub fn with_params(num_bits: u64, num_hashes: u32) -> Result<Self, PubkeyBloomError> {
+        let num_words = Self::num_words(num_bits, num_hashes)?;
+        let num_words = usize::try_from(num_words).map_err(|_| PubkeyBloomError::InvalidNumBits)?;
+        Ok(Self {
+            bits: vec![0; num_words],
+            num_bits,
+            num_hashes,
+        })
+    }
+
+    /// Check the parameters and return the number of words needed to store
+    /// `num_bits` bits.
+    fn num_words(num_bits: u64, num_hashes: u32) -> Result<u64, PubkeyBloomError> {
+        if num_bits == 0 || num_bits > MAX_NUM_BITS {
+            return Err(PubkeyBloomError::InvalidNumBits);
+        }
+        if num_hashes == 0 || num_hashes > MAX_NUM_HASHES {
+            return Err(PubkeyBloomError::InvalidNumHashes);
+        }
+        Ok(num_bits.div_ceil(BITS_PER_WORD))
+    }
+
+    pub fn num_bits(&self) -> u64 {
+        self.num_bits
+    }
+
+    pub fn num_hashes(&self) -> u32 {
+        self.num_hashes
+    }
+
+    pub fn insert(&mut self, key: &Pubkey) {
+        for index in self.indexes(key) {
+            self.bits[(index / BITS_PER_WORD) as usize] |= 1 << (index % BITS_PER_WORD);
+        }
+    }
+
+    /// Return `true` if `key` may have been inserted, `false` if it definitely
+    /// was not.
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        self.indexes(key).all(|index| {
+            self.bits[(index / BITS_PER_WORD) as usize] & (1 << (index % BITS_PER_WORD)) != 0
+        })
+    }
+
+    pub fn clear(&mut self) {
+        self.bits.fill(0);
+    }
+
+    // Double hashing: the i-th index is h1 + i * h2, where h1 and h2 are read
+    // straight from the pubkey's bytes.
+    fn indexes(&self, key: &Pubkey) -> impl Iterator<Item = u64> {
+        const WORD: usize = mem::size_of::<u64>();
+        let bytes = key.as_array();
+        let h1 = u64::from_le_bytes(
+            bytes[PUBKEY_BYTES - 2 * WORD..PUBKEY_BYTES - WORD]
+                .try_into()
+                .unwrap(),
+        );
+        // h2 must be odd so that successive indexes do not repeat early
+        let h2 = u64::from_le_bytes(bytes[PUBKEY_BYTES - WORD..].try_into().unwrap()) | 1;
+        let num_bits = self.num_bits;
+        (0..u64::from(self.num_hashes)).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+impl fmt::Debug for PubkeyBloom {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PubkeyBloom")
+            .field("num_bits", &self.num_bits)
+            .field("num_hashes", &self.num_hashes)
+            .field(
+                "num_set",
+                &self.bits.iter().map(|word| word.count_ones()).sum::<u32>(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct RawPubkeyBloom {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<RawPubkeyBloom> for PubkeyBloom {
+    type Error = PubkeyBloomError;
+
+    fn try_from(raw: RawPubkeyBloom) -> Result<Self, Self::Error> {
+        // validate before touching the bits, so that a large `num_bits` never
+        // allocates
+        let num_words = Self::num_words(raw.num_bits, raw.num_hashes)?;
+        if u64::try_from(raw.bits.len()).ok() != Some(num_words) {
+            return Err(PubkeyBloomError::InvalidNumBits);
+        }
+        Ok(Self {
+            bits: raw.bits,
+            num_bits: raw.num_bits,
+            num_hashes: raw.num_hashes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::new_rand};
+
+    #[test]
+    fn test_pubkey_bloom() {
+        let keys = (0..10_000).map(|_| new_rand()).collect::<Vec<_>>();
+        let mut bloom = PubkeyBloom::new(keys.len(), 0.01).unwrap();
+        assert_eq!(bloom.num_hashes(), 7);
+        for key in &keys {
+            bloom.insert(key);
+        }
+        assert!(keys.iter().all(|key| bloom.contains(key)));
+
+        let false_positives = (0..10_000).filter(|_| bloom.contains(&new_rand())).count();
+        assert!(false_positives < 300, "{false_positives}");
+
+        bloom.clear();
+        assert!(!bloom.contains(&keys[0]));
+    }
+
+    #[test]
+    fn test_pubkey_bloom_params() {
+        assert_eq!(
+            PubkeyBloom::with_params(0, 1),
+            Err(PubkeyBloomError::InvalidNumBits)
+        );
+        assert_eq!(
+            PubkeyBloom::with_params(1, 0),
+            Err(PubkeyBloomError::InvalidNumHashes)
+        );
+        assert_eq!(
+            PubkeyBloom::with_params(MAX_NUM_BITS + 1, 1),
+            Err(PubkeyBloomError::InvalidNumBits)
+        );
+        let bloom = PubkeyBloom::with_params(65, 3).unwrap();
+        assert_eq!(bloom.bits.len(), 2);
+
+        for false_positive_rate in [0.0, 1.0, -0.5, f64::NAN] {
+            assert_eq!(
+                PubkeyBloom::new(100, false_positive_rate),
+                Err(PubkeyBloomError::InvalidFalsePositiveRate)
+            );
+        }
+        assert_eq!(
+            PubkeyBloom::new(usize::MAX, 1e-9),
+            Err(PubkeyBloomError::InvalidNumBits)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_pubkey_bloom_serde() {
+        let key = new_rand();
+        let mut bloom = PubkeyBloom::new(100, 0.1).unwrap();
+        bloom.insert(&key);
+
+        let bytes = bincode::serialize(&bloom).unwrap();
+        let deserialized = bincode::deserialize::<PubkeyBloom>(&bytes).unwrap();
+        assert_eq!(deserialized, bloom);
+        assert!(deserialized.contains(&key));
+
+        let raw = (std::vec![0u64; 1], 65u64, 3u32);
+        let bytes = bincode::serialize(&raw).unwrap();
+        assert!(bincode::deserialize::<PubkeyBloom>(&bytes).is_err());
+
+        // an oversized number of bits is rejected without allocating it
+        for (num_bits, num_hashes) in [(u64::MAX, 3u32), (0, 3), (64, 0), (64, 33)] {
+            let raw = (std::vec![0u64; 1], num_bits, num_hashes);
+            let bytes = bincode::serialize(&raw).unwrap();
+            assert!(bincode::deserialize::<PubkeyBloom>(&bytes).is_err());
+        }
+        let raw = (std::vec![u64::MAX; 1], 64u64, 3u32);
+        let bytes = bincode::serialize(&raw).unwrap();
+        let deserialized = bincode::deserialize::<PubkeyBloom>(&bytes).unwrap();
+        assert!(deserialized.contains(&key));
+    }
+}
@@ -0,0 +1,84 @@
+//! Zero-copy conversions between slices of [`Pubkey`]s, arrays and bytes.
+use {
+    crate::{Pubkey, PUBKEY_BYTES},
+    bytemuck::{PodCastError, TransparentWrapper},
+};
+
+// Safe because `Pubkey` is `repr(transparent)` over `[u8; 32]`
+unsafe impl TransparentWrapper<[u8; PUBKEY_BYTES]> for Pubkey {}
+
+impl Pubkey {
+    /// View a slice of pubkeys as a slice of 32-byte arrays.
+    pub fn slice_as_arrays(pubkeys: &[Pubkey]) -> &[[u8; PUBKEY_BYTES]] {
+        Self::peel_slice(pubkeys)
+    }
+
+    /// View a slice of 32-byte arrays as a slice of pubkeys.
+    pub fn slice_from_arrays(arrays: &[[u8; PUBKEY_BYTES]]) -> &[Pubkey] {
+        Self::wrap_slice(arrays)
+    }
+
+    /// View a mutable slice of 32-byte arrays as a mutable slice of pubkeys.
+    pub fn slice_from_arrays_mut(arrays: &mut [[u8; PUBKEY_BYTES]]) -> &mut [Pubkey] {
+        Self::wrap_slice_mut(arrays)
+    }
+
+    /// View a slice of pubkeys as its underlying bytes.
+    pub fn slice_as_bytes(pubkeys: &[Pubkey]) -> &[u8] {
+        bytemuck::must_cast_slice(pubkeys)
+    }
+
+    /// View a byte slice as a slice of pubkeys.
+    ///
+    /// Fails with [`PodCastError::OutputSliceWouldHaveSlop`] if the length of
+    /// `bytes` is not a multiple of [`PUBKEY_BYTES`]. Pubkeys have no
+    /// alignment requirement, so any address is accepted.
+    pub fn try_slice_from_bytes(bytes: &[u8]) -> Result<&[Pubkey], PodCastError> {
+        bytemuck::try_cast_slice(bytes)
+    }
+
+    /// View a mutable byte slice as a mutable slice of pubkeys.
+    ///
+    /// Fails under the same conditions as [`Pubkey::try_slice_from_bytes`].
+    pub fn try_slice_from_bytes_mut(bytes: &mut [u8]) -> Result<&mut [Pubkey], PodCastError> {
+        bytemuck::try_cast_slice_mut(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::vec::Vec};
+
+    #[test]
+    fn test_slice_casts() {
+        let pubkeys = (0..4).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+        let arrays = Pubkey::slice_as_arrays(&pubkeys);
+        assert_eq!(arrays.len(), pubkeys.len());
+        assert_eq!(arrays[2], pubkeys[2].to_bytes());
+        assert_eq!(Pubkey::slice_from_arrays(arrays), &pubkeys[..]);
+
+        let bytes = Pubkey::slice_as_bytes(&pubkeys);
+        assert_eq!(bytes.len(), pubkeys.len() * PUBKEY_BYTES);
+        assert_eq!(&bytes[PUBKEY_BYTES..2 * PUBKEY_BYTES], pubkeys[1].as_ref());
+        assert_eq!(Pubkey::try_slice_from_bytes(bytes), Ok(&pubkeys[..]));
+        // unaligned
+        assert_eq!(
+            Pubkey::try_slice_from_bytes(&bytes[1..PUBKEY_BYTES + 1]),
+            Ok(&[Pubkey::try_from(&bytes[1..PUBKEY_BYTES + 1]).unwrap()][..])
+        );
+        assert_eq!(
+            Pubkey::try_slice_from_bytes(&bytes[1..]),
+            Err(PodCastError::OutputSliceWouldHaveSlop)
+        );
+
+        let mut arrays = [[0u8; PUBKEY_BYTES]; 2];
+        Pubkey::slice_from_arrays_mut(&mut arrays)[1] = pubkeys[0];
+        assert_eq!(arrays[1], pubkeys[0].to_bytes());
+
+        let mut bytes = [0u8; 2 * PUBKEY_BYTES];
+        Pubkey::try_slice_from_bytes_mut(&mut bytes).unwrap()[0] = pubkeys[3];
+        assert_eq!(&bytes[..PUBKEY_BYTES], pubkeys[3].as_ref());
+        assert!(Pubkey::try_slice_from_bytes_mut(&mut bytes[..PUBKEY_BYTES - 1]).is_err());
+    }
+}
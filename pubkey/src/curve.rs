@@ -0,0 +1,192 @@
+//! Group operations on ed25519 points stored as [`Pubkey`]s.
+//!
+//! On-chain these are computed by the `sol_curve_group_op` and
+//! `sol_curve_multiscalar_mul` syscalls, off-chain by `curve25519-dalek`,
+//! with the same results. Points are compressed edwards y coordinates, the
+//! encoding of ed25519 public keys, and scalars are canonical little endian
+//! encodings of integers modulo the group order. Every function returns
+//! `None` if an input is not a valid point or scalar.
+use crate::Pubkey;
+#[cfg(target_os = "solana")]
+use crate::PUBKEY_BYTES;
+
+/// Number of bytes in a scalar
+pub const SCALAR_BYTES: usize = 32;
+
+/// Maximum number of points accepted by [`multiscalar_multiply`], the limit
+/// of the `sol_curve_multiscalar_mul` syscall.
+pub const MAX_MULTISCALAR_POINTS: usize = 512;
+
+/// Curve id of curve25519 in the edwards representation, see
+/// `sol_curve_group_op`.
+#[cfg(target_os = "solana")]
+const CURVE25519_EDWARDS: u64 = 0;
+#[cfg(target_os = "solana")]
+const OP_ADD: u64 = 0;
+#[cfg(target_os = "solana")]
+const OP_SUB: u64 = 1;
+#[cfg(target_os = "solana")]
+const OP_MUL: u64 = 2;
+
+#[cfg(target_os = "solana")]
+fn group_op(op: u64, left: &[u8; 32], right: &[u8; PUBKEY_BYTES]) -> Option<Pubkey> {
+    let mut result = [0u8; PUBKEY_BYTES];
+    let rc = unsafe {
+        crate::syscalls::sol_curve_group_op(
+            CURVE25519_EDWARDS,
+            op,
+            left.as_ptr(),
+            right.as_ptr(),
+            result.as_mut_ptr(),
+        )
+    };
+    (rc == crate::SUCCESS).then_some(Pubkey(result))
+}
+
+#[cfg(not(target_os = "solana"))]
+fn decompress(point: &Pubkey) -> Option<curve25519_dalek::EdwardsPoint> {
+    curve25519_dalek::edwards::CompressedEdwardsY(point.to_bytes()).decompress()
+}
+
+#[cfg(not(target_os = "solana"))]
+fn scalar(bytes: &[u8; SCALAR_BYTES]) -> Option<curve25519_dalek::Scalar> {
+    curve25519_dalek::Scalar::from_canonical_bytes(*bytes).into()
+}
+
+#[cfg(not(target_os = "solana"))]
+fn compress(point: curve25519_dalek::EdwardsPoint) -> Pubkey {
+    Pubkey(point.compress().to_bytes())
+}
+
+/// Compute `left + right`.
+pub fn add(left: &Pubkey, right: &Pubkey) -> Option<Pubkey> {
+    #[cfg(not(target_os = "solana"))]
+    {
+        Some(compress(decompress(left)? + decompress(right)?))
+    }
+    #[cfg(target_os = "solana")]
+    {
+        group_op(OP_ADD, &left.0, &right.0)
+    }
+}
+
+/// Compute `left - right`.
+pub fn subtract(left: &Pubkey, right: &Pubkey) -> Option<Pubkey> {
+    #[cfg(not(target_os = "solana"))]
+    {
+        Some(compress(decompress(left)? - decompress(right)?))
+    }
+    #[cfg(target_os = "solana")]
+    {
+        group_op(OP_SUB, &left.0, &right.0)
+    }
+}
+
+/// Compute `scalar * point`.
+pub fn multiply(scalar: &[u8; SCALAR_BYTES], point: &Pubkey) -> Option<Pubkey> {
+    #[cfg(not(target_os = "solana"))]
+    {
+        Some(compress(self::scalar(scalar)? * decompress(point)?))
+    }
+    #[cfg(target_os = "solana")]
+    {
+        group_op(OP_MUL, scalar, &point.0)
+    }
+}
+
+/// Compute the sum of `scalars[i] * points[i]`.
+///
+/// Returns `None` if the slices differ in length, or are longer than
+/// [`MAX_MULTISCALAR_POINTS`].
+pub fn multiscalar_multiply(scalars: &[[u8; SCALAR_BYTES]], points: &[Pubkey]) -> Option<Pubkey> {
+    if scalars.len() != points.len() || points.len() > MAX_MULTISCALAR_POINTS {
+        return None;
+    }
+    #[cfg(not(target_os = "solana"))]
+    {
+        use curve25519_dalek::{traits::VartimeMultiscalarMul, EdwardsPoint};
+        if !scalars.iter().all(|bytes| scalar(bytes).is_some()) {
+            return None;
+        }
+        EdwardsPoint::optional_multiscalar_mul(
+            scalars.iter().map(|bytes| scalar(bytes).unwrap()),
+            points.iter().map(decompress),
+        )
+        .map(compress)
+    }
+    #[cfg(target_os = "solana")]
+    {
+        let mut result = [0u8; PUBKEY_BYTES];
+        let rc = unsafe {
+            crate::syscalls::sol_curve_multiscalar_mul(
+                CURVE25519_EDWARDS,
+                scalars.as_ptr() as *const u8,
+                points.as_ptr() as *const u8,
+                points.len() as u64,
+                result.as_mut_ptr(),
+            )
+        };
+        (rc == crate::SUCCESS).then_some(Pubkey(result))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        curve25519_dalek::{constants::ED25519_BASEPOINT_POINT, EdwardsPoint, Scalar},
+    };
+
+    fn scalar_bytes(n: u64) -> [u8; SCALAR_BYTES] {
+        Scalar::from(n).to_bytes()
+    }
+
+    #[test]
+    fn test_group_ops() {
+        let base = compress(ED25519_BASEPOINT_POINT);
+        let two = multiply(&scalar_bytes(2), &base).unwrap();
+        let three = multiply(&scalar_bytes(3), &base).unwrap();
+        assert_eq!(add(&base, &two), Some(three));
+        assert_eq!(subtract(&three, &two), Some(base));
+        assert_eq!(
+            subtract(&base, &base),
+            Some(compress(EdwardsPoint::default()))
+        );
+        assert_eq!(
+            multiscalar_multiply(&[scalar_bytes(2), scalar_bytes(5)], &[base, three]),
+            multiply(&scalar_bytes(17), &base)
+        );
+        assert_eq!(
+            multiscalar_multiply(&[], &[]),
+            Some(compress(EdwardsPoint::default()))
+        );
+    }
+
+    #[test]
+    fn test_group_ops_invalid_input() {
+        let base = compress(ED25519_BASEPOINT_POINT);
+        let off_curve = Pubkey::find_program_address(&[], &base).0;
+        assert!(!off_curve.is_on_curve());
+        assert_eq!(add(&base, &off_curve), None);
+        assert_eq!(subtract(&off_curve, &base), None);
+        assert_eq!(multiply(&scalar_bytes(2), &off_curve), None);
+        // not reduced modulo the group order
+        assert_eq!(multiply(&[0xff; SCALAR_BYTES], &base), None);
+
+        let scalars = [scalar_bytes(1), scalar_bytes(2)];
+        assert_eq!(multiscalar_multiply(&scalars, &[base]), None);
+        assert_eq!(multiscalar_multiply(&scalars, &[base, off_curve]), None);
+        assert_eq!(
+            multiscalar_multiply(&[scalars[0], [0xff; SCALAR_BYTES]], &[base, base]),
+            None
+        );
+        let scalars = [scalar_bytes(1); MAX_MULTISCALAR_POINTS + 1];
+        let points = [base; MAX_MULTISCALAR_POINTS + 1];
+        assert_eq!(multiscalar_multiply(&scalars, &points), None);
+        assert!(multiscalar_multiply(
+            &scalars[..MAX_MULTISCALAR_POINTS],
+            &points[..MAX_MULTISCALAR_POINTS]
+        )
+        .is_some());
+    }
+}
@@ -0,0 +1,138 @@
+//! A bounded cache of curve membership checks.
+use {
+    crate::Pubkey,
+    core::sync::atomic::{AtomicU64, Ordering},
+    hashlink::LruCache,
+    std::sync::Mutex,
+};
+
+/// A least recently used cache of [`Pubkey::is_on_curve`] results.
+///
+/// Meant for validators and indexers that check the same hot set of accounts
+/// over and over. The cache can be shared between threads; use [`stats`] to
+/// tune its capacity.
+///
+/// [`stats`]: CurveCache::stats
+///
+/// ```
+/// # use solana_pubkey::{CurveCache, Pubkey};
+/// let cache = CurveCache::new(1024);
+/// let key = Pubkey::new_unique();
+/// assert_eq!(cache.is_on_curve(&key), key.is_on_curve());
+/// assert_eq!(cache.is_on_curve(&key), key.is_on_curve());
+/// assert_eq!(cache.stats().hits, 1);
+/// ```
+#[derive(Debug)]
+pub struct CurveCache {
+    cache: Mutex<LruCache<Pubkey, bool>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// Hit and miss counts of a [`CurveCache`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CurveCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// The number of cached results.
+    pub len: usize,
+    pub capacity: usize,
+}
+
+impl CurveCacheStats {
+    /// The fraction of lookups answered from the cache, or 0 if there were
+    /// none.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits.saturating_add(self.misses);
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+impl CurveCache {
+    /// Create a cache holding at most `capacity` results.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            cache: Mutex::new(LruCache::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Same as [`Pubkey::is_on_curve`], but answered from the cache if
+    /// possible.
+    pub fn is_on_curve(&self, pubkey: &Pubkey) -> bool {
+        if let Some(on_curve) = self.cache.lock().unwrap().get(pubkey).copied() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return on_curve;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        // don't hold the lock while decompressing the point
+        let on_curve = pubkey.is_on_curve();
+        self.cache.lock().unwrap().insert(*pubkey, on_curve);
+        on_curve
+    }
+
+    pub fn stats(&self) -> CurveCacheStats {
+        let cache = self.cache.lock().unwrap();
+        CurveCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            len: cache.len(),
+            capacity: cache.capacity(),
+        }
+    }
+
+    /// Reset the hit and miss counts, keeping the cached results.
+    pub fn reset_stats(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    /// Remove all cached results.
+    pub fn clear(&self) {
+        self.cache.lock().unwrap().clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::vec::Vec};
+
+    #[test]
+    fn test_curve_cache() {
+        let cache = CurveCache::new(2);
+        assert_eq!(cache.stats().hit_rate(), 0.0);
+
+        let keys = (0..3).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let off_curve = Pubkey::find_program_address(&[], &keys[0]).0;
+        assert!(!cache.is_on_curve(&off_curve));
+        assert!(!cache.is_on_curve(&off_curve));
+        for key in &keys {
+            assert_eq!(cache.is_on_curve(key), key.is_on_curve());
+        }
+        assert_eq!(
+            cache.stats(),
+            CurveCacheStats {
+                hits: 1,
+                misses: 4,
+                len: 2,
+                capacity: 2,
+            }
+        );
+        assert_eq!(cache.stats().hit_rate(), 0.2);
+
+        // the least recently used key was evicted
+        cache.reset_stats();
+        cache.is_on_curve(&keys[2]);
+        cache.is_on_curve(&keys[0]);
+        assert_eq!(cache.stats().hits, 1);
+        assert_eq!(cache.stats().misses, 1);
+
+        cache.clear();
+        assert_eq!(cache.stats().len, 0);
+    }
+}
@@ -0,0 +1,158 @@
+//! Ethereum addresses, for tooling that bridges secp256k1 keys to Solana.
+use {
+    crate::{Pubkey, PUBKEY_BYTES},
+    core::{fmt, str::from_utf8_unchecked},
+};
+
+/// Number of bytes in an Ethereum address
+pub const ETH_ADDRESS_BYTES: usize = 20;
+/// Number of bytes in an uncompressed secp256k1 public key, without the
+/// leading `0x04` tag
+pub const SECP256K1_PUBKEY_BYTES: usize = 64;
+
+const PADDING_BYTES: usize = PUBKEY_BYTES - ETH_ADDRESS_BYTES;
+// "0x" followed by two hex digits per byte
+const ETH_ADDRESS_STR_LEN: usize = 2 + 2 * ETH_ADDRESS_BYTES;
+
+/// A 20-byte Ethereum address.
+///
+/// An address can be embedded in a [`Pubkey`], as Wormhole and similar
+/// bridges do: it is left-padded with zeros to 32 bytes.
+#[repr(transparent)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EthAddress(pub [u8; ETH_ADDRESS_BYTES]);
+
+impl EthAddress {
+    /// Derive the address of an uncompressed secp256k1 public key, i.e. the
+    /// last 20 bytes of its keccak256 hash.
+    pub fn from_secp256k1_pubkey(pubkey: &[u8; SECP256K1_PUBKEY_BYTES]) -> Self {
+        let hash = solana_keccak_hasher::hash(pubkey).to_bytes();
+        let mut address = [0; ETH_ADDRESS_BYTES];
+        address.copy_from_slice(&hash[PADDING_BYTES..]);
+        Self(address)
+    }
+
+    /// Recover an address embedded in `pubkey`, or `None` if its first 12
+    /// bytes are not zero.
+    pub fn from_pubkey(pubkey: &Pubkey) -> Option<Self> {
+        let (padding, address) = pubkey.as_array().split_at(PADDING_BYTES);
+        if padding.iter().any(|byte| *byte != 0) {
+            return None;
+        }
+        Some(Self(address.try_into().unwrap()))
+    }
+
+    pub const fn to_bytes(self) -> [u8; ETH_ADDRESS_BYTES] {
+        self.0
+    }
+}
+
+impl From<[u8; ETH_ADDRESS_BYTES]> for EthAddress {
+    fn from(address: [u8; ETH_ADDRESS_BYTES]) -> Self {
+        Self(address)
+    }
+}
+
+impl From<EthAddress> for Pubkey {
+    fn from(address: EthAddress) -> Self {
+        let mut bytes = [0; PUBKEY_BYTES];
+        bytes[PADDING_BYTES..].copy_from_slice(&address.0);
+        Pubkey::from(bytes)
+    }
+}
+
+impl AsRef<[u8]> for EthAddress {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Formats the address as `0x`-prefixed hex with the mixed-case checksum of
+/// [EIP-55](https://eips.ethereum.org/EIPS/eip-55).
+fn write_as_checksummed_hex(f: &mut fmt::Formatter, address: &EthAddress) -> fmt::Result {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut out = [0u8; ETH_ADDRESS_STR_LEN];
+    out[..2].copy_from_slice(b"0x");
+    for (byte, digits) in address.0.iter().zip(out[2..].chunks_mut(2)) {
+        digits[0] = HEX_DIGITS[usize::from(byte >> 4)];
+        digits[1] = HEX_DIGITS[usize::from(byte & 0xf)];
+    }
+    let hash = solana_keccak_hasher::hash(&out[2..]).to_bytes();
+    for (i, digit) in out[2..].iter_mut().enumerate() {
+        let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0xf;
+        if nibble >= 8 {
+            digit.make_ascii_uppercase();
+        }
+    }
+    // Safe because only ASCII characters were written
+    f.pad(unsafe { from_utf8_unchecked(&out) })
+}
+
+impl fmt::Debug for EthAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_as_checksummed_hex(f, self)
+    }
+}
+
+impl fmt::Display for EthAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_as_checksummed_hex(f, self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::string::ToString};
+
+    fn from_hex(s: &str) -> std::vec::Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_eth_address_from_secp256k1_pubkey() {
+        // the public key of the secret key 1, i.e. the generator point
+        let pubkey = from_hex(
+            "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798\
+             483ada7726a3c4655da4fbfc0e1108a8fd17b448a68554199c47d08ffb10d4b8",
+        );
+        let address = EthAddress::from_secp256k1_pubkey(&pubkey.try_into().unwrap());
+        assert_eq!(
+            address.to_string(),
+            "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf"
+        );
+    }
+
+    #[test]
+    fn test_eth_address_checksum() {
+        // test vectors from EIP-55
+        for expected in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let address = EthAddress(from_hex(&expected[2..]).try_into().unwrap());
+            assert_eq!(address.to_string(), expected);
+            assert_eq!(std::format!("{address:?}"), expected);
+        }
+        assert_eq!(
+            std::format!("{:>44}", EthAddress::default()),
+            "  0x0000000000000000000000000000000000000000"
+        );
+    }
+
+    #[test]
+    fn test_eth_address_embedding() {
+        let address = EthAddress([0xab; ETH_ADDRESS_BYTES]);
+        let pubkey = Pubkey::from(address);
+        assert_eq!(pubkey.as_array()[..PADDING_BYTES], [0; PADDING_BYTES]);
+        assert_eq!(EthAddress::from_pubkey(&pubkey), Some(address));
+
+        let mut bytes = pubkey.to_bytes();
+        bytes[0] = 1;
+        assert_eq!(EthAddress::from_pubkey(&Pubkey::from(bytes)), None);
+    }
+}
@@ -0,0 +1,203 @@
+//! A lightweight ed25519 point validity check built on [`fiat_crypto`] field
+//! arithmetic, for users who want to avoid the `curve25519-dalek` dependency.
+//!
+//! It must accept exactly the encodings that
+//! `curve25519_dalek::edwards::CompressedEdwardsY::decompress` accepts. That
+//! function reduces `y` modulo p, ignores the sign bit, and succeeds iff
+//! `(y^2 - 1) / (d * y^2 + 1)` is a square. The denominator is never zero
+//! since `-1/d` is not a square, so this is equivalent to `u * v` being zero
+//! or a square, which is decided with Euler's criterion.
+#[cfg(not(target_pointer_width = "64"))]
+use fiat_crypto::curve25519_32::{
+    fiat_25519_add, fiat_25519_carry, fiat_25519_carry_mul, fiat_25519_carry_square,
+    fiat_25519_from_bytes, fiat_25519_loose_field_element as Loose, fiat_25519_relax,
+    fiat_25519_sub, fiat_25519_tight_field_element as Tight, fiat_25519_to_bytes,
+};
+#[cfg(target_pointer_width = "64")]
+use fiat_crypto::curve25519_64::{
+    fiat_25519_add, fiat_25519_carry, fiat_25519_carry_mul, fiat_25519_carry_square,
+    fiat_25519_from_bytes, fiat_25519_loose_field_element as Loose, fiat_25519_relax,
+    fiat_25519_sub, fiat_25519_tight_field_element as Tight, fiat_25519_to_bytes,
+};
+
+/// The edwards curve constant d = -121665/121666, little endian
+const EDWARDS_D: [u8; 32] = [
+    0xa3, 0x78, 0x59, 0x13, 0xca, 0x4d, 0xeb, 0x75, 0xab, 0xd8, 0x41, 0x41, 0x4d, 0x0a, 0x70, 0x00,
+    0x98, 0xe8, 0x79, 0x77, 0x79, 0x40, 0xc7, 0x8c, 0x73, 0xfe, 0x6f, 0x2b, 0xee, 0x6c, 0x03, 0x52,
+];
+
+fn zero() -> Tight {
+    Tight(Default::default())
+}
+
+fn from_bytes(bytes: &[u8; 32]) -> Tight {
+    let mut bytes = *bytes;
+    // the sign of x is irrelevant to whether the point exists
+    bytes[31] &= 0x7f;
+    let mut out = zero();
+    fiat_25519_from_bytes(&mut out, &bytes);
+    out
+}
+
+fn to_bytes(a: &Tight) -> [u8; 32] {
+    let mut out = [0; 32];
+    fiat_25519_to_bytes(&mut out, a);
+    out
+}
+
+fn relax(a: &Tight) -> Loose {
+    let mut out = Loose(a.0);
+    fiat_25519_relax(&mut out, a);
+    out
+}
+
+fn carry(a: &Loose) -> Tight {
+    let mut out = zero();
+    fiat_25519_carry(&mut out, a);
+    out
+}
+
+fn add(a: &Tight, b: &Tight) -> Tight {
+    let mut out = relax(a);
+    fiat_25519_add(&mut out, a, b);
+    carry(&out)
+}
+
+fn sub(a: &Tight, b: &Tight) -> Tight {
+    let mut out = relax(a);
+    fiat_25519_sub(&mut out, a, b);
+    carry(&out)
+}
+
+fn mul(a: &Tight, b: &Tight) -> Tight {
+    let mut out = zero();
+    fiat_25519_carry_mul(&mut out, &relax(a), &relax(b));
+    out
+}
+
+fn square_n(a: &Tight, n: u32) -> Tight {
+    let mut out = *a;
+    for _ in 0..n {
+        let loose = relax(&out);
+        fiat_25519_carry_square(&mut out, &loose);
+    }
+    out
+}
+
+/// Compute `z^((p - 1) / 2) = z^(2^254 - 10)`, using the same addition chain
+/// as the ref10 implementation's inversion.
+fn pow_p_minus_1_over_2(z: &Tight) -> Tight {
+    let z2 = square_n(z, 1);
+    let z9 = mul(&square_n(&z2, 2), z);
+    let z11 = mul(&z9, &z2);
+    let z_5_0 = mul(&square_n(&z11, 1), &z9);
+    let z_10_0 = mul(&square_n(&z_5_0, 5), &z_5_0);
+    let z_20_0 = mul(&square_n(&z_10_0, 10), &z_10_0);
+    let z_40_0 = mul(&square_n(&z_20_0, 20), &z_20_0);
+    let z_50_0 = mul(&square_n(&z_40_0, 10), &z_10_0);
+    let z_100_0 = mul(&square_n(&z_50_0, 50), &z_50_0);
+    let z_200_0 = mul(&square_n(&z_100_0, 100), &z_100_0);
+    let z_250_0 = mul(&square_n(&z_200_0, 50), &z_50_0);
+    // (2^250 - 1) * 2^4 + 6 = 2^254 - 10
+    let z6 = mul(&z2, &square_n(&z2, 1));
+    mul(&square_n(&z_250_0, 4), &z6)
+}
+
+/// The constants needed to check points, computed once so that they can be
+/// reused when checking many points.
+pub(crate) struct CurvePointChecker {
+    one: Tight,
+    d: Tight,
+}
+
+impl CurvePointChecker {
+    pub(crate) fn new() -> Self {
+        let mut one = [0; 32];
+        one[0] = 1;
+        Self {
+            one: from_bytes(&one),
+            d: from_bytes(&EDWARDS_D),
+        }
+    }
+
+    pub(crate) fn check(&self, bytes: &[u8]) -> bool {
+        let Ok(bytes) = <&[u8; 32]>::try_from(bytes) else {
+            return false;
+        };
+        let y = from_bytes(bytes);
+        let yy = square_n(&y, 1);
+        let u = sub(&yy, &self.one);
+        let v = add(&mul(&yy, &self.d), &self.one);
+        let chi = to_bytes(&pow_p_minus_1_over_2(&mul(&u, &v)));
+        // 0 if u is zero, 1 if u * v is a non-zero square, and p - 1 otherwise
+        chi[1..].iter().all(|byte| *byte == 0) && chi[0] <= 1
+    }
+}
+
+#[cfg(test)]
+fn bytes_are_curve_point(bytes: &[u8]) -> bool {
+    CurvePointChecker::new().check(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, curve25519_dalek::edwards::CompressedEdwardsY, rand::RngCore};
+
+    fn dalek_bytes_are_curve_point(bytes: &[u8]) -> bool {
+        CompressedEdwardsY::from_slice(bytes)
+            .map(|compressed| compressed.decompress().is_some())
+            .unwrap_or(false)
+    }
+
+    #[test]
+    fn test_matches_dalek() {
+        let mut rng = rand::thread_rng();
+        let mut on_curve = 0;
+        for _ in 0..10_000 {
+            let mut bytes = [0u8; 32];
+            rng.fill_bytes(&mut bytes);
+            let expected = dalek_bytes_are_curve_point(&bytes);
+            assert_eq!(bytes_are_curve_point(&bytes), expected, "{bytes:?}");
+            on_curve += usize::from(expected);
+        }
+        // roughly half of all encodings are valid points
+        assert!((4_000..6_000).contains(&on_curve), "{on_curve}");
+    }
+
+    #[test]
+    fn test_matches_dalek_edge_cases() {
+        let p_minus = |n: u8| {
+            let mut bytes = [0xff; 32];
+            bytes[0] = 0xed - n;
+            bytes[31] = 0x7f;
+            bytes
+        };
+        let small = |n: u8| {
+            let mut bytes = [0; 32];
+            bytes[0] = n;
+            bytes
+        };
+        let mut cases = std::vec![[0; 32], [0xff; 32], EDWARDS_D, p_minus(0), p_minus(1)];
+        // non-canonical encodings of y, with and without the sign bit
+        cases.extend((0..=18).map(|n| {
+            let mut bytes = p_minus(0);
+            bytes[0] += n;
+            bytes
+        }));
+        cases.extend((0..=32).map(small));
+        for case in cases.clone() {
+            let mut signed = case;
+            signed[31] |= 0x80;
+            cases.push(signed);
+        }
+        for bytes in cases {
+            assert_eq!(
+                bytes_are_curve_point(&bytes),
+                dalek_bytes_are_curve_point(&bytes),
+                "{bytes:?}"
+            );
+        }
+        assert!(!bytes_are_curve_point(&[0; 31]));
+        assert!(!bytes_are_curve_point(&[0; 33]));
+    }
+}
@@ -8,32 +8,83 @@
 extern crate std;
 #[cfg(feature = "dev-context-only-utils")]
 use arbitrary::Arbitrary;
+#[cfg(feature = "borsh")]
+use borsh::{BorshDeserialize, BorshSerialize};
 #[cfg(feature = "bytemuck")]
 use bytemuck_derive::{Pod, Zeroable};
 #[cfg(feature = "serde")]
 use serde_derive::{Deserialize, Serialize};
 #[cfg(feature = "std")]
 use std::vec::Vec;
-#[cfg(feature = "borsh")]
-use {
-    borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
-    std::string::ToString,
-};
 use {
+    base58::Base58Error,
     core::{
         array,
         convert::{Infallible, TryFrom},
         fmt,
         hash::{Hash, Hasher},
         mem,
+        ops::RangeInclusive,
         str::{from_utf8_unchecked, FromStr},
     },
     num_traits::{FromPrimitive, ToPrimitive},
     solana_program_error::ProgramError,
 };
+#[cfg(all(feature = "borsh", feature = "std"))]
+use {borsh::BorshSchema, std::string::ToString};
 
+#[cfg(all(feature = "dev-context-only-utils", not(target_os = "solana")))]
+pub mod arbitrary_keys;
+mod base58;
+#[cfg(feature = "std")]
+mod bloom;
+#[cfg(feature = "bytemuck")]
+mod cast;
+#[cfg(any(target_os = "solana", feature = "curve25519"))]
+pub mod curve;
+#[cfg(all(feature = "curve-cache", not(target_os = "solana")))]
+mod curve_cache;
+#[cfg(feature = "secp256k1")]
+mod eth;
+#[cfg(all(not(target_os = "solana"), any(feature = "curve25519-fiat", test)))]
+mod fiat;
+#[cfg(feature = "std")]
+mod lines;
+#[cfg(all(
+    feature = "std",
+    any(
+        target_os = "solana",
+        feature = "curve25519",
+        feature = "curve25519-fiat"
+    )
+))]
+mod multisig;
+#[cfg(all(
+    not(target_os = "solana"),
+    any(feature = "curve25519", feature = "curve25519-fiat")
+))]
+mod pda_search;
+#[cfg(all(
+    feature = "std",
+    not(target_os = "solana"),
+    any(feature = "curve25519", feature = "curve25519-fiat")
+))]
+mod pda_trace;
+#[cfg(feature = "sqlx")]
+mod postgres;
+#[cfg(feature = "serde")]
+pub mod serde_as;
+#[cfg(feature = "std")]
+mod set;
+pub mod stable_layout;
+#[cfg(feature = "proptest")]
+pub mod strategies;
 #[cfg(target_os = "solana")]
 pub mod syscalls;
+#[cfg(feature = "std")]
+mod unique;
+#[cfg(feature = "weighted-shuffle")]
+pub mod weighted_shuffle;
 
 /// Number of bytes in a pubkey
 pub const PUBKEY_BYTES: usize = 32;
@@ -44,8 +95,22 @@ pub const MAX_SEEDS: usize = 16;
 /// Maximum string length of a base58 encoded pubkey
 const MAX_BASE58_LEN: usize = 44;
 
-#[cfg(any(target_os = "solana", feature = "sha2", feature = "curve25519"))]
+#[cfg(any(
+    target_os = "solana",
+    feature = "sha2",
+    feature = "sha2-const",
+    feature = "curve25519"
+))]
 const PDA_MARKER: &[u8; 21] = b"ProgramDerivedAddress";
+/// Prefix of every input hashed by [`Pubkey::create_with_seed_v2`]
+#[cfg(any(target_os = "solana", feature = "sha2"))]
+const CREATE_WITH_SEED_V2_TAG: &[u8] = b"solana-create-with-seed-v2";
+
+/// The ID of the associated token account program, used by
+/// [`Pubkey::derive_associated_address`]
+#[cfg(feature = "associated-address")]
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: Pubkey =
+    Pubkey::from_str_const("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
 
 /// Copied from `solana_program::entrypoint::SUCCESS`
 /// to avoid a `solana_program` dependency
@@ -135,6 +200,89 @@ impl From<PubkeyError> for ProgramError {
     }
 }
 
+impl TryFrom<ProgramError> for PubkeyError {
+    type Error = ProgramError;
+
+    fn try_from(error: ProgramError) -> Result<Self, Self::Error> {
+        match error {
+            Self::Error::MaxSeedLengthExceeded => Ok(Self::MaxSeedLengthExceeded),
+            Self::Error::InvalidSeeds => Ok(Self::InvalidSeeds),
+            Self::Error::IllegalOwner => Ok(Self::IllegalOwner),
+            _ => Err(error),
+        }
+    }
+}
+
+/// A [`PubkeyError`] with context about which input caused it.
+///
+/// Returned by the `try_*_detailed` variants of the address derivation
+/// functions, and convertible into the corresponding [`PubkeyError`].
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetailedPubkeyError {
+    /// More than [`MAX_SEEDS`] seeds were provided
+    TooManySeeds {
+        count: usize,
+    },
+    /// The seed at `index` is longer than [`MAX_SEED_LEN`]
+    MaxSeedLengthExceeded {
+        index: usize,
+        len: usize,
+    },
+    InvalidSeeds,
+    IllegalOwner,
+}
+
+impl core::error::Error for DetailedPubkeyError {}
+
+impl fmt::Display for DetailedPubkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DetailedPubkeyError::TooManySeeds { count } => write!(
+                f,
+                "{count} seeds were provided for address generation, the maximum is {MAX_SEEDS}"
+            ),
+            DetailedPubkeyError::MaxSeedLengthExceeded { index, len } => write!(
+                f,
+                "Seed {index} is {len} bytes long, the maximum for address generation is \
+                 {MAX_SEED_LEN}"
+            ),
+            DetailedPubkeyError::InvalidSeeds => fmt::Display::fmt(&PubkeyError::InvalidSeeds, f),
+            DetailedPubkeyError::IllegalOwner => fmt::Display::fmt(&PubkeyError::IllegalOwner, f),
+        }
+    }
+}
+
+impl From<DetailedPubkeyError> for PubkeyError {
+    fn from(error: DetailedPubkeyError) -> Self {
+        match error {
+            DetailedPubkeyError::TooManySeeds { .. }
+            | DetailedPubkeyError::MaxSeedLengthExceeded { .. } => Self::MaxSeedLengthExceeded,
+            DetailedPubkeyError::InvalidSeeds => Self::InvalidSeeds,
+            DetailedPubkeyError::IllegalOwner => Self::IllegalOwner,
+        }
+    }
+}
+
+impl From<DetailedPubkeyError> for ProgramError {
+    fn from(error: DetailedPubkeyError) -> Self {
+        PubkeyError::from(error).into()
+    }
+}
+
+fn check_seeds_detailed(seeds: &[&[u8]]) -> Result<(), DetailedPubkeyError> {
+    if seeds.len() > MAX_SEEDS {
+        return Err(DetailedPubkeyError::TooManySeeds { count: seeds.len() });
+    }
+    match seeds.iter().position(|seed| seed.len() > MAX_SEED_LEN) {
+        Some(index) => Err(DetailedPubkeyError::MaxSeedLengthExceeded {
+            index,
+            len: seeds[index].len(),
+        }),
+        None => Ok(()),
+    }
+}
+
 /// The address of a [Solana account][acc].
 ///
 /// Some account addresses are [ed25519] public keys, with corresponding secret
@@ -157,12 +305,25 @@ impl From<PubkeyError> for ProgramError {
     borsh(crate = "borsh")
 )]
 #[cfg_attr(all(feature = "borsh", feature = "std"), derive(BorshSchema))]
-#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(
+    all(feature = "serde", not(feature = "serde-human-readable")),
+    derive(Deserialize, Serialize)
+)]
 #[cfg_attr(feature = "bytemuck", derive(Pod, Zeroable))]
 #[derive(Clone, Copy, Default, Eq, Ord, PartialEq, PartialOrd)]
 #[cfg_attr(feature = "dev-context-only-utils", derive(Arbitrary))]
 pub struct Pubkey(pub(crate) [u8; 32]);
 
+/// Constant time comparison, for when a `Pubkey` must be compared against a
+/// secret, e.g. an address derived from secret seeds. `PartialEq` stays the
+/// ordinary comparison, so that pubkey constants work as match patterns.
+#[cfg(feature = "subtle")]
+impl subtle::ConstantTimeEq for Pubkey {
+    fn ct_eq(&self, other: &Self) -> subtle::Choice {
+        self.0[..].ct_eq(&other.0[..])
+    }
+}
+
 /// Custom impl of Hash for Pubkey
 /// allows us to skip hashing the length of the pubkey
 /// which is always the same anyway
@@ -315,8 +476,41 @@ mod hasher {
         }
     }
 }
+#[cfg(feature = "bs58")]
+pub use base58::Base58Backend;
+#[cfg(all(feature = "curve-cache", not(target_os = "solana")))]
+pub use curve_cache::{CurveCache, CurveCacheStats};
+#[cfg(feature = "secp256k1")]
+pub use eth::{EthAddress, ETH_ADDRESS_BYTES, SECP256K1_PUBKEY_BYTES};
 #[cfg(all(feature = "rand", not(target_os = "solana")))]
 pub use hasher::{PubkeyHasher, PubkeyHasherBuilder};
+#[cfg(all(
+    feature = "std",
+    any(
+        target_os = "solana",
+        feature = "curve25519",
+        feature = "curve25519-fiat"
+    )
+))]
+pub use multisig::MULTISIG_VAULT_SEED;
+#[cfg(all(
+    not(target_os = "solana"),
+    any(feature = "curve25519", feature = "curve25519-fiat")
+))]
+pub use pda_search::{BumpCandidates, CpuPdaSearch, PdaSearchBackend};
+#[cfg(all(
+    feature = "std",
+    not(target_os = "solana"),
+    any(feature = "curve25519", feature = "curve25519-fiat")
+))]
+pub use pda_trace::{PdaTrace, RejectedBump};
+#[cfg(feature = "std")]
+pub use {
+    bloom::{PubkeyBloom, PubkeyBloomError},
+    lines::{PubkeyLines, ReadPubkeysError},
+    set::PubkeySet,
+    unique::UniqueScope,
+};
 
 impl solana_sanitize::Sanitize for Pubkey {}
 
@@ -372,6 +566,103 @@ impl fmt::Display for ParsePubkeyError {
     }
 }
 
+/// A [`ParsePubkeyError`] with context about what is wrong with the input.
+///
+/// Returned by [`Pubkey::from_str_detailed`], and convertible into the
+/// corresponding [`ParsePubkeyError`].
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DetailedParsePubkeyError {
+    /// The string decodes to fewer than 32 bytes
+    TooShort { len: usize },
+    /// The string is longer than any base58 encoded pubkey, or decodes to
+    /// more than 32 bytes
+    TooLong { len: usize },
+    /// `character`, at byte offset `index`, is not in the base58 alphabet
+    InvalidChar { index: usize, character: char },
+}
+
+impl core::error::Error for DetailedParsePubkeyError {}
+
+impl fmt::Display for DetailedParsePubkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DetailedParsePubkeyError::TooShort { len } => {
+                write!(f, "String of length {len} is too short to be a pubkey")
+            }
+            DetailedParsePubkeyError::TooLong { len } => {
+                write!(f, "String of length {len} is too long to be a pubkey")
+            }
+            DetailedParsePubkeyError::InvalidChar { index, character } => {
+                write!(
+                    f,
+                    "Invalid Base58 character {character:?} at position {index}"
+                )
+            }
+        }
+    }
+}
+
+impl From<DetailedParsePubkeyError> for ParsePubkeyError {
+    fn from(error: DetailedParsePubkeyError) -> Self {
+        match error {
+            DetailedParsePubkeyError::TooShort { .. }
+            | DetailedParsePubkeyError::TooLong { .. } => Self::WrongSize,
+            DetailedParsePubkeyError::InvalidChar { .. } => Self::Invalid,
+        }
+    }
+}
+
+fn is_base58_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() && !matches!(c, '0' | 'I' | 'O' | 'l')
+}
+
+/// Hash `seeds` into a program address candidate, which is only a valid
+/// address if it is not on the curve.
+#[cfg(all(
+    not(target_os = "solana"),
+    any(feature = "curve25519", feature = "curve25519-fiat")
+))]
+fn program_address_bytes(seeds: &[&[u8]], program_id: &Pubkey) -> [u8; PUBKEY_BYTES] {
+    let mut hasher = solana_sha256_hasher::Hasher::default();
+    for seed in seeds.iter() {
+        hasher.hash(seed);
+    }
+    hasher.hashv(&[program_id.as_ref(), PDA_MARKER]);
+    hasher.result().to_bytes()
+}
+
+/// Check that `seeds` leave room for the bump seed appended by a bump seed
+/// search, returning [`PubkeyError::MaxSeedLengthExceeded`] otherwise.
+#[cfg(all(
+    not(target_os = "solana"),
+    any(feature = "curve25519", feature = "curve25519-fiat")
+))]
+fn check_seeds_before_bump(seeds: &[&[u8]]) -> Result<(), PubkeyError> {
+    // one seed is reserved for the bump
+    if seeds.len() >= MAX_SEEDS || seeds.iter().any(|seed| seed.len() > MAX_SEED_LEN) {
+        return Err(PubkeyError::MaxSeedLengthExceeded);
+    }
+    Ok(())
+}
+
+/// Trim the whitespace that [`Pubkey::from_str_lenient`] ignores.
+fn trim_lenient(s: &str) -> &str {
+    s.trim_matches(|c: char| c.is_whitespace() || matches!(c, '\u{200B}' | '\u{2060}' | '\u{FEFF}'))
+}
+
+/// Decode straight into `out`, with the same errors as [`Pubkey::from_str`].
+#[cfg(feature = "std")]
+fn decode_base58_into(s: &str, out: &mut [u8; PUBKEY_BYTES]) -> Result<(), ParsePubkeyError> {
+    if s.len() > MAX_BASE58_LEN {
+        return Err(ParsePubkeyError::WrongSize);
+    }
+    base58::decode_32(s, out).map_err(|e| match e {
+        Base58Error::InvalidChar => ParsePubkeyError::Invalid,
+        Base58Error::TooShort | Base58Error::TooLong => ParsePubkeyError::WrongSize,
+    })
+}
+
 impl From<Infallible> for ParsePubkeyError {
     fn from(_: Infallible) -> Self {
         unreachable!("Infallible uninhabited");
@@ -382,19 +673,7 @@ impl FromStr for Pubkey {
     type Err = ParsePubkeyError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        use five8::DecodeError;
-        if s.len() > MAX_BASE58_LEN {
-            return Err(ParsePubkeyError::WrongSize);
-        }
-        let mut bytes = [0; PUBKEY_BYTES];
-        five8::decode_32(s, &mut bytes).map_err(|e| match e {
-            DecodeError::InvalidChar(_) => ParsePubkeyError::Invalid,
-            DecodeError::TooLong
-            | DecodeError::TooShort
-            | DecodeError::LargestTermTooHigh
-            | DecodeError::OutputTooLong => ParsePubkeyError::WrongSize,
-        })?;
-        Ok(Pubkey(bytes))
+        Self::from_str_detailed(s).map_err(ParsePubkeyError::from)
     }
 }
 
@@ -441,22 +720,101 @@ impl TryFrom<&str> for Pubkey {
 // If target_os = "solana", then this panics so there are no dependencies.
 // When target_os != "solana", this should be opt-in so users
 // don't need the curve25519 dependency.
-#[cfg(any(target_os = "solana", feature = "curve25519"))]
+/// Return true if the bytes are a point on the ed25519 curve.
+///
+/// The check uses curve25519-dalek with the `curve25519` feature and
+/// fiat-crypto with the `curve25519-fiat` feature. Only one backend is meant
+/// to be enabled, but feature unification can enable both, in which case
+/// `curve25519-fiat` takes precedence. The two backends agree on every input.
+#[cfg(any(
+    target_os = "solana",
+    feature = "curve25519",
+    feature = "curve25519-fiat"
+))]
 #[allow(clippy::used_underscore_binding)]
 pub fn bytes_are_curve_point<T: AsRef<[u8]>>(_bytes: T) -> bool {
     #[cfg(not(target_os = "solana"))]
     {
-        let Ok(compressed_edwards_y) =
-            curve25519_dalek::edwards::CompressedEdwardsY::from_slice(_bytes.as_ref())
-        else {
-            return false;
-        };
-        compressed_edwards_y.decompress().is_some()
+        CurvePointChecker::new().check(_bytes.as_ref())
     }
     #[cfg(target_os = "solana")]
     unimplemented!();
 }
 
+/// Same as [`bytes_are_curve_point`], for many inputs at once.
+///
+/// This is a convenience, not a faster algorithm: each input still costs one
+/// field exponentiation, which cannot be shared between inputs, so the
+/// per-input cost is that of [`bytes_are_curve_point`]. With the `rayon`
+/// feature, `bytes_are_curve_points_par` spreads the work over several
+/// threads.
+#[cfg(all(
+    feature = "std",
+    not(target_os = "solana"),
+    any(feature = "curve25519", feature = "curve25519-fiat")
+))]
+pub fn bytes_are_curve_points<T: AsRef<[u8]>>(inputs: &[T]) -> Vec<bool> {
+    let checker = CurvePointChecker::new();
+    inputs
+        .iter()
+        .map(|bytes| checker.check(bytes.as_ref()))
+        .collect()
+}
+
+/// Same as [`bytes_are_curve_points`], but checks inputs on the rayon thread
+/// pool, so throughput scales with the number of threads rather than with
+/// any per-input saving.
+#[cfg(all(feature = "rayon", not(target_os = "solana")))]
+pub fn bytes_are_curve_points_par<T: AsRef<[u8]> + Sync>(inputs: &[T]) -> Vec<bool> {
+    use rayon::prelude::*;
+    inputs
+        .par_iter()
+        .map_init(CurvePointChecker::new, |checker, bytes| {
+            checker.check(bytes.as_ref())
+        })
+        .collect()
+}
+
+/// Checks whether bytes are a point on the curve with whichever backend is
+/// enabled, preferring fiat-crypto if both are.
+#[cfg(all(
+    not(target_os = "solana"),
+    any(feature = "curve25519", feature = "curve25519-fiat")
+))]
+struct CurvePointChecker {
+    #[cfg(feature = "curve25519-fiat")]
+    fiat: fiat::CurvePointChecker,
+}
+
+#[cfg(all(
+    not(target_os = "solana"),
+    any(feature = "curve25519", feature = "curve25519-fiat")
+))]
+impl CurvePointChecker {
+    fn new() -> Self {
+        Self {
+            #[cfg(feature = "curve25519-fiat")]
+            fiat: fiat::CurvePointChecker::new(),
+        }
+    }
+
+    fn check(&self, bytes: &[u8]) -> bool {
+        #[cfg(feature = "curve25519-fiat")]
+        {
+            self.fiat.check(bytes)
+        }
+        #[cfg(not(feature = "curve25519-fiat"))]
+        {
+            let Ok(compressed_edwards_y) =
+                curve25519_dalek::edwards::CompressedEdwardsY::from_slice(bytes)
+            else {
+                return false;
+            };
+            compressed_edwards_y.decompress().is_some()
+        }
+    }
+}
+
 impl Pubkey {
     pub const fn new_from_array(pubkey_array: [u8; 32]) -> Self {
         Self(pubkey_array)
@@ -468,9 +826,94 @@ impl Pubkey {
         Pubkey::new_from_array(id_array)
     }
 
+    /// Parse a base58 encoded `Pubkey`, like [`Pubkey::from_str`], but
+    /// report the offending character or whether the input is too short or
+    /// too long.
+    pub fn from_str_detailed(s: &str) -> Result<Self, DetailedParsePubkeyError> {
+        Self::decode_detailed(s, base58::decode_32)
+    }
+
+    fn decode_detailed(
+        s: &str,
+        decode_32: impl FnOnce(&str, &mut [u8; PUBKEY_BYTES]) -> Result<(), Base58Error>,
+    ) -> Result<Self, DetailedParsePubkeyError> {
+        let len = s.len();
+        if len > MAX_BASE58_LEN {
+            return Err(DetailedParsePubkeyError::TooLong { len });
+        }
+        let mut bytes = [0; PUBKEY_BYTES];
+        decode_32(s, &mut bytes).map_err(|e| match e {
+            Base58Error::InvalidChar => {
+                // the decoder does not report the character, find it
+                let (index, character) = s
+                    .char_indices()
+                    .find(|(_, c)| !is_base58_char(*c))
+                    .unwrap_or_default();
+                DetailedParsePubkeyError::InvalidChar { index, character }
+            }
+            Base58Error::TooShort => DetailedParsePubkeyError::TooShort { len },
+            Base58Error::TooLong => DetailedParsePubkeyError::TooLong { len },
+        })?;
+        Ok(Pubkey(bytes))
+    }
+
+    /// Parse a base58 encoded `Pubkey`, rejecting any whitespace.
+    ///
+    /// This is what [`Pubkey::from_str`] does, spelled out for call sites
+    /// that want to contrast it with [`Pubkey::from_str_lenient`].
+    pub fn from_str_strict(s: &str) -> Result<Self, ParsePubkeyError> {
+        Self::from_str(s)
+    }
+
+    /// Parse a base58 encoded `Pubkey`, ignoring surrounding whitespace.
+    ///
+    /// Besides ASCII whitespace this trims Unicode spaces, like no-break and
+    /// ideographic spaces, as well as zero width spaces and byte order marks,
+    /// which are easily picked up when copying addresses from web pages and
+    /// documents. Whitespace inside the address is still rejected.
+    pub fn from_str_lenient(s: &str) -> Result<Self, ParsePubkeyError> {
+        Self::from_str(trim_lenient(s))
+    }
+
+    /// Parse a base58 encoded `Pubkey`, like [`Pubkey::from_str`], with the
+    /// given `backend` rather than the one picked at compile time.
+    ///
+    /// Panics with [`Base58Backend::Differential`] if the backends disagree.
+    #[cfg(feature = "bs58")]
+    pub fn from_str_with(backend: Base58Backend, s: &str) -> Result<Self, ParsePubkeyError> {
+        Self::decode_detailed(s, |s, out| base58::decode_32_with(backend, s, out))
+            .map_err(ParsePubkeyError::from)
+    }
+
+    /// Format as base58, like the `Display` impl, with the given `backend`
+    /// rather than the one picked at compile time.
+    ///
+    /// Panics with [`Base58Backend::Differential`] if the backends disagree.
+    #[cfg(feature = "bs58")]
+    pub fn display_with(&self, backend: Base58Backend) -> impl fmt::Display + '_ {
+        struct DisplayWith<'a>(&'a Pubkey, Base58Backend);
+
+        impl fmt::Display for DisplayWith<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                let mut out = [0u8; MAX_BASE58_LEN];
+                let len = base58::encode_32_with(self.1, &self.0 .0, &mut out);
+                // any sequence of base58 chars is valid utf8
+                f.pad(unsafe { from_utf8_unchecked(&out[..len]) })
+            }
+        }
+
+        DisplayWith(self, backend)
+    }
+
     /// unique Pubkey for tests and benchmarks.
+    ///
+    /// See [`Pubkey::unique_scope`] to make the returned keys deterministic.
     pub fn new_unique() -> Self {
         use solana_atomic_u64::AtomicU64;
+        #[cfg(feature = "std")]
+        if let Some(pubkey) = unique::next_in_scope() {
+            return pubkey;
+        }
         static I: AtomicU64 = AtomicU64::new(1);
         type T = u32;
         const COUNTER_BYTES: usize = mem::size_of::<T>();
@@ -513,6 +956,22 @@ impl Pubkey {
         base: &Pubkey,
         seed: &str,
         owner: &Pubkey,
+    ) -> Result<Pubkey, PubkeyError> {
+        Self::create_with_seed_bytes(base, seed.as_bytes(), owner)
+    }
+
+    /// Same as [`create_with_seed`], but accepts any bytes as the seed rather
+    /// than only UTF-8 strings.
+    ///
+    /// For a UTF-8 seed the resulting address is the same as the one returned
+    /// by [`create_with_seed`].
+    ///
+    /// [`create_with_seed`]: Pubkey::create_with_seed
+    #[cfg(any(target_os = "solana", feature = "sha2"))]
+    pub fn create_with_seed_bytes(
+        base: &Pubkey,
+        seed: &[u8],
+        owner: &Pubkey,
     ) -> Result<Pubkey, PubkeyError> {
         if seed.len() > MAX_SEED_LEN {
             return Err(PubkeyError::MaxSeedLengthExceeded);
@@ -525,7 +984,64 @@ impl Pubkey {
                 return Err(PubkeyError::IllegalOwner);
             }
         }
-        let hash = solana_sha256_hasher::hashv(&[base.as_ref(), seed.as_ref(), owner]);
+        let hash = solana_sha256_hasher::hashv(&[base.as_ref(), seed, owner]);
+        Ok(Pubkey::from(hash.to_bytes()))
+    }
+
+    /// Derive an address from a `domain` tag, a `base` address, a list of
+    /// seeds and an `owner`, for off-chain address schemes.
+    ///
+    /// Unlike [`create_with_seed`] and [`find_program_address`], the domain
+    /// and every seed are hashed with their length, so different seed lists
+    /// never map to the same input, e.g. `["abc", "def"]` and `["abcdef"]`
+    /// give different addresses. Different domains give independent
+    /// addresses for the same seeds.
+    ///
+    /// The resulting addresses are not recognized by the runtime, so this
+    /// must not be used for accounts created with the system program's
+    /// `CreateAccountWithSeed`.
+    ///
+    /// Returns [`PubkeyError::MaxSeedLengthExceeded`] if more than
+    /// [`MAX_SEEDS`] seeds are provided, and [`PubkeyError::IllegalOwner`] if
+    /// `owner` ends with the program derived address marker, as
+    /// [`create_with_seed`] does.
+    ///
+    /// [`create_with_seed`]: Pubkey::create_with_seed
+    /// [`find_program_address`]: Pubkey::find_program_address
+    #[cfg(any(target_os = "solana", feature = "sha2"))]
+    pub fn create_with_seed_v2(
+        domain: &[u8],
+        base: &Pubkey,
+        seeds: &[&[u8]],
+        owner: &Pubkey,
+    ) -> Result<Pubkey, PubkeyError> {
+        if seeds.len() > MAX_SEEDS {
+            return Err(PubkeyError::MaxSeedLengthExceeded);
+        }
+        if owner.0.ends_with(PDA_MARKER) {
+            return Err(PubkeyError::IllegalOwner);
+        }
+
+        let domain_len = (domain.len() as u64).to_le_bytes();
+        let num_seeds = (seeds.len() as u64).to_le_bytes();
+        let mut seed_lens = [[0u8; 8]; MAX_SEEDS];
+        for (len, seed) in seed_lens.iter_mut().zip(seeds) {
+            *len = (seed.len() as u64).to_le_bytes();
+        }
+        let mut vals: [&[u8]; 6 + 2 * MAX_SEEDS] = [&[]; 6 + 2 * MAX_SEEDS];
+        vals[..6].copy_from_slice(&[
+            CREATE_WITH_SEED_V2_TAG,
+            &domain_len,
+            domain,
+            base.as_ref(),
+            owner.as_ref(),
+            &num_seeds,
+        ]);
+        for (i, (len, seed)) in seed_lens.iter().zip(seeds).enumerate() {
+            vals[6 + 2 * i] = len;
+            vals[7 + 2 * i] = seed;
+        }
+        let hash = solana_sha256_hasher::hashv(&vals[..6 + 2 * seeds.len()]);
         Ok(Pubkey::from(hash.to_bytes()))
     }
 
@@ -777,7 +1293,11 @@ impl Pubkey {
     // syscalls which bring no dependencies.
     // When target_os != "solana", this should be opt-in so users
     // don't need the curve25519 dependency.
-    #[cfg(any(target_os = "solana", feature = "curve25519"))]
+    #[cfg(any(
+        target_os = "solana",
+        feature = "curve25519",
+        feature = "curve25519-fiat"
+    ))]
     pub fn find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
         Self::try_find_program_address(seeds, program_id)
             .unwrap_or_else(|| panic!("Unable to find a viable program address bump seed"))
@@ -799,7 +1319,11 @@ impl Pubkey {
     // syscalls which bring no dependencies.
     // When target_os != "solana", this should be opt-in so users
     // don't need the curve25519 dependency.
-    #[cfg(any(target_os = "solana", feature = "curve25519"))]
+    #[cfg(any(
+        target_os = "solana",
+        feature = "curve25519",
+        feature = "curve25519-fiat"
+    ))]
     #[allow(clippy::same_item_push)]
     pub fn try_find_program_address(seeds: &[&[u8]], program_id: &Pubkey) -> Option<(Pubkey, u8)> {
         // Perform the calculation inline, calling this from within a program is
@@ -842,6 +1366,45 @@ impl Pubkey {
         }
     }
 
+    /// Same as [`find_program_address`], but tries bump seeds in parallel.
+    ///
+    /// The canonical, i.e. highest valid, bump seed is returned, so the result
+    /// is identical to that of [`find_program_address`].
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`find_program_address`].
+    ///
+    /// [`find_program_address`]: Pubkey::find_program_address
+    #[cfg(all(feature = "rayon", not(target_os = "solana")))]
+    pub fn find_program_address_par(seeds: &[&[u8]], program_id: &Pubkey) -> (Pubkey, u8) {
+        Self::try_find_program_address_par(seeds, program_id)
+            .unwrap_or_else(|| panic!("Unable to find a viable program address bump seed"))
+    }
+
+    /// Same as [`try_find_program_address`], but tries bump seeds in parallel.
+    ///
+    /// [`try_find_program_address`]: Pubkey::try_find_program_address
+    #[cfg(all(feature = "rayon", not(target_os = "solana")))]
+    pub fn try_find_program_address_par(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Option<(Pubkey, u8)> {
+        use rayon::prelude::*;
+
+        check_seeds_before_bump(seeds).ok()?;
+        (1..=u8::MAX)
+            .into_par_iter()
+            .rev()
+            .find_map_first(|bump_seed| {
+                let mut seeds_with_bump = seeds.to_vec();
+                seeds_with_bump.push(core::slice::from_ref(&bump_seed));
+                Self::create_program_address(&seeds_with_bump, program_id)
+                    .ok()
+                    .map(|address| (address, bump_seed))
+            })
+    }
+
     /// Create a valid [program derived address][pda] without searching for a bump seed.
     ///
     /// [pda]: https://solana.com/docs/core/cpi#program-derived-addresses
@@ -888,36 +1451,22 @@ impl Pubkey {
     // syscalls which bring no dependencies.
     // When target_os != "solana", this should be opt-in so users
     // don't need the curve225519 dep.
-    #[cfg(any(target_os = "solana", feature = "curve25519"))]
+    #[cfg(any(
+        target_os = "solana",
+        feature = "curve25519",
+        feature = "curve25519-fiat"
+    ))]
     pub fn create_program_address(
         seeds: &[&[u8]],
         program_id: &Pubkey,
     ) -> Result<Pubkey, PubkeyError> {
-        if seeds.len() > MAX_SEEDS {
-            return Err(PubkeyError::MaxSeedLengthExceeded);
-        }
-        for seed in seeds.iter() {
-            if seed.len() > MAX_SEED_LEN {
-                return Err(PubkeyError::MaxSeedLengthExceeded);
-            }
-        }
+        Self::validate_seeds(seeds)?;
 
         // Perform the calculation inline, calling this from within a program is
         // not supported
         #[cfg(not(target_os = "solana"))]
         {
-            let mut hasher = solana_sha256_hasher::Hasher::default();
-            for seed in seeds.iter() {
-                hasher.hash(seed);
-            }
-            hasher.hashv(&[program_id.as_ref(), PDA_MARKER]);
-            let hash = hasher.result();
-
-            if bytes_are_curve_point(hash) {
-                return Err(PubkeyError::InvalidSeeds);
-            }
-
-            Ok(Pubkey::from(hash.to_bytes()))
+            Self::create_program_address_unchecked(seeds, program_id)
         }
         // Call via a system call to perform the calculation
         #[cfg(target_os = "solana")]
@@ -938,54 +1487,367 @@ impl Pubkey {
         }
     }
 
-    pub const fn to_bytes(self) -> [u8; 32] {
-        self.0
+    /// Check that `seeds` are within [`MAX_SEEDS`] and [`MAX_SEED_LEN`], the
+    /// limits that [`create_program_address`] enforces, without deriving an
+    /// address.
+    ///
+    /// Returns [`PubkeyError::MaxSeedLengthExceeded`] if they are not. This
+    /// is cheap enough to validate user supplied seeds before building an
+    /// instruction. Seeds passed to [`find_program_address`] must leave room
+    /// for the bump seed, so there may be at most `MAX_SEEDS - 1` of them.
+    ///
+    /// ```
+    /// # use solana_pubkey::{Pubkey, PubkeyError, MAX_SEED_LEN};
+    /// assert_eq!(Pubkey::validate_seeds(&[b"vault", &[0; MAX_SEED_LEN]]), Ok(()));
+    /// assert_eq!(
+    ///     Pubkey::validate_seeds(&[&[0; MAX_SEED_LEN + 1]]),
+    ///     Err(PubkeyError::MaxSeedLengthExceeded)
+    /// );
+    /// ```
+    ///
+    /// [`create_program_address`]: Pubkey::create_program_address
+    /// [`find_program_address`]: Pubkey::find_program_address
+    pub fn validate_seeds(seeds: &[&[u8]]) -> Result<(), PubkeyError> {
+        check_seeds_detailed(seeds).map_err(PubkeyError::from)
     }
 
-    /// Return a reference to the `Pubkey`'s byte array.
-    #[inline(always)]
-    pub const fn as_array(&self) -> &[u8; 32] {
-        &self.0
-    }
+    /// Same as [`create_program_address`], but without the limits on the
+    /// number and length of the seeds.
+    ///
+    /// This is meant for tools that study derivations a program could not
+    /// perform, or derive addresses under proposed future limits. The runtime
+    /// enforces [`MAX_SEEDS`] and [`MAX_SEED_LEN`], so an address derived from
+    /// seeds beyond them can not be signed for by any program. Only available
+    /// off-chain.
+    ///
+    /// [`create_program_address`]: Pubkey::create_program_address
+    #[cfg(all(
+        not(target_os = "solana"),
+        any(feature = "curve25519", feature = "curve25519-fiat")
+    ))]
+    pub fn create_program_address_unchecked(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<Pubkey, PubkeyError> {
+        let bytes = program_address_bytes(seeds, program_id);
 
-    // If target_os = "solana", then this panics so there are no dependencies.
-    // When target_os != "solana", this should be opt-in so users
-    // don't need the curve25519 dependency.
-    #[cfg(any(target_os = "solana", feature = "curve25519"))]
-    pub fn is_on_curve(&self) -> bool {
-        bytes_are_curve_point(self)
+        if bytes_are_curve_point(bytes) {
+            return Err(PubkeyError::InvalidSeeds);
+        }
+
+        Ok(Pubkey::from(bytes))
     }
 
-    /// Log a `Pubkey` from a program
-    pub fn log(&self) {
-        #[cfg(target_os = "solana")]
-        unsafe {
-            crate::syscalls::sol_log_pubkey(self.as_ref() as *const _ as *const u8)
-        };
+    /// Same as [`create_with_seed`], but usable in a const context.
+    ///
+    /// # Panics
+    ///
+    /// Panics, or fails to compile when evaluated at compile time, in the
+    /// cases where [`create_with_seed`] would return an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use solana_pubkey::{pubkey, Pubkey};
+    /// const BASE: Pubkey = pubkey!("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq");
+    /// const OWNER: Pubkey = pubkey!("Stake11111111111111111111111111111111111111");
+    /// const ALIAS: Pubkey = Pubkey::create_with_seed_const(&BASE, "stake:0", &OWNER);
+    /// ```
+    ///
+    /// [`create_with_seed`]: Pubkey::create_with_seed
+    #[cfg(feature = "sha2-const")]
+    pub const fn create_with_seed_const(base: &Pubkey, seed: &str, owner: &Pubkey) -> Pubkey {
+        if seed.len() > MAX_SEED_LEN {
+            panic!("Length of the seed is too long for address generation");
+        }
 
-        #[cfg(all(not(target_os = "solana"), feature = "std"))]
-        std::println!("{}", std::string::ToString::to_string(&self));
-    }
-}
+        let marker_offset = PUBKEY_BYTES - PDA_MARKER.len();
+        let mut i = 0;
+        while i < PDA_MARKER.len() && owner.0[marker_offset + i] == PDA_MARKER[i] {
+            i += 1;
+        }
+        if i == PDA_MARKER.len() {
+            panic!("Provided owner is not allowed");
+        }
 
-impl AsRef<[u8]> for Pubkey {
-    fn as_ref(&self) -> &[u8] {
-        &self.0[..]
+        let hash = sha2_const_stable::Sha256::new()
+            .update(&base.0)
+            .update(seed.as_bytes())
+            .update(&owner.0)
+            .finalize();
+        Pubkey(hash)
     }
-}
 
-impl AsMut<[u8]> for Pubkey {
-    fn as_mut(&mut self) -> &mut [u8] {
-        &mut self.0[..]
+    /// Derive the associated token account address of `wallet` for `mint`.
+    ///
+    /// This is the program derived address of the associated token account
+    /// program for the seeds `[wallet, token_program, mint]`, and matches
+    /// `get_associated_token_address_with_program_id` from the
+    /// `spl-associated-token-account` crate.
+    #[cfg(feature = "associated-address")]
+    pub fn derive_associated_address(
+        wallet: &Pubkey,
+        token_program: &Pubkey,
+        mint: &Pubkey,
+    ) -> Pubkey {
+        Self::derive_associated_address_with_bump(
+            wallet,
+            token_program,
+            mint,
+            &ASSOCIATED_TOKEN_PROGRAM_ID,
+        )
+        .0
     }
-}
 
-fn write_as_base58(f: &mut fmt::Formatter, p: &Pubkey) -> fmt::Result {
-    let mut out = [0u8; MAX_BASE58_LEN];
-    let len = five8::encode_32(&p.0, &mut out) as usize;
+    /// Same as [`derive_associated_address`], but derives under the given
+    /// `program_id` and also returns the bump seed.
+    ///
+    /// [`derive_associated_address`]: Pubkey::derive_associated_address
+    #[cfg(feature = "associated-address")]
+    pub fn derive_associated_address_with_bump(
+        wallet: &Pubkey,
+        token_program: &Pubkey,
+        mint: &Pubkey,
+        program_id: &Pubkey,
+    ) -> (Pubkey, u8) {
+        Self::find_program_address(
+            &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+            program_id,
+        )
+    }
+
+    /// Same as [`create_with_seed`], but reports the actual seed length on
+    /// failure.
+    ///
+    /// [`create_with_seed`]: Pubkey::create_with_seed
+    #[cfg(any(target_os = "solana", feature = "sha2"))]
+    pub fn try_create_with_seed_detailed(
+        base: &Pubkey,
+        seed: &str,
+        owner: &Pubkey,
+    ) -> Result<Pubkey, DetailedPubkeyError> {
+        if seed.len() > MAX_SEED_LEN {
+            return Err(DetailedPubkeyError::MaxSeedLengthExceeded {
+                index: 0,
+                len: seed.len(),
+            });
+        }
+        Self::create_with_seed(base, seed, owner).map_err(|err| match err {
+            PubkeyError::IllegalOwner => DetailedPubkeyError::IllegalOwner,
+            PubkeyError::InvalidSeeds => DetailedPubkeyError::InvalidSeeds,
+            PubkeyError::MaxSeedLengthExceeded => unreachable!("seed length already checked"),
+        })
+    }
+
+    /// Same as [`create_program_address`], but reports which seed is invalid
+    /// on failure.
+    ///
+    /// [`create_program_address`]: Pubkey::create_program_address
+    #[cfg(any(
+        target_os = "solana",
+        feature = "curve25519",
+        feature = "curve25519-fiat"
+    ))]
+    pub fn try_create_program_address_detailed(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<Pubkey, DetailedPubkeyError> {
+        check_seeds_detailed(seeds)?;
+        Self::create_program_address(seeds, program_id).map_err(|err| match err {
+            PubkeyError::IllegalOwner => DetailedPubkeyError::IllegalOwner,
+            _ => DetailedPubkeyError::InvalidSeeds,
+        })
+    }
+
+    /// New random Pubkey drawn from the given RNG.
+    ///
+    /// Unlike [`new_rand`], this works without `std`, and with a seeded RNG
+    /// yields the same keys on every run.
+    ///
+    /// [`new_rand`]: crate::new_rand
+    #[cfg(feature = "rand-core")]
+    pub fn new_rand_with<R: rand_core::RngCore + ?Sized>(rng: &mut R) -> Self {
+        let mut bytes = [0; PUBKEY_BYTES];
+        rng.fill_bytes(&mut bytes);
+        Self(bytes)
+    }
+
+    pub const fn to_bytes(self) -> [u8; 32] {
+        self.0
+    }
+
+    /// Return a reference to the `Pubkey`'s byte array.
+    #[inline(always)]
+    pub const fn as_array(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Reinterpret a reference to a byte array as a reference to a `Pubkey`,
+    /// without copying.
+    ///
+    /// Lets maps and sets keyed by `Pubkey` be queried with raw bytes, e.g.
+    /// read from account data:
+    ///
+    /// ```
+    /// # use solana_pubkey::Pubkey;
+    /// # use std::collections::HashMap;
+    /// let key = Pubkey::new_unique();
+    /// let balances = HashMap::from([(key, 42)]);
+    /// let bytes: &[u8; 32] = key.as_array();
+    /// assert_eq!(balances.get(Pubkey::from_array_ref(bytes)), Some(&42));
+    /// ```
+    #[inline(always)]
+    pub const fn from_array_ref(bytes: &[u8; 32]) -> &Pubkey {
+        // Safe because `Pubkey` is `repr(transparent)` over `[u8; 32]`
+        unsafe { &*(bytes as *const [u8; 32] as *const Pubkey) }
+    }
+
+    /// Map the `Pubkey` to one of `n_shards` shards by its leading bytes.
+    ///
+    /// Shards cover contiguous, near equal ranges of the key space in
+    /// ascending order, see [`Pubkey::shard_range`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n_shards` is zero.
+    pub fn partition(&self, n_shards: usize) -> usize {
+        assert!(n_shards > 0, "n_shards must be non-zero");
+        ((u128::from(self.shard_prefix()) * n_shards as u128) >> u64::BITS) as usize
+    }
+
+    /// Return the range of keys that [`Pubkey::partition`] maps to `shard`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shard` is not less than `n_shards`.
+    pub fn shard_range(shard: usize, n_shards: usize) -> RangeInclusive<Pubkey> {
+        assert!(shard < n_shards, "shard must be less than n_shards");
+        // the smallest prefix p such that p * n_shards >= shard * 2^64
+        let first_prefix =
+            |shard: usize| ((shard as u128) << u64::BITS).div_ceil(n_shards as u128) as u64;
+        let start = first_prefix(shard);
+        let end = if shard + 1 == n_shards {
+            u64::MAX
+        } else {
+            first_prefix(shard + 1) - 1
+        };
+        let with_prefix = |prefix: u64, fill: u8| {
+            let mut bytes = [fill; PUBKEY_BYTES];
+            bytes[..8].copy_from_slice(&prefix.to_be_bytes());
+            Pubkey(bytes)
+        };
+        with_prefix(start, 0)..=with_prefix(end, u8::MAX)
+    }
+
+    fn shard_prefix(&self) -> u64 {
+        u64::from_be_bytes(self.0[..8].try_into().unwrap())
+    }
+
+    /// Return the smallest and the largest key starting with `prefix`.
+    ///
+    /// Since keys are ordered by their bytes, every key starting with
+    /// `prefix` is within this inclusive range and every key within it starts
+    /// with `prefix`, so a range scan over it visits exactly those keys.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `prefix` is longer than [`PUBKEY_BYTES`].
+    pub fn prefix_range(prefix: &[u8]) -> (Pubkey, Pubkey) {
+        assert!(
+            prefix.len() <= PUBKEY_BYTES,
+            "prefix must not be longer than a pubkey"
+        );
+        let with_fill = |fill: u8| {
+            let mut bytes = [fill; PUBKEY_BYTES];
+            bytes[..prefix.len()].copy_from_slice(prefix);
+            Pubkey(bytes)
+        };
+        (with_fill(0), with_fill(u8::MAX))
+    }
+
+    /// Return the next key, treating keys as big endian 256-bit integers, or
+    /// `None` if `self` is the largest key.
+    pub fn checked_increment(&self) -> Option<Pubkey> {
+        let mut bytes = self.0;
+        // trailing 0xff bytes wrap around to zero and carry into the next one
+        let last = bytes.iter().rposition(|byte| *byte != u8::MAX)?;
+        bytes[last] += 1;
+        bytes[last + 1..].fill(0);
+        Some(Pubkey(bytes))
+    }
+
+    /// Return the previous key, treating keys as big endian 256-bit
+    /// integers, or `None` if `self` is the smallest key.
+    pub fn checked_decrement(&self) -> Option<Pubkey> {
+        let mut bytes = self.0;
+        // trailing zero bytes wrap around to 0xff and borrow from the next one
+        let last = bytes.iter().rposition(|byte| *byte != 0)?;
+        bytes[last] -= 1;
+        bytes[last + 1..].fill(u8::MAX);
+        Some(Pubkey(bytes))
+    }
+
+    // If target_os = "solana", then this panics so there are no dependencies.
+    // When target_os != "solana", this should be opt-in so users
+    // don't need the curve25519 dependency.
+    /// Return true if the pubkey is a point on the ed25519 curve, which a
+    /// program derived address never is.
+    ///
+    /// If both the `curve25519` and `curve25519-fiat` features are enabled,
+    /// the fiat-crypto backend takes precedence, see
+    /// [`bytes_are_curve_point`].
+    #[cfg(any(
+        target_os = "solana",
+        feature = "curve25519",
+        feature = "curve25519-fiat"
+    ))]
+    pub fn is_on_curve(&self) -> bool {
+        bytes_are_curve_point(self)
+    }
+
+    /// Log a `Pubkey` from a program
+    pub fn log(&self) {
+        #[cfg(target_os = "solana")]
+        unsafe {
+            crate::syscalls::sol_log_pubkey(self.as_ref() as *const _ as *const u8)
+        };
+
+        #[cfg(all(not(target_os = "solana"), feature = "std"))]
+        std::println!("{}", std::string::ToString::to_string(&self));
+    }
+}
+
+impl AsRef<[u8]> for Pubkey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0[..]
+    }
+}
+
+impl AsMut<[u8]> for Pubkey {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0[..]
+    }
+}
+
+fn encode_base58<'a>(p: &Pubkey, out: &'a mut [u8; MAX_BASE58_LEN]) -> &'a str {
+    let len = base58::encode_32(&p.0, out);
     // any sequence of base58 chars is valid utf8
-    let as_str = unsafe { from_utf8_unchecked(&out[..len]) };
-    f.write_str(as_str)
+    unsafe { from_utf8_unchecked(&out[..len]) }
+}
+
+// `pad` applies any width, fill, alignment and precision flags, e.g.
+// `{:>48}` or `{:.8}`
+fn write_as_base58(f: &mut fmt::Formatter, p: &Pubkey) -> fmt::Result {
+    let mut out = [0u8; MAX_BASE58_LEN];
+    f.pad(encode_base58(p, &mut out))
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Pubkey {
+    fn format(&self, f: defmt::Formatter) {
+        let mut out = [0u8; MAX_BASE58_LEN];
+        defmt::write!(f, "{=str}", encode_base58(self, &mut out))
+    }
 }
 
 impl fmt::Debug for Pubkey {
@@ -1000,9 +1862,103 @@ impl fmt::Display for Pubkey {
     }
 }
 
+/// Serializes as a base58 string for human-readable formats (e.g. JSON, YAML),
+/// and as the same fixed 32-byte encoding as the derived impl for binary
+/// formats (e.g. bincode), so the wire format is unchanged.
+#[cfg(feature = "serde-human-readable")]
+impl serde::Serialize for Pubkey {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            let mut out = [0u8; MAX_BASE58_LEN];
+            serializer.serialize_str(encode_base58(self, &mut out))
+        } else {
+            serializer.serialize_newtype_struct("Pubkey", &self.0)
+        }
+    }
+}
+
+#[cfg(feature = "serde-human-readable")]
+impl<'de> serde::Deserialize<'de> for Pubkey {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            struct Base58Visitor;
+
+            impl serde::de::Visitor<'_> for Base58Visitor {
+                type Value = Pubkey;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    f.write_str("a base58 encoded pubkey")
+                }
+
+                fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Pubkey, E> {
+                    Pubkey::from_str(v).map_err(E::custom)
+                }
+            }
+
+            deserializer.deserialize_str(Base58Visitor)
+        } else {
+            #[derive(Deserialize)]
+            #[serde(rename = "Pubkey")]
+            struct Raw([u8; 32]);
+
+            Raw::deserialize(deserializer).map(|Raw(bytes)| Pubkey(bytes))
+        }
+    }
+}
+
+/// Describes the JSON produced by `Pubkey`'s serde implementation: a base58
+/// string with the `serde-human-readable` feature, and an array of 32 bytes
+/// otherwise.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Pubkey {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Pubkey".into()
+    }
+
+    fn schema_id() -> std::borrow::Cow<'static, str> {
+        "solana_pubkey::Pubkey".into()
+    }
+
+    #[cfg(feature = "serde-human-readable")]
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "A base58 encoded 32 byte address",
+            "type": "string",
+            "format": "base58",
+            "pattern": "^[1-9A-HJ-NP-Za-km-z]{32,44}$",
+        })
+    }
+
+    #[cfg(not(feature = "serde-human-readable"))]
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        <[u8; PUBKEY_BYTES]>::json_schema(generator)
+    }
+}
+
+/// A type that identifies a program at compile time.
+///
+/// Implemented by the marker types generated by [`declare_id`], so that APIs
+/// can accept a `P: ProgramId` generic instead of a raw program ID.
+pub trait ProgramId {
+    /// The const program ID.
+    const ID: Pubkey;
+
+    /// Returns the program ID.
+    fn id() -> Pubkey {
+        Self::ID
+    }
+
+    /// Returns `true` if given pubkey is the program ID.
+    fn check_id(id: &Pubkey) -> bool {
+        id == &Self::ID
+    }
+}
+
 /// Convenience macro to declare a static public key and functions to interact with it.
 ///
-/// Input: a single literal base58 string representation of a program's ID.
+/// Input: a single literal base58 string representation of a program's ID,
+/// optionally followed by the name of a zero-sized marker type to generate,
+/// which implements [`ProgramId`].
 ///
 /// # Example
 ///
@@ -1021,8 +1977,36 @@ impl fmt::Display for Pubkey {
 /// let my_id = Pubkey::from_str("My11111111111111111111111111111111111111111").unwrap();
 /// assert_eq!(id(), my_id);
 /// ```
+///
+/// With a marker type:
+///
+/// ```
+/// # mod item_wrapper {
+/// #   use solana_pubkey::declare_id;
+/// declare_id!("My11111111111111111111111111111111111111111", MyProgram);
+/// # }
+/// # use item_wrapper::{id, MyProgram};
+/// use solana_pubkey::{ProgramId, Pubkey};
+///
+/// fn program_id<P: ProgramId>() -> Pubkey {
+///     P::ID
+/// }
+///
+/// assert_eq!(program_id::<MyProgram>(), id());
+/// ```
 #[macro_export]
 macro_rules! declare_id {
+    ($address:expr, $program:ident $(,)?) => {
+        $crate::declare_id!($address);
+
+        /// Marker type for the program ID.
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+        pub struct $program;
+
+        impl $crate::ProgramId for $program {
+            const ID: $crate::Pubkey = ID;
+        }
+    };
     ($address:expr) => {
         /// The const program ID.
         pub const ID: $crate::Pubkey = $crate::Pubkey::from_str_const($address);
@@ -1077,9 +2061,113 @@ macro_rules! declare_deprecated_id {
     };
 }
 
+/// The cluster a program is deployed to, as used by [`declare_id_per_cluster`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cluster {
+    Mainnet,
+    Devnet,
+    Testnet,
+    Localnet,
+}
+
+/// Same as [`declare_id`] except that it accepts a different ID per cluster.
+///
+/// `ID` and `id()` resolve to the ID of the cluster selected by the invoking
+/// crate's cargo features: `localnet`, `testnet` or `devnet`, in that order of
+/// precedence, falling back to `mainnet` if none is enabled. Every ID is also
+/// available at runtime through `id_for(Cluster)`.
+///
+/// # Example
+///
+/// ```
+/// # // wrapper is used so that the macro invocation occurs in the item position
+/// # // rather than in the statement position which isn't allowed.
+/// use std::str::FromStr;
+/// use solana_pubkey::{declare_id_per_cluster, Cluster, Pubkey};
+///
+/// # mod item_wrapper {
+/// #   use solana_pubkey::declare_id_per_cluster;
+/// declare_id_per_cluster! {
+///     mainnet: "Main111111111111111111111111111111111111111",
+///     devnet: "Dev1111111111111111111111111111111111111111",
+///     testnet: "Test111111111111111111111111111111111111111",
+///     localnet: "Loca111111111111111111111111111111111111111",
+/// }
+/// # }
+/// # use item_wrapper::{id, id_for};
+///
+/// let devnet_id = Pubkey::from_str("Dev1111111111111111111111111111111111111111").unwrap();
+/// assert_eq!(id_for(Cluster::Devnet), devnet_id);
+/// assert_eq!(id(), id_for(Cluster::Mainnet));
+/// ```
+#[macro_export]
+macro_rules! declare_id_per_cluster {
+    (
+        mainnet: $mainnet:expr,
+        devnet: $devnet:expr,
+        testnet: $testnet:expr,
+        localnet: $localnet:expr $(,)?
+    ) => {
+        /// The const program ID on mainnet.
+        pub const MAINNET_ID: $crate::Pubkey = $crate::Pubkey::from_str_const($mainnet);
+        /// The const program ID on devnet.
+        pub const DEVNET_ID: $crate::Pubkey = $crate::Pubkey::from_str_const($devnet);
+        /// The const program ID on testnet.
+        pub const TESTNET_ID: $crate::Pubkey = $crate::Pubkey::from_str_const($testnet);
+        /// The const program ID on localnet.
+        pub const LOCALNET_ID: $crate::Pubkey = $crate::Pubkey::from_str_const($localnet);
+
+        /// The cluster selected by cargo features.
+        #[allow(unexpected_cfgs)]
+        pub const CLUSTER: $crate::Cluster = if cfg!(feature = "localnet") {
+            $crate::Cluster::Localnet
+        } else if cfg!(feature = "testnet") {
+            $crate::Cluster::Testnet
+        } else if cfg!(feature = "devnet") {
+            $crate::Cluster::Devnet
+        } else {
+            $crate::Cluster::Mainnet
+        };
+
+        /// The const program ID of the selected cluster.
+        pub const ID: $crate::Pubkey = id_for(CLUSTER);
+
+        /// Returns the program ID on the given cluster.
+        pub const fn id_for(cluster: $crate::Cluster) -> $crate::Pubkey {
+            match cluster {
+                $crate::Cluster::Mainnet => MAINNET_ID,
+                $crate::Cluster::Devnet => DEVNET_ID,
+                $crate::Cluster::Testnet => TESTNET_ID,
+                $crate::Cluster::Localnet => LOCALNET_ID,
+            }
+        }
+
+        /// Returns `true` if given pubkey is the program ID.
+        pub fn check_id(id: &$crate::Pubkey) -> bool {
+            id == &ID
+        }
+
+        /// Returns the program ID.
+        pub const fn id() -> $crate::Pubkey {
+            ID
+        }
+
+        #[cfg(test)]
+        #[test]
+        fn test_id() {
+            assert!(check_id(&id()));
+        }
+    };
+}
+
 /// Convenience macro to define a static public key.
 ///
-/// Input: a single literal base58 string representation of a Pubkey.
+/// Input: one of
+///
+/// - a single literal base58 string representation of a Pubkey,
+/// - an `env!("VAR")` invocation naming a compile-time environment variable
+///   that holds the base58 string,
+/// - a `[u8; 32]` array literal.
 ///
 /// # Example
 ///
@@ -1091,18 +2179,139 @@ macro_rules! declare_deprecated_id {
 ///
 /// let my_id = Pubkey::from_str("My11111111111111111111111111111111111111111").unwrap();
 /// assert_eq!(ID, my_id);
+///
+/// static ZEROES: Pubkey = pubkey!([0; 32]);
+/// assert_eq!(ZEROES, Pubkey::default());
+/// ```
+///
+/// Injecting the ID at build time, e.g. with `MY_PROGRAM_ID=... cargo build`:
+///
+/// ```ignore
+/// static ID: Pubkey = pubkey!(env!("MY_PROGRAM_ID"));
 /// ```
 #[macro_export]
 macro_rules! pubkey {
     ($input:literal) => {
         $crate::Pubkey::from_str_const($input)
     };
+    (env!($var:literal)) => {
+        $crate::Pubkey::from_str_const(env!($var))
+    };
+    ([$($bytes:tt)*]) => {
+        $crate::Pubkey::new_from_array([$($bytes)*])
+    };
+}
+
+/// Convenience macro to define a program derived address as a static that is
+/// derived on first use.
+///
+/// Input: the seeds, each of which must implement `AsRef<[u8]>`, followed by
+/// the program id. Expands to a `LazyLock<(Pubkey, u8)>` holding the result
+/// of [`Pubkey::find_program_address`].
+///
+/// # Example
+///
+/// ```
+/// use {
+///     solana_pubkey::{lazy_pda, pubkey, Pubkey},
+///     std::sync::LazyLock,
+/// };
+///
+/// const PROGRAM_ID: Pubkey = pubkey!("My11111111111111111111111111111111111111111");
+/// static CONFIG: LazyLock<(Pubkey, u8)> = lazy_pda!(b"config", b"v1", PROGRAM_ID);
+///
+/// let (address, bump) = *CONFIG;
+/// assert_eq!(
+///     (address, bump),
+///     Pubkey::find_program_address(&[b"config", b"v1"], &PROGRAM_ID)
+/// );
+/// ```
+#[cfg(all(
+    feature = "std",
+    any(
+        target_os = "solana",
+        feature = "curve25519",
+        feature = "curve25519-fiat"
+    )
+))]
+#[macro_export]
+macro_rules! lazy_pda {
+    ($($args:expr),+ $(,)?) => {
+        $crate::__lazy_pda!([] $($args),+)
+    };
+}
+
+// Splits the arguments of `lazy_pda!` into the seeds and the trailing program
+// id, since a repetition cannot be followed by a single fragment.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __lazy_pda {
+    ([$($seeds:expr),*] $program_id:expr) => {
+        ::std::sync::LazyLock::new(|| {
+            $crate::Pubkey::find_program_address(
+                &[$(::core::convert::AsRef::<[u8]>::as_ref(&$seeds)),*],
+                &$program_id,
+            )
+        })
+    };
+    ([$($seeds:expr),*] $seed:expr, $($rest:expr),+) => {
+        $crate::__lazy_pda!([$($seeds,)* $seed] $($rest),+)
+    };
+}
+
+/// Parse many base58 encoded pubkeys at once.
+///
+/// The pubkeys are decoded in place into a single allocation. On failure, the
+/// index of the first string that is not a valid pubkey is returned along
+/// with the error [`Pubkey::from_str`] would have returned for it.
+///
+/// ```
+/// # use solana_pubkey::{decode_many, ParsePubkeyError};
+/// let keys = decode_many(&[
+///     "11111111111111111111111111111111",
+///     "SysvarC1ock11111111111111111111111111111111",
+/// ])?;
+/// assert_eq!(keys.len(), 2);
+///
+/// assert_eq!(
+///     decode_many(&["11111111111111111111111111111111", "not a pubkey"]),
+///     Err((1, ParsePubkeyError::Invalid))
+/// );
+/// # Ok::<(), (usize, ParsePubkeyError)>(())
+/// ```
+#[cfg(feature = "std")]
+pub fn decode_many(strs: &[&str]) -> Result<Vec<Pubkey>, (usize, ParsePubkeyError)> {
+    let mut pubkeys = std::vec![Pubkey::default(); strs.len()];
+    for (i, (pubkey, s)) in pubkeys.iter_mut().zip(strs).enumerate() {
+        decode_base58_into(s, &mut pubkey.0).map_err(|e| (i, e))?;
+    }
+    Ok(pubkeys)
+}
+
+/// Same as [`decode_many`], but decodes on the rayon thread pool.
+///
+/// The reported error is still the one of the first invalid string.
+#[cfg(all(feature = "rayon", not(target_os = "solana")))]
+pub fn decode_many_par(strs: &[&str]) -> Result<Vec<Pubkey>, (usize, ParsePubkeyError)> {
+    use rayon::prelude::*;
+    let mut pubkeys = std::vec![Pubkey::default(); strs.len()];
+    let error = pubkeys
+        .par_iter_mut()
+        .zip(strs)
+        .enumerate()
+        .find_map_first(|(i, (pubkey, s))| {
+            decode_base58_into(s, &mut pubkey.0).err().map(|e| (i, e))
+        });
+    match error {
+        Some(error) => Err(error),
+        None => Ok(pubkeys),
+    }
 }
 
 /// New random Pubkey for tests and benchmarks.
 #[cfg(all(feature = "rand", not(target_os = "solana")))]
 pub fn new_rand() -> Pubkey {
-    Pubkey::from(rand::random::<[u8; PUBKEY_BYTES]>())
+    Pubkey::new_rand_with(&mut rand::thread_rng())
 }
 
 #[cfg(test)]
@@ -1154,6 +2363,47 @@ mod tests {
         assert_eq!(too_long.parse::<Pubkey>(), Err(ParsePubkeyError::WrongSize));
     }
 
+    #[test]
+    fn test_decode_many() {
+        use std::string::{String, ToString};
+
+        let keys = (0..100).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let strs = keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+        let mut strs = strs.iter().map(String::as_str).collect::<Vec<_>>();
+        assert_eq!(decode_many(&strs), Ok(keys.clone()));
+        assert_eq!(decode_many(&[]), Ok(Vec::new()));
+
+        let too_long = "1".repeat(MAX_BASE58_LEN + 1);
+        strs[70] = "0";
+        strs[40] = &too_long;
+        assert_eq!(decode_many(&strs), Err((40, ParsePubkeyError::WrongSize)));
+        strs[10] = "I";
+        assert_eq!(decode_many(&strs), Err((10, ParsePubkeyError::Invalid)));
+        for s in strs {
+            if let Err(e) = s.parse::<Pubkey>() {
+                assert_eq!(decode_many(&[s]), Err((0, e)));
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_decode_many_par() {
+        use std::string::{String, ToString};
+
+        let keys = (0..10_000)
+            .map(|_| Pubkey::new_unique())
+            .collect::<Vec<_>>();
+        let strs = keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+        let mut strs = strs.iter().map(String::as_str).collect::<Vec<_>>();
+        assert_eq!(decode_many_par(&strs), Ok(keys));
+
+        for i in [9_999, 5_000, 1_234, 0] {
+            strs[i] = "l";
+            assert_eq!(decode_many_par(&strs), Err((i, ParsePubkeyError::Invalid)));
+        }
+    }
+
     #[test]
     fn test_create_with_seed() {
         assert!(
@@ -1210,6 +2460,95 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_create_with_seed_bytes() {
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        assert_eq!(
+            Pubkey::create_with_seed_bytes(&base, b"limber chicken", &owner),
+            Pubkey::create_with_seed(&base, "limber chicken", &owner)
+        );
+        // not valid utf-8
+        assert!(Pubkey::create_with_seed_bytes(&base, &[0xff; MAX_SEED_LEN], &owner).is_ok());
+        assert_eq!(
+            Pubkey::create_with_seed_bytes(&base, &[0xff; MAX_SEED_LEN + 1], &owner),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+    }
+
+    #[test]
+    fn test_create_with_seed_v2() {
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let create = |domain: &[u8], seeds: &[&[u8]]| {
+            Pubkey::create_with_seed_v2(domain, &base, seeds, &owner).unwrap()
+        };
+
+        let address = create(b"wallet", &[b"abc", b"def"]);
+        assert_eq!(address, create(b"wallet", &[b"abc", b"def"]));
+        assert_ne!(address, create(b"wallet", &[b"abcdef"]));
+        assert_ne!(address, create(b"wallet", &[b"ab", b"cdef"]));
+        assert_ne!(address, create(b"wallet", &[b"abc", b"def", b""]));
+        assert_ne!(address, create(b"walletabc", &[b"def"]));
+        assert_ne!(address, create(b"vault", &[b"abc", b"def"]));
+        assert_ne!(create(b"", &[]), create(b"", &[b""]));
+
+        // pins the hashed layout: tag, length prefixed domain, base, owner,
+        // seed count and length prefixed seeds
+        let golden_base = Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq").unwrap();
+        let golden_owner = Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        assert_eq!(
+            Pubkey::create_with_seed_v2(b"wallet", &golden_base, &[b"abc", b"def"], &golden_owner),
+            Ok(Pubkey::from_str("67EsNPbQcYK6jsw1Gf4wowpA6gKCWWrTBMi1gxkGfWNg").unwrap())
+        );
+        assert_eq!(
+            Pubkey::create_with_seed_v2(b"", &golden_base, &[], &golden_owner),
+            Ok(Pubkey::from_str("DffubMYVp82e57rjmhMSfnqxZMaFjGX8QjpYNVaEBa36").unwrap())
+        );
+
+        let seeds = [&[0u8][..]; MAX_SEEDS];
+        assert!(Pubkey::create_with_seed_v2(b"", &base, &seeds, &owner).is_ok());
+        let seeds = [&[0u8][..]; MAX_SEEDS + 1];
+        assert_eq!(
+            Pubkey::create_with_seed_v2(b"", &base, &seeds, &owner),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+        let mut pda_owner = [0; PUBKEY_BYTES];
+        pda_owner[PUBKEY_BYTES - PDA_MARKER.len()..].copy_from_slice(PDA_MARKER);
+        assert_eq!(
+            Pubkey::create_with_seed_v2(b"", &base, &[], &Pubkey::from(pda_owner)),
+            Err(PubkeyError::IllegalOwner)
+        );
+    }
+
+    #[cfg(feature = "sha2-const")]
+    #[test]
+    fn test_create_with_seed_const() {
+        const BASE: Pubkey = Pubkey::new_from_array([0; 32]);
+        const ADDRESS: Pubkey =
+            Pubkey::create_with_seed_const(&BASE, "limber chicken: 4/45", &BASE);
+        assert_eq!(
+            ADDRESS,
+            Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq").unwrap()
+        );
+
+        let base = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        assert_eq!(
+            Pubkey::create_with_seed_const(&base, "☉", &owner),
+            Pubkey::create_with_seed(&base, "☉", &owner).unwrap()
+        );
+    }
+
+    #[cfg(feature = "sha2-const")]
+    #[test]
+    #[should_panic(expected = "Provided owner is not allowed")]
+    fn test_create_with_seed_const_rejects_illegal_owner() {
+        let mut owner = [0; PUBKEY_BYTES];
+        owner[PUBKEY_BYTES - PDA_MARKER.len()..].copy_from_slice(PDA_MARKER);
+        Pubkey::create_with_seed_const(&Pubkey::new_unique(), "", &Pubkey::from(owner));
+    }
+
     #[test]
     fn test_create_program_address() {
         let exceeded_seed = &[127; MAX_SEED_LEN + 1];
@@ -1298,6 +2637,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_validate_seeds() {
+        let max_seed = [0; MAX_SEED_LEN];
+        let max_seeds = [&max_seed[..]; MAX_SEEDS];
+        assert_eq!(Pubkey::validate_seeds(&[]), Ok(()));
+        assert_eq!(Pubkey::validate_seeds(&max_seeds), Ok(()));
+        assert_eq!(
+            Pubkey::validate_seeds(&[&max_seed[..]; MAX_SEEDS + 1]),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+        assert_eq!(
+            Pubkey::validate_seeds(&[b"ok", &[0; MAX_SEED_LEN + 1]]),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+    }
+
+    #[test]
+    fn test_create_program_address_unchecked() {
+        let program_id = Pubkey::new_unique();
+        let max_seeds = [&[1u8][..]; MAX_SEEDS];
+        assert_eq!(
+            Pubkey::create_program_address_unchecked(&max_seeds, &program_id),
+            Pubkey::create_program_address(&max_seeds, &program_id)
+        );
+
+        let long_seed = [7; MAX_SEED_LEN + 1];
+        let many_seeds = [&[1u8][..]; MAX_SEEDS + 1];
+        for seeds in [&[&long_seed[..]][..], &many_seeds] {
+            assert_eq!(
+                Pubkey::create_program_address(seeds, &program_id),
+                Err(PubkeyError::MaxSeedLengthExceeded)
+            );
+            let mut vals = seeds.to_vec();
+            vals.extend([program_id.as_array().as_slice(), PDA_MARKER]);
+            let hash = solana_sha256_hasher::hashv(&vals).to_bytes();
+            let expected = if bytes_are_curve_point(hash) {
+                Err(PubkeyError::InvalidSeeds)
+            } else {
+                Ok(Pubkey::from(hash))
+            };
+            assert_eq!(
+                Pubkey::create_program_address_unchecked(seeds, &program_id),
+                expected
+            );
+        }
+    }
+
     #[test]
     fn test_pubkey_off_curve() {
         // try a bunch of random input, all successful generated program
@@ -1331,6 +2717,132 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_bytes_are_curve_points() {
+        let mut inputs = (0..1_000)
+            .map(|_| rand::random::<[u8; 32]>().to_vec())
+            .collect::<Vec<_>>();
+        inputs.push(Vec::new());
+        inputs.push(std::vec![0; 33]);
+        let expected = inputs.iter().map(bytes_are_curve_point).collect::<Vec<_>>();
+        assert!(expected.contains(&true) && expected.contains(&false));
+        assert_eq!(bytes_are_curve_points(&inputs), expected);
+        assert_eq!(bytes_are_curve_points_par(&inputs), expected);
+        assert!(bytes_are_curve_points::<[u8; 32]>(&[]).is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_lazy_pda() {
+        const PROGRAM_ID: Pubkey = Pubkey::new_from_array([7; 32]);
+        static SINGLE: std::sync::LazyLock<(Pubkey, u8)> = lazy_pda!(b"single", PROGRAM_ID);
+        static MANY: std::sync::LazyLock<(Pubkey, u8)> =
+            lazy_pda!(b"Lil'", PROGRAM_ID, [1u8, 2, 3], PROGRAM_ID,);
+
+        assert_eq!(
+            *SINGLE,
+            Pubkey::find_program_address(&[b"single"], &PROGRAM_ID)
+        );
+        assert_eq!(
+            *MANY,
+            Pubkey::find_program_address(&[b"Lil'", PROGRAM_ID.as_ref(), &[1, 2, 3]], &PROGRAM_ID)
+        );
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_find_program_address_par() {
+        for _ in 0..1_000 {
+            let program_id = Pubkey::new_unique();
+            assert_eq!(
+                Pubkey::find_program_address_par(&[b"Lil'", b"Bits"], &program_id),
+                Pubkey::find_program_address(&[b"Lil'", b"Bits"], &program_id)
+            );
+        }
+
+        let program_id = Pubkey::new_unique();
+        let seeds = [&[0u8][..]; MAX_SEEDS];
+        assert_eq!(
+            Pubkey::try_find_program_address_par(&seeds, &program_id),
+            None
+        );
+        assert_eq!(
+            Pubkey::try_find_program_address_par(&[&[0; MAX_SEED_LEN + 1]], &program_id),
+            None
+        );
+    }
+
+    #[test]
+    fn test_detailed_errors() {
+        let program_id = Pubkey::new_unique();
+        let long_seed = [0; MAX_SEED_LEN + 1];
+
+        let err = Pubkey::try_create_program_address_detailed(&[b"ok", &long_seed], &program_id)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            DetailedPubkeyError::MaxSeedLengthExceeded {
+                index: 1,
+                len: MAX_SEED_LEN + 1
+            }
+        );
+        assert_eq!(PubkeyError::from(err), PubkeyError::MaxSeedLengthExceeded);
+
+        let seeds = [&[0u8][..]; MAX_SEEDS + 1];
+        assert_eq!(
+            Pubkey::try_create_program_address_detailed(&seeds, &program_id),
+            Err(DetailedPubkeyError::TooManySeeds {
+                count: MAX_SEEDS + 1
+            })
+        );
+
+        let (address, bump) = Pubkey::find_program_address(&[b"ok"], &program_id);
+        assert_eq!(
+            Pubkey::try_create_program_address_detailed(&[b"ok", &[bump]], &program_id),
+            Ok(address)
+        );
+
+        assert_eq!(
+            Pubkey::try_create_with_seed_detailed(
+                &program_id,
+                from_utf8(&long_seed).unwrap(),
+                &program_id
+            ),
+            Err(DetailedPubkeyError::MaxSeedLengthExceeded {
+                index: 0,
+                len: MAX_SEED_LEN + 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_derive_associated_address() {
+        let wallet = Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq").unwrap();
+        let token_program =
+            Pubkey::from_str("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA").unwrap();
+        // USDC
+        let mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        let expected = Pubkey::from_str("7NtECwuvzm3HaSpyz5E5WYKWPCujDQKyP8gHwvtuq6TL").unwrap();
+
+        assert_eq!(
+            Pubkey::derive_associated_address(&wallet, &token_program, &mint),
+            expected
+        );
+        assert_eq!(
+            Pubkey::derive_associated_address_with_bump(
+                &wallet,
+                &token_program,
+                &mint,
+                &ASSOCIATED_TOKEN_PROGRAM_ID
+            ),
+            (expected, 249)
+        );
+        assert_ne!(
+            Pubkey::derive_associated_address(&mint, &token_program, &wallet),
+            expected
+        );
+    }
+
     fn pubkey_from_seed_by_marker(marker: &[u8]) -> Result<Pubkey, PubkeyError> {
         let key = Pubkey::new_unique();
         let owner = Pubkey::default();
@@ -1365,6 +2877,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_pubkey_error_program_error_roundtrip() {
+        for variant in PubkeyError::iter() {
+            assert_eq!(
+                PubkeyError::try_from(ProgramError::from(variant.clone())),
+                Ok(variant)
+            );
+        }
+        assert_eq!(
+            PubkeyError::try_from(ProgramError::InvalidArgument),
+            Err(ProgramError::InvalidArgument)
+        );
+        assert_eq!(
+            PubkeyError::try_from(ProgramError::Custom(1)),
+            Err(ProgramError::Custom(1))
+        );
+    }
+
     #[test]
     fn test_parse_pubkey_error_from_primitive_exhaustive() {
         for variant in ParsePubkeyError::iter() {
@@ -1380,12 +2910,350 @@ mod tests {
     fn test_pubkey_macro() {
         const PK: Pubkey = Pubkey::from_str_const("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq");
         assert_eq!(pubkey!("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq"), PK);
+        assert_eq!(pubkey!([1; 32]), Pubkey::from([1; 32]));
+        const BYTES: Pubkey = pubkey!([
+            129, 29, 15, 2, 28, 125, 43, 246, 213, 159, 194, 41, 195, 125, 102, 175, 104, 119, 110,
+            175, 252, 155, 59, 237, 181, 207, 170, 217, 219, 112, 29, 216,
+        ]);
+        assert_eq!(BYTES, PK);
         assert_eq!(
             Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq").unwrap(),
             PK
         );
     }
 
+    #[cfg(feature = "serde-human-readable")]
+    #[test]
+    fn test_serde_human_readable() {
+        let key = Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq").unwrap();
+
+        let json = serde_json::to_string(&key).unwrap();
+        assert_eq!(json, "\"9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq\"");
+        assert_eq!(serde_json::from_str::<Pubkey>(&json).unwrap(), key);
+        assert!(serde_json::from_str::<Pubkey>("\"I9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5\"").is_err());
+
+        // binary formats keep the fixed 32-byte layout
+        let bytes = bincode::serialize(&key).unwrap();
+        assert_eq!(bytes, key.to_bytes());
+        assert_eq!(bincode::deserialize::<Pubkey>(&bytes).unwrap(), key);
+    }
+
+    mod per_cluster {
+        crate::declare_id_per_cluster! {
+            mainnet: "Main111111111111111111111111111111111111111",
+            devnet: "Dev1111111111111111111111111111111111111111",
+            testnet: "Test111111111111111111111111111111111111111",
+            localnet: "Loca111111111111111111111111111111111111111",
+        }
+    }
+
+    #[test]
+    fn test_declare_id_per_cluster() {
+        use per_cluster::*;
+        assert_eq!(CLUSTER, Cluster::Mainnet);
+        assert_eq!(id(), MAINNET_ID);
+        assert_eq!(id_for(Cluster::Mainnet), MAINNET_ID);
+        assert_eq!(id_for(Cluster::Devnet), DEVNET_ID);
+        assert_eq!(id_for(Cluster::Testnet), TESTNET_ID);
+        assert_eq!(id_for(Cluster::Localnet), LOCALNET_ID);
+        assert_eq!(
+            DEVNET_ID,
+            Pubkey::from_str("Dev1111111111111111111111111111111111111111").unwrap()
+        );
+        assert!(!check_id(&DEVNET_ID));
+    }
+
+    mod marker {
+        crate::declare_id!("My11111111111111111111111111111111111111111", MyProgram);
+    }
+
+    #[test]
+    fn test_declare_id_marker() {
+        use marker::MyProgram;
+        assert_eq!(core::mem::size_of::<MyProgram>(), 0);
+        assert_eq!(<MyProgram as ProgramId>::ID, marker::ID);
+        assert_eq!(MyProgram::id(), marker::id());
+        assert!(MyProgram::check_id(&marker::ID));
+        assert!(!MyProgram::check_id(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_partition_boundaries() {
+        for n_shards in [1, 2, 3, 7, 10, 256, 1000, usize::MAX] {
+            assert_eq!(Pubkey::from([0; 32]).partition(n_shards), 0);
+            assert_eq!(
+                Pubkey::from([u8::MAX; 32]).partition(n_shards),
+                n_shards - 1
+            );
+
+            let shards = [0, 1, n_shards / 2, n_shards - 1];
+            for shard in shards.into_iter().filter(|shard| *shard < n_shards) {
+                let range = Pubkey::shard_range(shard, n_shards);
+                assert_eq!(range.start().partition(n_shards), shard);
+                assert_eq!(range.end().partition(n_shards), shard);
+                if shard + 1 < n_shards {
+                    // the next shard starts right after this one ends
+                    let next = Pubkey::shard_range(shard + 1, n_shards);
+                    assert_eq!(range.end().shard_prefix() + 1, next.start().shard_prefix());
+                }
+            }
+        }
+        assert_eq!(
+            Pubkey::shard_range(0, 1),
+            Pubkey::from([0; 32])..=Pubkey::from([u8::MAX; 32])
+        );
+    }
+
+    #[test]
+    fn test_partition_uniformity() {
+        const N_SHARDS: usize = 16;
+        const N_KEYS: usize = 100_000;
+        let mut counts = [0usize; N_SHARDS];
+        for _ in 0..N_KEYS {
+            let key = Pubkey::new_from_array(rand::random());
+            let shard = key.partition(N_SHARDS);
+            assert!(Pubkey::shard_range(shard, N_SHARDS).contains(&key));
+            counts[shard] += 1;
+        }
+        // each count has a standard deviation of ~76, allow ~8 of them
+        let expected = N_KEYS / N_SHARDS;
+        for count in counts {
+            assert!(count.abs_diff(expected) < expected / 10, "{counts:?}");
+        }
+    }
+
+    #[test]
+    fn test_prefix_range() {
+        let (start, end) = Pubkey::prefix_range(&[]);
+        assert_eq!(start, Pubkey::new_from_array([0; 32]));
+        assert_eq!(end, Pubkey::new_from_array([u8::MAX; 32]));
+
+        let prefix = [0x12, 0xff];
+        let (start, end) = Pubkey::prefix_range(&prefix);
+        assert!(start.as_ref().starts_with(&prefix) && end.as_ref().starts_with(&prefix));
+        assert!(!start
+            .checked_decrement()
+            .unwrap()
+            .as_ref()
+            .starts_with(&prefix));
+        assert!(!end
+            .checked_increment()
+            .unwrap()
+            .as_ref()
+            .starts_with(&prefix));
+        for _ in 0..1_000 {
+            let key = Pubkey::new_from_array(rand::random());
+            assert_eq!(
+                (start..=end).contains(&key),
+                key.as_ref().starts_with(&prefix)
+            );
+        }
+
+        let key = Pubkey::new_unique();
+        assert_eq!(Pubkey::prefix_range(key.as_ref()), (key, key));
+    }
+
+    #[test]
+    #[should_panic(expected = "prefix must not be longer than a pubkey")]
+    fn test_prefix_range_too_long() {
+        Pubkey::prefix_range(&[0; PUBKEY_BYTES + 1]);
+    }
+
+    #[test]
+    fn test_increment_decrement() {
+        let min = Pubkey::new_from_array([0; 32]);
+        let max = Pubkey::new_from_array([u8::MAX; 32]);
+        assert_eq!(min.checked_decrement(), None);
+        assert_eq!(max.checked_increment(), None);
+
+        let mut one = [0; 32];
+        one[31] = 1;
+        assert_eq!(min.checked_increment(), Some(Pubkey::new_from_array(one)));
+
+        let mut carry = [u8::MAX; 32];
+        carry[0] = 0x12;
+        let mut carried = [0; 32];
+        carried[0] = 0x13;
+        assert_eq!(
+            Pubkey::new_from_array(carry).checked_increment(),
+            Some(Pubkey::new_from_array(carried))
+        );
+        assert_eq!(
+            Pubkey::new_from_array(carried).checked_decrement(),
+            Some(Pubkey::new_from_array(carry))
+        );
+
+        for _ in 0..1_000 {
+            let key = Pubkey::new_from_array(rand::random());
+            if let Some(next) = key.checked_increment() {
+                assert!(next > key);
+                assert_eq!(next.checked_decrement(), Some(key));
+            }
+        }
+    }
+
+    #[test]
+    fn test_new_rand_with() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let keys = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            [
+                Pubkey::new_rand_with(&mut rng),
+                Pubkey::new_rand_with(&mut rng),
+            ]
+        };
+        assert_eq!(keys(42), keys(42));
+        assert_ne!(keys(42), keys(43));
+        assert_ne!(keys(42)[0], keys(42)[1]);
+    }
+
+    #[cfg(feature = "subtle")]
+    #[test]
+    fn test_ct_eq() {
+        use subtle::ConstantTimeEq;
+
+        let key = Pubkey::new_unique();
+        let mut other = key;
+        assert!(bool::from(key.ct_eq(&other)));
+        other.0[31] ^= 1;
+        assert!(!bool::from(key.ct_eq(&other)));
+        assert_eq!(key == other, bool::from(key.ct_eq(&other)));
+    }
+
+    #[cfg(feature = "schemars")]
+    #[test]
+    fn test_json_schema() {
+        let schema = schemars::schema_for!(Pubkey);
+        assert_eq!(schema.get("title").unwrap(), "Pubkey");
+        #[cfg(not(feature = "serde-human-readable"))]
+        {
+            assert_eq!(schema.get("type").unwrap(), "array");
+            assert_eq!(schema.get("minItems").unwrap(), 32);
+            assert_eq!(schema.get("maxItems").unwrap(), 32);
+            let json = serde_json::to_value(Pubkey::new_unique()).unwrap();
+            assert_eq!(json.as_array().unwrap().len(), 32);
+        }
+        #[cfg(feature = "serde-human-readable")]
+        {
+            assert_eq!(schema.get("type").unwrap(), "string");
+            assert_eq!(schema.get("format").unwrap(), "base58");
+            let json = serde_json::to_value(Pubkey::new_unique()).unwrap();
+            assert!(json.is_string());
+        }
+    }
+
+    #[test]
+    fn test_from_str_detailed() {
+        let key = "9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq";
+        assert_eq!(
+            Pubkey::from_str_detailed(key).unwrap(),
+            Pubkey::from_str(key).unwrap()
+        );
+
+        let cases = [
+            (
+                "9h1HyLCW5dZnBVap8C5egQ0Z6pHyjsh5MNy83iPqqRuq",
+                DetailedParsePubkeyError::InvalidChar {
+                    index: 22,
+                    character: '0',
+                },
+            ),
+            (
+                "9h1HyLCW5dZnBVap8C5égQ9Z6pHyjsh5MNy83iPqqRu",
+                DetailedParsePubkeyError::InvalidChar {
+                    index: 19,
+                    character: 'é',
+                },
+            ),
+            (
+                " 9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRu",
+                DetailedParsePubkeyError::InvalidChar {
+                    index: 0,
+                    character: ' ',
+                },
+            ),
+            (
+                "9h1HyLCW5dZnBVap8C5egQ9Z6pHyj",
+                DetailedParsePubkeyError::TooShort { len: 29 },
+            ),
+            (
+                "9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq9",
+                DetailedParsePubkeyError::TooLong { len: 45 },
+            ),
+            (
+                "zzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzzz",
+                DetailedParsePubkeyError::TooLong { len: 44 },
+            ),
+        ];
+        for (s, expected) in cases {
+            let err = Pubkey::from_str_detailed(s).unwrap_err();
+            assert_eq!(err, expected, "{s}");
+            // the compatibility mapping matches what `from_str` returns
+            assert_eq!(
+                ParsePubkeyError::from(err),
+                Pubkey::from_str(s).unwrap_err()
+            );
+        }
+        assert_eq!(
+            Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ0Z6pHyjsh5MNy83iPqqRuq"),
+            Err(ParsePubkeyError::Invalid)
+        );
+        assert_eq!(
+            Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ9Z6pHyj"),
+            Err(ParsePubkeyError::WrongSize)
+        );
+    }
+
+    #[test]
+    fn test_from_str_strict_and_lenient() {
+        let key = Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq").unwrap();
+        for padded in [
+            " 9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq\n",
+            "\t9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq\r\n",
+            "\u{00A0}9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq\u{3000}",
+            "\u{FEFF}9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq\u{200B}",
+        ] {
+            assert_eq!(Pubkey::from_str_lenient(padded), Ok(key));
+            assert!(Pubkey::from_str_strict(padded).is_err());
+        }
+        assert_eq!(
+            Pubkey::from_str_strict("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq"),
+            Ok(key)
+        );
+        assert_eq!(
+            Pubkey::from_str_lenient("9h1HyLCW5dZnBVap8C5egQ9Z6p Hyjsh5MNy83iPqqRuq"),
+            Err(ParsePubkeyError::WrongSize)
+        );
+        assert_eq!(
+            Pubkey::from_str_lenient("9h1HyLCW5dZnBVap8C5egQ9 6pHyjsh5MNy83iPqqRuq"),
+            Err(ParsePubkeyError::Invalid)
+        );
+    }
+
+    #[test]
+    fn test_display_flags() {
+        use std::{format, string::ToString};
+
+        let key = Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq").unwrap();
+        assert_eq!(
+            format!("{key:>48}|"),
+            "    9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq|"
+        );
+        assert_eq!(
+            format!("{key:<48}|"),
+            "9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq    |"
+        );
+        assert_eq!(
+            format!("{key:*^48}"),
+            "**9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq**"
+        );
+        assert_eq!(format!("{key:.8}"), "9h1HyLCW");
+        assert_eq!(format!("{key:>10.4}"), "      9h1H");
+        assert_eq!(format!("{key:8}"), key.to_string());
+        assert_eq!(format!("{key:?}"), key.to_string());
+    }
+
     #[test]
     fn test_as_array() {
         let bytes = [1u8; 32];
@@ -1395,4 +3263,24 @@ mod tests {
         // Sanity check: ensure the pointer is the same.
         assert_eq!(key.as_array().as_ptr(), key.0.as_ptr());
     }
+
+    #[test]
+    fn test_from_array_ref() {
+        use std::collections::{BTreeMap, HashMap};
+
+        let keys = (0..10).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let hash_map = keys.iter().map(|key| (*key, 1)).collect::<HashMap<_, _>>();
+        let btree_map = keys.iter().map(|key| (*key, 1)).collect::<BTreeMap<_, _>>();
+        for key in &keys {
+            let bytes = key.to_bytes();
+            let borrowed = Pubkey::from_array_ref(&bytes);
+            assert_eq!(borrowed, key);
+            assert_eq!(borrowed.as_array().as_ptr(), bytes.as_ptr());
+            assert_eq!(hash_map.get(borrowed), Some(&1));
+            assert_eq!(btree_map.get(borrowed), Some(&1));
+        }
+        let missing = Pubkey::from_array_ref(&[0u8; PUBKEY_BYTES]);
+        assert_eq!(hash_map.get(missing), None);
+        assert_eq!(btree_map.get(missing), None);
+    }
 }
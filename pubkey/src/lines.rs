@@ -0,0 +1,156 @@
+//! Parsing of newline-delimited lists of pubkeys.
+use {
+    crate::{trim_lenient, DetailedParsePubkeyError, Pubkey},
+    core::fmt,
+    std::{
+        io::{self, BufRead, BufReader, Read},
+        vec::Vec,
+    },
+};
+
+/// Error returned when reading pubkeys with [`Pubkey::read_lines`].
+#[derive(Debug)]
+pub enum ReadPubkeysError {
+    /// Reading `line` failed, e.g. because it is not valid UTF-8
+    Io { line: usize, error: io::Error },
+    /// `line` is not a base58 encoded pubkey
+    Parse {
+        line: usize,
+        error: DetailedParsePubkeyError,
+    },
+}
+
+impl ReadPubkeysError {
+    /// The number of the offending line, starting at 1.
+    pub fn line(&self) -> usize {
+        match self {
+            ReadPubkeysError::Io { line, .. } | ReadPubkeysError::Parse { line, .. } => *line,
+        }
+    }
+}
+
+impl core::error::Error for ReadPubkeysError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            ReadPubkeysError::Io { error, .. } => Some(error),
+            ReadPubkeysError::Parse { error, .. } => Some(error),
+        }
+    }
+}
+
+impl fmt::Display for ReadPubkeysError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadPubkeysError::Io { line, error } => {
+                write!(f, "Failed to read line {line}: {error}")
+            }
+            ReadPubkeysError::Parse { line, error } => write!(f, "Line {line}: {error}"),
+        }
+    }
+}
+
+/// Iterator over the pubkeys of a newline-delimited list, returned by
+/// [`Pubkey::read_lines`].
+#[derive(Debug)]
+pub struct PubkeyLines<R> {
+    lines: io::Lines<BufReader<R>>,
+    line: usize,
+}
+
+impl<R: Read> Iterator for PubkeyLines<R> {
+    type Item = Result<Pubkey, ReadPubkeysError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let result = self.lines.next()?;
+            self.line = self.line.saturating_add(1);
+            let line = self.line;
+            let text = match result {
+                Ok(text) => text,
+                Err(error) => return Some(Err(ReadPubkeysError::Io { line, error })),
+            };
+            let text = trim_lenient(&text);
+            if text.is_empty() || text.starts_with('#') {
+                continue;
+            }
+            return Some(
+                Pubkey::from_str_detailed(text)
+                    .map_err(|error| ReadPubkeysError::Parse { line, error }),
+            );
+        }
+    }
+}
+
+impl Pubkey {
+    /// Read base58 encoded pubkeys from `reader`, one per line.
+    ///
+    /// Surrounding whitespace is ignored like in [`Pubkey::from_str_lenient`],
+    /// and so are blank lines and lines starting with `#`. Errors carry the
+    /// number of the offending line. Iteration can continue after an error.
+    ///
+    /// ```
+    /// # use solana_pubkey::Pubkey;
+    /// let allowlist = "# validators\n\
+    ///     9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq\n\
+    ///     \n\
+    ///     11111111111111111111111111111111\n";
+    /// let keys = Pubkey::read_lines(allowlist.as_bytes()).collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(keys.len(), 2);
+    ///
+    /// let err = Pubkey::read_lines_to_vec("1111\n0OIl\n".as_bytes()).unwrap_err();
+    /// assert_eq!(err.line(), 1);
+    /// # Ok::<(), solana_pubkey::ReadPubkeysError>(())
+    /// ```
+    pub fn read_lines<R: Read>(reader: R) -> PubkeyLines<R> {
+        PubkeyLines {
+            lines: BufReader::new(reader).lines(),
+            line: 0,
+        }
+    }
+
+    /// Same as [`Pubkey::read_lines`], but collects the pubkeys, stopping at
+    /// the first error.
+    pub fn read_lines_to_vec<R: Read>(reader: R) -> Result<Vec<Pubkey>, ReadPubkeysError> {
+        Self::read_lines(reader).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, core::str::FromStr, std::string::ToString};
+
+    #[test]
+    fn test_read_lines() {
+        let key = Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq").unwrap();
+        let input = "\u{FEFF}# comment\r\n  9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq \r\n\n\
+            \t\n11111111111111111111111111111111";
+        assert_eq!(
+            Pubkey::read_lines_to_vec(input.as_bytes()).unwrap(),
+            [key, Pubkey::default()]
+        );
+        assert!(Pubkey::read_lines_to_vec(&b""[..]).unwrap().is_empty());
+
+        let input = b"11111111111111111111111111111111\n\n1111\n9h1HyLCW5dZn\xff\n0OIl\n";
+        let mut lines = Pubkey::read_lines(&input[..]);
+        assert_eq!(lines.next().unwrap().unwrap(), Pubkey::default());
+        let err = lines.next().unwrap().unwrap_err();
+        assert_eq!(err.line(), 3);
+        assert!(matches!(
+            err,
+            ReadPubkeysError::Parse {
+                error: DetailedParsePubkeyError::TooShort { len: 4 },
+                ..
+            }
+        ));
+        let err = lines.next().unwrap().unwrap_err();
+        assert!(matches!(err, ReadPubkeysError::Io { line: 4, .. }));
+        let err = lines.next().unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Line 5: Invalid Base58 character '0' at position 0"
+        );
+        assert!(lines.next().is_none());
+
+        assert_eq!(Pubkey::read_lines_to_vec(&input[..]).unwrap_err().line(), 3);
+    }
+}
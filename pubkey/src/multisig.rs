@@ -0,0 +1,145 @@
+//! Canonical derivation of N-of-M multisig vault addresses.
+use {
+    crate::{Pubkey, PubkeyError},
+    std::vec::Vec,
+};
+
+/// The first seed of every multisig vault address.
+pub const MULTISIG_VAULT_SEED: &[u8] = b"multisig";
+
+// Hashed ahead of the members so that the digest can not collide with one
+// computed by an unrelated scheme over the same keys.
+const MULTISIG_DOMAIN: &[u8] = b"solana-multisig-vault-v1";
+
+impl Pubkey {
+    /// Compute the seed identifying an N-of-M multisig, where M is the number
+    /// of `members` and N is `threshold`.
+    ///
+    /// The members are sorted before hashing, so the seed does not depend on
+    /// the order in which they are given. Returns
+    /// [`PubkeyError::InvalidSeeds`] if `members` is empty, contains
+    /// duplicates or has more than `u8::MAX` entries, or if `threshold` is not
+    /// between 1 and M.
+    pub fn multisig_seed(members: &[Pubkey], threshold: u8) -> Result<[u8; 32], PubkeyError> {
+        let Ok(num_members) = u8::try_from(members.len()) else {
+            return Err(PubkeyError::InvalidSeeds);
+        };
+        if threshold == 0 || threshold > num_members {
+            return Err(PubkeyError::InvalidSeeds);
+        }
+        let mut sorted = members.iter().collect::<Vec<_>>();
+        sorted.sort_unstable();
+        if sorted.windows(2).any(|pair| pair[0] == pair[1]) {
+            return Err(PubkeyError::InvalidSeeds);
+        }
+
+        let header = [threshold, num_members];
+        let mut vals = Vec::with_capacity(sorted.len() + 2);
+        vals.push(MULTISIG_DOMAIN);
+        vals.push(&header[..]);
+        vals.extend(sorted.iter().map(|member| member.as_ref()));
+        Ok(solana_sha256_hasher::hashv(&vals).to_bytes())
+    }
+
+    /// Find the vault address of an N-of-M multisig owned by `program_id`.
+    ///
+    /// The address is the [program derived address][pda] of the seeds
+    /// `[MULTISIG_VAULT_SEED, seed]`, where `seed` is the result of
+    /// [`multisig_seed`], so a program can sign for the vault with
+    /// `[MULTISIG_VAULT_SEED, &seed, &[bump]]`.
+    ///
+    /// [pda]: Pubkey::find_program_address
+    /// [`multisig_seed`]: Pubkey::multisig_seed
+    ///
+    /// ```
+    /// # use solana_pubkey::Pubkey;
+    /// let program_id = Pubkey::new_unique();
+    /// let (alice, bob, carol) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+    ///
+    /// let (vault, _bump) = Pubkey::find_multisig_address(&[alice, bob, carol], 2, &program_id)?;
+    /// assert_eq!(
+    ///     Pubkey::find_multisig_address(&[carol, alice, bob], 2, &program_id)?.0,
+    ///     vault
+    /// );
+    /// assert_ne!(
+    ///     Pubkey::find_multisig_address(&[alice, bob, carol], 3, &program_id)?.0,
+    ///     vault
+    /// );
+    /// # Ok::<(), solana_pubkey::PubkeyError>(())
+    /// ```
+    pub fn find_multisig_address(
+        members: &[Pubkey],
+        threshold: u8,
+        program_id: &Pubkey,
+    ) -> Result<(Pubkey, u8), PubkeyError> {
+        let seed = Self::multisig_seed(members, threshold)?;
+        Ok(Self::find_program_address(
+            &[MULTISIG_VAULT_SEED, &seed],
+            program_id,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::vec};
+
+    #[test]
+    fn test_multisig_seed() {
+        let members = (0..3).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let seed = Pubkey::multisig_seed(&members, 2).unwrap();
+
+        let mut reversed = members.clone();
+        reversed.reverse();
+        assert_eq!(Pubkey::multisig_seed(&reversed, 2), Ok(seed));
+        assert_ne!(Pubkey::multisig_seed(&members, 1), Ok(seed));
+        assert_ne!(Pubkey::multisig_seed(&members[..2], 2), Ok(seed));
+
+        for threshold in [0, 4] {
+            assert_eq!(
+                Pubkey::multisig_seed(&members, threshold),
+                Err(PubkeyError::InvalidSeeds)
+            );
+        }
+        assert_eq!(
+            Pubkey::multisig_seed(&[], 1),
+            Err(PubkeyError::InvalidSeeds)
+        );
+        assert_eq!(
+            Pubkey::multisig_seed(&[members[0], members[1], members[0]], 1),
+            Err(PubkeyError::InvalidSeeds)
+        );
+        let too_many = vec![Pubkey::default(); usize::from(u8::MAX) + 1];
+        assert_eq!(
+            Pubkey::multisig_seed(&too_many, 1),
+            Err(PubkeyError::InvalidSeeds)
+        );
+    }
+
+    #[test]
+    fn test_find_multisig_address() {
+        let program_id = Pubkey::new_unique();
+        let members = (0..5).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let seed = Pubkey::multisig_seed(&members, 3).unwrap();
+        let (vault, bump) = Pubkey::find_multisig_address(&members, 3, &program_id).unwrap();
+        assert_eq!(
+            Pubkey::create_program_address(&[MULTISIG_VAULT_SEED, &seed, &[bump]], &program_id),
+            Ok(vault)
+        );
+    }
+
+    #[test]
+    fn test_find_multisig_address_golden() {
+        // pins the seed layout: domain, threshold, number of members and the
+        // sorted members
+        let members = [3, 1, 2].map(|byte| Pubkey::new_from_array([byte; 32]));
+        let program_id = Pubkey::from_str_const("BPFLoaderUpgradeab1e11111111111111111111111");
+        assert_eq!(
+            Pubkey::find_multisig_address(&members, 2, &program_id),
+            Ok((
+                Pubkey::from_str_const("FQ4rLBdt6x34zWeaZKs4naSoDgppiRgZqcefrCMfrbEp"),
+                254
+            ))
+        );
+    }
+}
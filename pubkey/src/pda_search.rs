@@ -0,0 +1,261 @@
+//! Pluggable search for the bump seeds of program derived addresses.
+use crate::{check_seeds_before_bump, Pubkey, PubkeyError, MAX_SEEDS};
+
+/// A searcher for the canonical bump seed of a [program derived
+/// address][pda].
+///
+/// This lets the search be offloaded to a GPU, an FPGA or a remote service,
+/// which can check many bump seeds at once and report every valid one it
+/// found as a candidate.
+///
+/// [pda]: https://solana.com/docs/core/cpi#program-derived-addresses
+pub trait PdaSearchBackend {
+    /// Return bump seeds in `1..=255` for which `seeds`, followed by the
+    /// bump seed, are a valid input to [`Pubkey::create_program_address`]
+    /// for `program_id`.
+    ///
+    /// The highest candidate is used, and must be the highest valid bump
+    /// seed, the canonical one. [`Pubkey::find_program_address_with`] fails
+    /// otherwise, while [`Pubkey::find_program_address_with_unverified`]
+    /// trusts it. Lower valid bump seeds may be included too.
+    fn find_bumps(&self, seeds: &[&[u8]], program_id: &Pubkey) -> BumpCandidates;
+}
+
+/// A set of candidate bump seeds, returned by
+/// [`PdaSearchBackend::find_bumps`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct BumpCandidates([u64; 4]);
+
+impl BumpCandidates {
+    /// Create an empty set.
+    pub const fn new() -> Self {
+        Self([0; 4])
+    }
+
+    /// Add `bump` to the set.
+    pub fn insert(&mut self, bump: u8) {
+        self.0[usize::from(bump / 64)] |= 1 << (bump % 64);
+    }
+
+    /// Return true if `bump` is in the set.
+    pub fn contains(&self, bump: u8) -> bool {
+        self.0[usize::from(bump / 64)] & (1 << (bump % 64)) != 0
+    }
+
+    /// Return the highest candidate, or `None` if there are none.
+    pub fn highest(&self) -> Option<u8> {
+        let (index, word) = self
+            .0
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, word)| **word != 0)?;
+        Some(index as u8 * 64 + (63 - word.leading_zeros() as u8))
+    }
+}
+
+impl FromIterator<u8> for BumpCandidates {
+    fn from_iter<I: IntoIterator<Item = u8>>(bumps: I) -> Self {
+        let mut candidates = Self::new();
+        for bump in bumps {
+            candidates.insert(bump);
+        }
+        candidates
+    }
+}
+
+/// The in-crate backend, which searches on the current thread like
+/// [`Pubkey::try_find_program_address`] and returns the single bump seed it
+/// finds.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CpuPdaSearch;
+
+impl PdaSearchBackend for CpuPdaSearch {
+    fn find_bumps(&self, seeds: &[&[u8]], program_id: &Pubkey) -> BumpCandidates {
+        Pubkey::try_find_program_address(seeds, program_id)
+            .map(|(_, bump)| bump)
+            .into_iter()
+            .collect()
+    }
+}
+
+fn create_with_bump(seeds: &[&[u8]], bump: u8, program_id: &Pubkey) -> Result<Pubkey, PubkeyError> {
+    let bump = [bump];
+    let mut seeds_with_bump: [&[u8]; MAX_SEEDS] = [&[]; MAX_SEEDS];
+    seeds_with_bump[..seeds.len()].copy_from_slice(seeds);
+    seeds_with_bump[seeds.len()] = &bump;
+    Pubkey::create_program_address(&seeds_with_bump[..=seeds.len()], program_id)
+}
+
+impl Pubkey {
+    /// Find a program derived address and its bump seed with the bump seed
+    /// search done by `backend`, verifying that the result is canonical.
+    ///
+    /// The highest candidate bump seed is verified to derive a valid address,
+    /// and every bump seed above it to derive a point on the curve, so a
+    /// faulty backend can never make this return an address other than the
+    /// canonical one [`Pubkey::find_program_address`] returns, which is the
+    /// one programs re-derive. Checking the bump seeds above the candidate
+    /// costs as much as the CPU search from the canonical bump seed, so this
+    /// is not faster than [`Pubkey::find_program_address`]. Use
+    /// [`Pubkey::find_program_address_with_unverified`] to actually save the
+    /// search with a trusted backend.
+    ///
+    /// Returns [`PubkeyError::MaxSeedLengthExceeded`] for the seeds that
+    /// [`Pubkey::find_program_address`] panics on, and
+    /// [`PubkeyError::InvalidSeeds`] if the backend returns no candidate, an
+    /// invalid one or a non-canonical one.
+    pub fn find_program_address_with<B: PdaSearchBackend + ?Sized>(
+        backend: &B,
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<(Pubkey, u8), PubkeyError> {
+        let (address, bump) =
+            Self::find_program_address_with_unverified(backend, seeds, program_id)?;
+        let is_canonical = (bump..u8::MAX)
+            .map(|skipped| skipped.saturating_add(1))
+            .all(|skipped| create_with_bump(seeds, skipped, program_id).is_err());
+        if !is_canonical {
+            return Err(PubkeyError::InvalidSeeds);
+        }
+        Ok((address, bump))
+    }
+
+    /// Same as [`Pubkey::find_program_address_with`], but trusting `backend`
+    /// to return the canonical bump seed.
+    ///
+    /// Only the highest candidate bump seed is verified to derive a valid
+    /// address, which costs a single derivation. A faulty backend can make
+    /// this return a valid program derived address that is not the canonical
+    /// one, which programs re-deriving the address with
+    /// [`Pubkey::find_program_address`] would reject.
+    ///
+    /// Returns [`PubkeyError::MaxSeedLengthExceeded`] for the seeds that
+    /// [`Pubkey::find_program_address`] panics on, and
+    /// [`PubkeyError::InvalidSeeds`] if the backend returns no candidate or
+    /// an invalid one.
+    pub fn find_program_address_with_unverified<B: PdaSearchBackend + ?Sized>(
+        backend: &B,
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<(Pubkey, u8), PubkeyError> {
+        check_seeds_before_bump(seeds)?;
+        let bump = backend
+            .find_bumps(seeds, program_id)
+            .highest()
+            .filter(|bump| *bump != 0)
+            .ok_or(PubkeyError::InvalidSeeds)?;
+        let address = create_with_bump(seeds, bump, program_id)?;
+        Ok((address, bump))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::MAX_SEED_LEN};
+
+    struct FixedBumps(BumpCandidates);
+
+    impl PdaSearchBackend for FixedBumps {
+        fn find_bumps(&self, _seeds: &[&[u8]], _program_id: &Pubkey) -> BumpCandidates {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_bump_candidates() {
+        let mut candidates = BumpCandidates::new();
+        assert_eq!(candidates.highest(), None);
+        for bump in [0, 63, 64, 200] {
+            candidates.insert(bump);
+            assert!(candidates.contains(bump));
+            assert_eq!(candidates.highest(), Some(bump));
+        }
+        assert!(!candidates.contains(1));
+        assert!(!candidates.contains(u8::MAX));
+        candidates.insert(u8::MAX);
+        assert_eq!(candidates.highest(), Some(u8::MAX));
+        assert_eq!(
+            candidates,
+            BumpCandidates::from_iter([u8::MAX, 200, 64, 63, 0])
+        );
+    }
+
+    #[test]
+    fn test_cpu_pda_search() {
+        for _ in 0..100 {
+            let program_id = Pubkey::new_unique();
+            let seeds: &[&[u8]] = &[b"Lil'", b"Bits"];
+            assert_eq!(
+                Pubkey::find_program_address_with(&CpuPdaSearch, seeds, &program_id),
+                Ok(Pubkey::find_program_address(seeds, &program_id))
+            );
+        }
+    }
+
+    #[test]
+    fn test_pda_search_verifies_backend() {
+        let seeds: &[&[u8]] = &[b"Lil'", b"Bits"];
+        // find a program id for which the canonical bump is not the highest
+        // one, and the one below it is valid too
+        let (program_id, bump) = core::iter::repeat_with(Pubkey::new_unique)
+            .map(|program_id| {
+                (
+                    program_id,
+                    CpuPdaSearch
+                        .find_bumps(seeds, &program_id)
+                        .highest()
+                        .unwrap(),
+                )
+            })
+            .find(|(program_id, bump)| {
+                *bump < u8::MAX && create_with_bump(seeds, bump - 1, program_id).is_ok()
+            })
+            .unwrap();
+        let search = |bumps: &[u8]| {
+            let backend = FixedBumps(bumps.iter().copied().collect());
+            Pubkey::find_program_address_with(&backend, seeds, &program_id)
+        };
+        let expected = Pubkey::find_program_address(seeds, &program_id);
+
+        assert_eq!(search(&[bump]), Ok(expected));
+        assert_eq!(search(&[bump - 1, bump]), Ok(expected));
+        // valid, but not canonical
+        assert_eq!(search(&[bump - 1]), Err(PubkeyError::InvalidSeeds));
+        let search_unverified = |bumps: &[u8]| {
+            let backend = FixedBumps(bumps.iter().copied().collect());
+            Pubkey::find_program_address_with_unverified(&backend, seeds, &program_id)
+        };
+        assert_eq!(search_unverified(&[bump]), Ok(expected));
+        // trusted to be canonical
+        assert_eq!(
+            search_unverified(&[bump - 1]),
+            Ok((
+                create_with_bump(seeds, bump - 1, &program_id).unwrap(),
+                bump - 1
+            ))
+        );
+        assert_eq!(
+            search_unverified(&[bump, u8::MAX]),
+            Err(PubkeyError::InvalidSeeds)
+        );
+        assert_eq!(search_unverified(&[]), Err(PubkeyError::InvalidSeeds));
+        // higher than the canonical bump, so not valid
+        assert_eq!(search(&[bump, u8::MAX]), Err(PubkeyError::InvalidSeeds));
+        assert_eq!(search(&[0]), Err(PubkeyError::InvalidSeeds));
+        assert_eq!(search(&[]), Err(PubkeyError::InvalidSeeds));
+
+        assert_eq!(
+            Pubkey::find_program_address_with(
+                &CpuPdaSearch,
+                &[&[0; MAX_SEED_LEN + 1]],
+                &program_id
+            ),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+        assert_eq!(
+            Pubkey::find_program_address_with(&CpuPdaSearch, &[&[][..]; MAX_SEEDS], &program_id),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Traced bump seed search, for debugging program derived addresses.
+use {
+    crate::{
+        bytes_are_curve_point, check_seeds_before_bump, program_address_bytes, Pubkey, PubkeyError,
+        MAX_SEEDS,
+    },
+    core::fmt,
+    std::vec::Vec,
+};
+
+/// A bump seed rejected by [`Pubkey::find_program_address_traced`] because it
+/// derives a point on the curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RejectedBump {
+    pub bump: u8,
+    /// The derived point, which is not a valid program address
+    pub candidate: Pubkey,
+}
+
+/// The steps of a bump seed search, returned by
+/// [`Pubkey::find_program_address_traced`].
+///
+/// The `Display` implementation lists one bump seed per line and is meant to
+/// be included in error reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PdaTrace {
+    /// The bump seeds tried before the canonical one, in the order they were
+    /// tried.
+    pub rejected: Vec<RejectedBump>,
+    /// The address and canonical bump seed, or `None` if every bump seed
+    /// derives a point on the curve.
+    pub found: Option<(Pubkey, u8)>,
+}
+
+impl PdaTrace {
+    /// Return all bump seeds tried, in order, including the canonical one.
+    pub fn attempted_bumps(&self) -> impl Iterator<Item = u8> + '_ {
+        self.rejected
+            .iter()
+            .map(|rejected| rejected.bump)
+            .chain(self.found.map(|(_, bump)| bump))
+    }
+}
+
+impl fmt::Display for PdaTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for RejectedBump { bump, candidate } in &self.rejected {
+            writeln!(f, "bump {bump}: {candidate} is on the curve")?;
+        }
+        match self.found {
+            Some((address, bump)) => write!(f, "bump {bump}: found {address}"),
+            None => f.write_str("no viable bump seed"),
+        }
+    }
+}
+
+impl Pubkey {
+    /// Same as [`Pubkey::find_program_address`], but also return the bump
+    /// seeds that were tried and rejected on the way to the canonical one.
+    ///
+    /// Returns [`PubkeyError::MaxSeedLengthExceeded`] for the seeds that
+    /// [`Pubkey::find_program_address`] panics on, and a trace without an
+    /// address if no bump seed is viable. Only available off-chain.
+    ///
+    /// ```
+    /// # use solana_pubkey::Pubkey;
+    /// let program_id = Pubkey::new_unique();
+    /// let trace = Pubkey::find_program_address_traced(&[b"vault"], &program_id)?;
+    /// let (address, bump) = Pubkey::find_program_address(&[b"vault"], &program_id);
+    /// assert_eq!(trace.found, Some((address, bump)));
+    /// // every bump seed above the canonical one derives a point on the curve
+    /// assert_eq!(trace.rejected.len(), usize::from(u8::MAX - bump));
+    /// assert!(trace.rejected.iter().all(|rejected| rejected.candidate.is_on_curve()));
+    /// assert_eq!(trace.attempted_bumps().last(), Some(bump));
+    /// # Ok::<(), solana_pubkey::PubkeyError>(())
+    /// ```
+    pub fn find_program_address_traced(
+        seeds: &[&[u8]],
+        program_id: &Pubkey,
+    ) -> Result<PdaTrace, PubkeyError> {
+        check_seeds_before_bump(seeds)?;
+        let mut rejected = Vec::new();
+        for bump in (1..=u8::MAX).rev() {
+            let bump_seed = [bump];
+            let mut seeds_with_bump: [&[u8]; MAX_SEEDS] = [&[]; MAX_SEEDS];
+            seeds_with_bump[..seeds.len()].copy_from_slice(seeds);
+            seeds_with_bump[seeds.len()] = &bump_seed;
+            let bytes = program_address_bytes(&seeds_with_bump[..=seeds.len()], program_id);
+            if !bytes_are_curve_point(bytes) {
+                return Ok(PdaTrace {
+                    rejected,
+                    found: Some((Pubkey::from(bytes), bump)),
+                });
+            }
+            rejected.push(RejectedBump {
+                bump,
+                candidate: Pubkey::from(bytes),
+            });
+        }
+        Ok(PdaTrace {
+            rejected,
+            found: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::MAX_SEED_LEN, std::string::ToString};
+
+    #[test]
+    fn test_find_program_address_traced() {
+        let seeds: &[&[u8]] = &[b"Lil'", b"Bits"];
+        // find a program id whose canonical bump is not the first one tried
+        let (program_id, trace) = core::iter::repeat_with(Pubkey::new_unique)
+            .map(|program_id| {
+                let trace = Pubkey::find_program_address_traced(seeds, &program_id).unwrap();
+                (program_id, trace)
+            })
+            .find(|(_, trace)| !trace.rejected.is_empty())
+            .unwrap();
+
+        let (address, bump) = Pubkey::find_program_address(seeds, &program_id);
+        assert_eq!(trace.found, Some((address, bump)));
+        assert_eq!(
+            trace.attempted_bumps().collect::<Vec<_>>(),
+            (bump..=u8::MAX).rev().collect::<Vec<_>>()
+        );
+        for RejectedBump { bump, candidate } in &trace.rejected {
+            assert!(candidate.is_on_curve());
+            assert_eq!(
+                Pubkey::create_program_address(&[seeds[0], seeds[1], &[*bump]], &program_id),
+                Err(PubkeyError::InvalidSeeds)
+            );
+        }
+
+        let report = trace.to_string();
+        assert_eq!(report.lines().count(), trace.rejected.len() + 1);
+        assert!(report.starts_with(&std::format!(
+            "bump 255: {} is on the curve\n",
+            trace.rejected[0].candidate
+        )));
+        assert!(report.ends_with(&std::format!("bump {bump}: found {address}")));
+
+        assert_eq!(
+            Pubkey::find_program_address_traced(&[&[0; MAX_SEED_LEN + 1]], &program_id),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+        assert_eq!(
+            Pubkey::find_program_address_traced(&[&[][..]; MAX_SEEDS], &program_id),
+            Err(PubkeyError::MaxSeedLengthExceeded)
+        );
+    }
+}
@@ -0,0 +1,80 @@
+//! [`sqlx`] bindings for storing a [`Pubkey`] in Postgres.
+//!
+//! A `Pubkey` is bound as its raw 32 bytes, to be stored in a `BYTEA` column.
+//! When decoding, `TEXT` and `VARCHAR` columns holding base58 strings are
+//! accepted as well, so tables that already store addresses as text can be
+//! read without a migration. To write such a column, bind
+//! `pubkey.to_string()` instead.
+use {
+    crate::Pubkey,
+    core::str::FromStr,
+    sqlx::{
+        encode::IsNull,
+        error::BoxDynError,
+        postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef, Postgres},
+        Decode, Encode, Type, ValueRef,
+    },
+};
+
+impl Type<Postgres> for Pubkey {
+    fn type_info() -> PgTypeInfo {
+        <[u8] as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <[u8] as Type<Postgres>>::compatible(ty) || <str as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl PgHasArrayType for Pubkey {
+    fn array_type_info() -> PgTypeInfo {
+        <&[u8] as PgHasArrayType>::array_type_info()
+    }
+}
+
+impl Encode<'_, Postgres> for Pubkey {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+        <&[u8] as Encode<Postgres>>::encode(self.as_ref(), buf)
+    }
+
+    fn size_hint(&self) -> usize {
+        self.as_ref().len()
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Pubkey {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        if <str as Type<Postgres>>::compatible(&value.type_info()) {
+            let s = <&str as Decode<Postgres>>::decode(value)?;
+            Ok(Pubkey::from_str(s)?)
+        } else {
+            let bytes = <&[u8] as Decode<Postgres>>::decode(value)?;
+            Ok(Pubkey::try_from(bytes)?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::string::String};
+
+    #[test]
+    fn test_postgres_type() {
+        assert_eq!(
+            <Pubkey as Type<Postgres>>::type_info(),
+            <[u8] as Type<Postgres>>::type_info()
+        );
+        let text = <String as Type<Postgres>>::type_info();
+        let int8 = <i64 as Type<Postgres>>::type_info();
+        assert!(<Pubkey as Type<Postgres>>::compatible(&text));
+        assert!(!<Pubkey as Type<Postgres>>::compatible(&int8));
+
+        let key = Pubkey::new_unique();
+        let mut buf = PgArgumentBuffer::default();
+        assert!(matches!(
+            Encode::<Postgres>::encode_by_ref(&key, &mut buf),
+            Ok(IsNull::No)
+        ));
+        assert_eq!(&buf[..], key.as_ref());
+    }
+}
@@ -0,0 +1,356 @@
+//! Serde adapters that encode [`Pubkey`]s as base58 strings.
+//!
+//! Each submodule is meant to be used with `#[serde(with = "...")]`:
+//!
+//! ```
+//! # use {serde_derive::{Deserialize, Serialize}, solana_pubkey::Pubkey, std::collections::HashMap};
+//! #[derive(Serialize, Deserialize)]
+//! struct Account {
+//!     #[serde(with = "solana_pubkey::serde_as::base58")]
+//!     address: Pubkey,
+//!     #[serde(with = "solana_pubkey::serde_as::option")]
+//!     delegate: Option<Pubkey>,
+//!     #[serde(with = "solana_pubkey::serde_as::vec")]
+//!     signers: Vec<Pubkey>,
+//!     #[serde(with = "solana_pubkey::serde_as::map_keys")]
+//!     balances: HashMap<Pubkey, u64>,
+//!     #[serde(with = "solana_pubkey::serde_as::padded")]
+//!     authority: Pubkey,
+//!     #[serde(with = "solana_pubkey::serde_as::object")]
+//!     mint: Pubkey,
+//! }
+//! ```
+use {
+    crate::{encode_base58, Pubkey, MAX_BASE58_LEN},
+    core::{fmt, str::FromStr},
+    serde::{
+        de::{self, Deserialize, Deserializer},
+        ser::{Serialize, Serializer},
+    },
+    serde_derive::{Deserialize, Serialize},
+};
+
+struct Base58<'a>(&'a Pubkey);
+
+impl Serialize for Base58<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut out = [0u8; MAX_BASE58_LEN];
+        serializer.serialize_str(encode_base58(self.0, &mut out))
+    }
+}
+
+struct FromBase58(Pubkey);
+
+impl<'de> Deserialize<'de> for FromBase58 {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Base58Visitor;
+
+        impl de::Visitor<'_> for Base58Visitor {
+            type Value = FromBase58;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a base58 encoded pubkey")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<FromBase58, E> {
+                Pubkey::from_str(v).map(FromBase58).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(Base58Visitor)
+    }
+}
+
+/// Adapter for a single `Pubkey`.
+pub mod base58 {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        Base58(pubkey).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        FromBase58::deserialize(deserializer).map(|FromBase58(pubkey)| pubkey)
+    }
+}
+
+/// Adapter for a single `Pubkey`, encoded as a base58 string right-padded
+/// with spaces to a fixed width of 44 characters.
+///
+/// Padded and unpadded strings are both accepted when deserializing.
+pub mod padded {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut encoded = [0u8; MAX_BASE58_LEN];
+        let len = encode_base58(pubkey, &mut encoded).len();
+        let mut out = [b' '; MAX_BASE58_LEN];
+        out[..len].copy_from_slice(&encoded[..len]);
+        // base58 characters and spaces are valid utf8
+        serializer.serialize_str(unsafe { core::str::from_utf8_unchecked(&out) })
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        struct PaddedVisitor;
+
+        impl de::Visitor<'_> for PaddedVisitor {
+            type Value = Pubkey;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a base58 encoded pubkey, optionally padded with spaces")
+            }
+
+            fn visit_str<E: de::Error>(self, v: &str) -> Result<Pubkey, E> {
+                if v.len() > MAX_BASE58_LEN {
+                    return Err(E::invalid_length(v.len(), &self));
+                }
+                Pubkey::from_str(v.trim_end_matches(' ')).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(PaddedVisitor)
+    }
+}
+
+/// Adapter for a single `Pubkey`, encoded as an object with a base58 string
+/// field, like `{ "base58": "11111111111111111111111111111111" }`.
+pub mod object {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Object<'a> {
+        base58: Base58<'a>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename = "Object")]
+    struct FromObject {
+        base58: FromBase58,
+    }
+
+    pub fn serialize<S: Serializer>(pubkey: &Pubkey, serializer: S) -> Result<S::Ok, S::Error> {
+        Object {
+            base58: Base58(pubkey),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Pubkey, D::Error> {
+        FromObject::deserialize(deserializer).map(|object| object.base58.0)
+    }
+}
+
+/// Adapter for `Option<Pubkey>`.
+pub mod option {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        pubkey: &Option<Pubkey>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        pubkey.as_ref().map(Base58).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Pubkey>, D::Error> {
+        Option::<FromBase58>::deserialize(deserializer)
+            .map(|pubkey| pubkey.map(|FromBase58(pubkey)| pubkey))
+    }
+}
+
+/// Adapter for `Vec<Pubkey>`.
+#[cfg(feature = "std")]
+pub mod vec {
+    use {super::*, serde::ser::SerializeSeq, std::vec::Vec};
+
+    pub fn serialize<S: Serializer>(pubkeys: &[Pubkey], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(pubkeys.len()))?;
+        for pubkey in pubkeys {
+            seq.serialize_element(&Base58(pubkey))?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<Pubkey>, D::Error> {
+        Vec::<FromBase58>::deserialize(deserializer).map(|pubkeys| {
+            pubkeys
+                .into_iter()
+                .map(|FromBase58(pubkey)| pubkey)
+                .collect()
+        })
+    }
+}
+
+/// Adapter for maps keyed by `Pubkey`, such as `HashMap<Pubkey, V>` and
+/// `BTreeMap<Pubkey, V>`.
+#[cfg(feature = "std")]
+pub mod map_keys {
+    use {super::*, core::marker::PhantomData, serde::ser::SerializeMap};
+
+    pub fn serialize<'a, S, V, M>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        V: Serialize + 'a,
+        &'a M: IntoIterator<Item = (&'a Pubkey, &'a V)>,
+    {
+        let mut ser = serializer.serialize_map(None)?;
+        for (pubkey, value) in map {
+            ser.serialize_entry(&Base58(pubkey), value)?;
+        }
+        ser.end()
+    }
+
+    pub fn deserialize<'de, D, V, M>(deserializer: D) -> Result<M, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: Deserialize<'de>,
+        M: FromIterator<(Pubkey, V)>,
+    {
+        struct MapVisitor<V, M>(PhantomData<(V, M)>);
+
+        impl<'de, V, M> de::Visitor<'de> for MapVisitor<V, M>
+        where
+            V: Deserialize<'de>,
+            M: FromIterator<(Pubkey, V)>,
+        {
+            type Value = M;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map keyed by base58 encoded pubkeys")
+            }
+
+            fn visit_map<A: de::MapAccess<'de>>(self, mut access: A) -> Result<M, A::Error> {
+                let mut error = None;
+                let map = core::iter::from_fn(|| match access.next_entry::<FromBase58, V>() {
+                    Ok(entry) => entry.map(|(FromBase58(pubkey), value)| (pubkey, value)),
+                    Err(err) => {
+                        error = Some(err);
+                        None
+                    }
+                })
+                .collect();
+                match error {
+                    Some(err) => Err(err),
+                    None => Ok(map),
+                }
+            }
+        }
+
+        deserializer.deserialize_map(MapVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{
+            collections::{BTreeMap, HashMap},
+            vec,
+            vec::Vec,
+        },
+    };
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Adapters {
+        #[serde(with = "super::base58")]
+        key: Pubkey,
+        #[serde(with = "super::option")]
+        some: Option<Pubkey>,
+        #[serde(with = "super::option")]
+        none: Option<Pubkey>,
+        #[serde(with = "super::vec")]
+        keys: Vec<Pubkey>,
+        #[serde(with = "super::map_keys")]
+        hash_map: HashMap<Pubkey, u64>,
+        #[serde(with = "super::map_keys")]
+        btree_map: BTreeMap<Pubkey, u64>,
+        #[serde(with = "super::padded")]
+        padded: Pubkey,
+        #[serde(with = "super::object")]
+        object: Pubkey,
+    }
+
+    #[test]
+    fn test_adapters_roundtrip() {
+        let key = Pubkey::from_str("9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq").unwrap();
+        let adapters = Adapters {
+            key,
+            some: Some(key),
+            none: None,
+            keys: vec![key, Pubkey::new_unique()],
+            hash_map: HashMap::from([(key, 1)]),
+            btree_map: BTreeMap::from([(key, 2), (Pubkey::new_unique(), 3)]),
+            padded: Pubkey::default(),
+            object: key,
+        };
+
+        let json = serde_json::to_value(&adapters).unwrap();
+        assert_eq!(json["key"], "9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq");
+        assert_eq!(json["some"], "9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq");
+        assert!(json["none"].is_null());
+        assert_eq!(
+            json["keys"][0],
+            "9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq"
+        );
+        assert_eq!(
+            json["hash_map"]["9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq"],
+            1
+        );
+        assert_eq!(
+            json["padded"],
+            "11111111111111111111111111111111            "
+        );
+        assert_eq!(
+            json["object"]["base58"],
+            "9h1HyLCW5dZnBVap8C5egQ9Z6pHyjsh5MNy83iPqqRuq"
+        );
+
+        assert_eq!(serde_json::from_value::<Adapters>(json).unwrap(), adapters);
+    }
+
+    #[test]
+    fn test_adapters_reject_invalid_base58() {
+        let json = r#"{"key":"0OIl","some":null,"none":null,"keys":[],"hash_map":{},"btree_map":{},"padded":"11111111111111111111111111111111","object":{"base58":"11111111111111111111111111111111"}}"#;
+        assert!(serde_json::from_str::<Adapters>(json).is_err());
+        let json = r#"{"key":"11111111111111111111111111111111","some":null,"none":null,"keys":[],"hash_map":{"0OIl":1},"btree_map":{},"padded":"11111111111111111111111111111111","object":{"base58":"11111111111111111111111111111111"}}"#;
+        assert!(serde_json::from_str::<Adapters>(json).is_err());
+    }
+
+    #[test]
+    fn test_padded_and_object() {
+        use std::string::ToString;
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Padded(#[serde(with = "super::padded")] Pubkey);
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Object(#[serde(with = "super::object")] Pubkey);
+
+        for key in [Pubkey::default(), Pubkey::new_unique()] {
+            let json = serde_json::to_string(&Padded(key)).unwrap();
+            assert_eq!(json.len(), MAX_BASE58_LEN + 2);
+            assert_eq!(serde_json::from_str::<Padded>(&json).unwrap(), Padded(key));
+        }
+        assert_eq!(
+            serde_json::from_str(r#""11111111111111111111111111111111""#).ok(),
+            Some(Padded(Pubkey::default()))
+        );
+        // leading spaces and overlong padding are rejected
+        assert!(serde_json::from_str::<Padded>(r#"" 11111111111111111111111111111111""#).is_err());
+        assert!(serde_json::from_str::<Padded>(
+            r#""11111111111111111111111111111111             ""#
+        )
+        .is_err());
+
+        let key = Pubkey::new_unique();
+        assert_eq!(
+            serde_json::to_value(Object(key)).unwrap(),
+            serde_json::json!({ "base58": key.to_string() })
+        );
+        assert!(serde_json::from_str::<Object>(r#"{"base58":"0OIl"}"#).is_err());
+        assert!(serde_json::from_str::<Object>(r#""11111111111111111111111111111111""#).is_err());
+    }
+}
@@ -0,0 +1,222 @@
+//! A compact set of pubkeys backed by a sorted vector.
+use {
+    crate::Pubkey,
+    core::{cmp::Ordering, iter::FromIterator, slice},
+    std::vec::{self, Vec},
+};
+
+/// A set of [`Pubkey`]s stored as a sorted, deduplicated `Vec`.
+///
+/// Compared to a `HashSet<Pubkey>` this uses exactly 32 bytes per key, has
+/// O(log n) lookups, and iterates in sorted order. It is best suited for sets
+/// that are built once, e.g. with [`from_sorted_iter`] or `collect()`, and
+/// then queried many times. Individual inserts and removals are O(n).
+///
+/// [`from_sorted_iter`]: PubkeySet::from_sorted_iter
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PubkeySet {
+    keys: Vec<Pubkey>,
+}
+
+impl PubkeySet {
+    pub const fn new() -> Self {
+        Self { keys: Vec::new() }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            keys: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Build a set from an iterator that yields keys in ascending order.
+    ///
+    /// Duplicates are removed. This takes O(n) for sorted input; unsorted
+    /// input is still accepted, but is sorted first.
+    pub fn from_sorted_iter<I: IntoIterator<Item = Pubkey>>(iter: I) -> Self {
+        let mut keys = iter.into_iter().collect::<Vec<_>>();
+        if !keys.windows(2).all(|w| w[0] <= w[1]) {
+            keys.sort_unstable();
+        }
+        keys.dedup();
+        Self { keys }
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn contains(&self, key: &Pubkey) -> bool {
+        self.keys.binary_search(key).is_ok()
+    }
+
+    /// Add a key to the set, returning `true` if it was not already present.
+    pub fn insert(&mut self, key: Pubkey) -> bool {
+        match self.keys.binary_search(&key) {
+            Ok(_) => false,
+            Err(index) => {
+                self.keys.insert(index, key);
+                true
+            }
+        }
+    }
+
+    /// Remove a key from the set, returning `true` if it was present.
+    pub fn remove(&mut self, key: &Pubkey) -> bool {
+        match self.keys.binary_search(key) {
+            Ok(index) => {
+                self.keys.remove(index);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Iterate over the keys in ascending order.
+    pub fn iter(&self) -> slice::Iter<'_, Pubkey> {
+        self.keys.iter()
+    }
+
+    /// Return the keys as a sorted slice.
+    pub fn as_slice(&self) -> &[Pubkey] {
+        &self.keys
+    }
+
+    /// Return the keys that are in either set, in O(n + m).
+    pub fn union(&self, other: &PubkeySet) -> PubkeySet {
+        let mut keys = Vec::with_capacity(self.len().max(other.len()));
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+        loop {
+            let next = match (a.peek(), b.peek()) {
+                (Some(x), Some(y)) => match x.cmp(y) {
+                    Ordering::Less => a.next(),
+                    Ordering::Greater => b.next(),
+                    Ordering::Equal => {
+                        b.next();
+                        a.next()
+                    }
+                },
+                (Some(_), None) => a.next(),
+                (None, Some(_)) => b.next(),
+                (None, None) => break,
+            };
+            keys.extend(next);
+        }
+        PubkeySet { keys }
+    }
+
+    /// Return the keys that are in both sets, in O(n + m).
+    pub fn intersection(&self, other: &PubkeySet) -> PubkeySet {
+        let mut keys = Vec::with_capacity(self.len().min(other.len()));
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            match x.cmp(y) {
+                Ordering::Less => {
+                    a.next();
+                }
+                Ordering::Greater => {
+                    b.next();
+                }
+                Ordering::Equal => {
+                    keys.push(**x);
+                    a.next();
+                    b.next();
+                }
+            }
+        }
+        PubkeySet { keys }
+    }
+
+    /// Return `true` if the sets have no keys in common.
+    pub fn is_disjoint(&self, other: &PubkeySet) -> bool {
+        let (small, large) = if self.len() <= other.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        !small.iter().any(|key| large.contains(key))
+    }
+}
+
+impl FromIterator<Pubkey> for PubkeySet {
+    fn from_iter<I: IntoIterator<Item = Pubkey>>(iter: I) -> Self {
+        Self::from_sorted_iter(iter)
+    }
+}
+
+impl Extend<Pubkey> for PubkeySet {
+    fn extend<I: IntoIterator<Item = Pubkey>>(&mut self, iter: I) {
+        self.keys.extend(iter);
+        self.keys.sort_unstable();
+        self.keys.dedup();
+    }
+}
+
+impl IntoIterator for PubkeySet {
+    type Item = Pubkey;
+    type IntoIter = vec::IntoIter<Pubkey>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.keys.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PubkeySet {
+    type Item = &'a Pubkey;
+    type IntoIter = slice::Iter<'a, Pubkey>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, std::vec};
+
+    #[test]
+    fn test_pubkey_set() {
+        let keys = (0..10).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        // new_unique keys are increasing, but feed them reversed and duplicated
+        let mut set = keys
+            .iter()
+            .rev()
+            .chain(keys.iter())
+            .copied()
+            .collect::<PubkeySet>();
+        assert_eq!(set.as_slice(), &keys[..]);
+        assert!(keys.iter().all(|key| set.contains(key)));
+        assert!(!set.contains(&Pubkey::default()));
+
+        assert!(set.insert(Pubkey::default()));
+        assert!(!set.insert(Pubkey::default()));
+        assert_eq!(set.as_slice()[0], Pubkey::default());
+        assert!(set.remove(&Pubkey::default()));
+        assert!(!set.remove(&Pubkey::default()));
+        assert_eq!(set.len(), keys.len());
+    }
+
+    #[test]
+    fn test_pubkey_set_ops() {
+        let keys = (0..6).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let a = PubkeySet::from_sorted_iter(keys[..4].iter().copied());
+        let b = PubkeySet::from_sorted_iter(keys[2..].iter().copied());
+        let c = PubkeySet::from_sorted_iter(keys[4..].iter().copied());
+
+        assert_eq!(a.union(&b).as_slice(), &keys[..]);
+        assert_eq!(a.intersection(&b).as_slice(), &keys[2..4]);
+        assert_eq!(b.union(&c), b);
+        assert!(a.intersection(&c).is_empty());
+        assert!(a.is_disjoint(&c));
+        assert!(!a.is_disjoint(&b));
+        assert_eq!(a.union(&PubkeySet::new()), a);
+        assert_eq!(
+            a.into_iter().collect::<Vec<_>>(),
+            vec![keys[0], keys[1], keys[2], keys[3]]
+        );
+    }
+}
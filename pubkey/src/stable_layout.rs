@@ -0,0 +1,102 @@
+//! Memory layout guarantees for [`Pubkey`] and related types.
+//!
+//! These hold for all versions of this crate and are checked at compile
+//! time:
+//!
+//! - [`Pubkey`] is `#[repr(transparent)]` over `[u8; 32]`. It has a size of
+//!   32 bytes, an alignment of 1, no padding, and every bit pattern is a valid
+//!   `Pubkey`. It can be passed across FFI as `uint8_t[32]`, and pointers to
+//!   pubkeys can be cast to and from pointers to 32-byte arrays.
+//! - `Pubkey` has no niche, so `Option<Pubkey>` is one byte larger than
+//!   `Pubkey`. Use a null pointer or [`Pubkey::default`] to express absence
+//!   across FFI.
+//! - [`EthAddress`], with the `secp256k1` feature, is `#[repr(transparent)]`
+//!   over `[u8; 20]`, with the same properties otherwise.
+//!
+//! The `bytemuck` feature relies on these guarantees to implement `Pod` and
+//! `Zeroable`.
+//!
+//! [`EthAddress`]: crate::EthAddress
+use {
+    crate::{Pubkey, PUBKEY_BYTES},
+    core::{mem, slice},
+};
+
+const _: () = assert!(mem::size_of::<Pubkey>() == PUBKEY_BYTES);
+const _: () = assert!(mem::align_of::<Pubkey>() == 1);
+const _: () = assert!(mem::size_of::<[Pubkey; 2]>() == 2 * PUBKEY_BYTES);
+// no niche for enums to use
+const _: () = assert!(mem::size_of::<Option<Pubkey>>() != mem::size_of::<Pubkey>());
+
+#[cfg(feature = "secp256k1")]
+mod eth {
+    use {
+        super::*,
+        crate::{EthAddress, ETH_ADDRESS_BYTES},
+    };
+
+    const _: () = assert!(mem::size_of::<EthAddress>() == ETH_ADDRESS_BYTES);
+    const _: () = assert!(mem::align_of::<EthAddress>() == 1);
+    const _: () = assert!(mem::size_of::<Option<EthAddress>>() != mem::size_of::<EthAddress>());
+}
+
+impl Pubkey {
+    /// Return a pointer to the 32 bytes of the pubkey, e.g. to pass it to C
+    /// as `const uint8_t *`.
+    pub const fn as_ptr(&self) -> *const u8 {
+        self.0.as_ptr()
+    }
+
+    /// Return a mutable pointer to the 32 bytes of the pubkey, e.g. for C
+    /// code to write a pubkey into.
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.0.as_mut_ptr()
+    }
+
+    /// View 32 bytes at `ptr` as a pubkey.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null and valid for reads of 32 bytes, and the bytes
+    /// must not be mutated for the lifetime `'a`. There is no alignment
+    /// requirement.
+    pub const unsafe fn from_ptr<'a>(ptr: *const u8) -> &'a Pubkey {
+        &*ptr.cast::<Pubkey>()
+    }
+
+    /// View `len` consecutive pubkeys at `ptr` as a slice.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null and valid for reads of `len * 32` bytes, and the
+    /// bytes must not be mutated for the lifetime `'a`. There is no alignment
+    /// requirement. See [`slice::from_raw_parts`] for the full requirements.
+    pub const unsafe fn slice_from_raw_parts<'a>(ptr: *const u8, len: usize) -> &'a [Pubkey] {
+        slice::from_raw_parts(ptr.cast::<Pubkey>(), len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi_views() {
+        let mut pubkeys = [Pubkey::new_unique(), Pubkey::new_unique()];
+        let ptr = pubkeys.as_ptr().cast::<u8>();
+        assert_eq!(ptr, pubkeys[0].as_ptr());
+        assert_eq!(unsafe { Pubkey::from_ptr(ptr) }, &pubkeys[0]);
+        assert_eq!(unsafe { Pubkey::slice_from_raw_parts(ptr, 2) }, &pubkeys);
+
+        // unaligned
+        let mut bytes = [0u8; PUBKEY_BYTES + 1];
+        bytes[1..].copy_from_slice(pubkeys[1].as_array());
+        assert_eq!(
+            unsafe { Pubkey::from_ptr(bytes[1..].as_ptr()) },
+            &pubkeys[1]
+        );
+
+        unsafe { pubkeys[0].as_mut_ptr().write(7) };
+        assert_eq!(pubkeys[0].as_array()[0], 7);
+    }
+}
@@ -0,0 +1,53 @@
+//! [`proptest`] strategies that respect this crate's invariants.
+//!
+//! `Pubkey` implements [`Arbitrary`], so `any::<Pubkey>()` works as usual. The
+//! functions in this module generate program address seeds that are accepted
+//! by [`Pubkey::create_program_address`] and friends.
+//!
+//! [`Pubkey::create_program_address`]: crate::Pubkey::create_program_address
+use {
+    crate::{Pubkey, MAX_SEEDS, MAX_SEED_LEN},
+    proptest::{
+        arbitrary::{any, Arbitrary},
+        collection,
+        strategy::{BoxedStrategy, Strategy},
+    },
+    std::vec::Vec,
+};
+
+impl Arbitrary for Pubkey {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_args: Self::Parameters) -> Self::Strategy {
+        any::<[u8; 32]>().prop_map(Pubkey::from).boxed()
+    }
+}
+
+/// A single seed no longer than [`MAX_SEED_LEN`].
+pub fn seed() -> impl Strategy<Value = Vec<u8>> {
+    collection::vec(any::<u8>(), 0..=MAX_SEED_LEN)
+}
+
+/// A list of seeds that leaves room for a bump seed, i.e. fewer than
+/// [`MAX_SEEDS`] seeds, each no longer than [`MAX_SEED_LEN`].
+pub fn seeds() -> impl Strategy<Value = Vec<Vec<u8>>> {
+    collection::vec(seed(), 0..MAX_SEEDS)
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, crate::PubkeyError, proptest::prelude::*};
+
+    proptest! {
+        #[test]
+        fn test_seeds_within_limits(seeds in seeds(), program_id in any::<Pubkey>()) {
+            let mut seeds = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            seeds.push(&[0]);
+            prop_assert_ne!(
+                Pubkey::create_program_address(&seeds, &program_id),
+                Err(PubkeyError::MaxSeedLengthExceeded)
+            );
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//! Deterministic scopes for [`Pubkey::new_unique`].
+use {
+    crate::{Pubkey, PUBKEY_BYTES},
+    core::{cell::Cell, marker::PhantomData, mem},
+};
+
+type Counter = u32;
+const COUNTER_BYTES: usize = mem::size_of::<Counter>();
+
+#[derive(Clone, Copy)]
+struct ScopeState {
+    seed: u64,
+    counter: Counter,
+}
+
+std::thread_local! {
+    static SCOPE: Cell<Option<ScopeState>> = const { Cell::new(None) };
+}
+
+/// A guard that makes [`Pubkey::new_unique`] deterministic on the current
+/// thread until it is dropped.
+///
+/// Created by [`Pubkey::unique_scope`].
+#[must_use = "the scope ends as soon as the guard is dropped"]
+pub struct UniqueScope {
+    previous: Option<ScopeState>,
+    // the scope is tied to the thread that created it
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Drop for UniqueScope {
+    fn drop(&mut self) {
+        SCOPE.with(|scope| scope.set(self.previous));
+    }
+}
+
+impl Pubkey {
+    /// Make [`Pubkey::new_unique`] return a fixed sequence of keys on the
+    /// current thread, determined only by `seed`, while the returned guard is
+    /// alive.
+    ///
+    /// This lets golden-file tests produce the same keys on every run,
+    /// regardless of which other tests share the process. Scopes nest, and
+    /// the enclosing scope, if any, resumes where it left off when an inner
+    /// scope ends.
+    ///
+    /// ```
+    /// # use solana_pubkey::Pubkey;
+    /// let first = {
+    ///     let _scope = Pubkey::unique_scope(7);
+    ///     Pubkey::new_unique()
+    /// };
+    /// let _scope = Pubkey::unique_scope(7);
+    /// assert_eq!(Pubkey::new_unique(), first);
+    /// ```
+    pub fn unique_scope(seed: u64) -> UniqueScope {
+        let previous = SCOPE.with(|scope| scope.replace(Some(ScopeState { seed, counter: 1 })));
+        UniqueScope {
+            previous,
+            _not_send: PhantomData,
+        }
+    }
+}
+
+/// Return the next key of the current thread's scope, if there is one.
+pub(crate) fn next_in_scope() -> Option<Pubkey> {
+    let ScopeState { seed, counter } = SCOPE.with(|scope| {
+        let state = scope.get()?;
+        scope.set(Some(ScopeState {
+            counter: state.counter.wrapping_add(1),
+            ..state
+        }));
+        Some(state)
+    })?;
+    // Same layout as the global sequence: a big endian counter so that later
+    // keys compare greater, followed by pseudorandom bytes. splitmix64 is used
+    // rather than `DefaultHasher` because its output must never change.
+    let mut bytes = [0u8; PUBKEY_BYTES];
+    bytes[..COUNTER_BYTES].copy_from_slice(&counter.to_be_bytes());
+    let mut state = seed ^ u64::from(counter).wrapping_mul(0xd1b5_4a32_d192_ed03);
+    for chunk in bytes[COUNTER_BYTES..].chunks_mut(mem::size_of::<u64>()) {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^= z >> 31;
+        chunk.copy_from_slice(&z.to_le_bytes()[..chunk.len()]);
+    }
+    Some(Pubkey::from(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        std::{string::ToString, vec::Vec},
+    };
+
+    fn keys(seed: u64) -> Vec<Pubkey> {
+        let _scope = Pubkey::unique_scope(seed);
+        (0..4).map(|_| Pubkey::new_unique()).collect()
+    }
+
+    #[test]
+    fn test_unique_scope() {
+        let expected = keys(1);
+        assert!(expected.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(keys(1), expected);
+        assert_ne!(keys(2), expected);
+
+        // same sequence on another thread, concurrently with this one
+        let handle = std::thread::spawn(|| keys(1));
+        assert_eq!(keys(1), expected);
+        assert_eq!(handle.join().unwrap(), expected);
+
+        // golden value, must never change
+        assert_eq!(
+            expected[0].to_string(),
+            "1114nR3YpA5N4wz9gBwJbVjqMRw8j2D4qcfeFvfNN6"
+        );
+    }
+
+    #[test]
+    fn test_unique_scope_nesting() {
+        let outer = Pubkey::unique_scope(1);
+        let first = Pubkey::new_unique();
+        {
+            let _inner = Pubkey::unique_scope(1);
+            assert_eq!(Pubkey::new_unique(), first);
+        }
+        assert_eq!(Pubkey::new_unique(), keys(1)[1]);
+        drop(outer);
+        assert!(SCOPE.with(|scope| scope.get()).is_none());
+    }
+}
@@ -0,0 +1,187 @@
+//! Deterministic stake-weighted ordering of pubkeys.
+//!
+//! Each position is filled by sampling one of the remaining keys with
+//! probability proportional to its weight, like leader schedules and
+//! turbine trees order validators by stake. Sampling is exact: weights are
+//! summed without overflow and random numbers are drawn without modulo bias.
+use {
+    crate::Pubkey,
+    rand_chacha::ChaChaRng,
+    rand_core::{RngCore, SeedableRng},
+    std::{vec, vec::Vec},
+};
+
+/// A Fenwick tree over the weights of the keys that are yet to be picked.
+struct Weights {
+    tree: Vec<u128>,
+}
+
+impl Weights {
+    fn new(weights: impl ExactSizeIterator<Item = u64>) -> Self {
+        let mut tree = vec![0u128; weights.len() + 1];
+        for (i, weight) in weights.enumerate() {
+            let i = i + 1;
+            tree[i] += u128::from(weight);
+            let parent = i + (i & i.wrapping_neg());
+            if parent < tree.len() {
+                tree[parent] += tree[i];
+            }
+        }
+        Self { tree }
+    }
+
+    /// Return the index of the key whose cumulative weight range contains
+    /// `target`, which must be below the total weight.
+    fn find(&self, mut target: u128) -> usize {
+        let len = self.tree.len() - 1;
+        let mut index = 0;
+        let mut step = if len == 0 { 0 } else { 1 << len.ilog2() };
+        while step > 0 {
+            if index + step <= len && self.tree[index + step] <= target {
+                index += step;
+                target -= self.tree[index];
+            }
+            step >>= 1;
+        }
+        index
+    }
+
+    fn remove(&mut self, index: usize, weight: u64) {
+        let mut i = index + 1;
+        while i < self.tree.len() {
+            self.tree[i] -= u128::from(weight);
+            i += i & i.wrapping_neg();
+        }
+    }
+}
+
+/// Draw a number uniformly from `0..bound`, which must not be 0.
+fn sample_below<R: RngCore + ?Sized>(rng: &mut R, bound: u128) -> u128 {
+    // reject the last, partial, multiple of `bound`
+    let zone = u128::MAX - (u128::MAX - bound + 1) % bound;
+    loop {
+        let sample = (u128::from(rng.next_u64()) << 64) | u128::from(rng.next_u64());
+        if sample <= zone {
+            return sample % bound;
+        }
+    }
+}
+
+/// Order the keys of `stakes` by a weighted shuffle seeded with `seed`.
+///
+/// The same stakes and seed always give the same order, on every platform.
+/// Keys with zero weight come last, in uniformly random order. Duplicate keys
+/// are treated as separate entries.
+///
+/// ```
+/// # use solana_pubkey::{weighted_shuffle, Pubkey};
+/// let stakes = [(Pubkey::new_unique(), 100), (Pubkey::new_unique(), 0)];
+/// let order = weighted_shuffle::shuffle(&stakes, [7; 32]);
+/// assert_eq!(order, [stakes[0].0, stakes[1].0]);
+/// ```
+pub fn shuffle(stakes: &[(Pubkey, u64)], seed: [u8; 32]) -> Vec<Pubkey> {
+    shuffle_with_rng(stakes, &mut ChaChaRng::from_seed(seed))
+}
+
+/// Same as [`shuffle`], but with randomness drawn from `rng`.
+pub fn shuffle_with_rng<R: RngCore + ?Sized>(stakes: &[(Pubkey, u64)], rng: &mut R) -> Vec<Pubkey> {
+    let mut weights = Weights::new(stakes.iter().map(|(_, weight)| *weight));
+    let mut total_weight = stakes
+        .iter()
+        .map(|(_, weight)| u128::from(*weight))
+        .sum::<u128>();
+    let mut order = Vec::with_capacity(stakes.len());
+    while total_weight > 0 {
+        let index = weights.find(sample_below(rng, total_weight));
+        let (pubkey, weight) = stakes[index];
+        order.push(pubkey);
+        weights.remove(index, weight);
+        total_weight -= u128::from(weight);
+    }
+
+    let mut unweighted = stakes
+        .iter()
+        .filter(|(_, weight)| *weight == 0)
+        .map(|(pubkey, _)| *pubkey)
+        .collect::<Vec<_>>();
+    for i in (1..unweighted.len()).rev() {
+        let j = sample_below(rng, i as u128 + 1) as usize;
+        unweighted.swap(i, j);
+    }
+    order.extend(unweighted);
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shuffle_is_deterministic_permutation() {
+        let stakes = (0..100u64)
+            .map(|i| (Pubkey::new_unique(), i % 7 * 1_000))
+            .collect::<Vec<_>>();
+        let order = shuffle(&stakes, [1; 32]);
+        assert_eq!(order, shuffle(&stakes, [1; 32]));
+        assert_ne!(order, shuffle(&stakes, [2; 32]));
+
+        let mut sorted = order.clone();
+        sorted.sort_unstable();
+        let mut expected = stakes.iter().map(|(pubkey, _)| *pubkey).collect::<Vec<_>>();
+        expected.sort_unstable();
+        assert_eq!(sorted, expected);
+
+        // zero weights last
+        let num_unweighted = stakes.iter().filter(|(_, weight)| *weight == 0).count();
+        assert!(order[order.len() - num_unweighted..]
+            .iter()
+            .all(|pubkey| stakes
+                .iter()
+                .any(|(key, weight)| key == pubkey && *weight == 0)));
+
+        assert!(shuffle(&[], [0; 32]).is_empty());
+        let huge = [
+            (Pubkey::new_unique(), u64::MAX),
+            (Pubkey::new_unique(), u64::MAX),
+        ];
+        assert_eq!(shuffle(&huge, [0; 32]).len(), 2);
+    }
+
+    #[test]
+    fn test_shuffle_known_answer() {
+        let weights = [0, 1, 10, 100, 1_000, 0, 5, 50, 500, u64::MAX, 0, 7];
+        let stakes = weights
+            .iter()
+            .enumerate()
+            .map(|(i, weight)| (Pubkey::new_from_array([i as u8; 32]), *weight))
+            .collect::<Vec<_>>();
+        let order = shuffle(&stakes, [42; 32])
+            .iter()
+            .map(|pubkey| pubkey.to_bytes()[0])
+            .collect::<Vec<_>>();
+        // pins the Fenwick walk, the rejection sampling and the order in
+        // which random numbers are drawn, which must never change
+        assert_eq!(order, [9, 4, 8, 3, 7, 11, 2, 6, 1, 10, 5, 0]);
+    }
+
+    #[test]
+    fn test_shuffle_distribution() {
+        let stakes = [
+            (Pubkey::new_unique(), 1),
+            (Pubkey::new_unique(), 2),
+            (Pubkey::new_unique(), 3),
+        ];
+        let mut counts = [0usize; 3];
+        let num_samples = 6_000;
+        for i in 0..num_samples {
+            let mut seed = [0u8; 32];
+            seed[..8].copy_from_slice(&(i as u64).to_le_bytes());
+            let first = shuffle(&stakes, seed)[0];
+            counts[stakes.iter().position(|(key, _)| *key == first).unwrap()] += 1;
+        }
+        for (count, (_, weight)) in counts.iter().zip(stakes) {
+            let expected = num_samples * weight as usize / 6;
+            assert!(count.abs_diff(expected) < expected / 10, "{counts:?}");
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! Python bindings for [`Pubkey`], program derived address derivation and
+//! [`compute_shred_version`], so Python code can use the same
+//! implementations as Rust code.
+//!
+//! The `solana_python` module is built with the `python` feature:
+//!
+//! ```python
+//! from solana_python import Pubkey, compute_shred_version
+//!
+//! program_id = Pubkey.from_string("11111111111111111111111111111111")
+//! address, bump = Pubkey.find_program_address([b"vault"], program_id)
+//! ```
+//!
+//! [`Pubkey`]: solana_pubkey::Pubkey
+//! [`compute_shred_version`]: solana_shred_version::compute_shred_version
+#![cfg(feature = "python")]
+use {
+    pyo3::{exceptions::PyValueError, prelude::*, types::PyBytes},
+    solana_hard_forks::HardForks,
+    solana_hash::Hash,
+    solana_pubkey::{Pubkey, PubkeyError, PUBKEY_BYTES},
+    std::str::FromStr,
+};
+
+fn value_error(err: impl ToString) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+fn array<const N: usize>(bytes: &[u8], name: &str) -> PyResult<[u8; N]> {
+    bytes
+        .try_into()
+        .map_err(|_| value_error(format!("{name} must be {N} bytes, not {}", bytes.len())))
+}
+
+fn seed_refs(seeds: &[Vec<u8>]) -> Vec<&[u8]> {
+    seeds.iter().map(Vec::as_slice).collect()
+}
+
+/// An account address, as [`solana_pubkey::Pubkey`].
+#[pyclass(module = "solana_python", name = "Pubkey", frozen, eq, ord, hash)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PyPubkey(pub Pubkey);
+
+#[pymethods]
+impl PyPubkey {
+    /// Create a pubkey from its 32 bytes.
+    #[new]
+    fn new(bytes: &[u8]) -> PyResult<Self> {
+        array::<PUBKEY_BYTES>(bytes, "pubkey").map(|bytes| Self(Pubkey::from(bytes)))
+    }
+
+    /// Parse a base58 encoded pubkey.
+    #[staticmethod]
+    fn from_string(s: &str) -> PyResult<Self> {
+        Pubkey::from_str(s).map(Self).map_err(value_error)
+    }
+
+    /// Find a program derived address and its bump seed, as
+    /// `Pubkey::try_find_program_address`.
+    #[staticmethod]
+    fn find_program_address(seeds: Vec<Vec<u8>>, program_id: &Self) -> PyResult<(Self, u8)> {
+        Pubkey::try_find_program_address(&seed_refs(&seeds), &program_id.0)
+            .map(|(address, bump)| (Self(address), bump))
+            .ok_or_else(|| value_error("unable to find a viable program address bump seed"))
+    }
+
+    /// Derive a program address from seeds that include the bump seed, as
+    /// `Pubkey::create_program_address`.
+    #[staticmethod]
+    fn create_program_address(seeds: Vec<Vec<u8>>, program_id: &Self) -> PyResult<Self> {
+        Pubkey::create_program_address(&seed_refs(&seeds), &program_id.0)
+            .map(Self)
+            .map_err(|err: PubkeyError| value_error(err))
+    }
+
+    /// Derive an address from a base pubkey, a seed and an owner, as
+    /// `Pubkey::create_with_seed`.
+    #[staticmethod]
+    fn create_with_seed(base: &Self, seed: &str, owner: &Self) -> PyResult<Self> {
+        Pubkey::create_with_seed(&base.0, seed, &owner.0)
+            .map(Self)
+            .map_err(value_error)
+    }
+
+    /// Whether the pubkey is a point on the ed25519 curve, which program
+    /// derived addresses never are.
+    fn is_on_curve(&self) -> bool {
+        self.0.is_on_curve()
+    }
+
+    fn __bytes__<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, self.0.as_array())
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Pubkey('{}')", self.0)
+    }
+}
+
+/// Compute the shred version of a cluster from its 32 byte genesis hash and
+/// the slots of its hard forks, as
+/// `solana_shred_version::compute_shred_version`.
+///
+/// A slot listed `n` times counts as `n` hard forks at that slot.
+#[pyfunction]
+#[pyo3(signature = (genesis_hash, hard_fork_slots=None))]
+fn compute_shred_version(genesis_hash: &[u8], hard_fork_slots: Option<Vec<u64>>) -> PyResult<u16> {
+    let genesis_hash = Hash::new_from_array(array(genesis_hash, "genesis hash")?);
+    let hard_forks = hard_fork_slots.map(|slots| {
+        let mut hard_forks = HardForks::default();
+        for slot in slots {
+            hard_forks.register(slot);
+        }
+        hard_forks
+    });
+    Ok(solana_shred_version::compute_shred_version(
+        &genesis_hash,
+        hard_forks.as_ref(),
+    ))
+}
+
+#[pymodule]
+fn solana_python(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPubkey>()?;
+    m.add_function(wrap_pyfunction!(compute_shred_version, m)?)?;
+    Ok(())
+}
+
+#[cfg(all(test, not(feature = "extension-module")))]
+mod tests {
+    use {super::*, std::ffi::CString};
+
+    #[test]
+    fn test_python_module() {
+        let program_id = Pubkey::new_unique();
+        let seeds: &[&[u8]] = &[b"vault", &[7; 32]];
+        let (address, bump) = Pubkey::find_program_address(seeds, &program_id);
+        let base = Pubkey::new_unique();
+        let with_seed = Pubkey::create_with_seed(&base, "seed", &program_id).unwrap();
+        let mut hard_forks = HardForks::default();
+        hard_forks.register(1);
+        hard_forks.register(1);
+        let shred_version =
+            solana_shred_version::compute_shred_version(&Hash::default(), Some(&hard_forks));
+
+        let script = CString::new(format!(
+            r#"
+from solana_python import Pubkey, compute_shred_version
+
+program_id = Pubkey.from_string("{program_id}")
+assert program_id == Pubkey(bytes(program_id))
+assert str(program_id) == "{program_id}"
+assert repr(program_id) == "Pubkey('{program_id}')"
+assert len({{program_id, Pubkey(bytes(program_id))}}) == 1
+
+address, bump = Pubkey.find_program_address([b"vault", bytes([7] * 32)], program_id)
+assert (str(address), bump) == ("{address}", {bump})
+assert not address.is_on_curve()
+assert Pubkey.create_program_address([b"vault", bytes([7] * 32), bytes([bump])], program_id) == address
+base = Pubkey.from_string("{base}")
+assert str(Pubkey.create_with_seed(base, "seed", program_id)) == "{with_seed}"
+
+assert compute_shred_version(bytes(32)) == 1
+assert compute_shred_version(bytes(32), [1, 1]) == {shred_version}
+
+for invalid in [lambda: Pubkey(bytes(31)), lambda: Pubkey.from_string("0"),
+                lambda: compute_shred_version(bytes(33)),
+                lambda: Pubkey.create_program_address([bytes(33)], program_id)]:
+    try:
+        invalid()
+        raise AssertionError("expected a ValueError")
+    except ValueError:
+        pass
+"#
+        ))
+        .unwrap();
+
+        pyo3::append_to_inittab!(solana_python);
+        Python::with_gil(|py| py.run(&script, None, None)).unwrap();
+    }
+}
@@ -5,4 +5,14 @@ here="$(dirname "$0")"
 src_root="$(readlink -f "${here}/..")"
 cd "${src_root}"
 
-./cargo nightly hack clippy --feature-powerset --no-dev-deps -- --deny=warnings
+./cargo nightly hack clippy --feature-powerset --no-dev-deps --workspace \
+  --exclude solana-message --exclude solana-pubkey -- --deny=warnings
+
+# The full powerset of these crates' features is too large to check, so check
+# every pair of features instead. Features that only implement the traits of
+# an external crate don't interact with each other and are checked together.
+./cargo nightly hack clippy --feature-powerset --depth 2 --no-dev-deps \
+  --package solana-message -- --deny=warnings
+./cargo nightly hack clippy --feature-powerset --depth 2 --no-dev-deps \
+  --package solana-pubkey --group-features defmt,proptest,schemars,sqlx,subtle \
+  -- --deny=warnings
@@ -0,0 +1,13 @@
+#!/usr/bin/env bash
+
+set -eo pipefail
+here="$(dirname "$0")"
+src_root="$(readlink -f "${here}/..")"
+cd "${src_root}"
+
+# The Python bindings are their own workspace, so that the main one does not
+# need pyo3 or a Python installation, and are checked separately here.
+manifest_path=python/Cargo.toml
+./cargo nightly clippy --manifest-path "${manifest_path}" --all-targets --features python -- \
+  --deny=warnings
+cargo test --manifest-path "${manifest_path}" --features python
@@ -6,3 +6,8 @@ src_root="$(readlink -f "${here}/..")"
 cd "${src_root}"
 
 cargo test --all --tests -- --nocapture
+
+# Features that change behavior for every crate in the graph, so they cannot
+# be enabled in dev-dependencies, e.g. serde-human-readable changes the
+# frozen-abi digests of messages.
+cargo test -p solana-pubkey --features serde-human-readable -- --nocapture
@@ -1,5 +1,7 @@
 #![no_std]
 
+pub mod registry;
+
 pub mod address_lookup_table {
     solana_pubkey::declare_id!("AddressLookupTab1e1111111111111111111111111");
 }
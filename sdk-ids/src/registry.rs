@@ -0,0 +1,102 @@
+//! Human-readable names for the well-known IDs declared in this crate.
+//!
+//! ```
+//! use solana_sdk_ids::{registry, system_program, sysvar};
+//!
+//! assert_eq!(registry::name_of(&system_program::ID), Some("System Program"));
+//! assert_eq!(registry::by_name("Clock Sysvar"), Some(sysvar::clock::ID));
+//! ```
+use solana_pubkey::Pubkey;
+
+/// All well-known IDs, paired with their human-readable names.
+pub const KNOWN_IDS: &[(&str, Pubkey)] = &[
+    (
+        "Address Lookup Table Program",
+        crate::address_lookup_table::ID,
+    ),
+    ("BPF Loader", crate::bpf_loader::ID),
+    ("BPF Loader (Deprecated)", crate::bpf_loader_deprecated::ID),
+    ("BPF Upgradeable Loader", crate::bpf_loader_upgradeable::ID),
+    ("Compute Budget Program", crate::compute_budget::ID),
+    ("Config Program", crate::config::ID),
+    ("Ed25519 SigVerify Precompile", crate::ed25519_program::ID),
+    ("Feature Program", crate::feature::ID),
+    ("Incinerator", crate::incinerator::ID),
+    ("Loader v4", crate::loader_v4::ID),
+    ("Native Loader", crate::native_loader::ID),
+    (
+        "Secp256k1 SigVerify Precompile",
+        crate::secp256k1_program::ID,
+    ),
+    (
+        "Secp256r1 SigVerify Precompile",
+        crate::secp256r1_program::ID,
+    ),
+    ("Stake Config", crate::stake::config::ID),
+    ("Stake Program", crate::stake::ID),
+    ("System Program", crate::system_program::ID),
+    ("Vote Program", crate::vote::ID),
+    ("Sysvar Owner", crate::sysvar::ID),
+    ("Clock Sysvar", crate::sysvar::clock::ID),
+    ("Epoch Rewards Sysvar", crate::sysvar::epoch_rewards::ID),
+    ("Epoch Schedule Sysvar", crate::sysvar::epoch_schedule::ID),
+    ("Fees Sysvar", crate::sysvar::fees::ID),
+    ("Instructions Sysvar", crate::sysvar::instructions::ID),
+    (
+        "Last Restart Slot Sysvar",
+        crate::sysvar::last_restart_slot::ID,
+    ),
+    (
+        "Recent Blockhashes Sysvar",
+        crate::sysvar::recent_blockhashes::ID,
+    ),
+    ("Rent Sysvar", crate::sysvar::rent::ID),
+    ("Rewards Sysvar", crate::sysvar::rewards::ID),
+    ("Slot Hashes Sysvar", crate::sysvar::slot_hashes::ID),
+    ("Slot History Sysvar", crate::sysvar::slot_history::ID),
+    ("Stake History Sysvar", crate::sysvar::stake_history::ID),
+    ("ZK Token Proof Program", crate::zk_token_proof_program::ID),
+    (
+        "ZK ElGamal Proof Program",
+        crate::zk_elgamal_proof_program::ID,
+    ),
+];
+
+/// Returns the human-readable name of a well-known ID.
+pub fn name_of(id: &Pubkey) -> Option<&'static str> {
+    KNOWN_IDS
+        .iter()
+        .find(|(_, known_id)| known_id == id)
+        .map(|(name, _)| *name)
+}
+
+/// Returns the well-known ID with the given human-readable name.
+pub fn by_name(name: &str) -> Option<Pubkey> {
+    KNOWN_IDS
+        .iter()
+        .find(|(known_name, _)| *known_name == name)
+        .map(|(_, id)| *id)
+}
+
+/// Iterates over all well-known IDs and their human-readable names.
+pub fn iter() -> impl Iterator<Item = (&'static str, &'static Pubkey)> {
+    KNOWN_IDS.iter().map(|(name, id)| (*name, id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_is_unique() {
+        for (i, (name, id)) in iter().enumerate() {
+            assert_eq!(name_of(id), Some(name));
+            assert_eq!(by_name(name), Some(*id));
+            assert!(KNOWN_IDS[i + 1..]
+                .iter()
+                .all(|(other_name, other_id)| other_name != &name && other_id != id));
+        }
+        assert_eq!(name_of(&Pubkey::new_unique()), None);
+        assert_eq!(by_name("system program"), None);
+    }
+}
@@ -788,6 +788,8 @@
 use serde_derive::{Deserialize, Serialize};
 #[cfg(feature = "bincode")]
 use solana_instruction::Instruction;
+#[cfg(feature = "verify")]
+use solana_precompile_error::PrecompileError;
 use {digest::Digest, solana_signature::error::Error};
 
 pub const SECP256K1_PUBKEY_SIZE: usize = 64;
@@ -899,3 +901,107 @@ pub fn eth_address_from_pubkey(
     assert_eq!(addr.len(), HASHED_PUBKEY_SERIALIZED_SIZE);
     addr
 }
+
+/// Verify the signatures of a secp256k1 program instruction with data `data`,
+/// as the runtime does before executing a transaction.
+///
+/// `instruction_datas` is the data of every instruction of the transaction,
+/// which the [`SecpSignatureOffsets`] index into.
+#[cfg(feature = "verify")]
+pub fn verify(data: &[u8], instruction_datas: &[&[u8]]) -> Result<(), PrecompileError> {
+    let Some(&num_signatures) = data.first() else {
+        return Err(PrecompileError::InvalidInstructionDataSize);
+    };
+    let num_signatures = usize::from(num_signatures);
+    if num_signatures == 0 && data.len() > 1 {
+        return Err(PrecompileError::InvalidInstructionDataSize);
+    }
+    let expected_data_size = num_signatures
+        .saturating_mul(SIGNATURE_OFFSETS_SERIALIZED_SIZE)
+        .saturating_add(1);
+    if data.len() < expected_data_size {
+        return Err(PrecompileError::InvalidInstructionDataSize);
+    }
+    for i in 0..num_signatures {
+        let start = i
+            .saturating_mul(SIGNATURE_OFFSETS_SERIALIZED_SIZE)
+            .saturating_add(1);
+        let offsets = read_signature_offsets(&data[start..]);
+
+        let signature_instruction = instruction_datas
+            .get(usize::from(offsets.signature_instruction_index))
+            .ok_or(PrecompileError::InvalidInstructionDataSize)?;
+        let signature_start = usize::from(offsets.signature_offset);
+        let signature_end = signature_start.saturating_add(SIGNATURE_SERIALIZED_SIZE);
+        // the recovery id follows the signature
+        if signature_end >= signature_instruction.len() {
+            return Err(PrecompileError::InvalidSignature);
+        }
+        let signature = libsecp256k1::Signature::parse_standard_slice(
+            &signature_instruction[signature_start..signature_end],
+        )
+        .map_err(|_| PrecompileError::InvalidSignature)?;
+        let recovery_id = libsecp256k1::RecoveryId::parse(signature_instruction[signature_end])
+            .map_err(|_| PrecompileError::InvalidRecoveryId)?;
+
+        let eth_address = get_data_slice(
+            instruction_datas,
+            offsets.eth_address_instruction_index,
+            offsets.eth_address_offset,
+            HASHED_PUBKEY_SERIALIZED_SIZE,
+        )?;
+        let message = get_data_slice(
+            instruction_datas,
+            offsets.message_instruction_index,
+            offsets.message_data_offset,
+            usize::from(offsets.message_data_size),
+        )?;
+
+        let message_hash: [u8; 32] = sha3::Keccak256::digest(message).into();
+        let pubkey = libsecp256k1::recover(
+            &libsecp256k1::Message::parse(&message_hash),
+            &signature,
+            &recovery_id,
+        )
+        .map_err(|_| PrecompileError::InvalidSignature)?;
+        // skip the 0x04 tag of the uncompressed public key
+        let pubkey = pubkey.serialize();
+        let pubkey: &[u8; SECP256K1_PUBKEY_SIZE] = pubkey[1..].try_into().unwrap();
+        if eth_address != eth_address_from_pubkey(pubkey) {
+            return Err(PrecompileError::InvalidSignature);
+        }
+    }
+    Ok(())
+}
+
+/// Read the offsets serialized with bincode, without depending on it.
+#[cfg(feature = "verify")]
+fn read_signature_offsets(data: &[u8]) -> SecpSignatureOffsets {
+    let read_u16 =
+        |offset: usize| u16::from_le_bytes([data[offset], data[offset.saturating_add(1)]]);
+    SecpSignatureOffsets {
+        signature_offset: read_u16(0),
+        signature_instruction_index: data[2],
+        eth_address_offset: read_u16(3),
+        eth_address_instruction_index: data[5],
+        message_data_offset: read_u16(6),
+        message_data_size: read_u16(8),
+        message_instruction_index: data[10],
+    }
+}
+
+#[cfg(feature = "verify")]
+fn get_data_slice<'a>(
+    instruction_datas: &'a [&[u8]],
+    instruction_index: u8,
+    offset: u16,
+    size: usize,
+) -> Result<&'a [u8], PrecompileError> {
+    let instruction = instruction_datas
+        .get(usize::from(instruction_index))
+        .ok_or(PrecompileError::InvalidDataOffsets)?;
+    let start = usize::from(offset);
+    instruction
+        .get(start..start.saturating_add(size))
+        .ok_or(PrecompileError::InvalidSignature)
+}
@@ -29,17 +29,9 @@ impl Serialize for ShortU16 {
         // generate an open bracket.
         let mut seq = serializer.serialize_tuple(1)?;
 
-        let mut rem_val = self.0;
-        loop {
-            let mut elem = (rem_val & 0x7f) as u8;
-            rem_val >>= 7;
-            if rem_val == 0 {
-                seq.serialize_element(&elem)?;
-                break;
-            } else {
-                elem |= 0x80;
-                seq.serialize_element(&elem)?;
-            }
+        let (bytes, len) = encode_shortu16_len(self.0);
+        for elem in &bytes[..len] {
+            seq.serialize_element(elem)?;
         }
         seq.end()
     }
@@ -252,6 +244,22 @@ impl<'de, T: Deserialize<'de>> Deserialize<'de> for ShortVec<T> {
     }
 }
 
+/// Return the encoding of `len` as a [`ShortU16`], and how many of the
+/// returned bytes it uses.
+pub fn encode_shortu16_len(len: u16) -> ([u8; MAX_ENCODING_LENGTH], usize) {
+    let mut bytes = [0; MAX_ENCODING_LENGTH];
+    let mut rem_val = len;
+    for (nth_byte, elem) in bytes.iter_mut().enumerate() {
+        *elem = (rem_val & 0x7f) as u8;
+        rem_val >>= 7;
+        if rem_val == 0 {
+            return (bytes, nth_byte + 1);
+        }
+        *elem |= 0x80;
+    }
+    unreachable!("a u16 fits in {MAX_ENCODING_LENGTH} bytes")
+}
+
 /// Return the decoded value and how many bytes it consumed.
 #[allow(clippy::result_unit_err)]
 pub fn decode_shortu16_len(bytes: &[u8]) -> Result<(usize, usize), ()> {
@@ -282,6 +290,8 @@ mod tests {
 
     fn assert_len_encoding(len: u16, bytes: &[u8]) {
         assert_eq!(encode_len(len), bytes, "unexpected usize encoding");
+        let (encoded, encoded_len) = encode_shortu16_len(len);
+        assert_eq!(&encoded[..encoded_len], bytes, "unexpected usize encoding");
         assert_eq!(
             decode_shortu16_len(bytes).unwrap(),
             (usize::from(len), bytes.len()),
@@ -173,6 +173,28 @@ impl FromStr for Signature {
     }
 }
 
+/// Describes the JSON produced by `Signature`'s serde implementation, an
+/// array of 64 bytes.
+#[cfg(feature = "schemars")]
+impl schemars::JsonSchema for Signature {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Signature".into()
+    }
+
+    fn schema_id() -> std::borrow::Cow<'static, str> {
+        "solana_signature::Signature".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "array",
+            "items": generator.subschema_for::<u8>(),
+            "minItems": SIGNATURE_BYTES,
+            "maxItems": SIGNATURE_BYTES,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -297,4 +319,17 @@ mod tests {
         // Sanity check: ensure the pointer is the same.
         assert_eq!(signature.as_array().as_ptr(), signature.0.as_ptr());
     }
+
+    #[test]
+    fn test_json_schema() {
+        let schema = serde_json::to_value(schemars::schema_for!(Signature)).unwrap();
+        assert_eq!(schema["title"], "Signature");
+        assert_eq!(schema["type"], "array");
+        assert_eq!(schema["minItems"], SIGNATURE_BYTES);
+        assert_eq!(schema["maxItems"], SIGNATURE_BYTES);
+        assert_eq!(schema["items"]["format"], "uint8");
+
+        let json = serde_json::to_value(Signature::from([1; SIGNATURE_BYTES])).unwrap();
+        assert_eq!(json.as_array().unwrap().len(), SIGNATURE_BYTES);
+    }
 }
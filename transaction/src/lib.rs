@@ -1033,12 +1033,9 @@ impl Transaction {
         if self.message.account_keys.len() < self.message.header.num_required_signatures as usize {
             return Err(TransactionError::InvalidAccountIndex);
         }
-        let signed_keys =
-            &self.message.account_keys[0..self.message.header.num_required_signatures as usize];
-
         Ok(pubkeys
             .iter()
-            .map(|pubkey| signed_keys.iter().position(|x| x == pubkey))
+            .map(|pubkey| self.message.signer_position(pubkey))
             .collect())
     }
 